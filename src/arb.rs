@@ -0,0 +1,167 @@
+//! Implementations of the `Arbitrary` traits from `arbitrary` and `proptest`, so that the code
+//! generic over [ModularInteger][crate::ModularInteger] can be fuzzed and property-tested.
+//!
+//! For [MontgomeryInt] both the residue and the modulus are generated, and the modulus is
+//! forced to be an odd integer larger than 1. For the types with a fixed modulus
+//! ([ConstMontgomeryInt], [FixedMersenneInt] and the types declared by [modtype!][crate::modtype])
+//! only the residue is generated.
+
+use crate::{umax, ConstMontgomeryInt, FixedMersenne, FixedMersenneInt, MontgomeryInt};
+
+#[cfg(feature = "arbitrary")]
+mod _arbitrary {
+    use super::*;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    macro_rules! impl_arbitrary_for {
+        ($($T:ty)*) => ($(
+            impl<'a> Arbitrary<'a> for MontgomeryInt<$T> {
+                fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+                    let (a, m) = <($T, $T)>::arbitrary(u)?;
+                    Ok(MontgomeryInt::new(a, &(m | 1).max(3)))
+                }
+
+                #[inline]
+                fn size_hint(depth: usize) -> (usize, Option<usize>) {
+                    <($T, $T)>::size_hint(depth)
+                }
+            }
+        )*);
+    }
+    impl_arbitrary_for!(u8 u16 u32 u64 u128 usize);
+
+    impl<'a, const M: u64> Arbitrary<'a> for ConstMontgomeryInt<M> {
+        #[inline]
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u64::arbitrary(u).map(Self::from)
+        }
+
+        #[inline]
+        fn size_hint(depth: usize) -> (usize, Option<usize>) {
+            u64::size_hint(depth)
+        }
+    }
+
+    impl<'a, const P: u8, const K: umax> Arbitrary<'a> for FixedMersenneInt<P, K> {
+        #[inline]
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let a = umax::arbitrary(u)?;
+            Ok(Self::new(a, &FixedMersenne::<P, K>::MODULUS))
+        }
+
+        #[inline]
+        fn size_hint(depth: usize) -> (usize, Option<usize>) {
+            umax::size_hint(depth)
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod _proptest {
+    use super::*;
+    use proptest::arbitrary::{any, Arbitrary};
+    use proptest::strategy::{Map, Strategy};
+
+    macro_rules! impl_arbitrary_for {
+        ($($T:ident)*) => ($(
+            impl Arbitrary for MontgomeryInt<$T> {
+                type Parameters = ();
+                type Strategy = Map<(proptest::num::$T::Any, proptest::num::$T::Any), fn(($T, $T)) -> Self>;
+
+                fn arbitrary_with(_: ()) -> Self::Strategy {
+                    fn from_parts((a, m): ($T, $T)) -> MontgomeryInt<$T> {
+                        MontgomeryInt::new(a, &(m | 1).max(3))
+                    }
+                    (any::<$T>(), any::<$T>()).prop_map(from_parts as fn(($T, $T)) -> Self)
+                }
+            }
+        )*);
+    }
+    impl_arbitrary_for!(u8 u16 u32 u64 u128 usize);
+
+    impl<const M: u64> Arbitrary for ConstMontgomeryInt<M> {
+        type Parameters = ();
+        type Strategy = Map<proptest::num::u64::Any, fn(u64) -> Self>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            any::<u64>().prop_map(Self::from as fn(u64) -> Self)
+        }
+    }
+
+    impl<const P: u8, const K: umax> Arbitrary for FixedMersenneInt<P, K> {
+        type Parameters = ();
+        type Strategy = Map<proptest::num::u128::Any, fn(umax) -> Self>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            fn from_residue<const P: u8, const K: umax>(a: umax) -> FixedMersenneInt<P, K> {
+                FixedMersenneInt::new(a, &FixedMersenne::<P, K>::MODULUS)
+            }
+            any::<umax>().prop_map(from_residue::<P, K> as fn(umax) -> Self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModularInteger;
+
+    crate::modtype!(Mod1e9p7: u32 = 1_000_000_007);
+
+    #[test]
+    #[cfg(feature = "arbitrary")]
+    fn arbitrary_test() {
+        use arbitrary::{Arbitrary, Unstructured};
+        use rand::random;
+
+        for _ in 0..100 {
+            let bytes: std::vec::Vec<u8> = (0..64).map(|_| random()).collect();
+            let mut u = Unstructured::new(&bytes);
+
+            let x = MontgomeryInt::<u64>::arbitrary(&mut u).unwrap();
+            assert!(x.modulus() % 2 == 1 && x.modulus() > 1);
+            assert!(x.residue() < x.modulus());
+            let x = MontgomeryInt::<u8>::arbitrary(&mut u).unwrap();
+            assert!(x.modulus() % 2 == 1 && x.modulus() > 1);
+
+            let y = ConstMontgomeryInt::<998244353>::arbitrary(&mut u).unwrap();
+            assert!(y.residue() < 998244353);
+            let z = FixedMersenneInt::<61, 1>::arbitrary(&mut u).unwrap();
+            assert!(z.residue() < (1 << 61) - 1);
+            let w = Mod1e9p7::arbitrary(&mut u).unwrap();
+            assert!(w.residue() < 1_000_000_007);
+            assert_eq!(w.pow(&2), w.square());
+        }
+
+        // the generation doesn't fail on exhausted data
+        let mut u = Unstructured::new(&[]);
+        assert_eq!(
+            MontgomeryInt::<u32>::arbitrary(&mut u).unwrap().modulus(),
+            3
+        );
+        assert_eq!(MontgomeryInt::<u32>::size_hint(0), (8, Some(8)));
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn proptest_montgomery_test(x: MontgomeryInt<u64>, e: u64) {
+            let m = x.modulus();
+            proptest::prop_assert!(m % 2 == 1 && m > 1);
+            proptest::prop_assert_eq!(x.square(), x * x);
+            if let Some(xi) = x.inv() {
+                proptest::prop_assert_eq!((x * xi).residue(), 1);
+            }
+            let e = e % 1000;
+            proptest::prop_assert_eq!(x.pow(&e).residue(), crate::ModularPow::powm(x.residue(), e, &m));
+        }
+
+        #[test]
+        fn proptest_fixed_test(x: ConstMontgomeryInt<998244353>, y: FixedMersenneInt<61, 1>, z: Mod1e9p7) {
+            proptest::prop_assert!(x.residue() < 998244353);
+            proptest::prop_assert!(y.residue() < (1 << 61) - 1);
+            proptest::prop_assert!(z.residue() < 1_000_000_007);
+            proptest::prop_assert_eq!(z.pow(&2), z.square());
+        }
+    }
+}
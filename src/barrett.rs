@@ -340,6 +340,131 @@ macro_rules! impl_premulinv_2by1_reducer_for {
     };
 }
 
+/// Barrett reduction with the precomputed reciprocal `r = ⌊(2^2N - 1) / m⌋` of the modulus,
+/// where N is the bit size of T. Any modulus `m >= 1` is supported, and the integers are
+/// kept in the normal form, so no conversion is needed for the residues.
+///
+/// Reducing a double word `x` estimates the quotient by the high half of `x * r`, which is
+/// at most one less than `⌊x / m⌋`, so only one correction step is required.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{Barrett, BarrettInt, ModularInteger};
+///
+/// let r = Barrett::<u64>::new(1 << 40);
+/// assert_eq!(r.reduce(12345678901234567890), 12345678901234567890 % (1 << 40));
+///
+/// let x = BarrettInt::<u64>::new(123456789, &1000000);
+/// assert_eq!((x * x).residue(), 123456789 * 123456789 % 1000000);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Barrett<T> {
+    m: T,
+    r: (T, T), // the (low, high) words of the reciprocal
+}
+
+macro_rules! impl_barrett_reducer_for {
+    ($T:ty) => {
+        impl Barrett<$T> {
+            /// Create the reducer for modulus m
+            ///
+            /// # Panics
+            /// if m is zero
+            pub const fn new(m: $T) -> Self {
+                assert!(m > 0, "the modulus should not be zero");
+                Self {
+                    m,
+                    r: split(DoubleWord::MAX / extend(m)),
+                }
+            }
+
+            /// Get the modulus and the precomputed reciprocal `⌊(2^2N - 1) / m⌋`
+            #[inline]
+            pub const fn raw_parts(&self) -> ($T, DoubleWord) {
+                (self.m, merge(self.r.0, self.r.1))
+            }
+
+            /// Calculate `x mod m` for a double word x
+            #[inline]
+            pub const fn reduce(&self, x: DoubleWord) -> $T {
+                let r = merge(self.r.0, self.r.1);
+                let q = DoubleWordModule::high(DoubleWordModule::wmul(x, r));
+
+                // q <= x / m, so the subtraction doesn't overflow
+                let rem = x - q * extend(self.m);
+                if rem >= extend(self.m) {
+                    (rem - extend(self.m)) as $T
+                } else {
+                    rem as $T
+                }
+            }
+        }
+
+        impl Reducer<$T> for Barrett<$T> {
+            #[inline]
+            fn new(m: &$T) -> Self {
+                Barrett::<$T>::new(*m)
+            }
+            #[inline]
+            fn transform(&self, target: $T) -> $T {
+                if target < self.m {
+                    target
+                } else {
+                    self.reduce(extend(target))
+                }
+            }
+            #[inline]
+            fn check(&self, target: &$T) -> bool {
+                *target < self.m
+            }
+            #[inline]
+            fn residue(&self, target: $T) -> $T {
+                target
+            }
+            #[inline]
+            fn modulus(&self) -> $T {
+                self.m
+            }
+            #[inline]
+            fn is_zero(&self, target: &$T) -> bool {
+                *target == 0
+            }
+
+            #[inline(always)]
+            fn add(&self, lhs: &$T, rhs: &$T) -> $T {
+                Vanilla::<$T>::add(&self.m, *lhs, *rhs)
+            }
+            #[inline(always)]
+            fn dbl(&self, target: $T) -> $T {
+                Vanilla::<$T>::dbl(&self.m, target)
+            }
+            #[inline(always)]
+            fn sub(&self, lhs: &$T, rhs: &$T) -> $T {
+                Vanilla::<$T>::sub(&self.m, *lhs, *rhs)
+            }
+            #[inline(always)]
+            fn neg(&self, target: $T) -> $T {
+                Vanilla::<$T>::neg(&self.m, target)
+            }
+
+            #[inline(always)]
+            fn inv(&self, target: $T) -> Option<$T> {
+                target.invm(&self.m)
+            }
+            #[inline]
+            fn mul(&self, lhs: &$T, rhs: &$T) -> $T {
+                self.reduce(wmul(*lhs, *rhs))
+            }
+            #[inline]
+            fn sqr(&self, target: $T) -> $T {
+                self.reduce(wsqr(target))
+            }
+
+            impl_reduced_binary_pow!($T);
+        }
+    };
+}
+
 /// Divide a 3-Word by a prearranged DoubleWord divisor.
 ///
 /// Assumes quotient fits in a Word.
@@ -580,6 +705,7 @@ macro_rules! collect_impls {
             impl_premulinv_2by1_reducer_for!(Word);
             impl_normdiv_3by2_for!(Word, DoubleWord);
             impl_premulinv_3by2_reducer_for!(Word, DoubleWord);
+            impl_barrett_reducer_for!(Word);
         }
     };
 }
@@ -701,6 +827,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_barrett_against_modops() {
+        for _ in 0..10 {
+            ReducedTester::<u8>::test_against_modops::<Barrett<u8>>(0);
+            ReducedTester::<u16>::test_against_modops::<Barrett<u16>>(0);
+            ReducedTester::<u32>::test_against_modops::<Barrett<u32>>(0);
+            ReducedTester::<u64>::test_against_modops::<Barrett<u64>>(0);
+            ReducedTester::<usize>::test_against_modops::<Barrett<usize>>(0);
+        }
+    }
+
+    #[test]
+    fn test_barrett_reduce() {
+        type Word = u64;
+        use crate::word::u64::*;
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for &m in [1, 2, 3, Word::MAX, Word::MAX - 1, Word::MAX / 2 + 1].iter() {
+            let r = Barrett::<Word>::new(m);
+            for &x in [0, DoubleWord::MAX, wsqr(m - 1), extend(m)].iter() {
+                assert_eq!(r.reduce(x), (x % extend(m)) as Word);
+            }
+        }
+        for _ in 0..100000 {
+            let m = (rng.gen_range(1..=Word::MAX) >> rng.gen_range(0..Word::BITS)).max(1);
+            let x: DoubleWord = rng.gen();
+            assert_eq!(Barrett::<Word>::new(m).reduce(x), (x % extend(m)) as Word);
+        }
+    }
+
     #[test]
     fn test_3by2_against_modops() {
         for _ in 0..10 {
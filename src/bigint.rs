@@ -1,7 +1,5 @@
 use crate::{ModularAbs, ModularCoreOps, ModularPow, ModularSymbols, ModularUnaryOps};
 use core::convert::TryInto;
-use num_integer::Integer;
-use num_traits::{One, ToPrimitive, Zero};
 
 // Efficient implementation for bigints can be found in "Handbook of Applied Cryptography"
 // Reference: https://cacr.uwaterloo.ca/hac/about/chap14.pdf
@@ -106,7 +104,8 @@ macro_rules! impl_mod_ops_by_ref {
 mod _num_bigint {
     use super::*;
     use num_bigint::{BigInt, BigUint};
-    use num_traits::Signed;
+    use num_integer::Integer;
+    use num_traits::{One, Signed, ToPrimitive, Zero};
 
     impl ModularCoreOps<&BigUint, &BigUint> for &BigUint {
         type Output = BigUint;
@@ -178,7 +177,7 @@ mod _num_bigint {
         fn dblm(self, m: &BigUint) -> BigUint {
             let x = self % m;
             let d = x << 1;
-            if &d > m {
+            if &d >= m {
                 d - m
             } else {
                 d
@@ -308,7 +307,7 @@ mod _num_bigint {
             // n is positive from now on
             let n = n.magnitude();
             if n.is_zero() {
-                return if self.is_one() { 1 } else { 0 };
+                return if self.magnitude().is_one() { 1 } else { 0 };
             }
             if n.is_one() {
                 return 1;
@@ -348,6 +347,89 @@ mod _num_bigint {
         }
     }
 
+    // signed operands are normalized into [0, m) and the operations are forwarded
+    // to BigUint, the modulus must be positive
+    #[inline]
+    fn positive_modulus(m: &BigInt) -> &BigUint {
+        assert!(m.is_positive(), "the modulus should be positive");
+        m.magnitude()
+    }
+
+    #[inline]
+    fn normalize(a: &BigInt, m: &BigUint) -> BigUint {
+        let r = a.magnitude() % m;
+        if a.is_negative() {
+            r.negm(m)
+        } else {
+            r
+        }
+    }
+
+    impl ModularCoreOps<&BigInt, &BigInt> for &BigInt {
+        type Output = BigInt;
+
+        #[inline]
+        fn addm(self, rhs: &BigInt, m: &BigInt) -> BigInt {
+            let m = positive_modulus(m);
+            normalize(self, m).addm(&normalize(rhs, m), m).into()
+        }
+        #[inline]
+        fn subm(self, rhs: &BigInt, m: &BigInt) -> BigInt {
+            let m = positive_modulus(m);
+            normalize(self, m).subm(&normalize(rhs, m), m).into()
+        }
+        #[inline]
+        fn mulm(self, rhs: &BigInt, m: &BigInt) -> BigInt {
+            let m = positive_modulus(m);
+            normalize(self, m).mulm(&normalize(rhs, m), m).into()
+        }
+    }
+
+    impl ModularUnaryOps<&BigInt> for &BigInt {
+        type Output = BigInt;
+
+        #[inline]
+        fn negm(self, m: &BigInt) -> BigInt {
+            let m = positive_modulus(m);
+            normalize(self, m).negm(m).into()
+        }
+        #[inline]
+        fn invm(self, m: &BigInt) -> Option<BigInt> {
+            let m = positive_modulus(m);
+            normalize(self, m).invm(m).map(BigInt::from)
+        }
+        #[inline]
+        fn dblm(self, m: &BigInt) -> BigInt {
+            let m = positive_modulus(m);
+            normalize(self, m).dblm(m).into()
+        }
+        #[inline]
+        fn sqm(self, m: &BigInt) -> BigInt {
+            let m = positive_modulus(m);
+            normalize(self, m).sqm(m).into()
+        }
+    }
+
+    impl ModularPow<&BigInt, &BigInt> for &BigInt {
+        type Output = BigInt;
+
+        // negative exponents are supported only if self is invertible
+        fn powm(self, exp: &BigInt, m: &BigInt) -> BigInt {
+            let m = positive_modulus(m);
+            let base = normalize(self, m);
+            if exp.is_negative() {
+                base.invm(m)
+                    .expect("the base should be invertible for negative exponents")
+                    .powm(exp.magnitude(), m)
+                    .into()
+            } else {
+                base.powm(exp.magnitude(), m).into()
+            }
+        }
+    }
+
+    impl_mod_ops_by_ref!(BigInt);
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -377,6 +459,62 @@ mod _num_bigint {
             }
         }
 
+        #[test]
+        fn boundary_tests() {
+            let m = BigUint::from(u128::MAX - 1);
+            let one = BigUint::one();
+            let half = (&m >> 1u8) + &one;
+            let boundary = [
+                (one.clone(), &m - &one),
+                (&m - &one, one.clone()),
+                (&m - &one, &m - &one),
+                (m.clone(), m.clone()),
+                (BigUint::from(u128::MAX), BigUint::from(u128::MAX)),
+            ];
+            for (a, b) in boundary.iter() {
+                let expected = (a + b) % &m;
+                assert_eq!(a.addm(b, &m), expected);
+                assert!(a.addm(b, &m) < m);
+                assert_eq!(a.dblm(&m), (a + a) % &m);
+            }
+
+            // 2x == m
+            let m = BigUint::from(1u8) << 200u8;
+            let x = BigUint::from(1u8) << 199u8;
+            assert!(x.dblm(&m).is_zero());
+            assert_eq!(half.dblm(&BigUint::from(u128::MAX - 1)), BigUint::from(2u8));
+        }
+
+        #[test]
+        fn subm_negm_boundary_test() {
+            for &m in [1, 2, 3, u64::MAX as u128, u128::MAX - 1, u128::MAX].iter() {
+                let rm = BigUint::from(m);
+                let values = [0, 1, m - 1, m, m.wrapping_add(1), u128::MAX];
+                for &a in values.iter() {
+                    let ra = BigUint::from(a);
+                    let expected = (&rm - &ra % &rm) % &rm;
+                    assert_eq!((&ra).negm(&rm), expected);
+                    assert_eq!(BigUint::from(a.negm(&m)), expected);
+                    for &b in values.iter() {
+                        let rb = BigUint::from(b);
+                        // (a - b) mod m = (a + (m - b mod m)) mod m
+                        let expected = (&ra + (&rm - &rb % &rm)) % &rm;
+                        assert_eq!((&ra).subm(&rb, &rm), expected, "{} - {} mod {}", a, b, m);
+                        assert_eq!(BigUint::from(a.subm(b, &m)), expected);
+                    }
+                }
+            }
+
+            // operands much larger than the modulus
+            let m = BigUint::from(7u8);
+            let a = BigUint::one() << 300u16;
+            let b = (BigUint::one() << 301u16) + 3u8;
+            assert_eq!((&a).subm(&b, &m), (&a + &m * &b - &b) % &m);
+            assert!((&a).negm(&m) < m);
+            assert!(BigUint::zero().negm(&m).is_zero());
+            assert!((&m).negm(&m).is_zero());
+        }
+
         #[test]
         fn test_against_prim() {
             for _ in 0..NRANDOM {
@@ -409,5 +547,413 @@ mod _num_bigint {
                 assert_eq!(ra.kronecker(rm), a.kronecker(&m));
             }
         }
+
+        #[test]
+        fn bigint_ops_test() {
+            // against the signed primitives
+            for _ in 0..NRANDOM {
+                let (a, b) = (random::<i64>(), random::<i64>());
+                let m = (random::<i64>() >> 1).max(1);
+                let (ra, rb, rm) = (&BigInt::from(a), &BigInt::from(b), &BigInt::from(m));
+                assert_eq!(ra.addm(rb, rm), a.addm(b, &m).into());
+                assert_eq!(ra.subm(rb, rm), a.subm(b, &m).into());
+                assert_eq!(ra.mulm(rb, rm), a.mulm(b, &m).into());
+                assert_eq!(ra.negm(rm), a.negm(&m).into());
+                assert_eq!(ra.dblm(rm), a.dblm(&m).into());
+                assert_eq!(ra.sqm(rm), a.sqm(&m).into());
+                assert_eq!(ra.invm(rm), a.invm(&m).map(BigInt::from));
+
+                let e = random::<i8>();
+                if e >= 0 || ra.invm(rm).is_some() {
+                    assert_eq!(ra.powm(BigInt::from(e), rm), a.powm(e as i64, &m).into());
+                }
+            }
+
+            // operands beyond the primitive range
+            let m = (BigInt::one() << 127u8) - 1u8;
+            let a = -(BigInt::one() << 300u16) - 5u8;
+            let r = a.mod_floor(&m);
+            assert_eq!((&a).negm(&m), (-&r).mod_floor(&m));
+            assert_eq!((&a).addm(&a, &m), (&r + &r).mod_floor(&m));
+            assert_eq!((&a).powm(BigInt::from(-1), &m), (&a).invm(&m).unwrap());
+            assert!(((&a).invm(&m).unwrap() * &r).mod_floor(&m).is_one());
+        }
+
+        #[test]
+        #[should_panic]
+        fn bigint_negative_modulus_test() {
+            BigInt::one().addm(BigInt::one(), &BigInt::from(-5));
+        }
+
+        #[test]
+        fn kronecker_test() {
+            // degenerate cases
+            for a in [0u8, 1, 2, 5].iter() {
+                let a = BigUint::from(*a);
+                assert_eq!(a.kronecker(&BigUint::one()), 1);
+                assert_eq!(a.kronecker(&BigUint::zero()), a.is_one() as i8);
+            }
+            for a in [0i8, 1, -1, 2, -2, 5].iter() {
+                let a = BigInt::from(*a);
+                assert_eq!(a.kronecker(&BigInt::one()), 1);
+                assert_eq!(a.kronecker(&BigInt::zero()), a.magnitude().is_one() as i8);
+            }
+
+            // multiplicativity in both arguments
+            for _ in 0..1000 {
+                let a = BigInt::from(random::<i64>());
+                let b = BigInt::from(random::<i64>());
+                let m = BigInt::from(random::<i64>());
+                let n = BigInt::from(random::<i64>());
+                assert_eq!((&a * &b).kronecker(&n), a.kronecker(&n) * b.kronecker(&n));
+                assert_eq!(a.kronecker(&(&m * &n)), a.kronecker(&m) * a.kronecker(&n));
+
+                let (a, b) = (a.magnitude(), b.magnitude());
+                let n = n.magnitude();
+                assert_eq!((a * b).kronecker(n), a.kronecker(n) * b.kronecker(n));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ibig")]
+mod _ibig {
+    use super::*;
+    use ibig::modular::ModuloRing;
+    use ibig::ops::UnsignedAbs;
+    use ibig::{IBig, UBig};
+
+    // the lowest three bits of n, i.e. n mod 8
+    #[inline]
+    fn low3(n: &UBig) -> u8 {
+        n.bit(0) as u8 | (n.bit(1) as u8) << 1 | (n.bit(2) as u8) << 2
+    }
+
+    impl ModularCoreOps<&UBig, &UBig> for &UBig {
+        type Output = UBig;
+
+        #[inline]
+        fn addm(self, rhs: &UBig, m: &UBig) -> UBig {
+            (self + rhs) % m
+        }
+        fn subm(self, rhs: &UBig, m: &UBig) -> UBig {
+            let (lhs, rhs) = (self % m, rhs % m);
+            if lhs >= rhs {
+                lhs - rhs
+            } else {
+                m - (rhs - lhs)
+            }
+        }
+        #[inline]
+        fn mulm(self, rhs: &UBig, m: &UBig) -> UBig {
+            (self % m) * (rhs % m) % m
+        }
+    }
+
+    impl ModularUnaryOps<&UBig> for &UBig {
+        type Output = UBig;
+        #[inline]
+        fn negm(self, m: &UBig) -> UBig {
+            let x = self % m;
+            if x == UBig::from(0u8) {
+                x
+            } else {
+                m - x
+            }
+        }
+        #[inline]
+        fn invm(self, m: &UBig) -> Option<UBig> {
+            let ring = ModuloRing::new(m);
+            ring.from(self).inverse().map(|x| x.residue())
+        }
+        #[inline]
+        fn dblm(self, m: &UBig) -> UBig {
+            let d = (self % m) << 1;
+            if &d >= m {
+                d - m
+            } else {
+                d
+            }
+        }
+        #[inline]
+        fn sqm(self, m: &UBig) -> UBig {
+            let x = self % m;
+            &x * &x % m
+        }
+    }
+
+    impl ModularPow<&UBig, &UBig> for &UBig {
+        type Output = UBig;
+        #[inline]
+        fn powm(self, exp: &UBig, m: &UBig) -> UBig {
+            // the ring is based on Montgomery multiplication for odd moduli
+            let ring = ModuloRing::new(m);
+            ring.from(self).pow(exp).residue()
+        }
+    }
+
+    impl ModularSymbols<&UBig> for UBig {
+        #[inline]
+        fn checked_legendre(&self, n: &UBig) -> Option<i8> {
+            let r = self.powm((n - 1u8) >> 1, n);
+            if r == UBig::from(0u8) {
+                Some(0)
+            } else if r == UBig::from(1u8) {
+                Some(1)
+            } else if &(r + 1u8) == n {
+                Some(-1)
+            } else {
+                None
+            }
+        }
+
+        fn checked_jacobi(&self, n: &UBig) -> Option<i8> {
+            if !n.bit(0) {
+                return None;
+            }
+            let (zero, one) = (UBig::from(0u8), UBig::from(1u8));
+            if n == &one {
+                return Some(1);
+            }
+
+            let mut a = self % n;
+            let mut n = n.clone();
+            let mut t = 1;
+            while a > zero {
+                while !a.bit(0) {
+                    a >>= 1;
+                    if low3(&n) == 3 || low3(&n) == 5 {
+                        t = -t;
+                    }
+                }
+                core::mem::swap(&mut a, &mut n);
+                if low3(&a) & 3 == 3 && low3(&n) & 3 == 3 {
+                    t = -t;
+                }
+                a %= &n;
+            }
+            Some(if n == one { t } else { 0 })
+        }
+
+        fn kronecker(&self, n: &UBig) -> i8 {
+            let one = UBig::from(1u8);
+            let f = match n.trailing_zeros() {
+                None => return if self == &one { 1 } else { 0 },
+                Some(f) => f,
+            };
+            if n == &one {
+                return 1;
+            }
+
+            // (a/2) = 0 for even a, 1 for a = ±1 (mod 8) and -1 otherwise
+            let t1: i8 = match low3(self) {
+                1 | 7 => 1,
+                3 | 5 => -1,
+                _ => 0,
+            };
+            let t2 = self.jacobi(&(n >> f));
+            t1.pow(f.try_into().unwrap()) * t2
+        }
+    }
+
+    impl_mod_ops_by_ref!(UBig);
+
+    impl ModularAbs<UBig> for IBig {
+        fn absm(self, m: &UBig) -> UBig {
+            normalize(&self, m)
+        }
+    }
+
+    // signed operands are normalized into [0, m) and the operations are forwarded
+    // to UBig, the modulus must be positive
+    #[inline]
+    fn positive_modulus(m: &IBig) -> UBig {
+        assert!(m > &IBig::from(0u8), "the modulus should be positive");
+        m.unsigned_abs()
+    }
+
+    #[inline]
+    fn normalize(a: &IBig, m: &UBig) -> UBig {
+        let r = a.unsigned_abs() % m;
+        if a < &IBig::from(0u8) {
+            r.negm(m)
+        } else {
+            r
+        }
+    }
+
+    impl ModularCoreOps<&IBig, &IBig> for &IBig {
+        type Output = IBig;
+
+        #[inline]
+        fn addm(self, rhs: &IBig, m: &IBig) -> IBig {
+            let m = &positive_modulus(m);
+            normalize(self, m).addm(&normalize(rhs, m), m).into()
+        }
+        #[inline]
+        fn subm(self, rhs: &IBig, m: &IBig) -> IBig {
+            let m = &positive_modulus(m);
+            normalize(self, m).subm(&normalize(rhs, m), m).into()
+        }
+        #[inline]
+        fn mulm(self, rhs: &IBig, m: &IBig) -> IBig {
+            let m = &positive_modulus(m);
+            normalize(self, m).mulm(&normalize(rhs, m), m).into()
+        }
+    }
+
+    impl ModularUnaryOps<&IBig> for &IBig {
+        type Output = IBig;
+
+        #[inline]
+        fn negm(self, m: &IBig) -> IBig {
+            let m = &positive_modulus(m);
+            normalize(self, m).negm(m).into()
+        }
+        #[inline]
+        fn invm(self, m: &IBig) -> Option<IBig> {
+            let m = &positive_modulus(m);
+            normalize(self, m).invm(m).map(IBig::from)
+        }
+        #[inline]
+        fn dblm(self, m: &IBig) -> IBig {
+            let m = &positive_modulus(m);
+            normalize(self, m).dblm(m).into()
+        }
+        #[inline]
+        fn sqm(self, m: &IBig) -> IBig {
+            let m = &positive_modulus(m);
+            normalize(self, m).sqm(m).into()
+        }
+    }
+
+    impl ModularPow<&IBig, &IBig> for &IBig {
+        type Output = IBig;
+
+        // negative exponents are supported only if self is invertible
+        fn powm(self, exp: &IBig, m: &IBig) -> IBig {
+            let m = &positive_modulus(m);
+            let base = normalize(self, m);
+            if exp < &IBig::from(0u8) {
+                base.invm(m)
+                    .expect("the base should be invertible for negative exponents")
+                    .powm(exp.unsigned_abs(), m)
+                    .into()
+            } else {
+                base.powm(exp.unsigned_abs(), m).into()
+            }
+        }
+    }
+
+    impl ModularSymbols<&IBig> for IBig {
+        #[inline]
+        fn checked_legendre(&self, n: &IBig) -> Option<i8> {
+            if n < &IBig::from(1u8) {
+                return None;
+            }
+            let n = n.unsigned_abs();
+            normalize(self, &n).checked_legendre(&n)
+        }
+
+        fn checked_jacobi(&self, n: &IBig) -> Option<i8> {
+            if n < &IBig::from(1u8) {
+                return None;
+            }
+            let n = n.unsigned_abs();
+            normalize(self, &n).checked_jacobi(&n)
+        }
+
+        fn kronecker(&self, n: &IBig) -> i8 {
+            let negative = self < &IBig::from(0u8);
+            if n < &IBig::from(0u8) {
+                // (a/-1) = -1 for negative a and 1 otherwise
+                let t = if negative { -1 } else { 1 };
+                return t * self.kronecker(&IBig::from(n.unsigned_abs()));
+            }
+
+            // n is non-negative from now on
+            let n = n.unsigned_abs();
+            let one = UBig::from(1u8);
+            let f = match n.trailing_zeros() {
+                None => return if self.unsigned_abs() == one { 1 } else { 0 },
+                Some(f) => f,
+            };
+            if n == one {
+                return 1;
+            }
+
+            // (a/2) depends on a mod 8, and (a/n) = (a mod n / n) for odd n
+            let t1: i8 = match low3(&normalize(self, &UBig::from(8u8))) {
+                1 | 7 => 1,
+                3 | 5 => -1,
+                _ => 0,
+            };
+            let n = n >> f;
+            let t2 = normalize(self, &n).jacobi(&n);
+            t1.pow(f.try_into().unwrap()) * t2
+        }
+    }
+
+    impl_mod_ops_by_ref!(IBig);
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use rand::random;
+
+        const NRANDOM: u32 = 10; // number of random tests to run
+
+        #[test]
+        fn ubig_against_prim_test() {
+            for _ in 0..NRANDOM {
+                let (a, b, e) = (random::<u128>(), random::<u128>(), random::<u16>() as u128);
+                let m = random::<u128>() >> (random::<u8>() % 128);
+                let m = m.max(1);
+                let (ra, rb, re, rm) = (UBig::from(a), UBig::from(b), UBig::from(e), UBig::from(m));
+                assert_eq!((&ra).addm(&rb, &rm), UBig::from(a.addm(b, &m)));
+                assert_eq!((&ra).subm(&rb, &rm), UBig::from(a.subm(b, &m)));
+                assert_eq!((&ra).mulm(&rb, &rm), UBig::from(a.mulm(b, &m)));
+                assert_eq!((&ra).negm(&rm), UBig::from(a.negm(&m)));
+                assert_eq!((&ra).dblm(&rm), UBig::from(a.dblm(&m)));
+                assert_eq!((&ra).sqm(&rm), UBig::from(a.sqm(&m)));
+                assert_eq!((&ra).powm(&re, &rm), UBig::from(a.powm(e, &m)));
+                assert_eq!((&ra).invm(&rm), a.invm(&m).map(UBig::from));
+                assert_eq!(ra.kronecker(&rm), a.kronecker(&m));
+                assert_eq!(ra.kronecker(&rb), a.kronecker(&b));
+                if m & 1 == 1 {
+                    assert_eq!(ra.jacobi(&rm), a.jacobi(&m));
+                }
+            }
+
+            // beyond the primitive range
+            let p = (UBig::from(1u8) << 127) - 1u8;
+            let x = UBig::from(random::<u128>()) * random::<u128>();
+            assert_eq!((&x).powm(&p - 1u8, &p), UBig::from(1u8));
+            assert_eq!((&x).mulm((&x).invm(&p).unwrap(), &p), UBig::from(1u8));
+            assert_eq!(UBig::from(3u8).legendre(&p), -1);
+        }
+
+        #[test]
+        fn ibig_against_prim_test() {
+            for _ in 0..NRANDOM {
+                let (a, b, e) = (random::<i64>(), random::<i64>(), random::<i8>() as i64);
+                let m = (random::<u32>() as i64).max(1);
+                let (ra, rb, re, rm) = (IBig::from(a), IBig::from(b), IBig::from(e), IBig::from(m));
+                assert_eq!((&ra).addm(&rb, &rm), IBig::from(a.addm(b, &m)));
+                assert_eq!((&ra).subm(&rb, &rm), IBig::from(a.subm(b, &m)));
+                assert_eq!((&ra).mulm(&rb, &rm), IBig::from(a.mulm(b, &m)));
+                assert_eq!((&ra).negm(&rm), IBig::from(a.negm(&m)));
+                assert_eq!((&ra).invm(&rm), a.invm(&m).map(IBig::from));
+                if e >= 0 || a.invm(&m).is_some() {
+                    assert_eq!((&ra).powm(&re, &rm), IBig::from(a.powm(e, &m)));
+                }
+                assert_eq!(
+                    ra.clone().absm(&UBig::from(m as u64)),
+                    UBig::from(a.absm(&(m as u64)))
+                );
+                assert_eq!(ra.kronecker(&rb), a.kronecker(&b));
+                assert_eq!(ra.kronecker(&rm), a.kronecker(&m));
+            }
+        }
     }
 }
@@ -0,0 +1,280 @@
+//! Factorials and binomial coefficients modulo a prime
+
+use crate::{AutoReducer, Reducer};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Precomputed tables of the factorials and their inverses modulo a prime p, which answer
+/// the binomial coefficients `C(n, k) mod p` with two multiplications.
+///
+/// The tables are built with `O(n)` multiplications and a single modular inverse, the
+/// entries are stored in the reduced form of the reducer `R`. The default [AutoReducer] uses
+/// [Montgomery][crate::Montgomery] for odd primes and also accepts p = 2.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::Binomial;
+///
+/// let table = Binomial::<u64>::new(1000, &1_000_000_007);
+/// assert_eq!(table.binom(10, 3), 120);
+/// assert_eq!(table.binom(1000, 500), 159835829);
+/// assert_eq!(table.factorial(20), 146326063); // 20! mod 1e9+7
+/// ```
+///
+/// For a small prime p, the tables up to `p - 1` also answer `C(n, k) mod p` for arbitrarily
+/// large n with [binom_lucas()][Binomial::binom_lucas].
+#[derive(Debug, Clone)]
+pub struct Binomial<T, R = AutoReducer<T>> {
+    fact: Vec<T>,
+    inv_fact: Vec<T>,
+    r: R,
+}
+
+impl<T: Clone + PartialEq + From<u8>, R: Reducer<T>> Binomial<T, R> {
+    /// Build the tables of `0!, 1!, ..., n!` and their inverses modulo a prime p.
+    ///
+    /// # Panics
+    /// if `n!` is not invertible modulo p, i.e. `n >= p` or p is not a prime
+    pub fn new(n: usize, p: &T) -> Self {
+        let r = R::new(p);
+        let one = r.transform(T::from(1));
+
+        let mut fact = Vec::with_capacity(n + 1);
+        fact.push(one.clone());
+        let mut i = one.clone();
+        for _ in 0..n {
+            let next = r.mul(fact.last().unwrap(), &i);
+            fact.push(next);
+            r.add_in_place(&mut i, &one);
+        }
+
+        // (i-1)!^-1 = i!^-1 * i
+        let mut inv_fact = Vec::with_capacity(n + 1);
+        inv_fact.push(
+            r.inv(fact[n].clone())
+                .expect("the factorials should be invertible modulo p"),
+        );
+        for _ in 0..n {
+            r.sub_in_place(&mut i, &one);
+            let next = r.mul(inv_fact.last().unwrap(), &i);
+            inv_fact.push(next);
+        }
+        inv_fact.reverse();
+
+        Self { fact, inv_fact, r }
+    }
+
+    /// The largest n covered by the tables
+    #[inline]
+    pub fn max_n(&self) -> usize {
+        self.fact.len() - 1
+    }
+
+    /// The prime modulus of the tables
+    #[inline]
+    pub fn modulus(&self) -> T {
+        self.r.modulus()
+    }
+
+    /// Get `n! mod p`
+    ///
+    /// # Panics
+    /// if n is out of the tables
+    #[inline]
+    pub fn factorial(&self, n: usize) -> T {
+        self.r.residue(self.fact[n].clone())
+    }
+
+    /// Get `(n!)^-1 mod p`
+    ///
+    /// # Panics
+    /// if n is out of the tables
+    #[inline]
+    pub fn inv_factorial(&self, n: usize) -> T {
+        self.r.residue(self.inv_fact[n].clone())
+    }
+
+    /// Get `n^-1 mod p` for `1 <= n <= max_n`, as `(n-1)! / n!`
+    ///
+    /// # Panics
+    /// if n is zero or out of the tables
+    #[inline]
+    pub fn inv(&self, n: usize) -> T {
+        assert!(n > 0, "zero is not invertible");
+        self.r
+            .residue(self.r.mul(&self.fact[n - 1], &self.inv_fact[n]))
+    }
+
+    /// Calculate the binomial coefficient `C(n, k) mod p`, which is zero if `k > n`
+    ///
+    /// # Panics
+    /// if n is out of the tables
+    pub fn binom(&self, n: usize, k: usize) -> T {
+        if k > n {
+            return T::from(0);
+        }
+        let v = self.r.mul(&self.fact[n], &self.inv_fact[k]);
+        self.r.residue(self.r.mul(&v, &self.inv_fact[n - k]))
+    }
+
+    /// Calculate the number of k-permutations `P(n, k) = n! / (n-k)! mod p`, which is zero
+    /// if `k > n`
+    ///
+    /// # Panics
+    /// if n is out of the tables
+    pub fn perm(&self, n: usize, k: usize) -> T {
+        if k > n {
+            return T::from(0);
+        }
+        self.r
+            .residue(self.r.mul(&self.fact[n], &self.inv_fact[n - k]))
+    }
+
+    /// Calculate the binomial coefficient `C(n, k) mod p` by Lucas' theorem, i.e. the
+    /// product of `C(nᵢ, kᵢ)` for the base p digits `nᵢ` and `kᵢ` of n and k. It takes
+    /// `O(log_p(n))` multiplications and supports any n, but the tables should be built
+    /// up to `p - 1` (with `Binomial::new(p - 1, &p)`) to cover all the digits.
+    ///
+    /// # Panics
+    /// if a digit of n is out of the tables
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::Binomial;
+    ///
+    /// let table = Binomial::<u32>::new(12, &13);
+    /// assert_eq!(table.binom_lucas(1000, 300), 10);
+    /// assert_eq!(table.binom_lucas(10u64.pow(18), 10u64.pow(9)), 0);
+    /// ```
+    pub fn binom_lucas(&self, mut n: u64, mut k: u64) -> T
+    where
+        u64: TryFrom<T>,
+    {
+        let p = u64::try_from(self.r.modulus())
+            .ok()
+            .expect("the modulus should fit in u64");
+        let mut result = self.r.transform(T::from(1));
+        while k > 0 {
+            let (ni, ki) = ((n % p) as usize, (k % p) as usize);
+            if ki > ni {
+                return T::from(0);
+            }
+            assert!(ni <= self.max_n(), "the tables should cover p - 1");
+            self.r.mul_in_place(&mut result, &self.fact[ni]);
+            self.r.mul_in_place(&mut result, &self.inv_fact[ki]);
+            self.r.mul_in_place(&mut result, &self.inv_fact[ni - ki]);
+            n /= p;
+            k /= p;
+        }
+        self.r.residue(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModularCoreOps, Vanilla};
+
+    #[test]
+    fn pascal_test() {
+        let p = 998244353u64;
+        let table = Binomial::<u64>::new(300, &p);
+        assert_eq!(table.max_n(), 300);
+        assert_eq!(table.modulus(), p);
+
+        let mut row = std::vec![1u64];
+        for n in 0..=300 {
+            for k in 0..=n + 1 {
+                let expected = row.get(k).cloned().unwrap_or(0);
+                assert_eq!(table.binom(n, k), expected, "C({}, {})", n, k);
+            }
+            let mut next = std::vec![1u64; n + 2];
+            for k in 1..=n {
+                next[k] = row[k - 1].addm(row[k], &p);
+            }
+            row = next;
+        }
+    }
+
+    #[test]
+    fn factorial_test() {
+        let p = 1_000_000_007u32;
+        let table = Binomial::<u32, Vanilla<u32>>::new(1000, &p);
+        let mut f = 1u32;
+        for n in 0..=1000 {
+            if n > 0 {
+                f = f.mulm(n as u32, &p);
+                assert_eq!(table.inv(n).mulm(n as u32, &p), 1);
+            }
+            assert_eq!(table.factorial(n), f);
+            assert_eq!(table.inv_factorial(n).mulm(f, &p), 1);
+        }
+        assert_eq!(table.perm(10, 3), 720);
+        assert_eq!(table.perm(3, 10), 0);
+
+        // the largest table for a small prime
+        let table = Binomial::<u8>::new(250, &251);
+        assert_eq!(table.factorial(250), 250); // Wilson's theorem
+        assert_eq!(table.binom(250, 125), 250); // C(p-1, k) = (-1)^k
+    }
+
+    #[test]
+    fn lucas_test() {
+        for &p in [2u64, 3, 7, 13, 251].iter() {
+            let table = Binomial::<u64>::new(p as usize - 1, &p);
+            let mut row = std::vec![1u64];
+            for n in 0..600u64 {
+                for k in 0..=n + 1 {
+                    let expected = row.get(k as usize).cloned().unwrap_or(0);
+                    assert_eq!(
+                        table.binom_lucas(n, k),
+                        expected,
+                        "C({}, {}) mod {}",
+                        n,
+                        k,
+                        p
+                    );
+                }
+                let mut next = std::vec![1u64; n as usize + 2];
+                for k in 1..=n as usize {
+                    next[k] = row[k - 1].addm(row[k], &p);
+                }
+                row = next;
+            }
+
+            // C(n*p^a, k*p^a) = C(n, k) mod p
+            let pa = p.pow(5);
+            assert_eq!(
+                table.binom_lucas(100 * pa, 37 * pa),
+                table.binom_lucas(100, 37)
+            );
+            assert_eq!(table.binom_lucas(u64::MAX, 0), 1);
+        }
+
+        // p = 2 with the default reducer: C(n, k) is odd iff k is a submask of n
+        let table = Binomial::<u32>::new(1, &2);
+        assert_eq!(table.modulus(), 2);
+        assert_eq!(table.binom(1, 1), 1);
+        for n in 0..64u64 {
+            for k in 0..=n {
+                assert_eq!(table.binom_lucas(n, k), (k & !n == 0) as u32);
+            }
+        }
+
+        // the tables larger than p - 1 are fine
+        let table = Binomial::<u32, Vanilla<u32>>::new(1000, &1_000_000_007);
+        assert_eq!(table.binom_lucas(1000, 500), table.binom(1000, 500));
+    }
+
+    #[test]
+    #[should_panic]
+    fn lucas_small_table_test() {
+        Binomial::<u32>::new(5, &13).binom_lucas(100, 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_large_test() {
+        Binomial::<u8>::new(251, &251);
+    }
+}
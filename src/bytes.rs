@@ -0,0 +1,347 @@
+//! Reduction of integers encoded in byte buffers
+
+use crate::{ModularCoreOps, ReducedInt, Reducer};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Streaming reducer of a big-endian integer given as a byte stream.
+///
+/// The bytes are consumed 8 at a time, and for each 64-bit limb the accumulator is
+/// updated as `acc = (acc * 2^64 + limb) mod m`, with `2^64 mod m` precomputed.
+/// So the whole integer is never materialized.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ModReducer, reduce_bytes};
+///
+/// let bytes = [0x12u8; 100];
+/// let mut r = ModReducer::<u64>::new(1_000_000_007);
+/// r.update(&bytes[..33]);
+/// r.update(&bytes[33..]);
+/// assert_eq!(r.finalize(), reduce_bytes(&bytes, &1_000_000_007u64));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ModReducer<T> {
+    m: T,
+    r64: T,       // 2^64 mod m
+    acc: T,       // the reduced value of the consumed limbs
+    pending: u64, // the bytes not yet forming a full limb
+    npending: u8,
+}
+
+/// Integer types supported by [reduce_bytes()]
+pub trait ReduceBytes: Sized {
+    /// Reduce a big-endian integer, see [reduce_bytes()]
+    fn reduce_bytes(bytes: &[u8], m: &Self) -> Self;
+
+    /// Reduce a little-endian integer, see [reduce_bytes_le()]
+    fn reduce_bytes_le(bytes: &[u8], m: &Self) -> Self;
+}
+
+/// Reduce the integer encoded in big-endian bytes modulo m, without converting it
+/// to a big integer first. See [ModReducer] for the streaming version.
+///
+/// # Panics
+/// if m is zero
+#[inline]
+pub fn reduce_bytes<T: ReduceBytes>(bytes: &[u8], m: &T) -> T {
+    T::reduce_bytes(bytes, m)
+}
+
+/// Reduce the integer encoded in little-endian bytes modulo m, without converting it
+/// to a big integer first.
+///
+/// # Panics
+/// if m is zero
+#[inline]
+pub fn reduce_bytes_le<T: ReduceBytes>(bytes: &[u8], m: &T) -> T {
+    T::reduce_bytes_le(bytes, m)
+}
+
+macro_rules! impl_reduce_bytes_for {
+    ($($T:ty)*) => {$(
+        impl ModReducer<$T> {
+            /// Create a reducer with modulus m
+            ///
+            /// # Panics
+            /// if m is zero
+            pub fn new(m: $T) -> Self {
+                assert!(m > 0, "the modulus should not be zero");
+                let r64 = ((u64::MAX as u128 % m as u128 + 1) % m as u128) as $T;
+                Self {
+                    m,
+                    r64,
+                    acc: 0,
+                    pending: 0,
+                    npending: 0,
+                }
+            }
+
+            /// The modulus of the reducer
+            #[inline]
+            pub fn modulus(&self) -> $T {
+                self.m
+            }
+
+            // acc = acc * 2^(8*nbytes) + limb mod m, where limb < 2^(8*nbytes)
+            #[inline]
+            fn push_limb(&self, acc: $T, limb: u64, nbytes: usize) -> $T {
+                let shift = if nbytes == 8 {
+                    self.r64
+                } else {
+                    ((1u128 << (8 * nbytes)) % self.m as u128) as $T
+                };
+                let limb = (limb as u128 % self.m as u128) as $T;
+                acc.mulm(shift, &self.m).addm(limb, &self.m)
+            }
+
+            /// Append the bytes to the end of the big-endian integer
+            pub fn update(&mut self, mut bytes: &[u8]) {
+                // fill the pending limb first
+                while self.npending > 0 && !bytes.is_empty() {
+                    self.pending = (self.pending << 8) | bytes[0] as u64;
+                    self.npending += 1;
+                    bytes = &bytes[1..];
+                    if self.npending == 8 {
+                        self.acc = self.push_limb(self.acc, self.pending, 8);
+                        self.pending = 0;
+                        self.npending = 0;
+                    }
+                }
+
+                let mut chunks = bytes.chunks_exact(8);
+                for chunk in &mut chunks {
+                    let mut limb = [0u8; 8];
+                    limb.copy_from_slice(chunk);
+                    self.acc = self.push_limb(self.acc, u64::from_be_bytes(limb), 8);
+                }
+                for &b in chunks.remainder() {
+                    self.pending = (self.pending << 8) | b as u64;
+                    self.npending += 1;
+                }
+            }
+
+            /// Get the residue of the integer formed by all the bytes consumed
+            pub fn finalize(&self) -> $T {
+                if self.npending == 0 {
+                    self.acc
+                } else {
+                    self.push_limb(self.acc, self.pending, self.npending as usize)
+                }
+            }
+        }
+
+        impl ReduceBytes for $T {
+            #[inline]
+            fn reduce_bytes(bytes: &[u8], m: &$T) -> $T {
+                let mut r = ModReducer::<$T>::new(*m);
+                r.update(bytes);
+                r.finalize()
+            }
+
+            fn reduce_bytes_le(bytes: &[u8], m: &$T) -> $T {
+                // the most significant (full) limbs come first in rchunks,
+                // only the last chunk can be shorter than 8 bytes
+                let r = ModReducer::<$T>::new(*m);
+                let mut acc = 0;
+                for chunk in bytes.rchunks(8) {
+                    let mut limb = [0u8; 8];
+                    limb[..chunk.len()].copy_from_slice(chunk);
+                    acc = r.push_limb(acc, u64::from_le_bytes(limb), chunk.len());
+                }
+                acc
+            }
+        }
+    )*};
+}
+impl_reduce_bytes_for!(u8 u16 u32 u64 u128 usize);
+
+macro_rules! impl_le_bytes_for {
+    ($($T:ty)*) => {$(
+        impl<R: Reducer<$T>> ReducedInt<$T, R> {
+            /// The length of the canonical byte encoding, which is `ceil(bits(m) / 8)`
+            /// and only depends on the modulus m.
+            #[inline]
+            pub fn encoded_len(&self) -> usize {
+                let m = self.reducer().modulus();
+                ((<$T>::BITS - m.leading_zeros() + 7) / 8) as usize
+            }
+
+            /// Encode the residue in exactly [encoded_len()][Self::encoded_len] little-endian bytes.
+            ///
+            /// The encoding is canonical: it only depends on the residue and the modulus, not
+            /// on the reducer or the width of the integer type.
+            ///
+            /// Example code:
+            /// ```rust
+            /// use num_modular::{ModularInteger, MontgomeryInt};
+            ///
+            /// let x = MontgomeryInt::new(1000u32, &65537);
+            /// assert_eq!(x.to_le_bytes(), [0xe8, 0x03, 0x00]);
+            /// let y = MontgomeryInt::<u64>::from_le_bytes(&[0xe8, 0x03, 0x00], &65537).unwrap();
+            /// assert_eq!(y.residue(), 1000);
+            /// ```
+            #[cfg(feature = "alloc")]
+            pub fn to_le_bytes(&self) -> Vec<u8> {
+                let residue = self.reducer().residue(*self.repr());
+                residue.to_le_bytes()[..self.encoded_len()].to_vec()
+            }
+
+            /// Decode the canonical little-endian encoding of a residue modulo m, see
+            /// [to_le_bytes()][Self::to_le_bytes].
+            ///
+            /// Return [None] if the length of the bytes is not `ceil(bits(m) / 8)`, or if the
+            /// encoded integer is not smaller than m (i.e. it's not canonical).
+            ///
+            /// # Panics
+            /// if m is not a valid modulus of the reducer
+            pub fn from_le_bytes(bytes: &[u8], m: &$T) -> Option<Self> {
+                let len = ((<$T>::BITS - m.leading_zeros() + 7) / 8) as usize;
+                if bytes.len() != len {
+                    return None;
+                }
+                let mut buf = [0u8; core::mem::size_of::<$T>()];
+                buf[..len].copy_from_slice(bytes);
+                let n = <$T>::from_le_bytes(buf);
+                if n >= *m {
+                    return None;
+                }
+                Some(Self::new(n, m))
+            }
+        }
+    )*};
+}
+impl_le_bytes_for!(u8 u16 u32 u64 u128 usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::random;
+    use std::vec::Vec;
+
+    const LENGTHS: [usize; 10] = [0, 1, 2, 7, 8, 9, 15, 16, 17, 64];
+
+    fn random_bytes(len: usize) -> Vec<u8> {
+        (0..len).map(|_| random()).collect()
+    }
+
+    #[test]
+    fn reduce_bytes_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for &len in LENGTHS.iter().take(8) {
+                    let bytes = random_bytes(len);
+                    let m = random::<$T>().max(1);
+
+                    // the integers up to 16 bytes can be checked with u128
+                    let be = bytes.iter().fold(0u128, |acc, &b| (acc << 8) | b as u128);
+                    let le = bytes.iter().rev().fold(0u128, |acc, &b| (acc << 8) | b as u128);
+                    assert_eq!(reduce_bytes(&bytes, &m) as u128, be % m as u128);
+                    assert_eq!(reduce_bytes_le(&bytes, &m) as u128, le % m as u128);
+                    assert_eq!(reduce_bytes(&bytes, &(1 as $T)), 0);
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+
+        assert_eq!(reduce_bytes(&[0xff; 8], &u64::MAX), 0);
+        assert_eq!(reduce_bytes(&[0xff; 16], &u64::MAX), 0);
+        assert_eq!(reduce_bytes(&[0xff; 16], &u128::MAX), 0);
+        assert_eq!(reduce_bytes(&[1, 0, 0, 0, 0, 0, 0, 0, 0], &u64::MAX), 1);
+    }
+
+    #[test]
+    fn streaming_test() {
+        for _ in 0..10 {
+            let bytes = random_bytes(random::<usize>() % 200);
+            let m = random::<u64>().max(1);
+            let expected = reduce_bytes(&bytes, &m);
+
+            let mut r = ModReducer::<u64>::new(m);
+            let mut rest = &bytes[..];
+            while !rest.is_empty() {
+                let (head, tail) = rest.split_at(random::<usize>() % (rest.len() + 1));
+                r.update(head);
+                assert_eq!(
+                    r.finalize(),
+                    reduce_bytes(&bytes[..bytes.len() - tail.len()], &m)
+                );
+                rest = tail;
+            }
+            assert_eq!(r.finalize(), expected);
+            assert_eq!(r.modulus(), m);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "num-bigint")]
+    fn against_biguint_test() {
+        use num_bigint::BigUint;
+
+        let mut lengths = LENGTHS.to_vec();
+        lengths.push(random::<usize>() % 1000);
+        lengths.push(3 << 20); // multi-megabyte buffer
+        for &len in lengths.iter() {
+            let bytes = random_bytes(len);
+            let be = BigUint::from_bytes_be(&bytes);
+            let le = BigUint::from_bytes_le(&bytes);
+
+            let m = random::<u32>().max(1);
+            assert_eq!(BigUint::from(reduce_bytes(&bytes, &m)), &be % m);
+            assert_eq!(BigUint::from(reduce_bytes_le(&bytes, &m)), &le % m);
+            let m = random::<u64>().max(1);
+            assert_eq!(BigUint::from(reduce_bytes(&bytes, &m)), &be % m);
+            assert_eq!(BigUint::from(reduce_bytes_le(&bytes, &m)), &le % m);
+            let m = random::<u128>().max(1);
+            assert_eq!(BigUint::from(reduce_bytes(&bytes, &m)), &be % m);
+            assert_eq!(BigUint::from(reduce_bytes_le(&bytes, &m)), &le % m);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn le_bytes_test() {
+        use crate::{ModularInteger, MontgomeryInt, VanillaInt};
+
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..10 {
+                    let m = random::<$T>() | 1;
+                    let x = MontgomeryInt::new(random::<$T>(), &m);
+                    let bytes = x.to_le_bytes();
+                    assert_eq!(bytes.len(), x.encoded_len());
+                    assert_eq!(bytes.len(), ((<$T>::BITS - m.leading_zeros() + 7) / 8) as usize);
+                    assert_eq!(MontgomeryInt::<$T>::from_le_bytes(&bytes, &m), Some(x));
+
+                    // non-canonical and wrong-length encodings
+                    let len = bytes.len();
+                    let mb = m.to_le_bytes();
+                    assert!(MontgomeryInt::<$T>::from_le_bytes(&mb[..len], &m).is_none());
+                    let mut long = bytes.clone();
+                    long.push(0);
+                    assert!(MontgomeryInt::<$T>::from_le_bytes(&long, &m).is_none());
+                    assert!(MontgomeryInt::<$T>::from_le_bytes(&bytes[..len - 1], &m).is_none());
+
+                    // the encoding doesn't depend on the reducer or the width
+                    let v = VanillaInt::new(x.residue(), &m);
+                    assert_eq!(v.to_le_bytes(), bytes);
+                    let w = MontgomeryInt::new(x.residue() as u128, &(m as u128));
+                    assert_eq!(w.to_le_bytes(), bytes);
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+
+        // short moduli use fewer bytes than the integer type
+        let x = MontgomeryInt::new(3u64, &5);
+        assert_eq!(x.to_le_bytes(), [3]);
+        assert!(MontgomeryInt::<u64>::from_le_bytes(&[5], &5).is_none());
+        assert!(MontgomeryInt::<u64>::from_le_bytes(&[3, 0], &5).is_none());
+        assert!(MontgomeryInt::<u64>::from_le_bytes(&[], &5).is_none());
+        let x = MontgomeryInt::new(0u32, &1);
+        assert_eq!(x.to_le_bytes(), [0]);
+        assert_eq!(MontgomeryInt::<u32>::from_le_bytes(&[0], &1), Some(x));
+        let x = VanillaInt::new(0x1234u32, &0x10000);
+        assert_eq!(x.to_le_bytes(), [0x34, 0x12, 0]);
+    }
+}
@@ -0,0 +1,257 @@
+//! Chinese Remainder Theorem for moduli that are not necessarily coprime
+
+use crate::{linear_congruence, ModularCoreOps};
+use core::fmt;
+
+/// Errors of solving a system of congruences with [crt()]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrtError {
+    /// One of the moduli is zero
+    ZeroModulus,
+    /// The congruences have no common solution
+    Inconsistent,
+    /// The lcm of the moduli cannot be represented by the integer type
+    Overflow,
+}
+
+impl fmt::Display for CrtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CrtError::ZeroModulus => "the modulus should not be zero",
+            CrtError::Inconsistent => "the congruences are inconsistent",
+            CrtError::Overflow => "the lcm of the moduli overflows",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CrtError {}
+
+/// Integer types supported by [crt()] and [crt_pair()]
+pub trait Crt: Sized {
+    /// Combine two congruences, see [crt_pair()] for details
+    fn crt_pair(a1: &Self, m1: &Self, a2: &Self, m2: &Self) -> Result<(Self, Self), CrtError>;
+
+    /// Combine all the congruences, see [crt()] for details
+    fn crt<I: IntoIterator<Item = (Self, Self)>>(congruences: I) -> Result<(Self, Self), CrtError>;
+}
+
+/// Solve `x ≡ a1 (mod m1)` and `x ≡ a2 (mod m2)`, returning `(x, lcm(m1, m2))`
+/// with `0 <= x < lcm(m1, m2)`.
+///
+/// The moduli don't need to be coprime. A solution exists iff
+/// `a1 ≡ a2 (mod gcd(m1, m2))`, otherwise [CrtError::Inconsistent] is returned.
+/// The residues don't need to be reduced. The solution is `x = a1 + m1·k`, where k is
+/// found by [linear_congruence()][crate::linear_congruence()] modulo `m2 / gcd(m1, m2)`.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{crt_pair, CrtError};
+///
+/// assert_eq!(crt_pair(&2u32, &6, &5, &9), Ok((14, 18)));
+/// assert_eq!(crt_pair(&1u32, &6, &2, &9), Err(CrtError::Inconsistent));
+/// ```
+#[inline]
+pub fn crt_pair<T: Crt>(a1: &T, m1: &T, a2: &T, m2: &T) -> Result<(T, T), CrtError> {
+    T::crt_pair(a1, m1, a2, m2)
+}
+
+/// Solve the system of congruences `x ≡ a_i (mod m_i)` given as the `(a_i, m_i)` pairs,
+/// returning `(x, M)` where M is the lcm of all the moduli and `0 <= x < M`.
+///
+/// The congruences are combined one by one with [crt_pair()], so the moduli don't need
+/// to be pairwise coprime. An empty system gives `(0, 1)`.
+///
+/// # Errors
+/// - [CrtError::ZeroModulus] if any modulus is zero
+/// - [CrtError::Inconsistent] if the congruences have no common solution
+/// - [CrtError::Overflow] if the lcm of the moduli cannot be represented
+///
+/// Example code:
+/// ```rust
+/// use num_modular::crt;
+///
+/// // x = 2 (mod 3), x = 3 (mod 5), x = 2 (mod 7)
+/// assert_eq!(crt([(2u64, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+/// // the moduli 4 and 6 share the factor 2
+/// assert_eq!(crt(vec![(3u64, 4), (5, 6)]), Ok((11, 12)));
+/// ```
+#[inline]
+pub fn crt<T: Crt, I: IntoIterator<Item = (T, T)>>(congruences: I) -> Result<(T, T), CrtError> {
+    T::crt(congruences)
+}
+
+macro_rules! impl_crt_for {
+    ($($T:ty)*) => {$(
+        impl Crt for $T {
+            fn crt_pair(a1: &$T, m1: &$T, a2: &$T, m2: &$T) -> Result<($T, $T), CrtError> {
+                let (m1, m2) = (*m1, *m2);
+                if m1 == 0 || m2 == 0 {
+                    return Err(CrtError::ZeroModulus);
+                }
+                let a1 = a1 % m1;
+
+                // x = a1 + m1 * k, where m1 * k = a2 - a1 (mod m2)
+                let diff = a2.subm(a1, &m2);
+                let k = linear_congruence(&m1, &diff, &m2).ok_or(CrtError::Inconsistent)?;
+                let lcm = m1.checked_mul(k.stride).ok_or(CrtError::Overflow)?;
+                Ok((a1 + m1 * k.x, lcm))
+            }
+
+            fn crt<I: IntoIterator<Item = ($T, $T)>>(congruences: I) -> Result<($T, $T), CrtError> {
+                congruences
+                    .into_iter()
+                    .try_fold((0, 1), |(x, m), (a, n)| Self::crt_pair(&x, &m, &a, &n))
+            }
+        }
+    )*};
+}
+impl_crt_for!(u8 u16 u32 u64 u128 usize);
+
+#[cfg(feature = "num-bigint")]
+mod _num_bigint {
+    use super::*;
+    use num_bigint::BigUint;
+    use num_traits::{One, Zero};
+
+    impl Crt for BigUint {
+        fn crt_pair(
+            a1: &BigUint,
+            m1: &BigUint,
+            a2: &BigUint,
+            m2: &BigUint,
+        ) -> Result<(BigUint, BigUint), CrtError> {
+            if m1.is_zero() || m2.is_zero() {
+                return Err(CrtError::ZeroModulus);
+            }
+            let a1 = a1 % m1;
+            let diff = a2.subm(&a1, m2);
+            let k = linear_congruence(m1, &diff, m2).ok_or(CrtError::Inconsistent)?;
+            Ok((a1 + m1 * k.x, m1 * k.stride))
+        }
+
+        fn crt<I: IntoIterator<Item = (BigUint, BigUint)>>(
+            congruences: I,
+        ) -> Result<(BigUint, BigUint), CrtError> {
+            congruences
+                .into_iter()
+                .try_fold((BigUint::zero(), BigUint::one()), |(x, m), (a, n)| {
+                    Self::crt_pair(&x, &m, &a, &n)
+                })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use rand::random;
+
+        #[test]
+        fn biguint_crt_test() {
+            // against the primitive implementation
+            for _ in 0..10 {
+                let congruences: [(u64, u64); 3] = [
+                    (random::<u64>(), random::<u16>() as u64 + 1),
+                    (random::<u64>(), random::<u16>() as u64 + 1),
+                    (random::<u64>(), random::<u8>() as u64 + 1),
+                ];
+                let expected = crt(congruences).map(|(x, m)| (BigUint::from(x), BigUint::from(m)));
+                let big = crt(congruences
+                    .iter()
+                    .map(|&(a, m)| (BigUint::from(a), BigUint::from(m))));
+                assert_eq!(big, expected);
+            }
+
+            // moduli beyond the primitive range
+            let p = (BigUint::one() << 127u8) - 1u8;
+            let q = (BigUint::one() << 89u8) - 1u8;
+            let x = BigUint::from(random::<u128>()) * random::<u64>();
+            let (r, m) = crt([(&x % &p, p.clone()), (&x % &q, q.clone())]).unwrap();
+            assert_eq!(m, &p * &q);
+            assert_eq!(r, &x % m);
+
+            let (r, m) = crt([(&x % &p, p.clone()), (&x % &p, p.clone())]).unwrap();
+            assert_eq!((r, &m), (&x % &p, &p));
+            assert_eq!(
+                BigUint::crt_pair(&BigUint::zero(), &p, &BigUint::one(), &(&p * 2u8)),
+                Err(CrtError::Inconsistent)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::random;
+
+    #[test]
+    fn crt_pair_test() {
+        // exhaustive on small moduli against brute force
+        for m1 in 1..=24u32 {
+            for m2 in 1..=24u32 {
+                let lcm = (1..).map(|k| k * m1).find(|v| v % m2 == 0).unwrap();
+                for a1 in 0..m1 {
+                    for a2 in 0..m2 {
+                        let expected = (0..lcm).find(|x| x % m1 == a1 && x % m2 == a2);
+                        let result = crt_pair(&a1, &m1, &a2, &m2);
+                        match expected {
+                            Some(x) => assert_eq!(result, Ok((x, lcm))),
+                            None => assert_eq!(result, Err(CrtError::Inconsistent)),
+                        }
+                        // unreduced residues
+                        assert_eq!(crt_pair(&(a1 + 3 * m1), &m1, &(a2 + m2), &m2), result);
+                    }
+                }
+            }
+        }
+
+        assert_eq!(crt_pair(&1u8, &0, &1, &3), Err(CrtError::ZeroModulus));
+        assert_eq!(crt_pair(&1u8, &16, &1, &17), Err(CrtError::Overflow));
+        assert_eq!(crt_pair(&1u8, &15, &1, &17), Ok((1, 255)));
+        assert_eq!(
+            crt_pair(&254u8, &255, &0, &255),
+            Err(CrtError::Inconsistent)
+        );
+    }
+
+    #[test]
+    fn crt_iter_test() {
+        assert_eq!(crt(core::iter::empty::<(u32, u32)>()), Ok((0, 1)));
+        assert_eq!(crt([(5u32, 7)]), Ok((5, 7)));
+
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..10 {
+                    // random congruences of a common solution
+                    let x = random::<$T>();
+                    let moduli = [2 as $T, 9, 12, 35, 11, 10, 13];
+                    let mut lcm: $T = 1;
+                    let mut n = 0;
+                    for (i, &m) in moduli.iter().enumerate() {
+                        let (_, l) = crt_pair(&0, &lcm, &0, &m).unwrap();
+                        if l > <$T>::MAX / 16 {
+                            break;
+                        }
+                        lcm = l;
+                        n = i + 1;
+                    }
+                    let congruences = moduli[..n].iter().map(|&m| (x % m, m));
+                    assert_eq!(crt(congruences), Ok((x % lcm, lcm)));
+
+                    // an inconsistent pair
+                    let congruences = [(x % 9, 9 as $T), (x % 6 + 1, 6)];
+                    assert_eq!(crt(congruences), Err(CrtError::Inconsistent));
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+
+        // the lcm overflows
+        assert_eq!(
+            crt([(0u64, 1 << 40), (0, 3u64.pow(20)), (0, 5)]),
+            Err(CrtError::Overflow)
+        );
+        assert_eq!(crt([(1u64, 1 << 40), (0, 0)]), Err(CrtError::ZeroModulus));
+    }
+}
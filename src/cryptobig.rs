@@ -0,0 +1,439 @@
+//! Implementations of the modular operations for the fixed-width integers of `crypto-bigint`
+//!
+//! The operands are first reduced by the modulus, and then the arithmetics are done with the
+//! constant time primitives of `crypto-bigint` (`add_mod`, `sub_mod`, `neg_mod`, `mul_wide`
+//! and the conditional selection). The double-width products are reduced by a schoolbook
+//! division whose quotient limbs are estimated with a precomputed reciprocal and corrected
+//! without branching, so the running time only depends on the modulus. The exponentiation
+//! goes through all the bits of the exponent (the full width of the type). The modular
+//! inverse and the modular symbols are based on the Euclidean algorithm, which is NOT
+//! constant time.
+
+use crate::{ModularCoreOps, ModularPow, ModularSymbols, ModularUnaryOps};
+use crypto_bigint::subtle::{
+    Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeGreater, ConstantTimeLess,
+};
+use crypto_bigint::{Limb, LimbUInt, UInt, WideLimbUInt};
+
+/// Get the i-th bit of x as a [Choice]
+#[inline]
+fn bit<const L: usize>(x: &UInt<L>, i: usize) -> Choice {
+    let limb = x.limbs()[i / Limb::BIT_SIZE].0;
+    Choice::from(((limb >> (i % Limb::BIT_SIZE)) & 1) as u8)
+}
+
+/// Get the lowest limb of x
+#[inline]
+fn low<const L: usize>(x: &UInt<L>) -> LimbUInt {
+    x.limbs()[0].0
+}
+
+/// Calculate the reciprocal `floor((B^2 - 1) / d) - B` of a limb d with the highest bit set,
+/// where B is the base of the limbs
+#[inline]
+fn reciprocal(d: LimbUInt) -> LimbUInt {
+    (WideLimbUInt::MAX / d as WideLimbUInt - (1 << Limb::BIT_SIZE)) as LimbUInt
+}
+
+/// Calculate `floor((u1 * B + u0) / d)` for `u1 < d` with the reciprocal v of d (the 2-by-1
+/// division of Möller and Granlund), where the corrections are done by selection.
+#[inline]
+fn div_2by1(u1: LimbUInt, u0: LimbUInt, d: LimbUInt, v: LimbUInt) -> LimbUInt {
+    let q = (v as WideLimbUInt * u1 as WideLimbUInt)
+        .wrapping_add(((u1 as WideLimbUInt) << Limb::BIT_SIZE) | u0 as WideLimbUInt);
+    let (q1, q0) = (
+        ((q >> Limb::BIT_SIZE) as LimbUInt).wrapping_add(1),
+        q as LimbUInt,
+    );
+    let r = u0.wrapping_sub(q1.wrapping_mul(d));
+
+    // the estimation q1 is larger by one if r > q0, and smaller by one if r >= d after that
+    let c = r.ct_gt(&q0);
+    let q1 = LimbUInt::conditional_select(&q1, &q1.wrapping_sub(1), c);
+    let r = LimbUInt::conditional_select(&r, &r.wrapping_add(d), c);
+    LimbUInt::conditional_select(&q1, &q1.wrapping_add(1), !r.ct_lt(&d))
+}
+
+/// Calculate `(hi * B^L + lo) mod m` where `hi < m`, with the schoolbook division.
+///
+/// After normalizing m to have the highest bit set, each quotient limb is estimated from the
+/// top two limbs of the partial remainder and the top limb of m, which is larger than the
+/// actual one by at most 2. The partial remainder is then corrected by adding back m twice
+/// under the masks, so that the running time doesn't depend on the values of lo and hi.
+fn reduce_wide<const L: usize>(lo: &UInt<L>, hi: &UInt<L>, m: &UInt<L>) -> UInt<L> {
+    let s = L * Limb::BIT_SIZE - m.bits();
+    let d = m.shl_vartime(s).into_limbs();
+    let lo_limbs = lo.shl_vartime(s).into_limbs();
+    let mut r = hi
+        .shl_vartime(s)
+        .bitor(&lo.shr_vartime(L * Limb::BIT_SIZE - s))
+        .into_limbs();
+    let (dt, v) = (d[L - 1], reciprocal(d[L - 1].0));
+
+    for j in (0..L).rev() {
+        // estimate the quotient of (r * B + lo[j]) / d, which is at most B - 1
+        let top = r[L - 1];
+        let next = if L > 1 { r[L - 2] } else { lo_limbs[j] };
+        let saturated = top.ct_eq(&dt);
+        let u1 = Limb::conditional_select(&top, &Limb::ZERO, saturated);
+        let q = Limb(div_2by1(u1.0, next.0, dt.0, v));
+        let q = Limb::conditional_select(&q, &Limb::MAX, saturated);
+
+        // r * B + lo[j] - q * d, which is in [-2d, d)
+        let (mut carry, mut borrow) = (Limb::ZERO, Limb::ZERO);
+        let mut w = [Limb::ZERO; L];
+        for i in 0..L {
+            let u = if i == 0 { lo_limbs[j] } else { r[i - 1] };
+            let (p, c) = Limb::ZERO.mac(q, d[i], carry);
+            let (x, b) = u.sbb(p, borrow);
+            carry = c;
+            borrow = b;
+            w[i] = x;
+        }
+        let mut wt = r[L - 1].sbb(carry, borrow).0;
+
+        // add back d while the partial remainder is negative
+        for _ in 0..2 {
+            let negative = Choice::from((wt.0 >> (Limb::BIT_SIZE - 1)) as u8);
+            let mut carry = Limb::ZERO;
+            for i in 0..L {
+                let (x, c) = w[i].adc(
+                    Limb::conditional_select(&Limb::ZERO, &d[i], negative),
+                    carry,
+                );
+                w[i] = x;
+                carry = c;
+            }
+            wt = wt.wrapping_add(carry);
+        }
+        r = w;
+    }
+    UInt::new(r).shr_vartime(s)
+}
+
+/// Calculate `a * b mod m` by the wide multiplication and reduction, where a < m.
+#[inline]
+fn mul_reduced<const L: usize>(a: &UInt<L>, b: &UInt<L>, m: &UInt<L>) -> UInt<L> {
+    let (lo, hi) = a.mul_wide(b);
+    reduce_wide(&lo, &hi, m)
+}
+
+// The divisions of crypto-bigint (v0.3) are not used here, because they return wrong
+// results when the dividend is smaller than the divisor or has the highest bit set.
+
+/// Calculate `x mod m` in constant time
+#[inline]
+pub(crate) fn reduce<const L: usize>(x: &UInt<L>, m: &UInt<L>) -> UInt<L> {
+    assert!(m != &UInt::ZERO, "the modulus should not be zero");
+    reduce_wide(x, &UInt::ZERO, m)
+}
+
+/// Calculate the quotient and the remainder of `a / b` with the long division (not in constant time)
+fn div_rem<const L: usize>(a: &UInt<L>, b: &UInt<L>) -> (UInt<L>, UInt<L>) {
+    assert!(b != &UInt::ZERO, "the divisor should not be zero");
+    let (mut q, mut r) = (UInt::<L>::ZERO, UInt::<L>::ZERO);
+    for i in (0..a.bits()).rev() {
+        // r = 2r + bit, the carry is kept since r can be as large as b - 1
+        let (d, carry) = r.adc(&r, Limb::ZERO);
+        r = d.bitor(&UInt::from_u8(bit(a, i).unwrap_u8()));
+        q = q.shl_vartime(1);
+        if carry.0 != 0 || &r >= b {
+            r = r.wrapping_sub(b);
+            q = q.bitor(&UInt::ONE);
+        }
+    }
+    (q, r)
+}
+
+impl<const L: usize> ModularCoreOps<&UInt<L>, &UInt<L>> for UInt<L> {
+    type Output = UInt<L>;
+    #[inline]
+    fn addm(self, rhs: &UInt<L>, m: &UInt<L>) -> UInt<L> {
+        reduce(&self, m).add_mod(&reduce(rhs, m), m)
+    }
+    #[inline]
+    fn subm(self, rhs: &UInt<L>, m: &UInt<L>) -> UInt<L> {
+        reduce(&self, m).sub_mod(&reduce(rhs, m), m)
+    }
+    #[inline]
+    fn mulm(self, rhs: &UInt<L>, m: &UInt<L>) -> UInt<L> {
+        mul_reduced(&reduce(&self, m), rhs, m)
+    }
+}
+
+impl<const L: usize> ModularCoreOps<&UInt<L>, &UInt<L>> for &UInt<L> {
+    type Output = UInt<L>;
+    #[inline]
+    fn addm(self, rhs: &UInt<L>, m: &UInt<L>) -> UInt<L> {
+        (*self).addm(rhs, m)
+    }
+    #[inline]
+    fn subm(self, rhs: &UInt<L>, m: &UInt<L>) -> UInt<L> {
+        (*self).subm(rhs, m)
+    }
+    #[inline]
+    fn mulm(self, rhs: &UInt<L>, m: &UInt<L>) -> UInt<L> {
+        (*self).mulm(rhs, m)
+    }
+}
+
+impl<const L: usize> ModularUnaryOps<&UInt<L>> for UInt<L> {
+    type Output = UInt<L>;
+    #[inline]
+    fn negm(self, m: &UInt<L>) -> UInt<L> {
+        reduce(&self, m).neg_mod(m)
+    }
+
+    fn invm(self, m: &UInt<L>) -> Option<UInt<L>> {
+        let (mut last_r, mut r) = (*m, reduce(&self, m));
+        let (mut last_t, mut t) = (UInt::ZERO, reduce(&UInt::ONE, m));
+
+        while r != UInt::ZERO {
+            let (quo, rem) = div_rem(&last_r, &r);
+            last_r = r;
+            r = rem;
+
+            let new_t = last_t.sub_mod(&mul_reduced(&t, &quo, m), m);
+            last_t = t;
+            t = new_t;
+        }
+
+        // if r = gcd(self, m) > 1, then inverse doesn't exist
+        if last_r == UInt::ONE {
+            Some(last_t)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn dblm(self, m: &UInt<L>) -> UInt<L> {
+        let x = reduce(&self, m);
+        x.add_mod(&x, m)
+    }
+    #[inline]
+    fn sqm(self, m: &UInt<L>) -> UInt<L> {
+        let x = reduce(&self, m);
+        mul_reduced(&x, &x, m)
+    }
+}
+
+impl<const L: usize> ModularUnaryOps<&UInt<L>> for &UInt<L> {
+    type Output = UInt<L>;
+    #[inline]
+    fn negm(self, m: &UInt<L>) -> UInt<L> {
+        (*self).negm(m)
+    }
+    #[inline]
+    fn invm(self, m: &UInt<L>) -> Option<UInt<L>> {
+        (*self).invm(m)
+    }
+    #[inline]
+    fn dblm(self, m: &UInt<L>) -> UInt<L> {
+        (*self).dblm(m)
+    }
+    #[inline]
+    fn sqm(self, m: &UInt<L>) -> UInt<L> {
+        (*self).sqm(m)
+    }
+}
+
+impl<const L: usize> ModularPow<&UInt<L>, &UInt<L>> for UInt<L> {
+    type Output = UInt<L>;
+    fn powm(self, exp: &UInt<L>, m: &UInt<L>) -> UInt<L> {
+        let base = reduce(&self, m);
+        let mut result = reduce(&UInt::ONE, m);
+        for i in (0..L * Limb::BIT_SIZE).rev() {
+            result = mul_reduced(&result, &result, m);
+            let product = mul_reduced(&result, &base, m);
+            result = UInt::conditional_select(&result, &product, bit(exp, i));
+        }
+        result
+    }
+}
+
+impl<const L: usize> ModularPow<&UInt<L>, &UInt<L>> for &UInt<L> {
+    type Output = UInt<L>;
+    #[inline]
+    fn powm(self, exp: &UInt<L>, m: &UInt<L>) -> UInt<L> {
+        (*self).powm(exp, m)
+    }
+}
+
+impl<const L: usize> ModularSymbols<&UInt<L>> for UInt<L> {
+    #[inline]
+    fn checked_legendre(&self, n: &UInt<L>) -> Option<i8> {
+        let r = self.powm(&n.wrapping_sub(&UInt::ONE).shr_vartime(1), n);
+        if r == UInt::ZERO {
+            Some(0)
+        } else if r == UInt::ONE {
+            Some(1)
+        } else if &r.wrapping_add(&UInt::ONE) == n {
+            Some(-1)
+        } else {
+            None
+        }
+    }
+
+    fn checked_jacobi(&self, n: &UInt<L>) -> Option<i8> {
+        if low(n) & 1 == 0 {
+            return None;
+        }
+        if n == &UInt::ONE {
+            return Some(1);
+        }
+
+        let mut a = div_rem(self, n).1;
+        let mut n = *n;
+        let mut t = 1;
+        while a != UInt::ZERO {
+            while low(&a) & 1 == 0 {
+                a = a.shr_vartime(1);
+                if low(&n) & 7 == 3 || low(&n) & 7 == 5 {
+                    t = -t;
+                }
+            }
+            core::mem::swap(&mut a, &mut n);
+            if low(&a) & 3 == 3 && low(&n) & 3 == 3 {
+                t = -t;
+            }
+            a = div_rem(&a, &n).1;
+        }
+        Some(if n == UInt::ONE { t } else { 0 })
+    }
+
+    fn kronecker(&self, n: &UInt<L>) -> i8 {
+        if n == &UInt::ZERO {
+            return if self == &UInt::ONE { 1 } else { 0 };
+        }
+        if n == &UInt::ONE {
+            return 1;
+        }
+
+        // (a/2) = 0 for even a, 1 for a = ±1 (mod 8) and -1 otherwise
+        let k2 = match low(self) & 7 {
+            1 | 7 => 1,
+            3 | 5 => -1,
+            _ => 0,
+        };
+        let mut n = *n;
+        let mut t = 1;
+        while low(&n) & 1 == 0 {
+            n = n.shr_vartime(1);
+            t *= k2;
+        }
+        t * self.jacobi(&n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModularInteger, VanillaInt};
+    use crypto_bigint::{U128, U256};
+    use rand::random;
+
+    const NRANDOM: u32 = 10;
+
+    #[test]
+    fn ops_against_prim_test() {
+        for _ in 0..NRANDOM {
+            let (a, b, e) = (random::<u128>(), random::<u128>(), random::<u16>() as u128);
+            let m = (random::<u128>() >> (random::<u8>() % 128)) | 1;
+            let (ca, cb, ce, cm) = (
+                U128::from_u128(a),
+                U128::from_u128(b),
+                U128::from_u128(e),
+                U128::from_u128(m),
+            );
+            assert_eq!(u128::from(ca.addm(&cb, &cm)), a.addm(b, &m));
+            assert_eq!(u128::from(ca.subm(&cb, &cm)), a.subm(b, &m));
+            assert_eq!(u128::from(ca.mulm(&cb, &cm)), a.mulm(b, &m));
+            assert_eq!(u128::from(ca.negm(&cm)), a.negm(&m));
+            assert_eq!(u128::from(ca.dblm(&cm)), a.dblm(&m));
+            assert_eq!(u128::from(ca.sqm(&cm)), a.sqm(&m));
+            assert_eq!(u128::from(ca.powm(&ce, &cm)), a.powm(e, &m));
+            assert_eq!(ca.invm(&cm).map(u128::from), a.invm(&m));
+            assert_eq!(ca.jacobi(&cm), a.jacobi(&m));
+            assert_eq!(ca.kronecker(&cm), a.kronecker(&m));
+            assert_eq!(ca.kronecker(&cb), a.kronecker(&b));
+
+            // the wider types hold the same values
+            let wide = |x: u128| U256::from_u128(x);
+            assert_eq!((&wide(a)).mulm(&wide(b), &wide(m)), wide(a.mulm(b, &m)));
+            assert_eq!((&wide(a)).powm(&wide(e), &wide(m)), wide(a.powm(e, &m)));
+        }
+
+        // the moduli with the highest bit set
+        for &m in &[u128::MAX, u128::MAX - 1, 1 << 127, (1 << 127) + 1] {
+            let (a, b) = (random::<u128>(), random::<u128>());
+            let (ca, cb, cm) = (U128::from_u128(a), U128::from_u128(b), U128::from_u128(m));
+            assert_eq!(u128::from(ca.addm(&cb, &cm)), a.addm(b, &m));
+            assert_eq!(u128::from(ca.subm(&cb, &cm)), a.subm(b, &m));
+            assert_eq!(u128::from(ca.mulm(&cb, &cm)), a.mulm(b, &m));
+            assert_eq!(ca.invm(&cm).map(u128::from), a.invm(&m));
+        }
+
+        let p = U128::from_u128(1_000_000_007);
+        assert_eq!(U128::from_u8(2).legendre(&p), 1);
+        assert_eq!(U128::from_u8(5).legendre(&p), -1);
+        assert_eq!(U128::ZERO.legendre(&p), 0);
+        assert_eq!(U128::ONE.mulm(&U128::MAX, &U128::ONE), U128::ZERO);
+        assert_eq!(U128::MAX.powm(&U128::MAX, &U128::MAX), U128::ZERO);
+    }
+
+    #[test]
+    #[cfg(feature = "num-bigint")]
+    fn reduce_wide_test() {
+        use crypto_bigint::Encoding;
+        use num_bigint::BigUint;
+
+        let big = |x: &U256| BigUint::from_bytes_be(&x.to_be_bytes());
+        let random_u256 = || {
+            let (hi, lo) = (random::<u128>(), random::<u128>());
+            U256::from_u128(hi)
+                .shl_vartime(128)
+                .bitor(&U256::from_u128(lo))
+        };
+        let mut moduli = std::vec![
+            U256::MAX,
+            U256::ONE,
+            U256::from_u8(2),
+            U256::ONE.shl_vartime(255),
+            U256::ONE.shl_vartime(128).wrapping_sub(&U256::ONE),
+            U256::ONE.shl_vartime(192).wrapping_add(&U256::ONE),
+        ];
+        for _ in 0..NRANDOM {
+            moduli.push(
+                random_u256()
+                    .shr_vartime(random::<usize>() % 256)
+                    .bitor(&U256::ONE),
+            );
+        }
+        for m in moduli {
+            for _ in 0..NRANDOM {
+                let (a, b) = (reduce(&random_u256(), &m), random_u256());
+                assert_eq!(big(&a.mulm(&b, &m)), big(&a) * big(&b) % big(&m));
+                assert_eq!(big(&reduce(&b, &m)), big(&b) % big(&m));
+            }
+            let a = m.wrapping_sub(&U256::ONE);
+            assert_eq!(
+                big(&a.mulm(&U256::MAX, &m)),
+                big(&a) * big(&U256::MAX) % big(&m)
+            );
+        }
+    }
+
+    #[test]
+    fn vanilla_int_test() {
+        // the prime 2^255 - 19
+        let p = U256::MAX.shr_vartime(1).wrapping_sub(&U256::from_u8(18));
+        let x = VanillaInt::new(U256::from_u64(random::<u64>() | 1), &p);
+        let y = x.convert(U256::from_u64(random::<u64>()));
+        assert_eq!((x + y - y).residue(), x.residue());
+        assert_eq!((x * y).residue(), x.residue().mulm(&y.residue(), &p));
+        assert_eq!((x / x).residue(), U256::ONE);
+        assert_eq!(x.pow(&p.wrapping_sub(&U256::ONE)).residue(), U256::ONE);
+        assert!((x - x).is_zero());
+    }
+}
@@ -0,0 +1,169 @@
+//! Constant-time operations backed by `subtle`, for handling secret residues.
+//!
+//! With the `subtle` feature, the final conditional subtractions in the REDC algorithm and in
+//! the modular additions, subtractions and negations of [Montgomery] (and [Vanilla][crate::Vanilla]
+//! on primitive integers) are performed with [ConditionallySelectable::conditional_select]
+//! instead of branches, where the conditions go through [Choice] so that the optimizer can't
+//! turn the selections back into branches. Besides, the traits
+//! [ConstantTimeEq] and [ConditionallySelectable] are implemented for [MontyForm], [Montgomery],
+//! [MontgomeryInt] and [ConstMontgomeryInt], which should be used instead of `==` and `if` on
+//! secret values.
+//!
+//! Note that only the arithmetics are covered: the conversions into the Montgomery form
+//! involve divisions, the inversion is based on the binary GCD, and the exponentiation
+//! branches on the bits of the exponent, so they shouldn't be applied to secret values.
+
+use crate::{
+    ConstMontgomery, ConstMontgomeryInt, Montgomery, MontgomeryInt, MontyForm, ReducedInt,
+};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+/// Branchless selection on the primitive integers for the modular arithmetics
+pub(crate) trait CtSelect: Sized {
+    /// Select b if cond is set, otherwise a
+    fn ct_select(a: Self, b: Self, cond: Choice) -> Self;
+}
+
+macro_rules! impl_ct_for {
+    ($($T:ty => $S:ty;)*) => ($(
+        impl CtSelect for $T {
+            #[inline(always)]
+            fn ct_select(a: $T, b: $T, cond: Choice) -> $T {
+                <$S>::conditional_select(&(a as $S), &(b as $S), cond) as $T
+            }
+        }
+
+        impl ConstantTimeEq for MontyForm<$T> {
+            #[inline]
+            fn ct_eq(&self, other: &Self) -> Choice {
+                self.raw().ct_eq(other.raw())
+            }
+        }
+
+        impl ConditionallySelectable for MontyForm<$T> {
+            #[inline]
+            fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+                let v = <$S>::conditional_select(&(*a.raw() as $S), &(*b.raw() as $S), choice);
+                MontyForm::from_raw_unchecked(v as $T)
+            }
+        }
+
+        impl ConstantTimeEq for Montgomery<$T> {
+            #[inline]
+            fn ct_eq(&self, other: &Self) -> Choice {
+                let ((m1, inv1), (m2, inv2)) = (self.raw_parts(), other.raw_parts());
+                m1.ct_eq(&m2) & inv1.ct_eq(&inv2)
+            }
+        }
+
+        impl ConditionallySelectable for Montgomery<$T> {
+            #[inline]
+            fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+                let ((m1, inv1), (m2, inv2)) = (a.raw_parts(), b.raw_parts());
+                let m = <$S>::conditional_select(&(m1 as $S), &(m2 as $S), choice);
+                let inv = <$S>::conditional_select(&(inv1 as $S), &(inv2 as $S), choice);
+                Montgomery::<$T>::from_raw_parts(m as $T, inv as $T)
+            }
+        }
+
+        /// The residues are compared only if the moduli are the same, the result is
+        /// false otherwise (unlike `==`, which panics).
+        impl ConstantTimeEq for MontgomeryInt<$T> {
+            #[inline]
+            fn ct_eq(&self, other: &Self) -> Choice {
+                self.reducer().ct_eq(other.reducer()) & self.repr().ct_eq(other.repr())
+            }
+        }
+
+        impl ConditionallySelectable for MontgomeryInt<$T> {
+            #[inline]
+            fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+                let r = Montgomery::<$T>::conditional_select(a.reducer(), b.reducer(), choice);
+                let v = MontyForm::conditional_select(&a.monty_form(), &b.monty_form(), choice);
+                ReducedInt::from_parts(*v.raw(), r)
+            }
+        }
+    )*);
+}
+
+// usize is selected through a fixed-width integer, since subtle doesn't support it
+impl_ct_for! {
+    u8 => u8;
+    u16 => u16;
+    u32 => u32;
+    u64 => u64;
+    u128 => u128;
+    usize => u64;
+}
+
+impl<const M: u64> ConstantTimeEq for ConstMontgomeryInt<M> {
+    #[inline]
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.repr().ct_eq(other.repr())
+    }
+}
+
+impl<const M: u64> ConditionallySelectable for ConstMontgomeryInt<M> {
+    #[inline]
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let v = u64::conditional_select(a.repr(), b.repr(), choice);
+        ReducedInt::from_parts(v, ConstMontgomery)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModularInteger, Reducer};
+    use rand::random;
+    use subtle::ConditionallyNegatable;
+
+    const NRANDOM: u32 = 10;
+
+    #[test]
+    fn ct_ops_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..NRANDOM {
+                    let m = random::<$T>() | 1;
+                    let (a, b) = (MontgomeryInt::new(random::<$T>(), &m), MontgomeryInt::new(random::<$T>(), &m));
+                    assert!(bool::from(a.ct_eq(&a)));
+                    assert_eq!(bool::from(a.ct_eq(&b)), a == b);
+                    assert_eq!(MontgomeryInt::conditional_select(&a, &b, Choice::from(0)), a);
+                    assert_eq!(MontgomeryInt::conditional_select(&a, &b, Choice::from(1)), b);
+
+                    let mut c = a;
+                    c.conditional_negate(Choice::from(1));
+                    assert_eq!(c, -a);
+                    c.conditional_assign(&b, Choice::from(0));
+                    assert_eq!(c, -a);
+
+                    // different moduli
+                    let m2 = m.wrapping_add(2).max(3);
+                    let c = MontgomeryInt::new(a.residue(), &m2);
+                    assert_eq!(bool::from(a.ct_eq(&c)), m == m2);
+                    let d = MontgomeryInt::conditional_select(&a, &c, Choice::from(1));
+                    assert_eq!(d.modulus(), m2);
+                    assert_eq!(d.residue(), a.residue() % m2);
+                    let r = Montgomery::<$T>::new(m);
+                    assert!(bool::from(r.ct_eq(a.reducer())));
+                    assert_eq!(bool::from(r.ct_eq(d.reducer())), m == m2);
+
+                    // the branchless arithmetics against the Vanilla reducer
+                    let v = crate::Vanilla::<$T>::new(&m);
+                    let (x, y) = (a.residue(), b.residue());
+                    assert_eq!((a + b).residue(), v.add(&x, &y));
+                    assert_eq!((a - b).residue(), v.sub(&x, &y));
+                    assert_eq!((-a).residue(), v.neg(x));
+                    assert_eq!((a * b).residue(), v.mul(&x, &y));
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+
+        type Mint = ConstMontgomeryInt<998244353>;
+        let (a, b) = (Mint::from(random::<u64>()), Mint::from(random::<u64>()));
+        assert_eq!(bool::from(a.ct_eq(&b)), a == b);
+        assert_eq!(Mint::conditional_select(&a, &b, Choice::from(1)), b);
+    }
+}
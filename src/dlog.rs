@@ -0,0 +1,786 @@
+//! Discrete logarithms modulo an integer
+
+use crate::prim::gcd;
+use crate::{crt_pair, ModularPow, VanillaInt};
+use crate::{ModularCoreOps, ModularInteger, ModularUnaryOps, MontgomeryInt, ReducedInt, Reducer};
+#[cfg(feature = "std")]
+use core::convert::TryFrom;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+/// Integer types supported by the discrete logarithm functions
+pub trait DiscreteLog: Sized {
+    /// Find the smallest discrete logarithm by the baby-step giant-step algorithm,
+    /// see [discrete_log()] for details
+    #[cfg(feature = "std")]
+    fn discrete_log(&self, target: &Self, m: &Self) -> Option<Self>;
+
+    /// Find a discrete logarithm by Pollard's rho algorithm, see [discrete_log_rho()] for details
+    fn discrete_log_rho(&self, target: &Self, m: &Self, order: &Self) -> Option<Self>;
+
+    /// Find a discrete logarithm by the Pohlig-Hellman algorithm, see [pohlig_hellman()] for details
+    fn pohlig_hellman(&self, target: &Self, m: &Self, factors: &[(Self, u32)]) -> Option<Self>;
+}
+
+/// Find the smallest `x >= 0` such that `base^x ≡ target (mod m)`, or return [None] if
+/// there is no such x.
+///
+/// The logarithm is found by the baby-step giant-step algorithm, which takes
+/// `O(sqrt(m))` multiplications and stores `O(sqrt(m))` entries in a hash table, so it's
+/// only feasible for moduli up to about 2^50. The multiplications are performed with
+/// [MontgomeryInt] for odd moduli. The base doesn't need to be coprime to the modulus, the
+/// common factors are removed first (the extended BSGS).
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::discrete_log;
+///
+/// assert_eq!(discrete_log(&3u32, &13, &17), Some(4)); // 3^4 = 81 = 13 (mod 17)
+/// assert_eq!(discrete_log(&2u32, &3, &7), None); // 2 generates {1, 2, 4} only
+/// assert_eq!(discrete_log(&2u32, &0, &16), Some(4)); // the base is not coprime to m
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn discrete_log<T: DiscreteLog>(base: &T, target: &T, m: &T) -> Option<T> {
+    base.discrete_log(target, m)
+}
+
+/// Find `x` in `[0, order)` such that `base^x ≡ target (mod m)` by Pollard's rho algorithm,
+/// where order is the multiplicative order of the base.
+///
+/// The algorithm walks pseudo-randomly through the elements `base^a * target^b` with Floyd's
+/// cycle detection, so it takes `O(sqrt(order))` multiplications but only constant memory,
+/// which makes it feasible for larger moduli than [discrete_log()]. The walk is deterministic,
+/// and it's restarted from different points when a collision gives no information.
+///
+/// Orders up to 256 are searched exhaustively instead, since the walk hardly mixes in such
+/// small groups. [None] is returned if target is not a power of base, or if the search fails
+/// after a few restarts (which is unlikely for a prime order). When the order is composite, the collision
+/// equation can have multiple candidates, and the restart happens if there are too many of
+/// them, so the algorithm works best for prime orders. The collisions only determine the
+/// logarithm modulo the true order, so the result is not reliable if a multiple of the order
+/// (like `φ(m)`) is given instead.
+///
+/// # Panics
+/// if m or order is zero, or the base is not coprime to m
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{discrete_log_rho, ModularPow};
+///
+/// let (p, q) = (2_000_000_579u64, 1_000_000_289u64); // p = 2q + 1
+/// let g = 4; // 4 generates the subgroup of order q
+/// let x = discrete_log_rho(&g, &g.powm(123_456_789, &p), &p, &q);
+/// assert_eq!(x, Some(123_456_789));
+/// ```
+#[inline]
+pub fn discrete_log_rho<T: DiscreteLog>(base: &T, target: &T, m: &T, order: &T) -> Option<T> {
+    base.discrete_log_rho(target, m, order)
+}
+
+/// Find the smallest `x >= 0` such that `base^x ≡ target (mod m)` by the Pohlig-Hellman
+/// algorithm, given the factorization of a multiple N of the order of the base (e.g. the
+/// group order `φ(m)`) as `factors = [(p1, k1), (p2, k2), ...]` for `N = p1^k1 * p2^k2 * ...`.
+///
+/// For each prime power, the exact order `pi^ti` of the base projected to that subgroup is
+/// determined first, then the logarithm modulo `pi^ti` is solved digit by digit (in base pi),
+/// where each digit is a logarithm in a subgroup of prime order pi found by exhaustive search
+/// for small pi and by [discrete_log_rho()] otherwise. The results are combined with
+/// [crt_pair()][crate::crt_pair()], so the cost is dominated by `sqrt(p)` of the largest prime
+/// factor p, which makes the logarithms feasible when N is smooth.
+///
+/// [None] is returned if target is not a power of base, or the order of the base doesn't
+/// divide N (or the rho search fails). The primes should be pairwise distinct.
+///
+/// # Panics
+/// if m is zero, N overflows, or the base is not coprime to m
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{pohlig_hellman, ModularPow};
+///
+/// let p = 1_000_000_007u64; // p - 1 = 2 * 500000003
+/// let factors = [(2, 1), (500_000_003, 1)];
+/// let x = pohlig_hellman(&5, &5u64.powm(987_654_321, &p), &p, &factors);
+/// assert_eq!(x, Some(987_654_321));
+///
+/// let q = 7_340_033u64; // q - 1 = 2^20 * 7
+/// let x = pohlig_hellman(&3, &3u64.powm(1_234_567, &q), &q, &[(2, 20), (7, 1)]);
+/// assert_eq!(x, Some(1_234_567));
+/// ```
+#[inline]
+pub fn pohlig_hellman<T: DiscreteLog>(
+    base: &T,
+    target: &T,
+    m: &T,
+    factors: &[(T, u32)],
+) -> Option<T> {
+    base.pohlig_hellman(target, m, factors)
+}
+
+/// The largest prime factor in Pohlig-Hellman (and the largest order in the rho algorithm)
+/// that is solved by exhaustive search
+const PH_SEARCH_LIMIT: u64 = 256;
+
+/// The number of restarts of the rho walks before giving up
+const RHO_MAX_RESTARTS: u64 = 32;
+
+/// The number of candidates checked when the collision equation is degenerate
+const RHO_MAX_CANDIDATES: u64 = 1 << 12;
+
+/// Find the smallest `y >= 0` such that `c * base^y = target` with `y < steps^2`,
+/// where base is invertible in the ring.
+#[cfg(feature = "std")]
+fn bsgs<T, R>(
+    base: ReducedInt<T, R>,
+    c: ReducedInt<T, R>,
+    target: ReducedInt<T, R>,
+    steps: u64,
+) -> Option<u64>
+where
+    T: Copy + Eq + From<u8> + core::hash::Hash,
+    R: Reducer<T> + Copy,
+{
+    if c == target {
+        return Some(0);
+    }
+
+    // baby steps: target * base^j for j in [0, steps), the largest j is kept
+    let mut table = HashMap::with_capacity(steps as usize);
+    let (mut v, mut giant) = (target, base.convert(T::from(1)));
+    for j in 0..steps {
+        table.insert(*v.repr(), j);
+        v = v * base;
+        giant = giant * base;
+    }
+
+    // giant steps: c * base^(steps * i) for i in [1, steps]
+    let mut v = c;
+    for i in 1..=steps {
+        v = v * giant;
+        if let Some(&j) = table.get(v.repr()) {
+            return Some(i * steps - j);
+        }
+    }
+    None
+}
+
+macro_rules! impl_dlog_for {
+    ($($T:ty)*) => {$(
+        impl DiscreteLog for $T {
+            #[cfg(feature = "std")]
+            fn discrete_log(&self, target: &$T, m: &$T) -> Option<$T> {
+                let mut m = *m;
+                assert!(m > 0, "the modulus should not be zero");
+                let (base, mut target) = (*self % m, *target % m);
+
+                // small exponents are checked directly, which covers the exponents
+                // before all the common factors of the base and m are removed
+                let mut v = 1 % m;
+                for x in 0..<$T>::BITS as $T {
+                    if v == target {
+                        return Some(x);
+                    }
+                    v = v.mulm(base, &m);
+                }
+
+                // reduce to c * base^y = target (mod m) with gcd(base, m) = 1,
+                // then x = y + k where k is the number of reductions
+                let (mut c, mut k) = (1 % m, 0);
+                loop {
+                    let g = gcd(base, m);
+                    if g == 1 {
+                        break;
+                    }
+                    if target % g != 0 {
+                        return None;
+                    }
+                    m /= g;
+                    target /= g;
+                    c = (base / g).mulm(c, &m);
+                    k += 1;
+                }
+
+                // steps = ceil(sqrt(m))
+                let mut steps = (m as f64).sqrt() as u64;
+                while (steps as u128) * (steps as u128) < m as u128 {
+                    steps += 1;
+                }
+                let y = if m & 1 == 1 {
+                    let base = MontgomeryInt::new(base, &m);
+                    bsgs(base, base.convert(c), base.convert(target), steps)
+                } else {
+                    let base = VanillaInt::new(base, &m);
+                    bsgs(base, base.convert(c), base.convert(target), steps)
+                }?;
+                <$T>::try_from(y).ok()?.checked_add(k)
+            }
+
+            fn discrete_log_rho(&self, target: &$T, m: &$T, order: &$T) -> Option<$T> {
+                /// Pollard's rho with Floyd's cycle detection in the given representation
+                fn rho<R: Reducer<$T> + Clone>(
+                    base: ReducedInt<$T, R>,
+                    target: ReducedInt<$T, R>,
+                    n: $T,
+                ) -> Option<$T> {
+                    assert!(base.clone().inv().is_some(), "the base should be coprime to the modulus");
+                    if target == base.convert(1) {
+                        return Some(0);
+                    }
+
+                    // the walk hardly mixes in tiny groups, so they are searched exhaustively
+                    if n as u128 <= PH_SEARCH_LIMIT as u128 {
+                        let mut v = base.convert(1);
+                        for x in 0..n {
+                            if v == target {
+                                return Some(x);
+                            }
+                            v = v * &base;
+                        }
+                        return None;
+                    }
+
+                    // one step of the walk on (x, a, b) with x = base^a * target^b, the
+                    // representations are mixed, so that they are partitioned evenly
+                    let step = |(x, a, b): (ReducedInt<$T, R>, $T, $T)| {
+                        match ((*x.repr() as u64).wrapping_mul(0x9E3779B97F4A7C15) >> 32) % 3 {
+                            0 => (x * &target, a, b.addm(1, &n)),
+                            1 => (x.square(), a.dblm(&n), b.dblm(&n)),
+                            _ => (x * &base, a.addm(1, &n), b),
+                        }
+                    };
+
+                    for seed in 1..=RHO_MAX_RESTARTS {
+                        let a0 = (seed.wrapping_mul(0x2545F4914F6CDD1D) as u128 % n as u128) as $T;
+                        let b0 = (seed as u128 % n as u128) as $T;
+                        let x0 = base.clone().pow(&a0) * target.clone().pow(&b0);
+                        let mut tortoise = (x0, a0, b0);
+                        let mut hare = step(tortoise.clone());
+                        while tortoise.0 != hare.0 {
+                            tortoise = step(tortoise);
+                            hare = step(step(hare));
+                        }
+
+                        // base^a1 * target^b1 = base^a2 * target^b2
+                        // => (b1 - b2) * x = a2 - a1 (mod n)
+                        let r = tortoise.2.subm(hare.2, &n);
+                        let e = hare.1.subm(tortoise.1, &n);
+                        let g = gcd(n, r);
+                        if r == 0 || e % g != 0 || g as u64 > RHO_MAX_CANDIDATES {
+                            continue;
+                        }
+
+                        // check the g candidates x0 + k * n / g
+                        let ng = n / g;
+                        let mut x = (e / g).mulm((r / g).invm(&ng).unwrap(), &ng);
+                        for _ in 0..g {
+                            if base.clone().pow(&x) == target {
+                                return Some(x);
+                            }
+                            x = x.wrapping_add(ng);
+                        }
+                    }
+                    None
+                }
+
+                let (m, n) = (*m, *order);
+                assert!(m > 0 && n > 0, "the modulus and the order should not be zero");
+                if m & 1 == 1 {
+                    let base = MontgomeryInt::new(*self, &m);
+                    rho(base, base.convert(*target), n)
+                } else {
+                    let base = VanillaInt::new(*self, &m);
+                    rho(base, base.convert(*target), n)
+                }
+            }
+
+            fn pohlig_hellman(&self, target: &$T, m: &$T, factors: &[($T, u32)]) -> Option<$T> {
+                const OVERFLOW: &str = "the order should fit in the integer type";
+                let m = *m;
+                assert!(m > 0, "the modulus should not be zero");
+                let (base, target) = (*self % m, *target % m);
+                let base_inv = base.invm(&m).expect("the base should be coprime to the modulus");
+                let n = factors.iter().fold(1 as $T, |acc, &(p, k)| {
+                    p.checked_pow(k).and_then(|pk| acc.checked_mul(pk)).expect(OVERFLOW)
+                });
+
+                let (mut acc, mut acc_m) = (0, 1);
+                for &(p, k) in factors {
+                    // solve x mod p^k in the subgroup of order dividing p^k
+                    let cofactor = n / p.pow(k);
+                    let (g, h) = (base.powm(cofactor, &m), target.powm(cofactor, &m));
+                    let g_inv = base_inv.powm(cofactor, &m);
+
+                    // the order of g is p^t with t <= k, then gamma has order p
+                    let (mut t, mut pt, mut gamma) = (0, 1 as $T, 1 % m);
+                    let mut v = g;
+                    while v != 1 % m {
+                        if t == k {
+                            return None; // the order of the base doesn't divide N
+                        }
+                        gamma = v;
+                        v = v.powm(p, &m);
+                        t += 1;
+                        pt *= p;
+                    }
+
+                    // x = d_0 + d_1 p + ... + d_(t-1) p^(t-1), and pj = p^j
+                    let (mut x, mut pj) = (0 as $T, 1 as $T);
+                    for _ in 0..t {
+                        // (g^-x * h)^(p^(t-1-j)) = gamma^d_j
+                        let hj = g_inv.powm(x, &m).mulm(h, &m).powm(pt / p / pj, &m);
+                        let d = if (p as u64) <= PH_SEARCH_LIMIT {
+                            let mut v = 1 % m;
+                            (0..p).find(|_| {
+                                let found = v == hj;
+                                v = v.mulm(gamma, &m);
+                                found
+                            })?
+                        } else {
+                            gamma.discrete_log_rho(&hj, &m, &p)?
+                        };
+                        x += d * pj;
+                        pj = pj.wrapping_mul(p);
+                    }
+                    let (a, b) = crt_pair(&acc, &acc_m, &x, &pt).ok()?;
+                    acc = a;
+                    acc_m = b;
+                }
+
+                if base.powm(acc, &m) == target {
+                    Some(acc)
+                } else {
+                    None
+                }
+            }
+        }
+    )*};
+}
+impl_dlog_for!(u8 u16 u32 u64 usize);
+
+#[cfg(feature = "num-bigint")]
+mod _num_bigint {
+    use super::*;
+    use num_bigint::BigUint;
+    use num_integer::Integer;
+    #[cfg(feature = "std")]
+    use num_traits::ToPrimitive;
+    use num_traits::{One, Zero};
+
+    impl DiscreteLog for BigUint {
+        #[cfg(feature = "std")]
+        fn discrete_log(&self, target: &BigUint, m: &BigUint) -> Option<BigUint> {
+            assert!(!m.is_zero(), "the modulus should not be zero");
+            let (base, mut target, mut m) = (self % m, target % m, m.clone());
+
+            // small exponents, see the primitive implementation for details
+            let mut v = BigUint::one() % &m;
+            for x in 0..m.bits() {
+                if v == target {
+                    return Some(x.into());
+                }
+                v = v.mulm(&base, &m);
+            }
+
+            let (mut c, mut k) = (BigUint::one() % &m, 0u64);
+            loop {
+                let g = base.gcd(&m);
+                if g.is_one() {
+                    break;
+                }
+                if !(&target % &g).is_zero() {
+                    return None;
+                }
+                m /= &g;
+                target /= &g;
+                c = (&base / &g).mulm(&c, &m);
+                k += 1;
+            }
+
+            // the table would be too large for steps that doesn't fit in u64 anyway
+            let mut steps = m.sqrt();
+            if &steps * &steps < m {
+                steps += 1u8;
+            }
+            let steps = steps.to_u64().expect("the modulus is too large");
+
+            let base = base % &m;
+            if c == target {
+                return Some(k.into());
+            }
+            let mut table = HashMap::with_capacity(steps as usize);
+            let (mut v, mut giant) = (target, BigUint::one() % &m);
+            for j in 0..steps {
+                v = {
+                    let next = (&v).mulm(&base, &m);
+                    table.insert(v, j);
+                    next
+                };
+                giant = giant.mulm(&base, &m);
+            }
+            let mut v = c;
+            for i in 1..=steps {
+                v = v.mulm(&giant, &m);
+                if let Some(&j) = table.get(&v) {
+                    return Some(BigUint::from(i) * steps - j + k);
+                }
+            }
+            None
+        }
+
+        fn discrete_log_rho(
+            &self,
+            target: &BigUint,
+            m: &BigUint,
+            order: &BigUint,
+        ) -> Option<BigUint> {
+            let (m, n) = (m, order);
+            assert!(
+                !m.is_zero() && !n.is_zero(),
+                "the modulus and the order should not be zero"
+            );
+            let (base, target) = (self % m, target % m);
+            assert!(
+                base.gcd(m).is_one(),
+                "the base should be coprime to the modulus"
+            );
+            if target == BigUint::one() % m {
+                return Some(BigUint::zero());
+            }
+            if n <= &BigUint::from(PH_SEARCH_LIMIT) {
+                let mut v = BigUint::one() % m;
+                let mut x = BigUint::zero();
+                while &x < n {
+                    if v == target {
+                        return Some(x);
+                    }
+                    v = v.mulm(&base, m);
+                    x += 1u8;
+                }
+                return None;
+            }
+
+            let step = |(x, a, b): (BigUint, BigUint, BigUint)| {
+                let h = x.iter_u64_digits().next().unwrap_or(0);
+                match (h.wrapping_mul(0x9E3779B97F4A7C15) >> 32) % 3 {
+                    0 => (x.mulm(&target, m), a, b.addm(BigUint::one(), n)),
+                    1 => ((&x).sqm(m), (&a).dblm(n), (&b).dblm(n)),
+                    _ => (x.mulm(&base, m), a.addm(BigUint::one(), n), b),
+                }
+            };
+
+            for seed in 1..=RHO_MAX_RESTARTS {
+                let a0 = BigUint::from(seed.wrapping_mul(0x2545F4914F6CDD1D)) % n;
+                let b0 = BigUint::from(seed) % n;
+                let x0 = (&base).powm(&a0, m).mulm((&target).powm(&b0, m), m);
+                let mut tortoise = (x0, a0, b0);
+                let mut hare = step(tortoise.clone());
+                while tortoise.0 != hare.0 {
+                    tortoise = step(tortoise);
+                    hare = step(step(hare));
+                }
+
+                let r = (&tortoise.2).subm(&hare.2, n);
+                let e = (&hare.1).subm(&tortoise.1, n);
+                let g = r.gcd(n);
+                if r.is_zero() || !(&e % &g).is_zero() || g > BigUint::from(RHO_MAX_CANDIDATES) {
+                    continue;
+                }
+
+                let ng = n / &g;
+                let mut x = (&e / &g).mulm(&(&r / &g).invm(&ng).unwrap(), &ng);
+                let mut k = BigUint::zero();
+                while k < g {
+                    if (&base).powm(&x, m) == target {
+                        return Some(x);
+                    }
+                    x += &ng;
+                    k += 1u8;
+                }
+            }
+            None
+        }
+
+        fn pohlig_hellman(
+            &self,
+            target: &BigUint,
+            m: &BigUint,
+            factors: &[(BigUint, u32)],
+        ) -> Option<BigUint> {
+            assert!(!m.is_zero(), "the modulus should not be zero");
+            let (base, target) = (self % m, target % m);
+            let base_inv = (&base)
+                .invm(m)
+                .expect("the base should be coprime to the modulus");
+            let n: BigUint = factors.iter().map(|(p, k)| p.pow(*k)).product();
+            let one = BigUint::one() % m;
+
+            let (mut acc, mut acc_m) = (BigUint::zero(), BigUint::one());
+            for (p, k) in factors {
+                // see the primitive implementation for details
+                let cofactor = &n / p.pow(*k);
+                let g = (&base).powm(&cofactor, m);
+                let h = (&target).powm(&cofactor, m);
+                let g_inv = (&base_inv).powm(&cofactor, m);
+
+                let (mut t, mut pt, mut gamma) = (0, BigUint::one(), one.clone());
+                let mut v = g;
+                while v != one {
+                    if t == *k {
+                        return None;
+                    }
+                    let next = (&v).powm(p, m);
+                    gamma = v;
+                    v = next;
+                    t += 1;
+                    pt *= p;
+                }
+
+                let (mut x, mut pj) = (BigUint::zero(), BigUint::one());
+                for _ in 0..t {
+                    let e = &pt / p / &pj;
+                    let hj = (&g_inv).powm(&x, m).mulm(&h, m).powm(&e, m);
+                    let d = if p <= &BigUint::from(PH_SEARCH_LIMIT) {
+                        let mut v = one.clone();
+                        let mut d = 0u64;
+                        while v != hj {
+                            d += 1;
+                            if d >= PH_SEARCH_LIMIT {
+                                return None;
+                            }
+                            v = v.mulm(&gamma, m);
+                        }
+                        BigUint::from(d)
+                    } else {
+                        gamma.discrete_log_rho(&hj, m, p)?
+                    };
+                    x += d * &pj;
+                    pj *= p;
+                }
+                let (a, b) = crt_pair(&acc, &acc_m, &x, &pt).ok()?;
+                acc = a;
+                acc_m = b;
+            }
+
+            if (&base).powm(&acc, m) == target {
+                Some(acc)
+            } else {
+                None
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use rand::random;
+
+        #[test]
+        fn biguint_dlog_test() {
+            // against the primitive implementation
+            for _ in 0..10 {
+                let m = random::<u16>() as u64 + 1;
+                let (base, target) = (random::<u64>() % m, random::<u64>() % m);
+                #[cfg(feature = "std")]
+                assert_eq!(
+                    BigUint::from(base).discrete_log(&target.into(), &m.into()),
+                    base.discrete_log(&target, &m).map(BigUint::from)
+                );
+            }
+
+            // a 91-bit prime modulus p = 2 * (2^70 + 8) * q + 1 with a prime order q subgroup
+            let q = BigUint::from(1000003u32);
+            let p = ((BigUint::one() << 70u8) + 8u8) * &q * 2u8 + 1u8;
+            let g = BigUint::from(3u8).powm((&p - 1u8) / &q, &p);
+            let x = BigUint::from(random::<u32>()) % &q;
+            let target = (&g).powm(&x, &p);
+            assert_eq!(g.discrete_log_rho(&target, &p, &q), Some(x));
+
+            // -1 has order 2
+            let (m1, two) = (&p - 1u8, BigUint::from(2u8));
+            assert_eq!(m1.discrete_log_rho(&m1, &p, &two), Some(BigUint::one()));
+            assert_eq!(m1.discrete_log_rho(&g, &p, &two), None);
+
+            // p - 1 = 2 * q * (2^70 + 8) is not smooth, so the subgroup of order 2q is used
+            let order = BigUint::from(2u8) * &q;
+            let g = BigUint::from(3u8).powm((&p - 1u8) / &order, &p);
+            let x = BigUint::from(random::<u64>()) % &order;
+            let target = (&g).powm(&x, &p);
+            let factors = [(BigUint::from(2u8), 1), (q.clone(), 1)];
+            let y = g.pohlig_hellman(&target, &p, &factors).unwrap();
+            assert_eq!((&g).powm(&y, &p), target);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::random;
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn bsgs_test() {
+        // exhaustive on small moduli against brute force
+        for m in 1..=40u32 {
+            for base in 0..m {
+                for target in 0..m {
+                    let mut v = 1 % m;
+                    let mut expected = None;
+                    for x in 0..2 * m {
+                        if v == target {
+                            expected = Some(x);
+                            break;
+                        }
+                        v = v * base % m;
+                    }
+                    assert_eq!(
+                        discrete_log(&base, &target, &m),
+                        expected,
+                        "log_{} {} mod {}",
+                        base,
+                        target,
+                        m
+                    );
+                }
+            }
+        }
+
+        macro_rules! tests_for {
+            ($($T:ty: $($m:expr),*;)*) => ($($(
+                let m: $T = $m;
+                for _ in 0..10 {
+                    let base = random::<$T>() % m;
+                    let target = base.powm(random::<$T>() % m, &m);
+                    let x = discrete_log(&base, &target, &m).unwrap();
+                    assert_eq!(base.powm(x, &m), target, "log_{} {} mod {}", base, target, m);
+                    // no smaller solution for small moduli
+                    if (m as u64) < 1 << 16 {
+                        assert!((0..x).all(|y| base.powm(y, &m) != target));
+                    }
+                }
+            )*)*);
+        }
+        tests_for!(
+            u8: 251, 255, 128;
+            u16: 65521, 65535, 1 << 15, 3 * 3 * 7 * 11 * 13;
+            u32: 4294967291, 1000000, 999999937;
+            u64: 1000000007, 1 << 40, 99999989 * 6;
+            usize: 65537, 10007 * 4;
+        );
+
+        // 5 is a primitive root of p, and 4 generates the quadratic residues only
+        let p = 1_000_000_007u64;
+        assert_eq!(
+            discrete_log(&5u64, &5u64.powm(123456789, &p), &p),
+            Some(123456789)
+        );
+        assert_eq!(discrete_log(&4u64, &5, &p), None);
+        assert_eq!(discrete_log(&0u64, &0, &p), Some(1));
+        assert_eq!(discrete_log(&0u64, &1, &1), Some(0));
+    }
+
+    #[test]
+    fn rho_test() {
+        // safe primes p = 2q + 1, where the squares generate the subgroup of prime order q
+        macro_rules! tests_for {
+            ($($T:ty: $($p:expr),*;)*) => ($($(
+                let p: $T = $p;
+                let q = p / 2;
+                for _ in 0..10 {
+                    let g = (random::<$T>() % (p - 3) + 2).powm(2, &p);
+                    let x = random::<$T>() % q;
+                    let target = g.powm(x, &p);
+                    assert_eq!(discrete_log_rho(&g, &target, &p, &q), Some(x), "log_{} {} mod {}", g, target, p);
+                }
+                // a non-residue is not a power of g
+                let z = (2..p).find(|a| a.powm(q, &p) != 1).unwrap();
+                assert_eq!(discrete_log_rho(&4, &z, &p, &q), None);
+            )*)*);
+        }
+        tests_for!(
+            u8: 227, 167;
+            u16: 65267, 1019;
+            u32: 2147483579, 1000667;
+            u64: 2000000579;
+            usize: 2000000579;
+        );
+
+        // an even modulus
+        let m = 2 * 1000667u64;
+        let g = 9u64;
+        let target = g.powm(12345, &m);
+        let x = discrete_log_rho(&g, &target, &m, &(1000667 / 2)).unwrap();
+        assert_eq!(g.powm(x, &m), target);
+        assert_eq!(discrete_log_rho(&g, &1, &m, &(1000667 / 2)), Some(0));
+
+        // tiny prime orders, where the walk alone fails often
+        assert_eq!(discrete_log_rho(&6u64, &6, &7, &2), Some(1));
+        for &(p, q) in [
+            (7u32, 3u32),
+            (11, 5),
+            (29, 7),
+            (43, 7),
+            (2999, 2),
+            (2971, 3),
+        ]
+        .iter()
+        {
+            let g = (2..p)
+                .map(|a| a.powm((p - 1) / q, &p))
+                .find(|&g| g != 1)
+                .unwrap();
+            for x in 0..q {
+                let target = g.powm(x, &p);
+                assert_eq!(discrete_log_rho(&g, &target, &p, &q), Some(x));
+            }
+            assert_eq!(discrete_log_rho(&g, &0, &p, &q), None);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn rho_non_unit_test() {
+        discrete_log_rho(&6u32, &1, &9, &6);
+    }
+
+    #[test]
+    fn pohlig_hellman_test() {
+        // primes with smooth p - 1, and a primitive root g
+        macro_rules! tests_for {
+            ($($T:ty: $p:expr, $g:expr, [$(($f:expr, $k:expr)),*];)*) => ($(
+                let p: $T = $p;
+                let factors: &[($T, u32)] = &[$(($f, $k)),*];
+                for _ in 0..10 {
+                    let x = random::<$T>() % (p - 1);
+                    let target = ($g as $T).powm(x, &p);
+                    assert_eq!(pohlig_hellman(&$g, &target, &p, factors), Some(x), "log_{} {} mod {}", $g, target, p);
+
+                    // a base of smaller order with the same factorization of p - 1
+                    let base = ($g as $T).powm(factors[0].0, &p);
+                    let y = pohlig_hellman(&base, &target, &p, factors);
+                    match y {
+                        Some(y) => assert_eq!(base.powm(y, &p), target),
+                        None => assert!(x % factors[0].0 != 0),
+                    }
+                }
+            )*);
+        }
+        tests_for!(
+            u8: 193, 5, [(2, 6), (3, 1)];
+            u16: 40961, 3, [(2, 13), (5, 1)];
+            u32: 998244353, 3, [(2, 23), (7, 1), (17, 1)];
+            u64: 1000000007, 5, [(2, 1), (500000003, 1)];
+            u64: 0xffffffff00000001, 7, [(2, 32), (3, 1), (5, 1), (17, 1), (257, 1), (65537, 1)];
+            usize: 7340033, 3, [(2, 20), (7, 1)];
+        );
+
+        // a composite modulus: the group (Z/15Z)* has order 8, and 2 has order 4
+        assert_eq!(pohlig_hellman(&2u32, &8, &15, &[(2, 3)]), Some(3));
+        assert_eq!(pohlig_hellman(&2u32, &7, &15, &[(2, 3)]), None);
+        assert_eq!(pohlig_hellman(&2u32, &1, &15, &[]), Some(0));
+    }
+}
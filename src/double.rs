@@ -1,6 +1,8 @@
 //! This module implements a double width integer type based on the largest built-in integer (u128)
 //! Part of the optimization comes from `ethnum` and `zkp-u256` crates.
 
+use core::convert::TryFrom;
+use core::num::TryFromIntError;
 use core::ops::*;
 
 /// Alias of the builtin integer type with max width (currently [u128])
@@ -26,7 +28,23 @@ const fn div_rem(n: umax, d: umax) -> (umax, umax) {
 /// to support double-width operations on it is the only goal for this type.
 ///
 /// Although it can be regarded as u256, it's not as feature-rich as in other crates
-/// since it's only designed to support this crate and few other crates (will be noted in comments).
+/// since it's mainly designed to support this crate (e.g. the [Montgomery][crate::Montgomery]
+/// arithmetics on [u128]) and few other crates (will be noted in comments). It can still be used
+/// standalone for the 256-bit intermediate results, with the widening operations, the checked
+/// arithmetics and the division.
+///
+/// Example code:
+/// ```rust
+/// use core::convert::TryFrom;
+/// use num_modular::udouble;
+///
+/// let (a, b) = (u128::MAX - 2, u128::MAX - 4);
+/// let p = udouble::widening_mul(a, b);
+/// assert_eq!(p.div_rem(udouble::from(b)), (udouble::from(a), udouble::ZERO));
+/// assert_eq!(p % 1_000_000_007, (a % 1_000_000_007) * (b % 1_000_000_007) % 1_000_000_007);
+/// assert_eq!(p.checked_add(udouble::MAX), None);
+/// assert_eq!(u128::try_from(p >> 128u8), Ok(u128::MAX - 7));
+/// ```
 pub struct udouble {
     /// Most significant part
     pub hi: umax,
@@ -35,10 +53,15 @@ pub struct udouble {
 }
 
 impl udouble {
+    /// The largest value of the type
     pub const MAX: Self = Self {
         lo: umax::MAX,
         hi: umax::MAX,
     };
+    /// The value zero
+    pub const ZERO: Self = Self { lo: 0, hi: 0 };
+    /// The value one
+    pub const ONE: Self = Self { lo: 1, hi: 0 };
 
     //> (used in u128::addm)
     #[inline]
@@ -96,9 +119,35 @@ impl udouble {
         (Self { lo, hi }, of1 || of2)
     }
 
-    // double by double multiplication, listed here in case of future use
-    #[allow(dead_code)]
-    fn overflowing_mul(&self, rhs: Self) -> (Self, bool) {
+    /// Subtraction with the flag of underflow
+    #[inline]
+    pub const fn overflowing_sub(&self, rhs: Self) -> (Self, bool) {
+        let (lo, borrow) = self.lo.overflowing_sub(rhs.lo);
+        let (hi, of1) = self.hi.overflowing_sub(rhs.hi);
+        let (hi, of2) = hi.overflowing_sub(borrow as umax);
+        (Self { lo, hi }, of1 || of2)
+    }
+
+    /// Addition that returns [None] on overflow
+    #[inline]
+    pub const fn checked_add(&self, rhs: Self) -> Option<Self> {
+        match self.overflowing_add(rhs) {
+            (v, false) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Subtraction that returns [None] on underflow
+    #[inline]
+    pub const fn checked_sub(&self, rhs: Self) -> Option<Self> {
+        match self.overflowing_sub(rhs) {
+            (v, false) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Multiplication of two double width integers with the flag of overflow
+    pub fn overflowing_mul(&self, rhs: Self) -> (Self, bool) {
         let c2 = self.hi != 0 && rhs.hi != 0;
         let Self { lo: z0, hi: c0 } = Self::widening_mul(self.lo, rhs.lo);
         let (z1x, c1x) = umax::overflowing_mul(self.lo, rhs.hi);
@@ -108,6 +157,15 @@ impl udouble {
         (Self { hi: z1, lo: z0 }, c1x | c1y | c1z | c1 | c2)
     }
 
+    /// Multiplication of two double width integers that returns [None] on overflow
+    #[inline]
+    pub fn checked_mul(&self, rhs: Self) -> Option<Self> {
+        match self.overflowing_mul(rhs) {
+            (v, false) => Some(v),
+            _ => None,
+        }
+    }
+
     /// Multiplication of double width and single width
     //> (used in num-order:NumHash)
     #[inline]
@@ -155,6 +213,19 @@ impl From<umax> for udouble {
     }
 }
 
+impl TryFrom<udouble> for umax {
+    type Error = TryFromIntError;
+    #[inline]
+    fn try_from(v: udouble) -> Result<Self, Self::Error> {
+        if v.hi == 0 {
+            Ok(v.lo)
+        } else {
+            // there's no public constructor of TryFromIntError
+            Err(u8::try_from(u16::MAX).unwrap_err())
+        }
+    }
+}
+
 impl Add for udouble {
     type Output = udouble;
 
@@ -398,9 +469,30 @@ impl udouble {
         }
     }
 
+    /// Number of trailing zeros in the binary representation
+    #[inline]
+    pub const fn trailing_zeros(self) -> u32 {
+        if self.lo == 0 {
+            self.hi.trailing_zeros() + umax::BITS
+        } else {
+            self.lo.trailing_zeros()
+        }
+    }
+
+    /// Calculate the quotient and the remainder of the division by another double width integer.
+    ///
+    /// # Panics
+    /// if the divisor is zero
+    pub fn div_rem(self, other: Self) -> (Self, Self) {
+        if other.hi == 0 {
+            let q = self / other.lo;
+            (q, Self::from(self % other.lo))
+        } else {
+            self.div_rem_2by2(other)
+        }
+    }
+
     // double by double division (long division), it's not the most efficient algorithm.
-    // listed here in case of future use
-    #[allow(dead_code)]
     fn div_rem_2by2(self, other: Self) -> (Self, Self) {
         let mut n = self; // numerator
         let mut d = other; // denominator
@@ -515,6 +607,30 @@ impl Div<umax> for udouble {
     }
 }
 
+impl Mul for udouble {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.checked_mul(rhs).expect("multiplication overflow!")
+    }
+}
+
+impl Div for udouble {
+    type Output = Self;
+    #[inline]
+    fn div(self, rhs: Self) -> Self::Output {
+        self.div_rem(rhs).0
+    }
+}
+
+impl Rem for udouble {
+    type Output = Self;
+    #[inline]
+    fn rem(self, rhs: Self) -> Self::Output {
+        self.div_rem(rhs).1
+    }
+}
+
 //> (used in Montgomery::<u128>::transform)
 impl Rem<umax> for udouble {
     type Output = umax;
@@ -643,6 +759,59 @@ mod tests {
         assert_eq!(TWOZERO.checked_mul1(MAX.lo), None);
     }
 
+    #[test]
+    fn test_double_ops() {
+        for _ in 0..10 {
+            let (a, b, c) = (random::<umax>(), random::<umax>(), random::<umax>());
+            let p = udouble::widening_mul(a, b);
+            assert_eq!(
+                p.div_rem(udouble::from(b)),
+                (udouble::from(a), udouble::ZERO)
+            );
+            assert_eq!(p / udouble::from(a), udouble::from(b));
+
+            // divisors of double width
+            let d = udouble {
+                hi: c >> 64,
+                lo: random(),
+            };
+            if !(d.hi == 0 && d.lo == 0) {
+                let (q, r) = p.div_rem(d);
+                assert!(r < d);
+                assert_eq!(q * d + r, p);
+                assert_eq!(p % d, r);
+            }
+
+            let (x, y) = (udouble::from(a), udouble::from(b));
+            assert_eq!(x * y, p);
+            assert_eq!(x.checked_mul(y), Some(p));
+            assert_eq!(
+                p.checked_mul(p),
+                if a == 0 || b == 0 {
+                    Some(udouble::ZERO)
+                } else {
+                    None
+                }
+            );
+            assert_eq!(p.overflowing_sub(x).0 + x, p);
+            assert_eq!(x.checked_sub(y), if a >= b { Some(x - y) } else { None });
+            assert_eq!(
+                p.checked_add(udouble::MAX),
+                if p == udouble::ZERO {
+                    Some(udouble::MAX)
+                } else {
+                    None
+                }
+            );
+        }
+
+        assert_eq!(umax::try_from(udouble::from(5)), Ok(5));
+        assert!(umax::try_from(udouble::MAX).is_err());
+        assert_eq!(udouble::ONE.trailing_zeros(), 0);
+        assert_eq!((udouble::ONE << 200u8).trailing_zeros(), 200);
+        assert_eq!(udouble::ZERO.trailing_zeros(), 256);
+    }
+
     #[test]
     fn test_assign_ops() {
         for _ in 0..10 {
@@ -0,0 +1,508 @@
+//! Factorization and irreducibility test of polynomials over ℤ/pℤ
+
+use crate::ntt::convert_u64;
+use crate::root::prime_powers;
+use crate::{ModularInteger, Poly, QuotientRing};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// The splitmix64 generator, the candidates for splitting are derived from a fixed seed so
+/// that the factorization is deterministic
+fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+impl<M: ModularInteger + Clone> Poly<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    /// The modulus of the coefficients as u64
+    fn prime(&self) -> u64 {
+        self.coeff(0).modulus().into()
+    }
+
+    /// The monomial `t`
+    fn t(&self) -> Self {
+        Poly::monomial(convert_u64(&self.coeff(0), 1), 1)
+    }
+
+    /// Calculate the p-th root of a polynomial with zero derivative, which has only the
+    /// terms of degrees divisible by p
+    fn pth_root(&self) -> Self {
+        let p = self.prime() as usize;
+        let coeffs = self.coeffs().iter().step_by(p).cloned().collect();
+        Poly::new(coeffs, &self.coeff(0))
+    }
+
+    /// Find the square-free factorization `self = lc * prod f_i^(e_i)`, where the factors
+    /// `f_i` are monic, square-free, pairwise coprime and non-constant, and the multiplicities
+    /// `e_i` are distinct. The factors are sorted by the multiplicities.
+    ///
+    /// The modulus should be a prime.
+    ///
+    /// # Panics
+    /// if the polynomial is zero
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{ModularInteger, MontgomeryInt, Poly};
+    ///
+    /// let x = MontgomeryInt::new(0u64, &7);
+    /// let f = Poly::from_residues([1, 1], &x); // t + 1
+    /// let g = Poly::from_residues([2, 0, 1], &x); // t^2 + 2
+    /// let h = &(&(&f * &f) * &f) * &g;
+    /// assert_eq!(h.square_free_factors(), vec![(g, 1), (f, 3)]);
+    /// ```
+    pub fn square_free_factors(&self) -> Vec<(Self, usize)> {
+        assert!(!self.is_zero(), "the polynomial should not be zero");
+        let mut result = Vec::new();
+        self.monic().square_free_into(1, &mut result);
+        result.sort_by_key(|(_, e)| *e);
+        result
+    }
+
+    fn square_free_into(&self, scale: usize, result: &mut Vec<(Self, usize)>) {
+        if self.degree() == Some(0) {
+            return;
+        }
+        let d = self.derivative();
+        if d.is_zero() {
+            return self
+                .pth_root()
+                .square_free_into(scale * self.prime() as usize, result);
+        }
+
+        // Yun's algorithm, the factors with multiplicities divisible by p are left in c
+        let mut c = self.gcd(&d);
+        let mut w = self / &c;
+        let mut i = 1;
+        while w.degree() != Some(0) {
+            let y = w.gcd(&c);
+            let fac = &w / &y;
+            if fac.degree() != Some(0) {
+                result.push((fac, i * scale));
+            }
+            i += 1;
+            w = y;
+            c = &c / &w;
+        }
+        if c.degree() != Some(0) {
+            c.pth_root()
+                .square_free_into(scale * self.prime() as usize, result);
+        }
+    }
+
+    /// Find the distinct-degree factorization of a monic square-free polynomial, which is
+    /// the list of `(g_d, d)` where `g_d` is the product of all the irreducible factors of
+    /// degree d. The degrees are increasing and the products are monic.
+    ///
+    /// The factor `g_d` is found as `gcd(f, t^(p^d) - t)`, where the powers of t are computed
+    /// in the [QuotientRing] modulo the remaining factor. The modulus should be a prime.
+    ///
+    /// # Panics
+    /// if the polynomial is zero
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{ModularInteger, MontgomeryInt, Poly};
+    ///
+    /// let x = MontgomeryInt::new(0u64, &5);
+    /// // (t + 1)(t + 2)(t^2 + 2), where t^2 + 2 is irreducible modulo 5
+    /// let f = Poly::from_residues([4, 1, 4, 3, 1], &x);
+    /// let ddf = f.distinct_degree_factors();
+    /// assert_eq!(ddf[0], (Poly::from_residues([2, 3, 1], &x), 1));
+    /// assert_eq!(ddf[1], (Poly::from_residues([2, 0, 1], &x), 2));
+    /// ```
+    pub fn distinct_degree_factors(&self) -> Vec<(Self, usize)> {
+        assert!(!self.is_zero(), "the polynomial should not be zero");
+        let p = self.prime();
+        let mut f = self.monic();
+        let mut result = Vec::new();
+        let mut h = self.t();
+        let mut d = 1;
+        while f.degree().unwrap() >= 2 * d {
+            let ring = QuotientRing::new(f.clone());
+            h = ring.pow(&h, p);
+            let g = f.gcd(&(&h - &f.t()));
+            if g.degree() != Some(0) {
+                f = &f / &g;
+                h = &h % &f;
+                result.push((g, d));
+            }
+            d += 1;
+        }
+        if let Some(deg) = f.degree().filter(|&deg| deg > 0) {
+            result.push((f, deg));
+        }
+        result
+    }
+
+    /// Split a monic square-free polynomial whose irreducible factors all have degree d into
+    /// the factors, by the equal-degree factorization of Cantor and Zassenhaus. The factors
+    /// are monic and sorted by the residues of their coefficients.
+    ///
+    /// For an odd prime p, `gcd(f, h^((p^d-1)/2) - 1)` splits f for half of the random
+    /// polynomials h, and for p = 2 the trace `h + h^2 + ... + h^(2^(d-1))` is used
+    /// instead. The random polynomials are generated deterministically. The modulus should
+    /// be a prime.
+    ///
+    /// # Panics
+    /// if d is zero or the degree of the polynomial is not a multiple of d
+    pub fn equal_degree_factors(&self, d: usize) -> Vec<Self> {
+        assert!(d > 0, "the degree of the factors should be positive");
+        let n = self.degree().unwrap_or(0);
+        assert!(
+            n % d == 0,
+            "the degree should be a multiple of the factor degree"
+        );
+        let mut result = Vec::new();
+        if n > 0 {
+            let mut state = 0;
+            self.monic().split_into(d, &mut state, &mut result);
+        }
+        result.sort_by(|a, b| {
+            let key =
+                |f: &Self| -> Vec<u64> { f.coeffs().iter().map(|c| c.residue().into()).collect() };
+            key(a).cmp(&key(b))
+        });
+        result
+    }
+
+    fn split_into(&self, d: usize, state: &mut u64, result: &mut Vec<Self>) {
+        let n = self.degree().unwrap();
+        if n == d {
+            result.push(self.clone());
+            return;
+        }
+
+        let p = self.prime();
+        let x = self.coeff(0);
+        let one = Poly::monomial(convert_u64(&x, 1), 0);
+        let ring = QuotientRing::new(self.clone());
+        loop {
+            let coeffs = (0..n)
+                .map(|_| convert_u64(&x, next_random(state) % p))
+                .collect();
+            let h = Poly::new(coeffs, &x);
+            if h.degree().map_or(true, |deg| deg == 0) {
+                continue;
+            }
+
+            // s = h + h^p + ... + h^(p^(d-1)), or the norm with products for odd p
+            let mut s = h.clone();
+            let mut hp = h;
+            for _ in 1..d {
+                hp = ring.pow(&hp, p);
+                s = if p == 2 {
+                    ring.add(&s, &hp)
+                } else {
+                    ring.mul(&s, &hp)
+                };
+            }
+            let candidate = if p == 2 {
+                s
+            } else {
+                &ring.pow(&s, (p - 1) / 2) - &one
+            };
+
+            let g = self.gcd(&candidate);
+            let deg = g.degree().unwrap_or(0);
+            if deg > 0 && deg < n {
+                (self / &g).split_into(d, state, result);
+                g.split_into(d, state, result);
+                return;
+            }
+        }
+    }
+
+    /// Factorize the polynomial into monic irreducible factors with their multiplicities,
+    /// such that `self = lc * prod f_i^(e_i)` where `lc` is the leading coefficient. The
+    /// factors are sorted by their degrees and then by the residues of their coefficients.
+    ///
+    /// It combines [square_free_factors()][Poly::square_free_factors],
+    /// [distinct_degree_factors()][Poly::distinct_degree_factors] and
+    /// [equal_degree_factors()][Poly::equal_degree_factors]. The modulus should be a prime.
+    ///
+    /// # Panics
+    /// if the polynomial is zero
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{Poly, VanillaInt};
+    ///
+    /// let x = VanillaInt::new(0u64, &2);
+    /// // t^5 + t^4 + t^2 + t = t(t + 1)^2(t^2 + t + 1)
+    /// let f = Poly::from_residues([0, 1, 1, 0, 1, 1], &x);
+    /// let factors: Vec<_> = f.factor().into_iter().map(|(g, e)| (g.residues(), e)).collect();
+    /// assert_eq!(factors, [(vec![0, 1], 1), (vec![1, 1], 2), (vec![1, 1, 1], 1)]);
+    /// ```
+    pub fn factor(&self) -> Vec<(Self, usize)> {
+        let mut result = Vec::new();
+        for (f, e) in self.square_free_factors() {
+            for (g, d) in f.distinct_degree_factors() {
+                result.extend(g.equal_degree_factors(d).into_iter().map(|h| (h, e)));
+            }
+        }
+        result.sort_by(|(a, _), (b, _)| {
+            let key = |f: &Self| -> (usize, Vec<u64>) {
+                let coeffs = f
+                    .coeffs()
+                    .iter()
+                    .rev()
+                    .map(|c| c.residue().into())
+                    .collect();
+                (f.degree().unwrap(), coeffs)
+            };
+            key(a).cmp(&key(b))
+        });
+        result
+    }
+
+    /// Check whether the polynomial is irreducible by Rabin's test: f of degree n is
+    /// irreducible iff `t^(p^n) = t mod f` and `gcd(f, t^(p^(n/q)) - t) = 1` for each prime
+    /// factor q of n. It takes n exponentiations by p in the [QuotientRing] modulo f, and it
+    /// returns false for the constants (including zero). The modulus should be a prime.
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{ModularInteger, MontgomeryInt, Poly, QuotientRing};
+    ///
+    /// // find a defining polynomial t^3 + t + b of GF(101^3)
+    /// let x = MontgomeryInt::new(0u64, &101);
+    /// let f = (0..101)
+    ///     .map(|b| Poly::from_residues([b, 1, 0, 1], &x))
+    ///     .find(|f| f.is_irreducible())
+    ///     .unwrap();
+    /// let field = QuotientRing::new(f);
+    /// let a = Poly::from_residues([3, 1], &x);
+    /// assert_eq!(field.pow(&a, 101u64.pow(3) - 1), field.one());
+    /// ```
+    pub fn is_irreducible(&self) -> bool {
+        let n = match self.degree() {
+            None | Some(0) => return false,
+            Some(1) => return true,
+            Some(n) => n,
+        };
+        let p = self.prime();
+        let f = self.monic();
+        let ring = QuotientRing::new(f.clone());
+        let t = f.t();
+
+        // the exponents n / q for the prime factors q, in increasing order
+        let mut checks: Vec<usize> = prime_powers(n as u128)
+            .map(|(q, _)| n / q as usize)
+            .collect();
+        checks.sort_unstable();
+        let mut checks = checks.into_iter().peekable();
+
+        let mut h = t.clone();
+        for i in 1..=n {
+            h = ring.pow(&h, p);
+            if checks.peek() == Some(&i) {
+                checks.next();
+                if f.gcd(&(&h - &t)).degree() != Some(0) {
+                    return false;
+                }
+            }
+        }
+        h == t
+    }
+
+    /// Find all the distinct roots of the polynomial in ℤ/pℤ, sorted by their residues.
+    /// The linear factors are extracted by `gcd(f, t^p - t)` and then split by
+    /// [equal_degree_factors()][Poly::equal_degree_factors]. The modulus should be a prime.
+    ///
+    /// # Panics
+    /// if the polynomial is zero
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{ModularInteger, MontgomeryInt, Poly};
+    ///
+    /// let x = MontgomeryInt::new(0u64, &1_000_000_007);
+    /// let f = Poly::from_residues([1_000_000_003, 0, 1], &x); // t^2 - 4
+    /// let roots: Vec<u64> = f.roots().iter().map(|r| r.residue()).collect();
+    /// assert_eq!(roots, [2, 1_000_000_005]);
+    /// ```
+    pub fn roots(&self) -> Vec<M> {
+        assert!(!self.is_zero(), "the polynomial should not be zero");
+        if self.degree() == Some(0) {
+            return Vec::new();
+        }
+        let f = self.monic();
+        let ring = QuotientRing::new(f.clone());
+        let tp = ring.pow(&f.t(), self.prime());
+        let g = f.gcd(&(&tp - &f.t()));
+        let mut roots: Vec<M> = g
+            .equal_degree_factors(1)
+            .into_iter()
+            .map(|h| -h.coeff(0))
+            .collect();
+        roots.sort_by_key(|r| -> u64 { r.residue().into() });
+        roots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VanillaInt;
+    use rand::random;
+
+    type Mint = VanillaInt<u64>;
+
+    fn random_monic(x: &Mint, deg: usize) -> Poly<Mint> {
+        let mut coeffs: Vec<Mint> = (0..deg).map(|_| x.convert(random())).collect();
+        coeffs.push(x.convert(1));
+        Poly::new(coeffs, x)
+    }
+
+    fn product(factors: &[(Poly<Mint>, usize)], x: &Mint) -> Poly<Mint> {
+        let mut result = Poly::monomial(x.convert(1), 0);
+        for (f, e) in factors {
+            for _ in 0..*e {
+                result = &result * f;
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn factor_test() {
+        for &p in [2u64, 3, 5, 101, 998244353, 1_000_000_007].iter() {
+            let x = Mint::new(0, &p);
+            for _ in 0..5 {
+                // random polynomials with repeated factors
+                let (a, b) = (random_monic(&x, 4), random_monic(&x, 3));
+                let f = &(&(&a * &a) * &b) * &random_monic(&x, 6);
+                let f = f.scale(&x.convert(p - 1));
+                let factors = f.factor();
+                assert_eq!(
+                    product(&factors, &x).scale(f.leading().unwrap()),
+                    f,
+                    "p = {}",
+                    p
+                );
+                for (g, _) in factors.iter() {
+                    assert_eq!(g.leading(), Some(&x.convert(1)));
+                    assert_eq!(
+                        g.distinct_degree_factors(),
+                        [(g.clone(), g.degree().unwrap())]
+                    );
+                }
+                for w in factors.windows(2) {
+                    assert_ne!(w[0].0, w[1].0);
+                }
+            }
+        }
+
+        // p-th powers in characteristic p
+        let x = Mint::new(0, &3);
+        let f = Poly::from_residues([1, 0, 0, 1], &x); // t^3 + 1 = (t + 1)^3
+        assert_eq!(f.factor(), [(Poly::from_residues([1, 1], &x), 3)]);
+        let g = Poly::from_residues([2, 0, 0, 0, 0, 0, 0, 0, 0, 1], &x); // (t - 1)^9
+        assert_eq!(g.factor(), [(Poly::from_residues([2, 1], &x), 9)]);
+        let h = &(&f * &f) * &Poly::from_residues([1, 0, 1], &x);
+        assert_eq!(
+            h.factor(),
+            [
+                (Poly::from_residues([1, 1], &x), 6),
+                (Poly::from_residues([1, 0, 1], &x), 1)
+            ]
+        );
+
+        assert!(Poly::monomial(x.convert(2), 0).factor().is_empty());
+    }
+
+    #[test]
+    fn irreducible_count_test() {
+        // t^(p^k) - t is the product of all monic irreducible polynomials of degree dividing k
+        let x = VanillaInt::new(0u32, &3);
+        let mut coeffs = std::vec![0u32; 28];
+        coeffs[1] = 2;
+        coeffs[27] = 1;
+        let f = Poly::from_residues(coeffs, &x);
+        let factors = f.factor();
+        assert_eq!(
+            factors
+                .iter()
+                .filter(|(g, _)| g.degree() == Some(1))
+                .count(),
+            3
+        );
+        assert_eq!(
+            factors
+                .iter()
+                .filter(|(g, _)| g.degree() == Some(3))
+                .count(),
+            8
+        );
+        assert!(factors.iter().all(|(_, e)| *e == 1));
+    }
+
+    #[test]
+    fn is_irreducible_test() {
+        // compare with the factorization
+        for &p in [2u64, 3, 7, 998244353].iter() {
+            let x = Mint::new(0, &p);
+            for deg in 1..9 {
+                for _ in 0..5 {
+                    let f = random_monic(&x, deg);
+                    let expected = matches!(f.factor().as_slice(), [(_, 1)]);
+                    assert_eq!(f.is_irreducible(), expected, "{:?}", f.residues());
+                }
+            }
+        }
+
+        // the number of monic irreducible polynomials of degree 4 over GF(3) is (81 - 9) / 4
+        let x = Mint::new(0, &3);
+        let count = (0..81u64)
+            .filter(|v| {
+                let coeffs = [v % 3, v / 3 % 3, v / 9 % 3, v / 27, 1];
+                Poly::from_residues(coeffs, &x).is_irreducible()
+            })
+            .count();
+        assert_eq!(count, 18);
+
+        // (t^2 + 1)(t^3 + 2t + 1) has no linear factor, only the final check fails
+        let f = Poly::from_residues([1, 2, 1, 0, 0, 1], &x);
+        assert!(f.roots().is_empty());
+        assert!(!f.is_irreducible());
+        assert!(!Poly::from_residues([0, 0, 2, 1, 1], &x).is_irreducible());
+        assert!(!Poly::monomial(x.convert(2), 0).is_irreducible());
+        assert!(!Poly::zero(&x).is_irreducible());
+        assert!(Poly::from_residues([1, 2], &x).is_irreducible());
+    }
+
+    #[test]
+    fn roots_test() {
+        for &p in [2u64, 7, 101, 998244353, 1_000_000_007].iter() {
+            let x = Mint::new(0, &p);
+            let mut roots: Vec<u64> = (0..5).map(|_| random::<u64>() % p).collect();
+            let mut f = random_monic(&x, 3);
+            for &r in roots.iter() {
+                f = &f * &Poly::new(std::vec![-x.convert(r), x.convert(1)], &x);
+            }
+
+            let found: Vec<u64> = f.roots().iter().map(|r| r.residue()).collect();
+            assert!(found.windows(2).all(|w| w[0] < w[1]));
+            assert!(found.iter().all(|&r| f.eval(&x.convert(r)).is_zero()));
+            if p <= 101 {
+                roots = (0..p)
+                    .filter(|&r| f.eval(&x.convert(r)).is_zero())
+                    .collect();
+                assert_eq!(found, roots, "p = {}", p);
+            } else {
+                assert!(roots.iter().all(|r| found.contains(r)));
+            }
+        }
+
+        let x = Mint::new(0, &1_000_000_007);
+        assert!(Poly::from_residues([1, 0, 1], &x).roots().is_empty()); // -1 is a QNR
+        assert!(Poly::monomial(x.convert(3), 0).roots().is_empty());
+    }
+}
@@ -0,0 +1,260 @@
+//! Square matrices of a size fixed at compile time over [ModularInteger]
+
+use crate::ModularInteger;
+use core::ops::{Add, Mul, Sub};
+
+/// A square matrix of size `N x N` with entries of a [ModularInteger] type.
+///
+/// Different from [ModMatrix][crate::ModMatrix], the entries are stored inline in an array,
+/// so no allocation is needed, and the size mismatches are rejected at compile time. All the
+/// entries should have the same modulus, otherwise the operations panic (same as the
+/// [ModularInteger] operators). It's designed for the small matrices used by linear
+/// recurrences, where [pow()][FixedMatrix::pow] is the main operation.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{Matrix2, ModularInteger, MontgomeryInt};
+///
+/// // the Fibonacci numbers by matrix exponentiation
+/// let one = MontgomeryInt::new(1u64, &1_000_000_007);
+/// let zero = one.convert(0);
+/// let fib = Matrix2::new([[one, one], [one, zero]]);
+/// assert_eq!(fib.pow(90)[0][1].residue(), 2880067194370816120 % 1_000_000_007);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedMatrix<M, const N: usize> {
+    entries: [[M; N]; N],
+}
+
+/// A 2x2 matrix over [ModularInteger]
+pub type Matrix2<M> = FixedMatrix<M, 2>;
+
+impl<M: ModularInteger + Clone, const N: usize> FixedMatrix<M, N>
+where
+    M::Base: From<u8>,
+{
+    /// Create a matrix from the rows of entries
+    #[inline]
+    pub const fn new(entries: [[M; N]; N]) -> Self {
+        Self { entries }
+    }
+
+    /// Create a matrix with all entries being zero, with the same modulus as x
+    pub fn zeros(x: &M) -> Self {
+        let zero = x.convert(M::Base::from(0));
+        Self {
+            entries: [(); N].map(|_| [(); N].map(|_| zero.clone())),
+        }
+    }
+
+    /// Create an identity matrix, with the same modulus as x
+    pub fn identity(x: &M) -> Self {
+        let mut result = Self::zeros(x);
+        let one = x.convert(M::Base::from(1));
+        for (i, row) in result.entries.iter_mut().enumerate() {
+            row[i] = one.clone();
+        }
+        result
+    }
+
+    /// Get the rows of entries
+    #[inline]
+    pub fn entries(&self) -> &[[M; N]; N] {
+        &self.entries
+    }
+
+    /// Take the rows of entries
+    #[inline]
+    pub fn into_entries(self) -> [[M; N]; N] {
+        self.entries
+    }
+
+    /// Calculate the product of self and a column vector
+    pub fn mul_vec(&self, v: &[M; N]) -> [M; N] {
+        self.entries.clone().map(|row| {
+            let mut terms = IntoIterator::into_iter(row)
+                .zip(v.iter().cloned())
+                .map(|(a, b)| a * b);
+            let first = terms.next().expect("the matrix should not be empty");
+            terms.fold(first, |acc, t| acc + t)
+        })
+    }
+
+    /// Calculate self ^ exp by binary exponentiation
+    ///
+    /// # Panics
+    /// if N is zero
+    pub fn pow(&self, mut exp: u64) -> Self {
+        let mut result = Self::identity(&self.entries[0][0]);
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+impl<M, const N: usize> core::ops::Index<usize> for FixedMatrix<M, N> {
+    type Output = [M; N];
+    #[inline]
+    fn index(&self, i: usize) -> &[M; N] {
+        &self.entries[i]
+    }
+}
+
+impl<M, const N: usize> core::ops::IndexMut<usize> for FixedMatrix<M, N> {
+    #[inline]
+    fn index_mut(&mut self, i: usize) -> &mut [M; N] {
+        &mut self.entries[i]
+    }
+}
+
+impl<M: ModularInteger + Clone, const N: usize> Mul for &FixedMatrix<M, N> {
+    type Output = FixedMatrix<M, N>;
+
+    fn mul(self, rhs: Self) -> FixedMatrix<M, N> {
+        let entries = self.entries.clone().map(|row| {
+            let mut j = 0;
+            [(); N].map(|_| {
+                let col = j;
+                j += 1;
+                let mut terms = row
+                    .iter()
+                    .zip(rhs.entries.iter())
+                    .map(|(a, brow)| a.clone() * brow[col].clone());
+                let first = terms.next().expect("the matrix should not be empty");
+                terms.fold(first, |acc, t| acc + t)
+            })
+        });
+        FixedMatrix { entries }
+    }
+}
+
+impl<M: ModularInteger + Clone, const N: usize> Mul for FixedMatrix<M, N> {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        &self * &rhs
+    }
+}
+
+macro_rules! impl_elementwise_op {
+    ($op:ident, $method:ident) => {
+        impl<M: ModularInteger + Clone, const N: usize> $op for FixedMatrix<M, N> {
+            type Output = Self;
+            fn $method(mut self, rhs: Self) -> Self {
+                for (row, rrow) in self.entries.iter_mut().zip(rhs.entries) {
+                    for (a, b) in row.iter_mut().zip(rrow) {
+                        *a = a.clone().$method(b);
+                    }
+                }
+                self
+            }
+        }
+    };
+}
+impl_elementwise_op!(Add, add);
+impl_elementwise_op!(Sub, sub);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fibm, ModularCoreOps, MontgomeryInt, VanillaInt};
+    use rand::random;
+
+    type Mint = MontgomeryInt<u64>;
+
+    fn random_matrix<const N: usize>(x: &Mint) -> FixedMatrix<Mint, N> {
+        FixedMatrix::new([[(); N]; N].map(|row| row.map(|_| x.convert(random()))))
+    }
+
+    #[test]
+    fn arithmetic_test() {
+        let x = Mint::new(0, &(random::<u64>() | 1));
+        let m = x.modulus();
+        let (a, b, c) = (
+            random_matrix::<3>(&x),
+            random_matrix::<3>(&x),
+            random_matrix::<3>(&x),
+        );
+        assert_eq!((a * b) * c, a * (b * c));
+        assert_eq!(a * (b + c), a * b + a * c);
+        assert_eq!(a + b - b, a);
+        assert_eq!(FixedMatrix::identity(&x) * a, a);
+        assert_eq!(a * FixedMatrix::zeros(&x), FixedMatrix::zeros(&x));
+
+        // against the scalar operations
+        let ab = a * b;
+        for i in 0..3 {
+            for j in 0..3 {
+                let v = (0..3).fold(0u64, |acc, k| {
+                    acc.addm(a[i][k].residue().mulm(b[k][j].residue(), &m), &m)
+                });
+                assert_eq!(ab[i][j].residue(), v);
+            }
+        }
+        let v = [
+            x.convert(random()),
+            x.convert(random()),
+            x.convert(random()),
+        ];
+        let av = a.mul_vec(&v);
+        for i in 0..3 {
+            assert_eq!(av[i], a[i][0] * v[0] + a[i][1] * v[1] + a[i][2] * v[2]);
+        }
+
+        let mut d = a;
+        d[1][2] = x.convert(5);
+        assert_eq!(d.entries()[1][2].residue(), 5 % m);
+        assert_eq!(d.into_entries()[0], a[0]);
+    }
+
+    #[test]
+    fn pow_test() {
+        let x = Mint::new(0, &(random::<u64>() | 1));
+        let a = random_matrix::<4>(&x);
+        let mut expected = FixedMatrix::identity(&x);
+        for e in 0..20u64 {
+            assert_eq!(a.pow(e), expected);
+            expected = expected * a;
+        }
+
+        // Fibonacci numbers with an even modulus
+        let one = VanillaInt::<u32>::new(1, &(1 << 20));
+        let fib = Matrix2::new([[one, one], [one, one.convert(0)]]);
+        for &n in [0u64, 1, 2, 50, 1000, 123456789].iter() {
+            assert_eq!(fib.pow(n)[0][1].residue(), fibm(&(n as u32), &(1 << 20)));
+        }
+    }
+
+    #[test]
+    fn linear_recurrence_test() {
+        // a(n) = 2a(n-1) + 3a(n-2) + 5a(n-3), a(0) = a(1) = a(2) = 1
+        let m = 998244353u64;
+        let x = Mint::new(0, &m);
+        let mut seq = std::vec![1u64, 1, 1];
+        while seq.len() <= 200 {
+            let l = seq.len();
+            let next = 2u64
+                .mulm(seq[l - 1], &m)
+                .addm(3u64.mulm(seq[l - 2], &m), &m)
+                .addm(5u64.mulm(seq[l - 3], &m), &m);
+            seq.push(next);
+        }
+
+        let c = |v: u64| x.convert(v);
+        let comp = FixedMatrix::new([[c(2), c(3), c(5)], [c(1), c(0), c(0)], [c(0), c(1), c(0)]]);
+        let state = [c(1), c(1), c(1)];
+        for k in [0u64, 1, 17, 100, 197].iter() {
+            let v = comp.pow(*k).mul_vec(&state);
+            assert_eq!(v[0].residue(), seq[*k as usize + 2]);
+            assert_eq!(v[2].residue(), seq[*k as usize]);
+        }
+    }
+}
@@ -0,0 +1,523 @@
+//! The quadratic extension field GF(p²) over [ModularInteger]
+
+use crate::quad::{inv_parts, mul_parts, norm_parts, sqr_parts, QuadBase};
+use crate::{FindQnr, ModularInteger, ModularRefOps, ModularSqrt, ModularSymbols, ModularUnaryOps};
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+/// The operations required on the base type of the [ModularInteger] components of [Fp2],
+/// which are the modular operations, the square roots and [FindQnr]. It's implemented for
+/// all the types with these operations, such as the unsigned primitives and `BigUint`.
+pub trait Fp2Base:
+    ModularRefOps + PartialEq + From<u8> + FindQnr + for<'r> ModularSqrt<&'r Self, Output = Self>
+{
+}
+impl<T> Fp2Base for T where
+    T: ModularRefOps + PartialEq + From<u8> + FindQnr + for<'r> ModularSqrt<&'r T, Output = T>
+{
+}
+
+/// An element `a + b·√n` of the finite field `GF(p²) = GF(p)[ω]/(ω² - n)`, where the
+/// components are [ModularInteger] values modulo an odd prime p and n is a quadratic
+/// non-residue modulo p. The base type of the components should implement [Fp2Base].
+///
+/// Compared with [QuadExt][crate::QuadExt], which works on the raw integers with a
+/// [Reducer][crate::Reducer] and accepts any modulus and any `d`, this type works with
+/// the existing [ModularInteger] values and always forms a field, so the division is
+/// supported by the operators. The multiplication, norm and inverse formulas are shared
+/// with [QuadExt][crate::QuadExt]. The non-residue is carried by every element, and the
+/// operators panic in debug builds if the extensions of the two operands differ, the same as
+/// the modulus checks of [ModularInteger].
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{Fp2, ModularInteger, MontgomeryInt};
+///
+/// let x = MontgomeryInt::new(0u64, &7);
+/// let z = Fp2::new(x.convert(1), x.convert(2)); // 1 + 2√3
+/// assert_eq!(z.non_residue().residue(), 3);
+/// assert_eq!((z * z).residue(), (6, 4)); // 1 + 12 + 4√3
+/// assert_eq!((z / z).residue(), (1, 0));
+/// assert_eq!(z.pow(48), z.one()); // the multiplicative group has order 7² - 1
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fp2<M> {
+    a: M,
+    b: M,
+    n: M,
+}
+
+/// Calculate the modular inverse of x in GF(p)
+fn inv_base<M: ModularInteger>(x: &M) -> Option<M>
+where
+    M::Base: Fp2Base,
+{
+    x.residue().invm(&x.modulus()).map(|v| x.convert(v))
+}
+
+/// Calculate a square root of x in GF(p)
+fn sqrt_base<M: ModularInteger>(x: &M) -> Option<M>
+where
+    M::Base: Fp2Base,
+{
+    x.residue().sqrtm(&x.modulus()).map(|(r, _)| x.convert(r))
+}
+
+/// [QuadBase] on the [ModularInteger] values, which carry the modulus themselves
+struct IntegerOps;
+
+impl<M: ModularInteger + Clone> QuadBase<M> for IntegerOps
+where
+    M::Base: Fp2Base,
+{
+    #[inline]
+    fn add(&self, lhs: &M, rhs: &M) -> M {
+        lhs.clone() + rhs.clone()
+    }
+    #[inline]
+    fn sub(&self, lhs: &M, rhs: &M) -> M {
+        lhs.clone() - rhs.clone()
+    }
+    #[inline]
+    fn mul(&self, lhs: &M, rhs: &M) -> M {
+        lhs.clone() * rhs.clone()
+    }
+    #[inline]
+    fn dbl(&self, target: M) -> M {
+        target.double()
+    }
+    #[inline]
+    fn neg(&self, target: M) -> M {
+        -target
+    }
+    #[inline]
+    fn sqr(&self, target: M) -> M {
+        target.square()
+    }
+    #[inline]
+    fn inv(&self, target: M) -> Option<M> {
+        inv_base(&target)
+    }
+}
+
+impl<M: ModularInteger + Clone> Fp2<M>
+where
+    M::Base: Fp2Base,
+{
+    /// Create the element `a + b·√n`, where n is the smallest quadratic non-residue
+    /// modulo p (see [find_qnr()][crate::find_qnr()]).
+    ///
+    /// # Panics
+    /// if the modulus is not an odd number larger than 2
+    pub fn new(a: M, b: M) -> Self {
+        let n = M::Base::find_qnr(&a.modulus());
+        assert!(n != M::Base::from(0), "the modulus should be an odd prime");
+        let n = a.convert(n);
+        Self { a, b, n }
+    }
+
+    /// Create the element `a + b·√n` with the given non-residue n. The extension is
+    /// a field only if n is a quadratic non-residue, which is not checked.
+    #[inline]
+    pub fn with_non_residue(a: M, b: M, n: M) -> Self {
+        Self { a, b, n }
+    }
+
+    /// Create the element `a + b·√n` in the same field as self
+    #[inline]
+    pub fn convert(&self, a: M, b: M) -> Self {
+        Self {
+            a,
+            b,
+            n: self.n.clone(),
+        }
+    }
+
+    /// The zero of the field
+    #[inline]
+    pub fn zero(&self) -> Self {
+        let zero = self.n.convert(M::Base::from(0));
+        self.convert(zero.clone(), zero)
+    }
+
+    /// The one of the field
+    #[inline]
+    pub fn one(&self) -> Self {
+        self.convert(
+            self.n.convert(M::Base::from(1)),
+            self.n.convert(M::Base::from(0)),
+        )
+    }
+
+    /// Get the components `(a, b)` of `a + b·√n`
+    #[inline]
+    pub fn parts(&self) -> (M, M) {
+        (self.a.clone(), self.b.clone())
+    }
+
+    /// Get the residues of the components `(a, b)` of `a + b·√n`
+    #[inline]
+    pub fn residue(&self) -> (M::Base, M::Base) {
+        (self.a.residue(), self.b.residue())
+    }
+
+    /// Get the non-residue n
+    #[inline]
+    pub fn non_residue(&self) -> M {
+        self.n.clone()
+    }
+
+    /// Test whether the element is zero
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.a.is_zero() && self.b.is_zero()
+    }
+
+    /// Test whether the element lies in the prime field GF(p), i.e. `b = 0`
+    #[inline]
+    pub fn is_base(&self) -> bool {
+        self.b.is_zero()
+    }
+
+    #[inline(always)]
+    fn check_field_eq(&self, rhs: &Self) {
+        if cfg!(debug_assertions) && self.n != rhs.n {
+            panic!("The extensions of two operands should be the same!");
+        }
+    }
+
+    /// Calculate the conjugate `a - b·√n`, which is also the Frobenius map `x^p`
+    #[inline]
+    pub fn conj(&self) -> Self {
+        self.convert(self.a.clone(), -self.b.clone())
+    }
+
+    /// Calculate the norm `x * conj(x) = a² - n·b²` in GF(p)
+    #[inline]
+    pub fn norm(&self) -> M {
+        norm_parts(&IntegerOps, &self.n, (&self.a, &self.b))
+    }
+
+    /// Calculate the trace `x + conj(x) = 2a` in GF(p)
+    #[inline]
+    pub fn trace(&self) -> M {
+        self.a.clone().double()
+    }
+
+    /// Calculate self * self
+    #[inline]
+    pub fn square(&self) -> Self {
+        let (a, b) = sqr_parts(&IntegerOps, &self.n, (&self.a, &self.b));
+        self.convert(a, b)
+    }
+
+    /// Calculate the multiplicative inverse as `conj(x) / norm(x)`, it returns [None] only
+    /// for zero (or if n is not a non-residue).
+    pub fn inv(&self) -> Option<Self> {
+        let (a, b) = inv_parts(&IntegerOps, &self.n, (&self.a, &self.b))?;
+        Some(self.convert(a, b))
+    }
+
+    /// Test whether the element is a square in GF(p²), which holds iff its norm is a
+    /// square in GF(p). In particular, every element of GF(p) is a square in GF(p²).
+    pub fn is_square(&self) -> bool {
+        let norm = self.norm();
+        norm.residue().legendre(&norm.modulus()) != -1
+    }
+
+    /// Calculate a square root of the element, the other root is its negation. It returns
+    /// [None] if the element is not a square (see [is_square()][Fp2::is_square]).
+    ///
+    /// The root is found with the square roots in GF(p) ([sqrtm()][crate::sqrtm()]) only:
+    /// for `z = a + b·√n` with `b ≠ 0`, the root `x + y·√n` satisfies `x² = (a ± s) / 2`
+    /// where `s² = norm(z)`, and then `y = b / 2x`. For `b = 0`, the root is either `√a` or
+    /// `√(a/n)·√n`.
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{Fp2, ModularInteger, MontgomeryInt};
+    ///
+    /// let x = MontgomeryInt::new(0u64, &1_000_000_007);
+    /// let z = Fp2::new(x.convert(12345), x.convert(67890));
+    /// let w = (z * z).sqrt().unwrap();
+    /// assert!(w == z || w == -z);
+    ///
+    /// // -1 is not a square modulo 1e9+7, but it is a square in GF(p²)
+    /// let m1 = z.convert(x.convert(1_000_000_006), x.convert(0));
+    /// assert_eq!(m1.sqrt().unwrap().square(), m1);
+    /// ```
+    pub fn sqrt(&self) -> Option<Self> {
+        let zero = self.n.convert(M::Base::from(0));
+
+        if self.b.is_zero() {
+            return match sqrt_base(&self.a) {
+                Some(r) => Some(self.convert(r, zero)),
+                None => {
+                    let r = sqrt_base(&(self.a.clone() * inv_base(&self.n)?))?;
+                    Some(self.convert(zero, r))
+                }
+            };
+        }
+
+        let s = sqrt_base(&self.norm())?;
+        let half = inv_base(&zero.convert(M::Base::from(2)))?;
+        let x2 = (self.a.clone() + s.clone()) * half.clone();
+        let x = match sqrt_base(&x2) {
+            Some(x) => x,
+            None => sqrt_base(&((self.a.clone() - s) * half.clone()))?,
+        };
+        // x is nonzero, otherwise the norm would be -n·b² = s², a contradiction
+        let y = self.b.clone() * half * inv_base(&x)?;
+        Some(self.convert(x, y))
+    }
+
+    /// Calculate self ^ exp by binary exponentiation. The exponent is 128 bits, so that
+    /// it covers the group order `p² - 1` for the moduli up to 64 bits.
+    pub fn pow(&self, exp: u128) -> Self {
+        let mut result = self.one();
+        for i in (0..128 - exp.leading_zeros()).rev() {
+            result = result.square();
+            if (exp >> i) & 1 == 1 {
+                result = result * self.clone();
+            }
+        }
+        result
+    }
+}
+
+impl<M: ModularInteger + Clone> Add for Fp2<M>
+where
+    M::Base: Fp2Base,
+{
+    type Output = Self;
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        self.check_field_eq(&rhs);
+        Self {
+            a: self.a + rhs.a,
+            b: self.b + rhs.b,
+            n: self.n,
+        }
+    }
+}
+
+impl<M: ModularInteger + Clone> Sub for Fp2<M>
+where
+    M::Base: Fp2Base,
+{
+    type Output = Self;
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        self.check_field_eq(&rhs);
+        Self {
+            a: self.a - rhs.a,
+            b: self.b - rhs.b,
+            n: self.n,
+        }
+    }
+}
+
+impl<M: ModularInteger + Clone> Mul for Fp2<M>
+where
+    M::Base: Fp2Base,
+{
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        self.check_field_eq(&rhs);
+        let (x, y) = ((&self.a, &self.b), (&rhs.a, &rhs.b));
+        let (a, b) = mul_parts(&IntegerOps, &self.n, x, y);
+        Self { a, b, n: self.n }
+    }
+}
+
+impl<M: ModularInteger + Clone> Div for Fp2<M>
+where
+    M::Base: Fp2Base,
+{
+    type Output = Self;
+    /// # Panics
+    /// if rhs is zero
+    #[inline]
+    fn div(self, rhs: Self) -> Self {
+        Mul::mul(self, rhs.inv().expect("the divisor should not be zero"))
+    }
+}
+
+impl<M: ModularInteger + Clone> Neg for Fp2<M>
+where
+    M::Base: Fp2Base,
+{
+    type Output = Self;
+    #[inline]
+    fn neg(self) -> Self {
+        Self {
+            a: -self.a,
+            b: -self.b,
+            n: self.n,
+        }
+    }
+}
+
+macro_rules! impl_ref_binops {
+    ($($op:ident, $method:ident;)*) => {$(
+        impl<M: ModularInteger + Clone> $op<&Fp2<M>> for Fp2<M>
+        where
+            M::Base: Fp2Base,
+        {
+            type Output = Self;
+            #[inline]
+            fn $method(self, rhs: &Self) -> Self {
+                self.$method(rhs.clone())
+            }
+        }
+
+        impl<M: ModularInteger + Clone> $op<&Fp2<M>> for &Fp2<M>
+        where
+            M::Base: Fp2Base,
+        {
+            type Output = Fp2<M>;
+            #[inline]
+            fn $method(self, rhs: &Fp2<M>) -> Fp2<M> {
+                self.clone().$method(rhs.clone())
+            }
+        }
+    )*};
+}
+impl_ref_binops!(Add, add; Sub, sub; Mul, mul; Div, div;);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModInt998244353, MontgomeryInt, MontgomeryQuadExt, VanillaInt};
+    use rand::random;
+
+    const PRIMES: [u64; 5] = [3, 7, 65537, 1_000_000_007, 18446744073709551557];
+
+    #[test]
+    fn field_ops_test() {
+        for &p in PRIMES.iter() {
+            let x = MontgomeryInt::new(0u64, &p);
+            for _ in 0..10 {
+                let rand = || Fp2::new(x.convert(random()), x.convert(random()));
+                let (u, v, w) = (rand(), rand(), rand());
+                assert_eq!(u.square(), u * u);
+                assert_eq!((u + v) * w, u * w + v * w);
+                assert_eq!((u - v) + v, u);
+                assert_eq!(-u + u, u.zero());
+                assert_eq!(u.norm(), (u * u.conj()).parts().0);
+                assert!((u * u.conj()).is_base());
+                assert_eq!(u.trace(), (u + u.conj()).parts().0);
+                assert_eq!(u.pow(p as u128), u.conj());
+
+                // compare with QuadExt
+                let n = u.non_residue().residue();
+                let (ua, ub) = u.residue();
+                let (va, vb) = v.residue();
+                let q = MontgomeryQuadExt::<u64>::new(ua, ub, n, &p);
+                assert_eq!((u * v).residue(), (q * q.convert(va, vb)).residue());
+
+                if u.is_zero() {
+                    assert_eq!(u.inv(), None);
+                    continue;
+                }
+                assert_eq!(u * u.inv().unwrap(), u.one());
+                assert_eq!((v / u) * u, v);
+                assert_eq!(u.pow((p as u128) * (p as u128) - 1), u.one());
+            }
+        }
+    }
+
+    #[test]
+    fn sqrt_test() {
+        for &p in PRIMES.iter() {
+            let x = MontgomeryInt::new(0u64, &p);
+            for _ in 0..10 {
+                let z = Fp2::new(x.convert(random()), x.convert(random()));
+                let w = z.square().sqrt().unwrap();
+                assert!(w == z || w == -z);
+                assert!(z.square().is_square());
+
+                // about half of the elements are squares
+                match z.sqrt() {
+                    Some(r) => {
+                        assert_eq!(r.square(), z);
+                        assert!(z.is_square());
+                    }
+                    None => assert!(!z.is_square()),
+                }
+
+                // all the elements of GF(p) have roots
+                let a = z.convert(z.parts().0, x.convert(0));
+                assert_eq!(a.sqrt().unwrap().square(), a);
+            }
+            let z = Fp2::new(x.convert(0), x.convert(0));
+            assert_eq!(z.sqrt(), Some(z));
+        }
+
+        // count the squares in GF(7²): (49 - 1) / 2 nonzero squares and zero
+        let x = MontgomeryInt::new(0u32, &7);
+        let mut count = 0;
+        for v in 0..49 {
+            let z = Fp2::new(x.convert(v % 7), x.convert(v / 7));
+            if z.sqrt().is_some() {
+                count += 1;
+            }
+            assert_eq!(z.sqrt().is_some(), z.is_square());
+        }
+        assert_eq!(count, 25);
+    }
+
+    #[test]
+    fn other_types_test() {
+        let x = ModInt998244353::from(0);
+        let z = Fp2::new(x.convert(3), x.convert(1));
+        assert_eq!(z.non_residue().residue(), 3);
+        assert_eq!(z.pow(998244353 * 998244353 - 1), z.one());
+
+        let x = VanillaInt::new(0u8, &251);
+        let z = Fp2::new(x.convert(10), x.convert(20));
+        assert_eq!(z * z.inv().unwrap(), z.one());
+        let w = Fp2::with_non_residue(x.convert(1), x.convert(1), x.convert(6));
+        assert_eq!(w.non_residue().residue(), 6);
+
+        // moduli beyond 64 bits
+        let p = u128::MAX - 158;
+        let x = MontgomeryInt::new(0u128, &p);
+        for _ in 0..10 {
+            let z = Fp2::new(x.convert(random()), x.convert(random()));
+            assert_eq!(z * z.inv().unwrap(), z.one());
+            assert_eq!(z.pow(p), z.conj());
+            let w = z.square().sqrt().unwrap();
+            assert!(w == z || w == -z);
+        }
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn biguint_test() {
+        use num_bigint::BigUint;
+
+        let p = (BigUint::from(1u8) << 255u32) - 19u8;
+        let x = MontgomeryInt::new(BigUint::from(0u8), &p);
+        for _ in 0..10 {
+            let (a, b) = (
+                BigUint::from(random::<u128>()),
+                BigUint::from(random::<u128>()),
+            );
+            let z = Fp2::new(x.convert(a), x.convert(b));
+            assert_eq!(z.clone() * z.inv().unwrap(), z.one());
+            assert_eq!((z.clone() / z.clone()), z.one());
+            let w = z.square().sqrt().unwrap();
+            assert!(w == z || w == -z.clone());
+        }
+        let z = Fp2::new(x.convert(BigUint::from(2u8)), x.convert(BigUint::from(0u8)));
+        assert_eq!(z.non_residue().residue(), BigUint::from(2u8));
+    }
+
+    #[test]
+    #[should_panic]
+    fn even_modulus_test() {
+        let x = VanillaInt::new(0u64, &16);
+        let _ = Fp2::new(x.convert(1), x.convert(1));
+    }
+}
@@ -0,0 +1,403 @@
+//! Formal power series operations over ℤ/pℤ, truncated to a number of terms
+
+use crate::ntt::{convert_u64, pow_u64};
+use crate::poly::{inv, mul_coeffs};
+use crate::{ModularInteger, ModularSqrt, Poly};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Get the first n coefficients of the series, padded with zeros
+fn take<M: ModularInteger + Clone>(p: &Poly<M>, start: usize, n: usize) -> Vec<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    (start..start + n).map(|i| p.coeff(i)).collect()
+}
+
+/// Calculate `a * b mod t^n`
+fn mul_trunc<M: ModularInteger + Clone>(a: &[M], b: &[M], n: usize) -> Vec<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    let mut c = mul_coeffs(&a[..a.len().min(n)], &b[..b.len().min(n)]);
+    c.truncate(n);
+    c
+}
+
+/// The inverses of `1, 2, ..., n - 1` (with a dummy zero at index 0) modulo the prime p,
+/// by the recurrence `i^-1 = -(p / i) * (p mod i)^-1`
+fn inverses<M: ModularInteger + Clone>(x: &M, n: usize) -> Vec<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    let p: u64 = x.modulus().into();
+    assert!(
+        n as u64 <= p,
+        "the number of terms should not exceed the modulus"
+    );
+    let mut invs = Vec::with_capacity(n);
+    invs.push(convert_u64(x, 0));
+    for i in 1..n as u64 {
+        let v = if i == 1 {
+            convert_u64(x, 1)
+        } else {
+            -(convert_u64(x, p / i) * invs[(p % i) as usize].clone())
+        };
+        invs.push(v);
+    }
+    invs
+}
+
+impl<M: ModularInteger + Clone> Poly<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    /// Get the remainder `self mod t^n`, i.e. the terms of degree less than n
+    pub fn truncate(&self, n: usize) -> Self {
+        let n = n.min(self.coeffs().len());
+        Poly::new(self.coeffs()[..n].to_vec(), &self.coeff(0))
+    }
+
+    /// Calculate the power series inverse g such that `self * g = 1 mod t^n` by Newton's
+    /// iteration `g = g * (2 - self * g)`, which doubles the precision in each step, so it
+    /// takes `O(n log n)` operations with an NTT-friendly modulus. It returns [None] if the
+    /// constant term is not invertible.
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{ModInt998244353, Poly};
+    ///
+    /// let x = ModInt998244353::from(0);
+    /// let f = Poly::from_residues([1, 998244352], &x); // 1 - t
+    /// assert_eq!(f.inv_series(5).unwrap().residues(), [1, 1, 1, 1, 1]);
+    /// ```
+    pub fn inv_series(&self, n: usize) -> Option<Self> {
+        let zero = convert_u64(&self.coeff(0), 0);
+        if n == 0 {
+            return Some(Poly::zero(&zero));
+        }
+        let mut g = alloc::vec![inv(&self.coeff(0))?];
+        let two = convert_u64(&zero, 2);
+        let mut len = 1;
+        while len < n {
+            len = (len * 2).min(n);
+            let mut e = mul_trunc(&take(self, 0, len), &g, len);
+            for v in e.iter_mut() {
+                *v = -v.clone();
+            }
+            e[0] = e[0].clone() + two.clone();
+            g = mul_trunc(&g, &e, len);
+        }
+        Some(Poly::new(g, &zero))
+    }
+
+    /// Calculate the power series logarithm `log(self) mod t^n` as the integral of
+    /// `self' / self`. It returns [None] if the constant term is not one.
+    ///
+    /// The modulus should be a prime not smaller than n, so that the integral is defined.
+    ///
+    /// # Panics
+    /// if n is larger than the modulus
+    pub fn log_series(&self, n: usize) -> Option<Self> {
+        let zero = convert_u64(&self.coeff(0), 0);
+        if self.coeff(0) != convert_u64(&zero, 1) {
+            return None;
+        }
+        let invs = inverses(&zero, n);
+        if n <= 1 {
+            return Some(Poly::zero(&zero));
+        }
+        let d = take(&self.truncate(n).derivative(), 0, n - 1);
+        let q = mul_trunc(&d, self.inv_series(n - 1)?.coeffs(), n - 1);
+
+        let mut coeffs = Vec::with_capacity(n);
+        coeffs.push(zero.clone());
+        coeffs.extend(
+            q.into_iter()
+                .zip(invs.into_iter().skip(1))
+                .map(|(c, i)| c * i),
+        );
+        Some(Poly::new(coeffs, &zero))
+    }
+
+    /// Calculate the power series exponential `exp(self) mod t^n` by Newton's iteration
+    /// `g = g * (1 - log(g) + self)`. It returns [None] if the constant term is not zero.
+    ///
+    /// The modulus should be a prime not smaller than n, see [log_series()][Poly::log_series].
+    ///
+    /// # Panics
+    /// if n is larger than the modulus
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{ModInt998244353, Poly};
+    ///
+    /// // exp(log(1 + t)) = 1 + t
+    /// let x = ModInt998244353::from(0);
+    /// let f = Poly::from_residues([1, 1], &x);
+    /// let g = f.log_series(10).unwrap();
+    /// assert_eq!(g.coeffs()[2] * ModInt998244353::from(2), ModInt998244353::from(998244352));
+    /// assert_eq!(g.exp_series(10).unwrap(), f);
+    /// ```
+    pub fn exp_series(&self, n: usize) -> Option<Self> {
+        let zero = self.coeff(0);
+        if !zero.is_zero() {
+            return None;
+        }
+        let p: u64 = zero.modulus().into();
+        assert!(
+            n as u64 <= p,
+            "the number of terms should not exceed the modulus"
+        );
+        if n == 0 {
+            return Some(Poly::zero(&zero));
+        }
+        let one = convert_u64(&zero, 1);
+        let mut g = Poly::monomial(one.clone(), 0);
+        let mut len = 1;
+        while len < n {
+            len = (len * 2).min(n);
+            let l = g.log_series(len).unwrap();
+            let mut e = take(self, 0, len);
+            for (v, lv) in e.iter_mut().zip(take(&l, 0, len)) {
+                *v = v.clone() - lv;
+            }
+            e[0] = e[0].clone() + one.clone();
+            g = Poly::new(mul_trunc(g.coeffs(), &e, len), &zero);
+        }
+        Some(g)
+    }
+
+    /// Calculate a power series square root `sqrt(self) mod t^n` by Newton's iteration
+    /// `g = (g + self / g) / 2`. It returns [None] if the series has no square root, i.e.
+    /// the lowest nonzero term has an odd degree or its coefficient is a quadratic non-residue.
+    /// The other root is the negation of the result.
+    ///
+    /// The modulus should be an odd prime.
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{ModInt998244353, Poly};
+    ///
+    /// let x = ModInt998244353::from(0);
+    /// let f = Poly::from_residues([0, 0, 4, 4, 1], &x); // (2t + t^2)^2
+    /// let g = f.sqrt_series(5).unwrap();
+    /// assert!(g.residues() == [0, 2, 1] || g.residues() == [0, 998244351, 998244352]);
+    /// ```
+    pub fn sqrt_series(&self, n: usize) -> Option<Self> {
+        let zero = convert_u64(&self.coeff(0), 0);
+        let k = match self.coeffs().iter().position(|c| !c.is_zero()) {
+            Some(k) => k,
+            None => return Some(Poly::zero(&zero)),
+        };
+        if k % 2 == 1 {
+            return None;
+        }
+        if k / 2 >= n {
+            return Some(Poly::zero(&zero));
+        }
+
+        // self = c * t^k * b with b(0) = 1
+        let c = self.coeff(k);
+        let (cv, p): (u64, u64) = (c.residue().into(), c.modulus().into());
+        let s = convert_u64(&c, cv.sqrtm(&p)?.0);
+        let m = n - k / 2;
+        let b = Poly::new(take(self, k, m), &zero).scale(&inv(&c).unwrap());
+
+        let mut g = Poly::monomial(convert_u64(&zero, 1), 0);
+        let mut len = 1;
+        if m > 1 {
+            let half = inv(&convert_u64(&zero, 2)).expect("the modulus should be odd");
+            while len < m {
+                len = (len * 2).min(m);
+                let h = g.inv_series(len).unwrap();
+                let q = Poly::new(mul_trunc(&take(&b, 0, len), h.coeffs(), len), &zero);
+                g = (&g + &q).scale(&half);
+            }
+        }
+
+        let mut coeffs = alloc::vec![zero.clone(); k / 2];
+        coeffs.extend(g.scale(&s).into_coeffs());
+        Some(Poly::new(coeffs, &zero))
+    }
+
+    /// Calculate the power `self^k mod t^n` as `exp(k * log(self))`, after the lowest
+    /// nonzero term is factored out. It takes `O(n log n)` operations regardless of k.
+    ///
+    /// The modulus should be a prime not smaller than n, see [log_series()][Poly::log_series].
+    ///
+    /// # Panics
+    /// if n is larger than the modulus (only when the logarithm is needed)
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{ModInt998244353, Poly};
+    ///
+    /// let x = ModInt998244353::from(0);
+    /// let f = Poly::from_residues([0, 1, 1], &x); // t + t^2
+    /// assert_eq!(f.pow_series(3, 6).residues(), [0, 0, 0, 1, 3, 3]);
+    /// ```
+    pub fn pow_series(&self, k: u64, n: usize) -> Self {
+        let zero = convert_u64(&self.coeff(0), 0);
+        if k == 0 {
+            return Poly::monomial(convert_u64(&zero, 1), 0).truncate(n);
+        }
+        let d = match self.coeffs().iter().position(|c| !c.is_zero()) {
+            Some(d) => d,
+            None => return Poly::zero(&zero),
+        };
+        if d as u128 * k as u128 >= n as u128 {
+            return Poly::zero(&zero);
+        }
+
+        // self = c * t^d * b with b(0) = 1
+        let shift = d * k as usize;
+        let m = n - shift;
+        let c = self.coeff(d);
+        let b = Poly::new(take(self, d, m), &zero).scale(&inv(&c).unwrap());
+        let p: u64 = zero.modulus().into();
+        let l = b.log_series(m).unwrap().scale(&convert_u64(&zero, k % p));
+        let e = l.exp_series(m).unwrap().scale(&pow_u64(&c, k));
+
+        let mut coeffs = alloc::vec![zero.clone(); shift];
+        coeffs.extend(e.into_coeffs());
+        Poly::new(coeffs, &zero)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModInt998244353, MontgomeryInt};
+    use rand::random;
+
+    type Mint = MontgomeryInt<u64>;
+
+    fn random_series(x: &Mint, len: usize, c0: Option<u64>) -> Poly<Mint> {
+        let mut coeffs: Vec<Mint> = (0..len).map(|_| x.convert(random())).collect();
+        if let Some(c) = c0 {
+            coeffs[0] = x.convert(c);
+        }
+        Poly::new(coeffs, x)
+    }
+
+    fn one(x: &Mint) -> Poly<Mint> {
+        Poly::monomial(x.convert(1), 0)
+    }
+
+    #[test]
+    fn inv_series_test() {
+        for &p in [998244353u64, 1_000_000_007].iter() {
+            let x = Mint::new(0, &p);
+            for &n in [1usize, 2, 7, 64, 100, 257].iter() {
+                let f = random_series(&x, 150, Some(random::<u64>() % (p - 1) + 1));
+                let g = f.inv_series(n).unwrap();
+                assert!(g.degree() < Some(n));
+                assert_eq!((&f * &g).truncate(n), one(&x));
+            }
+            let f = random_series(&x, 10, Some(0));
+            assert!(f.inv_series(10).is_none());
+            assert!(f.inv_series(0).unwrap().is_zero());
+        }
+    }
+
+    #[test]
+    fn log_exp_test() {
+        let x = Mint::new(0, &998244353);
+        for &n in [1usize, 2, 5, 64, 200].iter() {
+            let f = random_series(&x, 300, Some(1));
+            let l = f.log_series(n).unwrap();
+            assert_eq!(l.coeff(0).residue(), 0);
+            assert_eq!(l.exp_series(n).unwrap(), f.truncate(n));
+
+            let g = random_series(&x, 300, Some(0));
+            assert_eq!(
+                g.exp_series(n).unwrap().log_series(n).unwrap(),
+                g.truncate(n)
+            );
+        }
+
+        // exp(t) = sum t^i / i!
+        let t = Poly::monomial(x.convert(1), 1);
+        let e = t.exp_series(20).unwrap();
+        let mut fact = 1u64;
+        for i in 0..20u64 {
+            if i > 0 {
+                fact *= i;
+            }
+            assert_eq!((e.coeff(i as usize) * x.convert(fact)).residue(), 1);
+        }
+
+        assert!(random_series(&x, 10, Some(2)).log_series(10).is_none());
+        assert!(random_series(&x, 10, Some(2)).exp_series(10).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn log_too_long_test() {
+        let x = Mint::new(0, &13);
+        let _ = one(&x).log_series(20);
+    }
+
+    #[test]
+    fn sqrt_series_test() {
+        let x = Mint::new(0, &1_000_000_007);
+        for &(shift, n) in [(0usize, 1usize), (0, 50), (2, 50), (6, 100), (10, 4)].iter() {
+            let g = random_series(&x, 60, None);
+            let mut coeffs = std::vec![x; shift];
+            coeffs.extend((&g * &g).into_coeffs());
+            let f = Poly::new(coeffs, &x);
+
+            let h = f.sqrt_series(n).unwrap();
+            assert_eq!((&h * &h).truncate(n), f.truncate(n));
+        }
+
+        // odd degree and non-residue
+        let f = Poly::from_residues([0, 0, 0, 1, 1], &x);
+        assert!(f.sqrt_series(10).is_none());
+        assert!(Poly::from_residues([5, 1], &x).sqrt_series(10).is_none()); // 5 is a QNR
+        assert!(Poly::zero(&x).sqrt_series(10).unwrap().is_zero());
+    }
+
+    #[test]
+    fn pow_series_test() {
+        let x = Mint::new(0, &998244353);
+        for &(shift, k, n) in [
+            (0usize, 0u64, 10usize),
+            (0, 1, 10),
+            (0, 5, 100),
+            (1, 3, 50),
+            (3, 7, 30),
+            (2, 100, 50),
+        ]
+        .iter()
+        {
+            let mut coeffs = std::vec![x; shift];
+            coeffs
+                .extend(random_series(&x, 40, Some(random::<u64>() % 998244352 + 1)).into_coeffs());
+            let f = Poly::new(coeffs, &x);
+
+            let mut expected = one(&x).truncate(n);
+            for _ in 0..k {
+                expected = (&expected * &f).truncate(n);
+            }
+            assert_eq!(f.pow_series(k, n), expected, "shift={} k={}", shift, k);
+        }
+
+        // exponents larger than the modulus
+        let x = ModInt998244353::from(0);
+        let f = Poly::from_residues([3, 1, 4, 1, 5], &x);
+        let k = 998244353 * 2 + 3;
+        let mut expected = Poly::monomial(ModInt998244353::from(1), 0);
+        let mut base = f.truncate(30);
+        let mut e = k;
+        while e > 0 {
+            if e & 1 == 1 {
+                expected = (&expected * &base).truncate(30);
+            }
+            base = (&base * &base).truncate(30);
+            e >>= 1;
+        }
+        assert_eq!(f.pow_series(k, 30), expected);
+    }
+}
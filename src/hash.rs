@@ -0,0 +1,193 @@
+//! Polynomial rolling hash over the Mersenne prime `2^61 - 1`
+
+use crate::{umax, FixedMersenne, Reducer};
+
+type M61 = FixedMersenne<61, 1>;
+const R: M61 = FixedMersenne();
+
+/// A polynomial rolling hash with modulus `2^61 - 1`.
+///
+/// The hash of a sequence `x_1, x_2, ..., x_n` is `x_1 * b^(n-1) + x_2 * b^(n-2) + ... + x_n mod 2^61 - 1`,
+/// where `b` is the base of the hasher. All the reductions are performed with the shift-and-add
+/// trick of [FixedMersenne], so no division is involved.
+///
+/// Note that the hash is deterministic given the seed, so it's not suitable for defending
+/// against adversarial inputs unless the seed is kept secret.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::PolyHash;
+///
+/// let mut ha = PolyHash::new(42);
+/// ha.update(b"hello ");
+/// let mut hb = PolyHash::new(42);
+/// hb.update(b"world");
+/// let mut hab = PolyHash::new(42);
+/// hab.update(b"hello world");
+/// assert_eq!(ha.concat(hb.len(), hb.digest()), hab);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolyHash {
+    base: umax,
+    hash: umax,
+    len: u64,
+}
+
+impl PolyHash {
+    /// The modulus of the hash
+    pub const MODULUS: u64 = M61::MODULUS as u64;
+
+    /// Create an empty hasher with a base derived from the seed. The base
+    /// is guaranteed to be in range `[2, 2^61 - 3]`.
+    pub const fn new(seed: u64) -> Self {
+        // SplitMix64 as the seed mixer
+        let mut z = seed.wrapping_add(0x9E3779B97F4A7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^= z >> 31;
+        Self::with_base(z % (Self::MODULUS - 3) + 2)
+    }
+
+    /// Create an empty hasher with the given base
+    ///
+    /// # Panics
+    /// if base is not in range `[0, 2^61 - 1)`
+    pub const fn with_base(base: u64) -> Self {
+        assert!(
+            base < Self::MODULUS,
+            "the base should be smaller than the modulus"
+        );
+        Self {
+            base: base as umax,
+            hash: 0,
+            len: 0,
+        }
+    }
+
+    /// The base of the polynomial
+    #[inline]
+    pub const fn base(&self) -> u64 {
+        self.base as u64
+    }
+
+    /// The number of elements that have been pushed into the hasher
+    #[inline]
+    pub const fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Check if no element has been pushed into the hasher
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The hash value of the elements pushed so far
+    #[inline]
+    pub const fn digest(&self) -> u64 {
+        self.hash as u64
+    }
+
+    /// Append a byte to the hashed sequence
+    #[inline]
+    pub fn push(&mut self, byte: u8) {
+        self.push_u64(byte as u64)
+    }
+
+    /// Append an 64-bit integer to the hashed sequence. The integer
+    /// will be reduced modulo `2^61 - 1` first.
+    #[inline]
+    pub fn push_u64(&mut self, value: u64) {
+        let h = R.mul(&self.hash, &self.base);
+        self.hash = R.add(&h, &R.transform(value as umax));
+        self.len += 1;
+    }
+
+    /// Append all the bytes to the hashed sequence
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.push(b);
+        }
+    }
+
+    /// Get the hasher for the concatenation of the current sequence and another sequence, given
+    /// the length and the hash value (with the same base) of the other sequence.
+    pub fn concat(&self, len: u64, other: u64) -> Self {
+        let shift = R.pow(self.base, &(len as umax));
+        let h = R.mul(&self.hash, &shift);
+        Self {
+            base: self.base,
+            hash: R.add(&h, &R.transform(other as umax)),
+            len: self.len + len,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::random;
+
+    fn hash_of(seed: u64, bytes: &[u8]) -> PolyHash {
+        let mut h = PolyHash::new(seed);
+        h.update(bytes);
+        h
+    }
+
+    #[test]
+    fn concat_test() {
+        for _ in 0..100 {
+            let seed = random::<u64>();
+            let data: [u8; 32] = random();
+            let split = random::<usize>() % 33;
+            let (a, b) = data.split_at(split);
+
+            let (ha, hb) = (hash_of(seed, a), hash_of(seed, b));
+            let hab = hash_of(seed, &data);
+            assert_eq!(ha.concat(hb.len(), hb.digest()), hab);
+            assert_eq!(hab.len(), 32);
+
+            // concatenation with an empty sequence
+            assert_eq!(ha.concat(0, 0), ha);
+            assert_eq!(PolyHash::new(seed).concat(hab.len(), hab.digest()), hab);
+        }
+    }
+
+    #[test]
+    fn stability_test() {
+        const DIGEST_NUM_MODULAR: u64 = 486741473163220032;
+
+        let h = hash_of(0, b"num-modular");
+        assert_eq!(h, hash_of(0, b"num-modular"));
+        assert_eq!(h.base(), 153307352162749901);
+        assert_eq!(h.digest(), DIGEST_NUM_MODULAR);
+        assert_ne!(hash_of(1, b"num-modular").base(), h.base());
+
+        let mut h = PolyHash::with_base(2);
+        h.update(&[1, 0, 1]);
+        assert_eq!(h.digest(), 5);
+        h.push_u64(u64::MAX);
+        assert_eq!(
+            h.digest(),
+            (10 + u64::MAX % PolyHash::MODULUS) % PolyHash::MODULUS
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "num-bigint")]
+    fn against_biguint_test() {
+        use num_bigint::BigUint;
+        let p = BigUint::from(PolyHash::MODULUS);
+        for _ in 0..10 {
+            let mut h = PolyHash::new(random());
+            let base = BigUint::from(h.base());
+            let mut expected = BigUint::from(0u8);
+            for _ in 0..(random::<usize>() % 256) {
+                let v = random::<u64>();
+                h.push_u64(v);
+                expected = (expected * &base + v) % &p;
+            }
+            assert_eq!(BigUint::from(h.digest()), expected);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Lifting roots of polynomials modulo prime powers
+
+use crate::{ModularCoreOps, ModularUnaryOps};
+
+/// Integer types supported by [hensel_lift()]
+pub trait HenselLift: Sized {
+    /// Lift a root of f modulo p to a root modulo p^k, see [hensel_lift()] for details
+    fn hensel_lift<F, DF>(f: F, df: DF, r: Self, p: Self, k: u32) -> Option<Self>
+    where
+        F: Fn(Self, Self) -> Self,
+        DF: Fn(Self, Self) -> Self;
+}
+
+/// Lift a root r of a polynomial f modulo p to a root modulo `p^k` with Hensel's lemma.
+///
+/// The closures `f(x, m)` and `df(x, m)` should evaluate the polynomial and its derivative
+/// at x modulo m respectively. The lifting is done by the Newton iteration
+/// `r <- r - f(r) / f'(r)`, which doubles the exponent of the modulus in each step.
+///
+/// The result is congruent to r modulo p, and it's the unique such root modulo `p^k`.
+/// [None] is returned if
+/// - r is not a root of f modulo p, or
+/// - `f'(r)` is not invertible modulo p (the singular case, where the root may
+///   lift to zero or multiple roots), or
+/// - `p^k` overflows the integer type.
+///
+/// If k = 0, the modulus `p^k` is 1 and zero is returned.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{hensel_lift, ModularCoreOps};
+///
+/// // the square root of 2 modulo 7^10, starting from 3^2 = 2 mod 7
+/// let f = |x: u64, m: u64| x.mulm(x, &m).subm(2, &m);
+/// let df = |x: u64, m: u64| x.addm(x, &m);
+/// let r = hensel_lift(f, df, 3, 7, 10).unwrap();
+/// assert_eq!(r * r % 7u64.pow(10), 2);
+/// assert_eq!(r % 7, 3);
+/// ```
+#[inline]
+pub fn hensel_lift<T: HenselLift, F, DF>(f: F, df: DF, r: T, p: T, k: u32) -> Option<T>
+where
+    F: Fn(T, T) -> T,
+    DF: Fn(T, T) -> T,
+{
+    T::hensel_lift(f, df, r, p, k)
+}
+
+macro_rules! impl_hensel_lift_for {
+    ($($T:ty)*) => {$(
+        impl HenselLift for $T {
+            fn hensel_lift<F, DF>(f: F, df: DF, r: $T, p: $T, k: u32) -> Option<$T>
+            where
+                F: Fn($T, $T) -> $T,
+                DF: Fn($T, $T) -> $T,
+            {
+                let pk = p.checked_pow(k)?;
+                if k == 0 {
+                    return Some(0);
+                }
+
+                let mut r = r % p;
+                if f(r, p) != 0 {
+                    return None;
+                }
+                // the derivative is invertible modulo p^e iff it's invertible modulo p
+                df(r, p).invm(&p)?;
+
+                let mut e = 1;
+                while e < k {
+                    e = (e * 2).min(k);
+                    let q = if e == k { pk } else { p.pow(e) };
+                    let d = df(r, q).invm(&q)?;
+                    r = r.subm(f(r, q).mulm(d, &q), &q);
+                }
+                Some(r)
+            }
+        }
+    )*};
+}
+impl_hensel_lift_for!(u8 u16 u32 u64 u128 usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModularPow;
+
+    #[test]
+    fn sqrt_lift_test() {
+        // f(x) = x^2 - a
+        for &p in [3u64, 5, 7, 11, 13].iter() {
+            for k in 1..=4 {
+                let pk = p.pow(k);
+                for a in 1..p {
+                    let f = |x: u64, m: u64| x.mulm(x, &m).subm(a, &m);
+                    let df = |x: u64, m: u64| x.addm(x, &m);
+                    for r in 0..p {
+                        let lifted = hensel_lift(f, df, r, p, k);
+                        if r * r % p != a {
+                            assert_eq!(lifted, None);
+                            continue;
+                        }
+
+                        // a is coprime to p, so the root is non-singular
+                        let lifted = lifted.unwrap();
+                        assert!(lifted < pk);
+                        assert_eq!(lifted % p, r);
+                        let roots: std::vec::Vec<u64> = (0..pk)
+                            .filter(|&x| x * x % pk == a % pk && x % p == r)
+                            .collect();
+                        assert_eq!(roots, [lifted]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cbrt_lift_test() {
+        // f(x) = x^3 - a
+        for &p in [2u32, 5, 7, 11].iter() {
+            for k in 1..=4 {
+                let pk = p.pow(k);
+                for a in 1..p {
+                    let f = |x: u32, m: u32| x.powm(3, &m).subm(a, &m);
+                    let df = |x: u32, m: u32| x.mulm(x, &m).mulm(3, &m);
+                    for r in 1..p {
+                        if r.powm(3, &p) != a {
+                            continue;
+                        }
+                        let lifted = hensel_lift(f, df, r, p, k).unwrap();
+                        assert_eq!(lifted.powm(3, &pk), a);
+                        let count = (0..pk)
+                            .filter(|&x| x.powm(3, &pk) == a && x % p == r)
+                            .count();
+                        assert_eq!(count, 1);
+                    }
+                }
+            }
+        }
+
+        // large modulus: 5^27 fits in u64
+        let a = 2u64;
+        let f = |x: u64, m: u64| x.powm(3, &m).subm(a, &m);
+        let df = |x: u64, m: u64| x.mulm(x, &m).mulm(3, &m);
+        let r = hensel_lift(f, df, 3, 5, 27).unwrap(); // 3^3 = 2 mod 5
+        assert_eq!(r.powm(3, &5u64.pow(27)), a);
+    }
+
+    #[test]
+    fn singular_test() {
+        // the derivative of x^2 - a vanishes modulo 2
+        let f = |x: u32, m: u32| x.mulm(x, &m).subm(1, &m);
+        let df = |x: u32, m: u32| x.addm(x, &m);
+        assert_eq!(hensel_lift(f, df, 1, 2, 3), None);
+
+        // the derivative of x^3 - a vanishes modulo 3
+        let f = |x: u32, m: u32| x.powm(3, &m).subm(2, &m);
+        let df = |x: u32, m: u32| x.mulm(x, &m).mulm(3, &m);
+        assert_eq!(hensel_lift(f, df, 2, 3, 2), None);
+
+        // not a root
+        let f = |x: u32, m: u32| x.mulm(x, &m).subm(2, &m);
+        let df = |x: u32, m: u32| x.addm(x, &m);
+        assert_eq!(hensel_lift(f, df, 2, 7, 2), None);
+
+        // overflow and trivial modulus
+        assert_eq!(hensel_lift(f, df, 3, 7, 20), None);
+        assert_eq!(hensel_lift(f, df, 3, 7, 0), Some(0));
+    }
+
+    #[test]
+    fn inverse_lift_test() {
+        // f(x) = a * x - 1 lifts the modular inverse
+        for &(a, p, k) in [(3u64, 2u64, 63u32), (10, 3, 40), (12345, 7, 22)].iter() {
+            let f = |x: u64, m: u64| x.mulm(a, &m).subm(1, &m);
+            let df = |_: u64, m: u64| a % m;
+            let r = a.invm(&p).unwrap();
+            let pk = p.pow(k);
+            let lifted = hensel_lift(f, df, r, p, k).unwrap();
+            assert_eq!(Some(lifted), a.invm(&pk));
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Iterators over the elements of a modulo ring ℤ/mℤ, and tables of successive powers
+
+use crate::{ReducedInt, Reducer};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+/// Iterator over all elements `0, 1, ..., m-1` of the ring ℤ/mℤ, see [ReducedInt::elements()].
+///
+/// All the yielded integers share the same reducer, and each element is produced by adding
+/// the reduced form of `1` to the previous one, so no division is involved after the first element.
+///
+/// The number of the remaining elements is tracked as a `usize`, it saturates at `usize::MAX`
+/// if the modulus doesn't fit in a `usize`, in which case [len()][ExactSizeIterator::len] is not exact.
+#[derive(Debug, Clone)]
+pub struct Elements<T, R> {
+    r: R,
+    one: T,
+    next: Option<T>,
+    remaining: Option<usize>, // None if the count overflows usize
+}
+
+impl<T: Clone, R: Reducer<T> + Clone> Iterator for Elements<T, R> {
+    type Item = ReducedInt<T, R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let a = self.next.take()?;
+        let b = self.r.add(&a, &self.one);
+        if !self.r.is_zero(&b) {
+            self.next = Some(b);
+        }
+        if let Some(n) = self.remaining.as_mut() {
+            *n -= 1;
+        }
+        Some(ReducedInt::from_parts(a, self.r.clone()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.remaining {
+            Some(n) => (n, Some(n)),
+            None => (usize::MAX, None),
+        }
+    }
+}
+
+impl<T: Clone, R: Reducer<T> + Clone> ExactSizeIterator for Elements<T, R> {}
+
+/// Iterator over all units (integers coprime to the modulus) of the ring ℤ/mℤ,
+/// see [ReducedInt::units()].
+#[derive(Debug, Clone)]
+pub struct Units<T, R> {
+    elements: Elements<T, R>,
+}
+
+impl<T: Clone, R: Reducer<T> + Clone> Iterator for Units<T, R> {
+    type Item = ReducedInt<T, R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let v = self.elements.next()?;
+            if self.elements.r.inv(v.repr().clone()).is_some() {
+                return Some(v);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.elements.size_hint().1)
+    }
+}
+
+impl<T: Clone + From<u8> + TryInto<usize>, R: Reducer<T> + Clone> ReducedInt<T, R> {
+    /// Iterate through all elements of the ring ℤ/mℤ in the order of `0, 1, ..., m-1`.
+    ///
+    /// Only one reducer is created and shared by all the yielded integers.
+    pub fn elements(m: &T) -> Elements<T, R> {
+        let r = R::new(m);
+        let next = Some(r.transform(T::from(0)));
+        let one = r.transform(T::from(1));
+        let remaining = m.clone().try_into().ok();
+        Elements {
+            r,
+            one,
+            next,
+            remaining,
+        }
+    }
+
+    /// Iterate through all units (integers coprime to m) of the ring ℤ/mℤ in ascending order.
+    ///
+    /// The number of yielded integers equals to the Euler's totient function φ(m).
+    pub fn units(m: &T) -> Units<T, R> {
+        Units {
+            elements: Self::elements(m),
+        }
+    }
+}
+
+/// Table of the successive powers `1, x, x^2, ..., x^(len-1)` of an integer in a modulo ring.
+///
+/// The powers are stored in the reduced form next to a single reducer, so the lookups with
+/// [lookup_raw()][PowerTable::lookup_raw] are cache friendly, which is the building block for
+/// baby-step giant-step and fixed-base exponentiation. The table always starts at the identity.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ModularInteger, MontgomeryInt, PowerTable};
+///
+/// let x = MontgomeryInt::new(3u32, &1000003);
+/// let table = PowerTable::new(&x, 5);
+/// let powers: Vec<u32> = table.iter().map(|v| v.residue()).collect();
+/// assert_eq!(powers, [1, 3, 9, 27, 81]);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct PowerTable<T, R> {
+    r: R,
+    powers: Vec<T>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Clone + From<u8>, R: Reducer<T> + Clone> PowerTable<T, R> {
+    /// Build the table of the powers `base^0, ..., base^(len-1)` with exactly `len - 1`
+    /// modular multiplications (none if len is zero).
+    pub fn new(base: &ReducedInt<T, R>, len: usize) -> Self {
+        let r = base.reducer().clone();
+        let mut powers = Vec::with_capacity(len);
+        if len > 0 {
+            let mut p = r.transform(T::from(1));
+            for _ in 1..len {
+                let next = r.mul(&p, base.repr());
+                powers.push(p);
+                p = next;
+            }
+            powers.push(p);
+        }
+        Self { r, powers }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Clone, R: Reducer<T> + Clone> PowerTable<T, R> {
+    /// The number of powers in the table
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.powers.len()
+    }
+
+    /// Whether the table is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.powers.is_empty()
+    }
+
+    /// Get `base^i` in the reduced form.
+    ///
+    /// # Panics
+    /// if i is not less than the length of the table
+    #[inline]
+    pub fn lookup_raw(&self, i: usize) -> &T {
+        &self.powers[i]
+    }
+
+    /// Get `base^i`, or None if i is not less than the length of the table
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<ReducedInt<T, R>> {
+        let a = self.powers.get(i)?.clone();
+        Some(ReducedInt::from_parts(a, self.r.clone()))
+    }
+
+    /// All the powers in the reduced form
+    #[inline]
+    pub fn as_raw(&self) -> &[T] {
+        &self.powers
+    }
+
+    /// Consume the table and return the powers in the reduced form
+    #[inline]
+    pub fn into_raw(self) -> Vec<T> {
+        self.powers
+    }
+
+    /// Iterate through the powers in the order of `base^0, base^1, ..., base^(len-1)`
+    #[inline]
+    pub fn iter(&self) -> PowerTableIter<'_, T, R> {
+        PowerTableIter {
+            r: &self.r,
+            iter: self.powers.iter(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T: Clone, R: Reducer<T> + Clone> IntoIterator for &'a PowerTable<T, R> {
+    type Item = ReducedInt<T, R>;
+    type IntoIter = PowerTableIter<'a, T, R>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the powers in a [PowerTable], see [PowerTable::iter()]
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct PowerTableIter<'a, T, R> {
+    r: &'a R,
+    iter: core::slice::Iter<'a, T>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T: Clone, R: Reducer<T> + Clone> Iterator for PowerTableIter<'a, T, R> {
+    type Item = ReducedInt<T, R>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let a = self.iter.next()?.clone();
+        Some(ReducedInt::from_parts(a, self.r.clone()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T: Clone, R: Reducer<T> + Clone> DoubleEndedIterator for PowerTableIter<'a, T, R> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let a = self.iter.next_back()?.clone();
+        Some(ReducedInt::from_parts(a, self.r.clone()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T: Clone, R: Reducer<T> + Clone> ExactSizeIterator for PowerTableIter<'a, T, R> {}
+
+/// Collect the powers `base^0, base^1, ..., base^(len-1)` into a vector, see [PowerTable]
+/// for details.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{power_table, ModularInteger, MontgomeryInt};
+///
+/// let x = MontgomeryInt::new(2u64, &1000000007);
+/// let table = power_table(&x, 100);
+/// assert_eq!(table[0].residue(), 1);
+/// assert_eq!(table[99].residue(), x.pow(&99).residue());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn power_table<T: Clone + From<u8>, R: Reducer<T> + Clone>(
+    base: &ReducedInt<T, R>,
+    len: usize,
+) -> Vec<ReducedInt<T, R>> {
+    PowerTable::new(base, len).iter().collect()
+}
+
+/// Collect the powers `base^0, base^1, ..., base^(len-1)` in the reduced form, see [PowerTable]
+/// for details.
+#[cfg(feature = "alloc")]
+pub fn power_table_raw<T: Clone + From<u8>, R: Reducer<T> + Clone>(
+    base: &ReducedInt<T, R>,
+    len: usize,
+) -> Vec<T> {
+    PowerTable::new(base, len).into_raw()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ModularInteger, ModularUnaryOps, MontgomeryInt, VanillaInt};
+
+    #[test]
+    fn elements_test() {
+        for m in [1u32, 2, 3, 15, 64, 255, 1001] {
+            let mut count = 0;
+            for (i, v) in VanillaInt::<u32>::elements(&m).enumerate() {
+                assert_eq!(v.residue(), i as u32);
+                assert_eq!(v.modulus(), m);
+                count += 1;
+            }
+            assert_eq!(count, m);
+
+            let mut iter = VanillaInt::<u32>::elements(&m);
+            assert_eq!(iter.len(), m as usize);
+            iter.next();
+            assert_eq!(iter.size_hint(), (m as usize - 1, Some(m as usize - 1)));
+
+            if m % 2 == 1 {
+                let mut count = 0;
+                for (i, v) in MontgomeryInt::<u32>::elements(&m).enumerate() {
+                    assert_eq!(v.residue(), i as u32);
+                    count += 1;
+                }
+                assert_eq!(count, m);
+            }
+        }
+
+        // the iteration terminates at the type boundary
+        assert_eq!(VanillaInt::<u8>::elements(&u8::MAX).count(), 255);
+        assert_eq!(MontgomeryInt::<u8>::elements(&u8::MAX).count(), 255);
+        assert_eq!(MontgomeryInt::<u8>::elements(&u8::MAX).len(), 255);
+    }
+
+    #[test]
+    fn units_test() {
+        // (m, φ(m))
+        const CASES: [(u64, usize); 10] = [
+            (1, 1),
+            (2, 1),
+            (9, 6),
+            (12, 4),
+            (15, 8),
+            (30, 8),
+            (97, 96),
+            (100, 40),
+            (1001, 720),
+            (30030, 5760),
+        ];
+        for &(m, phi) in CASES.iter() {
+            assert_eq!(VanillaInt::<u64>::units(&m).count(), phi);
+            assert_eq!(
+                VanillaInt::<u64>::units(&m).size_hint(),
+                (0, Some(m as usize))
+            );
+            for v in VanillaInt::<u64>::units(&m) {
+                assert!(v.residue().invm(&m).is_some());
+            }
+            if m % 2 == 1 {
+                assert_eq!(MontgomeryInt::<u64>::units(&m).count(), phi);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn power_table_test() {
+        use super::*;
+        use crate::{ModularPow, MontgomeryInt};
+        use rand::random;
+
+        for &len in [0usize, 1, 2, 3000].iter() {
+            for _ in 0..10 {
+                let m = random::<u64>() | 1;
+                let x = MontgomeryInt::new(random::<u64>(), &m);
+                let table = PowerTable::new(&x, len);
+                assert_eq!(table.len(), len);
+                assert_eq!(table.iter().len(), len);
+
+                let powers = power_table(&x, len);
+                let raw = power_table_raw(&x, len);
+                assert_eq!(powers.len(), len);
+                assert_eq!(raw, table.as_raw());
+                for (i, (p, v)) in powers.iter().zip(table.iter()).enumerate() {
+                    let expected = x.residue().powm(i as u64, &m);
+                    assert_eq!(p.residue(), expected);
+                    assert_eq!(p.modulus(), m);
+                    assert_eq!(v, *p);
+                    assert_eq!(table.lookup_raw(i), p.repr());
+                }
+                assert!(table.get(len).is_none());
+                assert_eq!(table.iter().rev().count(), len);
+            }
+        }
+
+        // the table of a vanilla integer with an even modulus
+        let x = VanillaInt::new(3u32, &1000);
+        let powers: std::vec::Vec<u32> = power_table(&x, 4).iter().map(|v| v.residue()).collect();
+        assert_eq!(powers, [1, 3, 9, 27]);
+    }
+}
@@ -0,0 +1,217 @@
+//! Lagrange interpolation over [ModularInteger] in quadratic time
+
+use crate::{ModularInteger, ModularUnaryOps};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Calculate the modular inverse of x by the inversion of its residue
+fn inv<M: ModularInteger>(x: &M) -> Option<M>
+where
+    for<'r> &'r M::Base: ModularUnaryOps<&'r M::Base, Output = M::Base>,
+{
+    let (v, m) = (x.residue(), x.modulus());
+    (&v).invm(&m).map(|v| x.convert(v))
+}
+
+/// Evaluate the polynomial of degree less than n passing through the n points
+/// `(points[i], values[i])` at `at`, by the Lagrange formula
+/// `sum y_i * prod_(j != i) (at - x_j) / (x_i - x_j)`.
+///
+/// The terms are accumulated as a single fraction, so it takes `O(n^2)` multiplications and
+/// only one modular inversion. It returns [None] if a difference of two points is not
+/// invertible, in particular if the points are not distinct. It works with any modulus, but
+/// the interpolating polynomial is unique only if the modulus is a prime.
+///
+/// For many evaluations or long inputs, see [Poly::interpolate()][crate::Poly::interpolate].
+///
+/// # Panics
+/// if the numbers of points and values differ
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{lagrange_eval, ModularInteger, MontgomeryInt};
+///
+/// // the sum of the squares 1^2 + ... + k^2 is a cubic polynomial of k
+/// let x = MontgomeryInt::new(0u64, &1_000_000_007);
+/// let points: Vec<_> = (0..4).map(|k| x.convert(k)).collect();
+/// let values: Vec<_> = [0, 1, 5, 14].iter().map(|&v| x.convert(v)).collect();
+/// let s = lagrange_eval(&points, &values, &x.convert(100)).unwrap();
+/// assert_eq!(s.residue(), 338350);
+/// ```
+pub fn lagrange_eval<M: ModularInteger + Clone>(points: &[M], values: &[M], at: &M) -> Option<M>
+where
+    M::Base: From<u8>,
+    for<'r> &'r M::Base: ModularUnaryOps<&'r M::Base, Output = M::Base>,
+{
+    assert_eq!(
+        points.len(),
+        values.len(),
+        "the numbers of points and values should be the same"
+    );
+
+    // the sum is num / den
+    let mut num = at.convert(M::Base::from(0));
+    let mut den = at.convert(M::Base::from(1));
+    for (i, (xi, yi)) in points.iter().zip(values).enumerate() {
+        let mut n = yi.clone();
+        let mut d = at.convert(M::Base::from(1));
+        for (j, xj) in points.iter().enumerate() {
+            if j != i {
+                n = n * (at.clone() - xj.clone());
+                d = d * (xi.clone() - xj.clone());
+            }
+        }
+        num = num * d.clone() + n * den.clone();
+        den = den * d;
+    }
+    Some(num * inv(&den)?)
+}
+
+/// Find the coefficients (from the lowest degree) of the polynomial of degree less than n
+/// passing through the n points `(points[i], values[i])`. The result always has n
+/// coefficients, which may include trailing zeros.
+///
+/// The polynomial is `sum w_i * P(t) / (t - x_i)` with `P = prod (t - x_i)`, where each
+/// quotient is found by synthetic division, so it takes `O(n^2)` multiplications. The weights
+/// `w_i = y_i / prod_(j != i) (x_i - x_j)` are inverted together with one modular inversion.
+/// It returns [None] if a difference of two points is not invertible.
+///
+/// For long inputs with a base type of at most 64 bits, see
+/// [Poly::interpolate()][crate::Poly::interpolate] for the asymptotically faster method.
+///
+/// # Panics
+/// if the numbers of points and values differ
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{lagrange_interpolate, ModularInteger, VanillaInt};
+///
+/// let x = VanillaInt::new(0u8, &13);
+/// let points: Vec<_> = [1, 2, 3].iter().map(|&v| x.convert(v)).collect();
+/// let values: Vec<_> = [6, 11, 5].iter().map(|&v| x.convert(v)).collect();
+/// let coeffs = lagrange_interpolate(&points, &values).unwrap();
+/// let residues: Vec<u8> = coeffs.iter().map(|c| c.residue()).collect();
+/// assert_eq!(residues, [3, 2, 1]); // 3 + 2t + t^2
+/// ```
+#[cfg(feature = "alloc")]
+pub fn lagrange_interpolate<M: ModularInteger + Clone>(points: &[M], values: &[M]) -> Option<Vec<M>>
+where
+    M::Base: From<u8>,
+    for<'r> &'r M::Base: ModularUnaryOps<&'r M::Base, Output = M::Base>,
+{
+    assert_eq!(
+        points.len(),
+        values.len(),
+        "the numbers of points and values should be the same"
+    );
+    let n = points.len();
+    if n == 0 {
+        return Some(Vec::new());
+    }
+    let zero = points[0].convert(M::Base::from(0));
+    let one = points[0].convert(M::Base::from(1));
+
+    // p = prod (t - x_i), with n + 1 coefficients
+    let mut p = alloc::vec![zero.clone(); n + 1];
+    p[0] = one.clone();
+    for (k, x) in points.iter().enumerate() {
+        for j in (1..=k + 1).rev() {
+            p[j] = p[j - 1].clone() - x.clone() * p[j].clone();
+        }
+        p[0] = -(x.clone() * p[0].clone());
+    }
+
+    // invert all the denominators with prefix products
+    let dens: Vec<M> = points
+        .iter()
+        .enumerate()
+        .map(|(i, xi)| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold(one.clone(), |acc, (_, xj)| acc * (xi.clone() - xj.clone()))
+        })
+        .collect();
+    let mut prefix = Vec::with_capacity(n);
+    let mut acc = one;
+    for d in dens.iter() {
+        prefix.push(acc.clone());
+        acc = acc * d.clone();
+    }
+    let mut acc_inv = inv(&acc)?;
+
+    let mut coeffs = alloc::vec![zero; n];
+    for i in (0..n).rev() {
+        let w = values[i].clone() * prefix[i].clone() * acc_inv.clone();
+        acc_inv = acc_inv * dens[i].clone();
+
+        // add w * p / (t - x_i), the quotient is found from the highest degree
+        let mut q = p[n].clone();
+        for j in (0..n).rev() {
+            coeffs[j] = coeffs[j].clone() + w.clone() * q.clone();
+            q = p[j].clone() + points[i].clone() * q;
+        }
+    }
+    Some(coeffs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MontgomeryInt;
+    use rand::random;
+    use std::vec::Vec;
+
+    type Mint = MontgomeryInt<u64>;
+
+    fn eval(coeffs: &[Mint], t: &Mint) -> Mint {
+        coeffs.iter().rev().fold(t.convert(0), |acc, c| acc * t + c)
+    }
+
+    #[test]
+    fn lagrange_eval_test() {
+        let x = Mint::new(0, &998244353);
+        for &n in [1usize, 2, 5, 30].iter() {
+            let coeffs: Vec<_> = (0..n).map(|_| x.convert(random())).collect();
+            let points: Vec<_> = (0..n).map(|_| x.convert(random())).collect();
+            let values: Vec<_> = points.iter().map(|t| eval(&coeffs, t)).collect();
+            for _ in 0..10 {
+                let t = x.convert(random());
+                assert_eq!(lagrange_eval(&points, &values, &t), Some(eval(&coeffs, &t)));
+            }
+            assert_eq!(lagrange_eval(&points, &values, &points[0]), Some(values[0]));
+        }
+
+        assert_eq!(lagrange_eval(&[], &[], &x.convert(5)), Some(x.convert(0)));
+        let points = [x.convert(3), x.convert(7), x.convert(3)];
+        let values = [x.convert(1), x.convert(2), x.convert(3)];
+        assert_eq!(lagrange_eval(&points, &values, &x.convert(5)), None);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn lagrange_interpolate_test() {
+        let x = Mint::new(0, &998244353);
+        for &n in [1usize, 2, 5, 30].iter() {
+            let coeffs: Vec<_> = (0..n).map(|_| x.convert(random())).collect();
+            let points: Vec<_> = (0..n).map(|_| x.convert(random())).collect();
+            let values: Vec<_> = points.iter().map(|t| eval(&coeffs, t)).collect();
+            assert_eq!(lagrange_interpolate(&points, &values).unwrap(), coeffs);
+        }
+
+        assert_eq!(lagrange_interpolate::<Mint>(&[], &[]), Some(Vec::new()));
+        let points = [x.convert(3), x.convert(7), x.convert(3)];
+        let values = [x.convert(1), x.convert(2), x.convert(3)];
+        assert_eq!(lagrange_interpolate(&points, &values), None);
+
+        // composite modulus where the differences are units
+        let x = crate::VanillaInt::new(0u32, &(1 << 20));
+        let points = [x.convert(0), x.convert(1)];
+        let values = [x.convert(5), x.convert(12)];
+        let coeffs = lagrange_interpolate(&points, &values).unwrap();
+        assert_eq!(coeffs, [x.convert(5), x.convert(7)]);
+        let points = [x.convert(0), x.convert(2)];
+        assert_eq!(lagrange_interpolate(&points, &values), None);
+    }
+}
@@ -22,10 +22,10 @@
 //! # Comparison of fast division / modular arithmetics
 //! Several fast division / modulo tricks are provided in these crate, the difference of them are listed below:
 //! - [PreModInv]: pre-compute modular inverse of the divisor, only applicable to exact division
-//! - Barrett (to be implemented): pre-compute (rational approximation of) the reciprocal of the divisor,
-//!     applicable to fast division and modulo
+//! - [Barrett]: pre-compute (rational approximation of) the reciprocal of the divisor,
+//!   applicable to fast modulo with any modulus
 //! - [Montgomery]: Convert the dividend into a special form by shifting and pre-compute a modular inverse,
-//!     only applicable to fast modulo, but faster than Barrett reduction
+//!   only applicable to fast modulo, but faster than Barrett reduction
 //! - [FixedMersenne]: Specialization of modulo in form `2^P-K` under 2^127.
 //!
 
@@ -36,6 +36,8 @@
 // REF: Faster Interleaved Modular Multiplication Based on Barrett and Montgomery Reduction Methods (work for modulus in certain form)
 
 #![no_std]
+#[cfg(any(feature = "alloc", feature = "num-bigint", feature = "ibig"))]
+extern crate alloc;
 #[cfg(any(feature = "std", test))]
 extern crate std;
 
@@ -69,7 +71,8 @@ pub trait ModularUnaryOps<Modulus = Self> {
     /// Calculate modular inverse (x such that self*x = 1 mod m).
     ///
     /// This operation is only available for integer that is coprime to `m`. If not,
-    /// the result will be [None].
+    /// the result will be [None]. To solve `self*x = b mod m` in the non-coprime case,
+    /// see [linear_congruence()].
     fn invm(self, m: Modulus) -> Option<Self::Output>;
 
     /// Calculate modular double ( x+x mod m)
@@ -78,9 +81,8 @@ pub trait ModularUnaryOps<Modulus = Self> {
     /// Calculate modular square ( x*x mod m )
     fn sqm(self, m: Modulus) -> Self::Output;
 
-    // TODO: Modular sqrt aka Quadratic residue, follow the behavior of FLINT `n_sqrtmod`
-    // fn sqrtm(self, m: Modulus) -> Option<Self::Output>;
-    // REF: https://stackoverflow.com/questions/6752374/cube-root-modulo-p-how-do-i-do-this
+    // Modular sqrt (quadratic residue) is provided by the [ModularSqrt] trait
+    // TODO: Modular cube root, REF: https://stackoverflow.com/questions/6752374/cube-root-modulo-p-how-do-i-do-this
 }
 
 /// Modular power functions
@@ -97,6 +99,8 @@ pub trait ModularSymbols<Modulus = Self> {
     ///
     /// Note that this function doesn't perform a full primality check, since
     /// is costly. So if n is not a prime, the result can be not reasonable.
+    /// With the `prime-check` feature, [legendre()] additionally checks the
+    /// primality of n in debug builds.
     ///
     /// # Panics
     /// Only if n is not prime
@@ -132,10 +136,6 @@ pub trait ModularSymbols<Modulus = Self> {
     fn kronecker(&self, n: Modulus) -> i8;
 }
 
-// TODO: Discrete log aka index, follow the behavior of FLINT `n_discrete_log_bsgs`
-// REF: https://github.com/vks/discrete-log
-// fn logm(self, base: Modulus, m: Modulus);
-
 /// Collection of common modular arithmetic operations
 pub trait ModularOps<Rhs = Self, Modulus = Self, Output = Self>:
     ModularCoreOps<Rhs, Modulus, Output = Output>
@@ -199,8 +199,32 @@ pub trait ModularInteger:
     fn square(self) -> Self;
 }
 
+/// Errors of violating the invariants of the modular integer representations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModularError {
+    /// The modulus is even, while the representation only supports odd modulus
+    EvenModulus,
+    /// The precomputed inverse is not consistent with the modulus
+    InvalidInverse,
+    /// The reduced representation is not in range `[0, m)`
+    Unreduced,
+}
+
+impl core::fmt::Display for ModularError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ModularError::EvenModulus => "the modulus should be odd",
+            ModularError::InvalidInverse => "the precomputed inverse doesn't match the modulus",
+            ModularError::Unreduced => "the representation is not reduced",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ModularError {}
+
 // XXX: implement ModularInteger for ff::PrimeField?
-// TODO: implement invm_range (Modular inverse in certain range) and crt (Chinese Remainder Theorem), REF: bubblemath crate
+// TODO: implement invm_range (Modular inverse in certain range), REF: bubblemath crate
 
 /// Utility function for exact division, with precomputed helper values
 ///
@@ -219,6 +243,11 @@ pub trait DivExact<Rhs, Precompute>: Sized {
 /// in a modular ring.
 ///
 /// Essential information for performing the modulo operation will be stored in the reducer.
+///
+/// The operands of the arithmetic methods should be in the reduced form, i.e. the values
+/// returned by [transform()][Reducer::transform] or the other arithmetic methods, for which
+/// [check()][Reducer::check] returns true. Otherwise the results are unspecified, and the
+/// implementations may panic in debug builds.
 pub trait Reducer<T> {
     /// Create a reducer for a modulus m
     fn new(m: &T) -> Self;
@@ -280,30 +309,201 @@ pub trait Reducer<T> {
 }
 
 mod barrett;
+#[cfg(feature = "alloc")]
+mod binom;
+mod bytes;
+mod crt;
+mod dlog;
 mod double;
+#[cfg(feature = "alloc")]
+mod factor;
+mod fixmat;
+mod fp2;
+#[cfg(feature = "alloc")]
+mod fps;
+mod hash;
+mod hensel;
+mod iter;
+mod lagrange;
+pub mod limb;
+mod lincong;
+mod lucas;
+#[cfg(feature = "alloc")]
+mod matrix;
 mod mersenne;
+mod modint;
+mod modtype;
 mod monty;
+#[cfg(feature = "alloc")]
+mod multipoint;
+mod ntt;
+mod order;
+mod packed;
+#[cfg(feature = "alloc")]
+mod poly;
+mod pow;
+#[cfg(feature = "alloc")]
+mod pratt;
 mod preinv;
 mod prim;
+mod qnr;
+mod quad;
+#[cfg(feature = "alloc")]
+mod quotient;
+#[cfg(feature = "rand")]
+mod random;
+mod rational;
+#[cfg(feature = "alloc")]
+mod recurrence;
 mod reduced;
+#[cfg(feature = "alloc")]
+mod rns;
+mod root;
+mod slice;
+mod solinas;
+mod sqrt;
+mod witness;
 mod word;
 
 pub use barrett::{
-    Normalized2by1Divisor, Normalized3by2Divisor, PreMulInv1by1, PreMulInv2by1, PreMulInv3by2,
+    Barrett, Normalized2by1Divisor, Normalized3by2Divisor, PreMulInv1by1, PreMulInv2by1,
+    PreMulInv3by2,
 };
+#[cfg(feature = "alloc")]
+pub use binom::Binomial;
+pub use bytes::{reduce_bytes, reduce_bytes_le, ModReducer, ReduceBytes};
+pub use crt::{crt, crt_pair, Crt, CrtError};
+#[cfg(feature = "std")]
+pub use dlog::discrete_log;
+pub use dlog::{discrete_log_rho, pohlig_hellman, DiscreteLog};
 pub use double::{udouble, umax};
-pub use mersenne::FixedMersenne;
-pub use monty::Montgomery;
+pub use fixmat::{FixedMatrix, Matrix2};
+pub use fp2::{Fp2, Fp2Base};
+pub use hash::PolyHash;
+pub use hensel::{hensel_lift, HenselLift};
+#[cfg(feature = "alloc")]
+pub use iter::{power_table, power_table_raw, PowerTable, PowerTableIter};
+pub use iter::{Elements, Units};
+pub use lagrange::lagrange_eval;
+#[cfg(feature = "alloc")]
+pub use lagrange::lagrange_interpolate;
+pub use lincong::{linear_congruence, CongruenceSolution, LinearCongruence};
+pub use lucas::{fibm, lucasm, pisano_period, LucasSequence};
+#[cfg(feature = "alloc")]
+pub use matrix::{LinearSolution, MatrixError, ModMatrix};
+pub use mersenne::{FixedMersenne, PseudoMersenne};
+pub use modint::AutoReducer;
+pub use monty::{ConstMontgomery, Montgomery, MontyForm};
+#[cfg(feature = "alloc")]
+pub use ntt::{convolution, convolution_mod};
+pub use ntt::{intt, ntt, ntt_max_len, root_of_unity};
+pub use order::{carmichael, reduce_exp, Carmichael};
+pub use order::{ord, ord_with_factors, MultiplicativeOrder};
+pub use packed::{PackedResidues16, PackedResidues8};
+#[cfg(feature = "alloc")]
+pub use poly::Poly;
+pub use pow::{multi_powm, powm_reduced, tetration, MultiPowm, PowmReduced, Tetration};
+#[cfg(feature = "alloc")]
+pub use pow::{naf, wnaf, SignedDigits};
+pub use pow::{powm_crt, powm_fast, powm_hex_exp, powm_str_exp};
+pub use pow::{ParseError, PowmCrt, PowmFast, PowmStrExp};
+#[cfg(feature = "alloc")]
+pub use pratt::PrattCertificate;
 pub use preinv::PreModInv;
-pub use reduced::{ReducedInt, Vanilla, VanillaInt};
+pub use qnr::{find_qnr, FindQnr};
+#[cfg(feature = "rand")]
+pub use qnr::{find_qnr_random, random_qr};
+pub use quad::QuadExt;
+#[cfg(feature = "alloc")]
+pub use quotient::QuotientRing;
+#[cfg(feature = "rand")]
+pub use random::{
+    powm_blinded, random_residue, random_unit, PowmBlinded, RandomResidue, RandomUnit,
+    UniformResidue, UniformUnit,
+};
+pub use rational::{ratrecon, ratrecon_bounded, RationalReconstruction};
+#[cfg(feature = "alloc")]
+pub use recurrence::{berlekamp_massey, linear_recurrence_nth};
+pub use reduced::{convert_repr, ReducedInt, Vanilla, VanillaInt};
+#[cfg(feature = "alloc")]
+pub use rns::{rns_cmp, rns_in_range, to_mixed_radix, MixedRadix, RnsContext, RnsError, RnsInt};
+#[cfg(feature = "alloc")]
+pub use root::nth_rootm_all;
+pub use root::{nth_rootm, ModularRoot};
+pub use slice::{dotm, fmam_slice, prodm_iter, summ_iter, Accumulator, Dotm, FmamSlice, FoldIter};
+#[cfg(feature = "alloc")]
+pub use slice::{invm_batch_or_factor, InvmBatch};
+pub use solinas::{Goldilocks, Mersenne61, Mersenne89, Solinas, SpecialReduce};
+#[cfg(feature = "alloc")]
+pub use sqrt::sqrtm_factored_all;
+pub use sqrt::{sqrtm, sqrtm_factored, ModularSqrt, ModularSqrtFactored};
+#[cfg(feature = "rand")]
+pub use witness::find_witness;
+#[cfg(feature = "prime-check")]
+pub use witness::legendre;
+pub use witness::{smallest_witness, Witness};
 
 /// An integer in modulo ring based on [Montgomery form](https://en.wikipedia.org/wiki/Montgomery_modular_multiplication#Montgomery_form)
+///
+/// With the `num-bigint` feature, `MontgomeryInt<BigUint>` is supported for odd moduli of any size,
+/// which is much faster than [ModularCoreOps::mulm] on the moduli of thousands of bits. The same
+/// holds for `MontgomeryInt<UBig>` with the `ibig` feature.
 pub type MontgomeryInt<T> = ReducedInt<T, Montgomery<T>>;
 
+/// An integer in modulo ring with an odd modulus fixed at compile time, based on [Montgomery form]
+///
+/// [Montgomery form]: https://en.wikipedia.org/wiki/Montgomery_modular_multiplication#Montgomery_form
+pub type ConstMontgomeryInt<const M: u64> = ReducedInt<u64, ConstMontgomery<M>>;
+
+/// An integer modulo the NTT-friendly prime `998244353 = 119 * 2^23 + 1`, see [convolution()]
+pub type ModInt998244353 = ConstMontgomeryInt<998244353>;
+
+/// An integer modulo the prime `1000000007`
+pub type ModInt1000000007 = ConstMontgomeryInt<1_000_000_007>;
+
+/// An integer in modulo ring accepting any modulus, based on [Montgomery form] for odd
+/// moduli and [Barrett reduction][Barrett] for even moduli (see [AutoReducer])
+///
+/// [Montgomery form]: https://en.wikipedia.org/wiki/Montgomery_modular_multiplication#Montgomery_form
+pub type ModInt<T> = ReducedInt<T, AutoReducer<T>>;
+
+/// An element of a quadratic extension based on [Montgomery] form
+pub type MontgomeryQuadExt<T> = QuadExt<T, Montgomery<T>>;
+
+/// An element of a quadratic extension based on the naive reducer [Vanilla]
+pub type VanillaQuadExt<T> = QuadExt<T, Vanilla<T>>;
+
 /// An integer in modulo ring with a fixed (pseudo) Mersenne number as modulus
 pub type FixedMersenneInt<const P: u8, const K: umax> = ReducedInt<umax, FixedMersenne<P, K>>;
 
-// pub type BarrettInt<T> = ReducedInt<T, BarrettInt<T>>;
+/// An integer in modulo ring with a pseudo-Mersenne modulus `2^n - c` given at runtime
+pub type PseudoMersenneInt<T> = ReducedInt<T, PseudoMersenne<T>>;
+
+/// An integer in modulo ring with a special prime modulus, see [SpecialReduce]
+pub type SolinasInt<P> = ReducedInt<<P as SpecialReduce>::Word, Solinas<P>>;
+
+/// An integer in the prime field of the [Goldilocks] prime `2^64 - 2^32 + 1`
+pub type GoldilocksInt = SolinasInt<Goldilocks>;
+
+/// An integer in modulo ring accepting any modulus, based on [Barrett] reduction
+pub type BarrettInt<T> = ReducedInt<T, Barrett<T>>;
 
-#[cfg(feature = "num-bigint")]
+#[cfg(any(feature = "num-bigint", feature = "ibig"))]
 mod bigint;
+#[cfg(feature = "crypto-bigint")]
+mod cryptobig;
+#[cfg(feature = "primitive-types")]
+mod ptypes;
+
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+mod arb;
+#[cfg(feature = "subtle")]
+mod ct;
+
+// Re-exported for the implementations generated by the modtype! macro
+#[cfg(feature = "arbitrary")]
+#[doc(hidden)]
+pub use arbitrary as __arbitrary;
+#[cfg(feature = "proptest")]
+#[doc(hidden)]
+pub use proptest as __proptest;
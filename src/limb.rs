@@ -0,0 +1,726 @@
+//! Word-level primitives for multi-precision Montgomery arithmetic.
+//!
+//! The multi-precision integers are represented as little-endian slices of 64-bit limbs.
+//! These functions are building blocks for fixed-width big integer types, they don't
+//! allocate and they are not required to run in constant time.
+//!
+//! The modular operation traits are implemented for `[u64; 2]` (a 128-bit integer as a
+//! little-endian pair) with these primitives, which is useful on targets where [u128]
+//! is not natively supported.
+//!
+//! Example code:
+//! ```rust
+//! use num_modular::limb::{mont_mul, neg_inv};
+//!
+//! // (2^64 + 3) * (2^64 + 5) * R^-1 mod m, with R = 2^128
+//! let m = [0xffffffffffffffc5, 0x7fffffffffffffff];
+//! let minv = neg_inv(m[0]);
+//! let mut out = [0; 3];
+//! mont_mul(&[3, 1], &[5, 1], &m, minv, &mut out);
+//! assert_eq!(out[2], 0); // the result is fully reduced in out[..2]
+//! assert!(out[1] <= m[1]);
+//! ```
+
+use crate::monty::neg_mod_inv;
+use crate::Normalized2by1Divisor;
+use crate::{ModularCoreOps, ModularPow, ModularSymbols, ModularUnaryOps};
+
+/// Calculate `-m^-1 mod 2^64` for an odd m, which is the `minv` argument used by the
+/// functions in this module. Only the lowest limb of the modulus is needed.
+///
+/// # Panics
+/// if m is even
+#[inline]
+pub const fn neg_inv(m: u64) -> u64 {
+    assert!(m & 1 == 1, "the modulus should be odd");
+    neg_mod_inv::u64::neginv(m)
+}
+
+/// Calculate `a + b + carry`, returning the low word and the carry
+#[inline(always)]
+pub const fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let t = a as u128 + b as u128 + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+/// Calculate `a - b - borrow`, returning the low word and the borrow (0 or 1)
+#[inline(always)]
+pub const fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let t = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (t as u64, (t >> 127) as u64)
+}
+
+/// Calculate `acc + a * b + carry`, returning the low word and the high word. The result
+/// never overflows 128 bits.
+#[inline(always)]
+pub const fn mac(acc: u64, a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let t = acc as u128 + a as u128 * b as u128 + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+/// One step of the CIOS (coarsely integrated operand scanning) Montgomery multiplication:
+/// `acc = (acc + a_limb * b + u * m) / 2^64`, where `u = acc[0] * minv mod 2^64` is chosen
+/// such that the division is exact. Therefore `acc` is updated to
+/// `(acc + a_limb * b) * 2^-64 mod m` (not fully reduced).
+///
+/// With `n = m.len()`, `acc` must have `n + 1` limbs, `b` must have `n` limbs and
+/// `minv = -m^-1 mod 2^64` (see [neg_inv()]). If `acc < 2m` and `b < m` before the step,
+/// then `acc < 2m` holds after the step.
+///
+/// # Panics
+/// if the lengths of the slices are not consistent
+pub fn cios_step(acc: &mut [u64], a_limb: u64, b: &[u64], m: &[u64], minv: u64) {
+    let n = m.len();
+    assert!(
+        n > 0 && b.len() == n && acc.len() == n + 1,
+        "the lengths of the limbs are not consistent"
+    );
+
+    // acc += a_limb * b, the top carry is kept in `top`
+    let mut carry = 0;
+    for (t, &bj) in acc[..n].iter_mut().zip(b) {
+        let (lo, hi) = mac(*t, a_limb, bj, carry);
+        *t = lo;
+        carry = hi;
+    }
+    let (lo, top) = adc(acc[n], carry, 0);
+    acc[n] = lo;
+
+    // acc = (acc + u * m) >> 64
+    let u = acc[0].wrapping_mul(minv);
+    let (_, mut carry) = mac(acc[0], u, m[0], 0);
+    for j in 1..n {
+        let (lo, hi) = mac(acc[j], u, m[j], carry);
+        acc[j - 1] = lo;
+        carry = hi;
+    }
+    let (lo, hi) = adc(acc[n], carry, 0);
+    acc[n - 1] = lo;
+    acc[n] = top + hi;
+}
+
+/// Compare two multi-precision integers with the same number of limbs
+fn ge(a: &[u64], b: &[u64]) -> bool {
+    for (x, y) in a.iter().zip(b).rev() {
+        if x != y {
+            return x > y;
+        }
+    }
+    true
+}
+
+/// Subtract m from acc in place if `acc >= m`, where `acc` has one more limb than m.
+/// The result is fully reduced if `acc < 2m`.
+///
+/// # Panics
+/// if `acc.len() != m.len() + 1`
+pub fn final_sub(acc: &mut [u64], m: &[u64]) {
+    let n = m.len();
+    assert!(
+        acc.len() == n + 1,
+        "the lengths of the limbs are not consistent"
+    );
+    if acc[n] == 0 && !ge(&acc[..n], m) {
+        return;
+    }
+    let mut borrow = 0;
+    for (t, &mj) in acc[..n].iter_mut().zip(m) {
+        let (v, b) = sbb(*t, mj, borrow);
+        *t = v;
+        borrow = b;
+    }
+    acc[n] -= borrow;
+}
+
+/// Montgomery multiplication `a * b * R^-1 mod m` with `R = 2^(64n)` and `n = m.len()`,
+/// implemented with [cios_step()] and [final_sub()].
+///
+/// The result is written into `out[..n]`, where `out` must have `n + 1` limbs and its
+/// original content is ignored. The operands a and b should have `n` limbs and be
+/// smaller than m, then the result is fully reduced.
+///
+/// # Panics
+/// if the lengths of the slices are not consistent
+pub fn mont_mul(a: &[u64], b: &[u64], m: &[u64], minv: u64, out: &mut [u64]) {
+    assert!(
+        a.len() == m.len(),
+        "the lengths of the limbs are not consistent"
+    );
+    for t in out.iter_mut() {
+        *t = 0;
+    }
+    for &ai in a {
+        cios_step(out, ai, b, m, minv);
+    }
+    final_sub(out, m);
+}
+
+// Modular operations on two-limb integers, the reference for the multi-precision types
+
+const ZERO2: [u64; 2] = [0, 0];
+const ONE2: [u64; 2] = [1, 0];
+
+#[inline]
+fn add2(a: &[u64; 2], b: &[u64; 2]) -> ([u64; 2], u64) {
+    let (lo, c) = adc(a[0], b[0], 0);
+    let (hi, c) = adc(a[1], b[1], c);
+    ([lo, hi], c)
+}
+
+#[inline]
+fn sub2(a: &[u64; 2], b: &[u64; 2]) -> ([u64; 2], u64) {
+    let (lo, c) = sbb(a[0], b[0], 0);
+    let (hi, c) = sbb(a[1], b[1], c);
+    ([lo, hi], c)
+}
+
+/// `a << s` for `s < 64`, returning the limb shifted out
+#[inline]
+fn shl2(a: &[u64; 2], s: u32) -> ([u64; 2], u64) {
+    if s == 0 {
+        (*a, 0)
+    } else {
+        ([a[0] << s, a[1] << s | a[0] >> (64 - s)], a[1] >> (64 - s))
+    }
+}
+
+/// `a >> s` for `s < 128`
+#[inline]
+fn shr2(a: &[u64; 2], s: u32) -> [u64; 2] {
+    match s {
+        0 => *a,
+        1..=63 => [a[0] >> s | a[1] << (64 - s), a[1] >> s],
+        _ => [a[1] >> (s - 64), 0],
+    }
+}
+
+#[inline]
+fn trailing_zeros2(a: &[u64; 2]) -> u32 {
+    if a[0] == 0 {
+        64 + a[1].trailing_zeros()
+    } else {
+        a[0].trailing_zeros()
+    }
+}
+
+/// (a + b) mod m, where a, b < m
+#[inline]
+fn add2_reduced(a: &[u64; 2], b: &[u64; 2], m: &[u64; 2]) -> [u64; 2] {
+    let (s, carry) = add2(a, b);
+    if carry == 1 || ge(&s, m) {
+        sub2(&s, m).0
+    } else {
+        s
+    }
+}
+
+/// A normalized two-limb divisor (the top bit is set) with its reciprocal
+/// `v = floor((2^192 - 1) / d) - 2^64`, which is
+/// [Normalized3by2Divisor][crate::Normalized3by2Divisor] on the limbs.
+///
+/// Möller, Granlund, "Improved division by invariant integers", Algorithm 5 and 6.
+struct Divisor2 {
+    d: [u64; 2],
+    v: u64,
+}
+
+impl Divisor2 {
+    fn new(d: [u64; 2]) -> Self {
+        debug_assert!(d[1].leading_zeros() == 0);
+        let [d0, d1] = d;
+        let mut v = Normalized2by1Divisor::<u64>::invert_word(d1);
+
+        // then B^2 - d1 <= (B + v)d1 < B^2
+        let (mut p, c) = d1.wrapping_mul(v).overflowing_add(d0);
+        if c {
+            v -= 1;
+            if p >= d1 {
+                v -= 1;
+                p -= d1;
+            }
+            p = p.wrapping_sub(d1);
+        }
+
+        // then B^2 - d1 <= (B + v)d1 + d0 < B^2
+        let (t0, t1) = mac(0, v, d0, 0);
+        let (p, c) = p.overflowing_add(t1);
+        if c {
+            v -= 1;
+            if ge(&[t0, p], &d) {
+                v -= 1;
+            }
+        }
+        Self { d, v }
+    }
+
+    /// Divide the three limbs `[u0, u[0], u[1]]` by d where `u < d`, returning the quotient
+    /// limb and the remainder
+    fn div_rem_3by2(&self, u0: u64, u: [u64; 2]) -> (u64, [u64; 2]) {
+        let [d0, d1] = self.d;
+        let [u1, u2] = u;
+
+        // [q0, q1] = v * u2 + [u1, u2], which doesn't overflow because u2 <= d1
+        let (q0, q1) = mac(u1, self.v, u2, 0);
+        let q1 = q1.wrapping_add(u2);
+
+        // the first guess of the quotient is q1 + 1, with the remainder modulo 2^128
+        let r1 = u1.wrapping_sub(q1.wrapping_mul(d1));
+        let (t0, t1) = mac(0, d0, q1, 0);
+        let r = sub2(&sub2(&[u0, r1], &[t0, t1]).0, &self.d).0;
+        let (mut q, mut r) = if r[1] >= q0 {
+            (q1, add2(&r, &self.d).0)
+        } else {
+            (q1.wrapping_add(1), r)
+        };
+
+        // the following fix step is unlikely to happen
+        if ge(&r, &self.d) {
+            q += 1;
+            r = sub2(&r, &self.d).0;
+        }
+        (q, r)
+    }
+}
+
+/// Divide a by a nonzero b, returning the quotient and the remainder
+fn div_rem2(a: &[u64; 2], b: &[u64; 2]) -> ([u64; 2], [u64; 2]) {
+    if b[1] == 0 {
+        let shift = b[0].leading_zeros();
+        let div = Normalized2by1Divisor::<u64>::new(b[0] << shift);
+        let (a, top) = shl2(a, shift);
+        let (q1, r) = div.div_rem_2by1((top as u128) << 64 | a[1] as u128);
+        let (q0, r) = div.div_rem_2by1((r as u128) << 64 | a[0] as u128);
+        ([q0, q1], [r >> shift, 0])
+    } else {
+        // the quotient fits in a single limb
+        let shift = b[1].leading_zeros();
+        let div = Divisor2::new(shl2(b, shift).0);
+        let (a, top) = shl2(a, shift);
+        let (q, r) = div.div_rem_3by2(a[0], [a[1], top]);
+        ([q, 0], shr2(&r, shift))
+    }
+}
+
+/// Reduce the little-endian multi-precision integer by m, with one division by the
+/// precomputed reciprocal of the normalized modulus ([Normalized2by1Divisor] for a single-limb
+/// modulus and [Divisor2] otherwise) for each limb of the dividend
+fn reduce2(v: &[u64], m: &[u64; 2]) -> [u64; 2] {
+    assert!(m != &ZERO2, "the modulus should not be zero");
+    if v.len() == 2 && !ge(v, m) {
+        return [v[0], v[1]];
+    }
+
+    // the limbs of v << shift from the top, including the extra limb shifted out
+    let limbs = |shift: u32| {
+        let carry = move |i: usize| match (shift, i) {
+            (0, _) | (_, 0) => 0,
+            _ => v[i - 1] >> (64 - shift),
+        };
+        (0..=v.len()).rev().map(move |i| match v.get(i) {
+            Some(&x) => x << shift | carry(i),
+            None => carry(i),
+        })
+    };
+
+    // the remainder stays below the normalized divisor, as required by the divisions
+    if m[1] == 0 {
+        let shift = m[0].leading_zeros();
+        let div = Normalized2by1Divisor::<u64>::new(m[0] << shift);
+        let r = limbs(shift).fold(0u64, |r, limb| {
+            div.div_rem_2by1((r as u128) << 64 | limb as u128).1
+        });
+        [r >> shift, 0]
+    } else {
+        let shift = m[1].leading_zeros();
+        let div = Divisor2::new(shl2(m, shift).0);
+        let r = limbs(shift).fold(ZERO2, |r, limb| div.div_rem_3by2(limb, r).1);
+        shr2(&r, shift)
+    }
+}
+
+/// Modular operations on 128-bit integers represented by two little-endian 64-bit limbs,
+/// implemented with the limb primitives, and the reductions are divisions by the precomputed
+/// reciprocal of the modulus.
+impl ModularCoreOps<[u64; 2], &[u64; 2]> for [u64; 2] {
+    type Output = [u64; 2];
+
+    fn addm(self, rhs: [u64; 2], m: &[u64; 2]) -> [u64; 2] {
+        add2_reduced(&reduce2(&self, m), &reduce2(&rhs, m), m)
+    }
+
+    fn subm(self, rhs: [u64; 2], m: &[u64; 2]) -> [u64; 2] {
+        let (d, borrow) = sub2(&reduce2(&self, m), &reduce2(&rhs, m));
+        if borrow == 1 {
+            add2(&d, m).0
+        } else {
+            d
+        }
+    }
+
+    fn mulm(self, rhs: [u64; 2], m: &[u64; 2]) -> [u64; 2] {
+        // schoolbook multiplication into four limbs
+        let mut prod = [0u64; 4];
+        for (i, &a) in self.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in rhs.iter().enumerate() {
+                let (lo, hi) = mac(prod[i + j], a, b, carry);
+                prod[i + j] = lo;
+                carry = hi;
+            }
+            prod[i + 2] = carry;
+        }
+        reduce2(&prod, m)
+    }
+}
+
+impl ModularUnaryOps<&[u64; 2]> for [u64; 2] {
+    type Output = [u64; 2];
+
+    fn negm(self, m: &[u64; 2]) -> [u64; 2] {
+        let x = reduce2(&self, m);
+        if x == ZERO2 {
+            ZERO2
+        } else {
+            sub2(m, &x).0
+        }
+    }
+
+    fn invm(self, m: &[u64; 2]) -> Option<[u64; 2]> {
+        // extended Euclidean algorithm, the same as the primitive integers
+        let (mut last_r, mut r) = (*m, reduce2(&self, m));
+        let (mut last_t, mut t) = (ZERO2, ONE2);
+
+        while r != ZERO2 {
+            let (quo, rem) = div_rem2(&last_r, &r);
+            last_r = r;
+            r = rem;
+
+            let new_t = last_t.subm(quo.mulm(t, m), m);
+            last_t = t;
+            t = new_t;
+        }
+
+        // if r = gcd(self, m) > 1, then inverse doesn't exist
+        if last_r == ONE2 {
+            Some(last_t)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn dblm(self, m: &[u64; 2]) -> [u64; 2] {
+        self.addm(self, m)
+    }
+
+    #[inline]
+    fn sqm(self, m: &[u64; 2]) -> [u64; 2] {
+        self.mulm(self, m)
+    }
+}
+
+impl ModularPow<[u64; 2], &[u64; 2]> for [u64; 2] {
+    type Output = [u64; 2];
+
+    fn powm(self, exp: [u64; 2], m: &[u64; 2]) -> [u64; 2] {
+        let base = reduce2(&self, m);
+        let mut result = reduce2(&ONE2, m);
+        for &limb in exp.iter().rev() {
+            for i in (0..64).rev() {
+                result = result.mulm(result, m);
+                if (limb >> i) & 1 == 1 {
+                    result = result.mulm(base, m);
+                }
+            }
+        }
+        result
+    }
+}
+
+impl ModularSymbols<&[u64; 2]> for [u64; 2] {
+    fn checked_legendre(&self, n: &[u64; 2]) -> Option<i8> {
+        let r = self.powm(shr2(&sub2(n, &ONE2).0, 1), n);
+        if r == ZERO2 {
+            Some(0)
+        } else if r == ONE2 {
+            Some(1)
+        } else if add2(&r, &ONE2).0 == *n {
+            Some(-1)
+        } else {
+            None
+        }
+    }
+
+    fn checked_jacobi(&self, n: &[u64; 2]) -> Option<i8> {
+        if n[0] % 2 == 0 {
+            return None;
+        }
+        if self == &ZERO2 {
+            return Some(if n == &ONE2 { 1 } else { 0 });
+        }
+        if self == &ONE2 {
+            return Some(1);
+        }
+
+        let mut a = reduce2(self, n);
+        let mut n = *n;
+        let mut t = 1;
+        while a != ZERO2 {
+            while a[0] % 2 == 0 {
+                a = shr2(&a, 1);
+                if n[0] % 8 == 3 || n[0] % 8 == 5 {
+                    t *= -1;
+                }
+            }
+            core::mem::swap(&mut a, &mut n);
+            if a[0] % 4 == 3 && n[0] % 4 == 3 {
+                t *= -1;
+            }
+            a = reduce2(&a, &n);
+        }
+        Some(if n == ONE2 { t } else { 0 })
+    }
+
+    fn kronecker(&self, n: &[u64; 2]) -> i8 {
+        match *n {
+            ZERO2 => {
+                if self == &ONE2 {
+                    1
+                } else {
+                    0
+                }
+            }
+            ONE2 => 1,
+            [2, 0] => {
+                if self[0] % 2 == 0 {
+                    0
+                } else if self[0] % 8 == 1 || self[0] % 8 == 7 {
+                    1
+                } else {
+                    -1
+                }
+            }
+            _ => {
+                let f = trailing_zeros2(n);
+                self.kronecker(&[2, 0]).pow(f) * self.jacobi(&shr2(n, f))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Montgomery, Reducer};
+    use rand::random;
+
+    fn to_u128(v: [u64; 2]) -> u128 {
+        v[0] as u128 | (v[1] as u128) << 64
+    }
+
+    fn from_u128(v: u128) -> [u64; 2] {
+        [v as u64, (v >> 64) as u64]
+    }
+
+    const NRANDOM: u32 = 10;
+
+    #[test]
+    fn primitives_test() {
+        assert_eq!(adc(u64::MAX, 1, 1), (1, 1));
+        assert_eq!(adc(u64::MAX, u64::MAX, 1), (u64::MAX, 1));
+        assert_eq!(sbb(0, 1, 0), (u64::MAX, 1));
+        assert_eq!(sbb(0, u64::MAX, 1), (0, 1));
+        assert_eq!(sbb(5, 3, 1), (1, 0));
+        assert_eq!(
+            mac(u64::MAX, u64::MAX, u64::MAX, u64::MAX),
+            (u64::MAX, u64::MAX)
+        );
+        for _ in 0..NRANDOM {
+            let m = random::<u64>() | 1;
+            assert_eq!(m.wrapping_mul(neg_inv(m)), u64::MAX);
+        }
+    }
+
+    #[test]
+    fn single_limb_test() {
+        // agree with the Montgomery reducer for a single limb
+        for _ in 0..NRANDOM {
+            let m = random::<u64>() | 1;
+            let r = Montgomery::<u64>::new(m);
+            let (a, b) = (random::<u64>() % m, random::<u64>() % m);
+            let mut out = [0; 2];
+            mont_mul(&[a], &[b], &[m], neg_inv(m), &mut out);
+            assert_eq!(out, [r.mul(&a, &b), 0]);
+        }
+    }
+
+    #[test]
+    fn two_limbs_test() {
+        use crate::ModularRefOps;
+        fn assert_ops<T: ModularRefOps>() {}
+        assert_ops::<[u64; 2]>();
+
+        let edges = [
+            0u128,
+            1,
+            2,
+            u64::MAX as u128,
+            1 << 64,
+            (1 << 64) + 1,
+            u128::MAX - 1,
+            u128::MAX,
+        ];
+        let mut values: std::vec::Vec<u128> = edges.to_vec();
+        for _ in 0..NRANDOM {
+            values.push(random());
+            values.push(random::<u64>() as u128);
+            values.push(random::<u128>() >> 1 | 1 << 127);
+        }
+
+        for &m in values.iter().filter(|&&m| m > 0) {
+            let lm = from_u128(m);
+            for &a in values.iter() {
+                let la = from_u128(a);
+                assert_eq!(to_u128(la.negm(&lm)), a.negm(&m));
+                assert_eq!(to_u128(la.dblm(&lm)), a.dblm(&m));
+                assert_eq!(to_u128(la.sqm(&lm)), a.sqm(&m));
+                assert_eq!(la.invm(&lm).map(to_u128), a.invm(&m));
+                assert_eq!(la.kronecker(&lm), a.kronecker(&m));
+                assert_eq!(la.checked_jacobi(&lm), a.checked_jacobi(&m));
+                for &b in values.iter().take(edges.len() + 3) {
+                    let lb = from_u128(b);
+                    assert_eq!(
+                        to_u128(la.addm(lb, &lm)),
+                        a.addm(b, &m),
+                        "{} + {} mod {}",
+                        a,
+                        b,
+                        m
+                    );
+                    assert_eq!(
+                        to_u128(la.subm(lb, &lm)),
+                        a.subm(b, &m),
+                        "{} - {} mod {}",
+                        a,
+                        b,
+                        m
+                    );
+                    assert_eq!(
+                        to_u128(la.mulm(lb, &lm)),
+                        a.mulm(b, &m),
+                        "{} * {} mod {}",
+                        a,
+                        b,
+                        m
+                    );
+                }
+            }
+            for _ in 0..NRANDOM {
+                let (b, e) = (random::<u128>(), random::<u128>());
+                assert_eq!(to_u128(from_u128(b).powm(from_u128(e), &lm)), b.powm(e, &m));
+            }
+        }
+
+        let max = [u64::MAX; 2];
+        assert_eq!(max.mulm(max, &max), [0, 0]);
+        assert_eq!(max.addm(max, &[u64::MAX - 1, u64::MAX]), [2, 0]);
+        assert_eq!([5u64, 0].powm([0, 0], &[1, 0]), [0, 0]);
+        let p = from_u128(u128::MAX - 158); // the largest 128-bit prime
+        for _ in 0..NRANDOM {
+            let a = random::<u128>();
+            let la = from_u128(a);
+            assert_eq!(la.legendre(&p), a.legendre(&to_u128(p)));
+            assert_eq!(la.invm(&p).map(to_u128), a.invm(&to_u128(p)));
+            let (q, r) = div_rem2(&from_u128(a), &from_u128(a >> 70 | 1));
+            assert_eq!(
+                (to_u128(q), to_u128(r)),
+                (a / (a >> 70 | 1), a % (a >> 70 | 1))
+            );
+        }
+        assert_eq!([2u64, 0].legendre(&p), 1); // p = 1 (mod 8)
+    }
+
+    #[cfg(feature = "num-bigint")]
+    mod bigint {
+        use super::*;
+        use crate::ModularUnaryOps;
+        use num_bigint::BigUint;
+        use std::vec::Vec;
+
+        fn random_limbs(n: usize) -> Vec<u64> {
+            (0..n).map(|_| random()).collect()
+        }
+
+        fn to_biguint(limbs: &[u64]) -> BigUint {
+            limbs
+                .iter()
+                .rev()
+                .fold(BigUint::from(0u8), |acc, &v| (acc << 64u8) + v)
+        }
+
+        fn to_limbs(v: &BigUint, n: usize) -> Vec<u64> {
+            let mut limbs = v.to_u64_digits();
+            limbs.resize(n, 0);
+            limbs
+        }
+
+        fn random_modulus(n: usize) -> Vec<u64> {
+            let mut m = random_limbs(n);
+            m[0] |= 1;
+            m[n - 1] |= 1 << 63;
+            m
+        }
+
+        #[test]
+        fn cios_step_test() {
+            for &n in [1usize, 4, 8].iter() {
+                for _ in 0..NRANDOM {
+                    let m = random_modulus(n);
+                    let bm = to_biguint(&m);
+                    let minv = neg_inv(m[0]);
+                    let r_inv = (BigUint::from(1u8) << 64u8).invm(&bm).unwrap();
+
+                    let b = to_limbs(&(to_biguint(&random_limbs(n)) % &bm), n);
+                    let mut acc =
+                        to_limbs(&(to_biguint(&random_limbs(n + 1)) % (&bm << 1u8)), n + 1);
+                    let a_limb = random::<u64>();
+
+                    let before = to_biguint(&acc);
+                    cios_step(&mut acc, a_limb, &b, &m, minv);
+                    let after = to_biguint(&acc);
+
+                    assert!(after < &bm << 1u8);
+                    assert_eq!(
+                        &after % &bm,
+                        (before + to_biguint(&b) * a_limb) * &r_inv % &bm
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn mont_mul_test() {
+            for &n in [2usize, 4, 8].iter() {
+                for _ in 0..NRANDOM {
+                    let m = random_modulus(n);
+                    let bm = to_biguint(&m);
+                    let minv = neg_inv(m[0]);
+                    let r_inv = (BigUint::from(1u8) << (64 * n)).invm(&bm).unwrap();
+
+                    let a = to_biguint(&random_limbs(n)) % &bm;
+                    let b = to_biguint(&random_limbs(n)) % &bm;
+                    let mut out = Vec::new();
+                    out.resize(n + 1, random());
+                    mont_mul(&to_limbs(&a, n), &to_limbs(&b, n), &m, minv, &mut out);
+                    assert_eq!(out[n], 0);
+                    assert_eq!(to_biguint(&out), &a * &b * &r_inv % &bm);
+
+                    // the largest operands
+                    let max = to_limbs(&(&bm - 1u8), n);
+                    mont_mul(&max, &max, &m, minv, &mut out);
+                    assert_eq!(to_biguint(&out), &r_inv % &bm);
+                }
+            }
+        }
+    }
+}
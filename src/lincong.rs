@@ -0,0 +1,214 @@
+//! Linear congruences `a·x ≡ b (mod m)` with a not necessarily coprime to m
+
+use crate::prim::gcd;
+use crate::{ModularCoreOps, ModularUnaryOps};
+
+/// Solutions of a linear congruence found by [linear_congruence()]
+///
+/// The solutions modulo m are `x + i * stride` for `0 <= i < count`, where
+/// `stride * count = m`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CongruenceSolution<T> {
+    /// The smallest non-negative solution
+    pub x: T,
+    /// The difference between consecutive solutions, which is `m / gcd(a, m)`
+    pub stride: T,
+    /// The number of solutions modulo m, which is `gcd(a, m)`
+    pub count: T,
+}
+
+impl<T: PartialEq + From<u8>> CongruenceSolution<T> {
+    /// Whether the solution is unique modulo m
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        self.count == T::from(1)
+    }
+}
+
+/// Integer types supported by [linear_congruence()]
+pub trait LinearCongruence: Sized {
+    /// Solve `a·x ≡ b (mod m)`, see [linear_congruence()] for details
+    fn linear_congruence(a: &Self, b: &Self, m: &Self) -> Option<CongruenceSolution<Self>>;
+}
+
+/// Solve the linear congruence `a·x ≡ b (mod m)`, returning all the solutions modulo m as a
+/// [CongruenceSolution], or [None] if there is no solution.
+///
+/// With `g = gcd(a, m)`, a solution exists iff g divides b, and then the congruence is
+/// equivalent to `(a/g)·x ≡ b/g (mod m/g)` where `a/g` is invertible. So there are exactly g
+/// solutions modulo m, which are spaced `m/g` apart. For `g = 1` it's the same as
+/// multiplying b by [invm()][ModularUnaryOps::invm]. The inputs don't need to be reduced.
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::linear_congruence;
+///
+/// // 6x = 4 (mod 10) <=> 3x = 2 (mod 5), so x = 4, 9 (mod 10)
+/// let s = linear_congruence(&6u32, &4, &10).unwrap();
+/// assert_eq!((s.x, s.stride, s.count), (4, 5, 2));
+/// assert!(linear_congruence(&6u32, &5, &10).is_none()); // gcd(6, 10) doesn't divide 5
+/// assert!(linear_congruence(&3u32, &5, &10).unwrap().is_unique());
+/// ```
+#[inline]
+pub fn linear_congruence<T: LinearCongruence>(
+    a: &T,
+    b: &T,
+    m: &T,
+) -> Option<CongruenceSolution<T>> {
+    T::linear_congruence(a, b, m)
+}
+
+macro_rules! impl_linear_congruence_for {
+    ($($T:ty)*) => {$(
+        impl LinearCongruence for $T {
+            fn linear_congruence(a: &$T, b: &$T, m: &$T) -> Option<CongruenceSolution<$T>> {
+                let m = *m;
+                assert!(m > 0, "the modulus should not be zero");
+                let (a, b) = (a % m, b % m);
+
+                let g = gcd(m, a);
+                if b % g != 0 {
+                    return None;
+                }
+                let mg = m / g;
+                let inv = (a / g).invm(&mg).unwrap(); // a/g and m/g are coprime
+                Some(CongruenceSolution {
+                    x: (b / g).mulm(inv, &mg),
+                    stride: mg,
+                    count: g,
+                })
+            }
+        }
+    )*};
+}
+impl_linear_congruence_for!(u8 u16 u32 u64 u128 usize);
+
+#[cfg(feature = "num-bigint")]
+mod _num_bigint {
+    use super::*;
+    use num_bigint::BigUint;
+    use num_integer::Integer;
+    use num_traits::Zero;
+
+    impl LinearCongruence for BigUint {
+        fn linear_congruence(
+            a: &BigUint,
+            b: &BigUint,
+            m: &BigUint,
+        ) -> Option<CongruenceSolution<BigUint>> {
+            assert!(!m.is_zero(), "the modulus should not be zero");
+            let (a, b) = (a % m, b % m);
+            let g = a.gcd(m);
+            if !(&b % &g).is_zero() {
+                return None;
+            }
+            let mg = m / &g;
+            let inv = (a / &g).invm(&mg).unwrap();
+            Some(CongruenceSolution {
+                x: (b / &g).mulm(&inv, &mg),
+                stride: mg,
+                count: g,
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use rand::random;
+
+        #[test]
+        fn biguint_linear_congruence_test() {
+            // against the primitive implementation
+            for _ in 0..20 {
+                let (a, b, m) = (
+                    random::<u32>() as u64,
+                    random::<u64>(),
+                    random::<u16>() as u64 + 1,
+                );
+                let a = a * (m & 7); // share factors with m more often
+                let expected = linear_congruence(&a, &b, &m).map(|s| CongruenceSolution {
+                    x: BigUint::from(s.x),
+                    stride: BigUint::from(s.stride),
+                    count: BigUint::from(s.count),
+                });
+                let big =
+                    linear_congruence(&BigUint::from(a), &BigUint::from(b), &BigUint::from(m));
+                assert_eq!(big, expected);
+            }
+
+            // modulus beyond the primitive range
+            let m = BigUint::from(random::<u128>() | 1) << 100u8;
+            let (a, x) = (
+                BigUint::from(random::<u64>()) << 3u8,
+                BigUint::from(random::<u128>()),
+            );
+            let b = (&a * &x) % &m;
+            let s = linear_congruence(&a, &b, &m).unwrap();
+            assert_eq!(&s.stride * &s.count, m);
+            assert_eq!((&a * &s.x) % &m, b);
+            assert_eq!(s.x, x % &s.stride);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::random;
+
+    #[test]
+    fn linear_congruence_test() {
+        // exhaustive on small moduli against brute force
+        for m in 1..=40u32 {
+            for a in 0..m {
+                for b in 0..m {
+                    let solutions: std::vec::Vec<u32> = (0..m).filter(|x| a * x % m == b).collect();
+                    let result = linear_congruence(&a, &b, &m);
+                    match solutions.first() {
+                        None => assert_eq!(result, None),
+                        Some(&x) => {
+                            let s = result.unwrap();
+                            assert_eq!((s.x, s.count), (x, solutions.len() as u32));
+                            assert_eq!(s.stride * s.count, m);
+                            assert!(solutions
+                                .iter()
+                                .enumerate()
+                                .all(|(i, &y)| y == s.x + i as u32 * s.stride));
+                        }
+                    }
+                    // unreduced inputs
+                    assert_eq!(linear_congruence(&(a + 2 * m), &(b + m), &m), result);
+                }
+            }
+        }
+
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..10 {
+                    let (a, x, m) = (random::<$T>(), random::<$T>(), random::<$T>().max(1));
+                    let b = a.mulm(x, &m);
+                    let s = linear_congruence(&a, &b, &m).unwrap();
+                    assert_eq!(a.mulm(s.x, &m), b);
+                    assert_eq!(s.x, x % m % s.stride);
+                    assert_eq!(s.stride.checked_mul(s.count), Some(m));
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+
+        let s = linear_congruence(&0u64, &0, &u64::MAX).unwrap();
+        assert_eq!((s.x, s.stride, s.count), (0, 1, u64::MAX));
+        assert_eq!(linear_congruence(&0u64, &1, &u64::MAX), None);
+        assert!(linear_congruence(&7u8, &3, &1).unwrap().is_unique());
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_modulus_test() {
+        linear_congruence(&1u32, &1, &0);
+    }
+}
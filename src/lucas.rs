@@ -0,0 +1,349 @@
+//! Lucas sequences and the Fibonacci numbers modulo an integer
+
+use crate::prim::gcd;
+use crate::root::prime_powers;
+use crate::{ModularInteger, MontgomeryInt, VanillaInt};
+
+/// Integer types supported by [lucasm()] and [fibm()]
+pub trait LucasSequence: Sized {
+    /// Calculate the Lucas sequences `(U_n(P, Q), V_n(P, Q))` modulo m, see [lucasm()] for details
+    fn lucasm(p: &Self, q: &Self, n: &Self, m: &Self) -> (Self, Self);
+
+    /// Calculate the n-th Fibonacci number modulo m, see [fibm()] for details
+    fn fibm(n: &Self, m: &Self) -> Self;
+}
+
+/// Calculate the n-th terms of the Lucas sequences `(U_n(P, Q) mod m, V_n(P, Q) mod m)`,
+/// which are defined by `U_0 = 0, U_1 = 1, V_0 = 2, V_1 = P` and the recurrence
+/// `X_{k+1} = P·X_k - Q·X_{k-1}`.
+///
+/// The terms are calculated by a binary ladder over `(U_k, U_{k+1})` with the doubling
+/// formulas `U_{2k} = U_k·(2U_{k+1} - P·U_k)` and `U_{2k+1} = U_{k+1}² - Q·U_k²`, and
+/// finally `V_n = 2U_{n+1} - P·U_n`. Since there is no division by 2, any modulus is
+/// supported. The arithmetics are performed in [MontgomeryInt] for odd moduli.
+///
+/// The parameters P and Q are taken modulo m, so a negative Q should be passed as `m - |Q|`.
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::lucasm;
+///
+/// // P = 1, Q = -1 gives the Fibonacci numbers and the Lucas numbers
+/// let m = 1000u32;
+/// assert_eq!(lucasm(&1, &(m - 1), &10, &m), (55, 123));
+/// // P = 3, Q = 2 gives U_n = 2^n - 1 and V_n = 2^n + 1
+/// assert_eq!(lucasm(&3u64, &2, &20, &1_000_000_007), ((1 << 20) - 1, (1 << 20) + 1));
+/// ```
+#[inline]
+pub fn lucasm<T: LucasSequence>(p: &T, q: &T, n: &T, m: &T) -> (T, T) {
+    T::lucasm(p, q, n, m)
+}
+
+/// Calculate the n-th Fibonacci number `F_n mod m`, where `F_0 = 0` and `F_1 = 1`.
+///
+/// The Fibonacci numbers are the Lucas sequence `U_n(1, -1)`, so they are calculated by the
+/// doubling formulas `F_{2k} = F_k·(2F_{k+1} - F_k)` and `F_{2k+1} = F_{k+1}² + F_k²`
+/// (see [lucasm()]) with modular multiplications, which don't overflow for any modulus.
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::fibm;
+///
+/// assert_eq!(fibm(&10u32, &1000), 55);
+/// assert_eq!(fibm(&100u64, &u64::MAX), (354224848179261915075u128 % u64::MAX as u128) as u64);
+/// ```
+#[inline]
+pub fn fibm<T: LucasSequence>(n: &T, m: &T) -> T {
+    T::fibm(n, m)
+}
+
+/// Calculate the Pisano period `π(m)`, i.e. the period of the Fibonacci numbers modulo m.
+///
+/// The period is the least common multiple of `π(p^k)` for the prime power factors of m.
+/// For a prime `p ≠ 2, 5`, `π(p)` divides `p - 1` if `p = ±1 (mod 5)` and `2(p + 1)` otherwise,
+/// and `π(p^k)` divides `p^(k-1)·π(p)`, so the period is found by removing the prime factors
+/// from these multiples while `F_d = 0` and `F_{d+1} = 1` still hold. The result always
+/// satisfies `π(m) <= 6m`.
+///
+/// The factorizations of m and of `p ± 1` are done by trial division and Pollard's rho
+/// algorithm, which takes at most a few milliseconds for any u64.
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::pisano_period;
+///
+/// assert_eq!(pisano_period(10), 60);
+/// assert_eq!(pisano_period(1_000_000_007), 2_000_000_016);
+/// ```
+pub fn pisano_period(m: u64) -> u128 {
+    assert!(m > 0, "the modulus should not be zero");
+
+    // check whether F_d = 0 and F_{d+1} = 1 modulo pk
+    let is_period = |d: u128, pk: u128| fibm(&d, &pk) == 0 && fibm(&(d + 1), &pk) == 1 % pk;
+
+    let mut period = 1u128;
+    for (p, k) in prime_powers(m as u128) {
+        let pp = match p {
+            2 => 3,
+            5 => 20,
+            _ => {
+                let mut d = if p % 5 == 1 || p % 5 == 4 {
+                    p - 1
+                } else {
+                    2 * (p + 1)
+                };
+                for (q, _) in prime_powers(d) {
+                    while d % q == 0 && is_period(d / q, p) {
+                        d /= q;
+                    }
+                }
+                d
+            }
+        };
+
+        // π(p^k) is pp * p^j for some j < k
+        let pk = p.pow(k);
+        let mut d = pp * (pk / p); // pp * pk can overflow for p > 2^63
+        while d % p == 0 && is_period(d / p, pk) {
+            d /= p;
+        }
+
+        period = period / gcd(period, d) * d;
+    }
+    period
+}
+
+macro_rules! impl_lucas_for {
+    ($T:ty, $m:ident) => {
+        mod $m {
+            use super::*;
+
+            fn lucas_ladder<M: ModularInteger<Base = $T> + Clone>(p: M, q: M, n: $T) -> ($T, $T) {
+                let (mut uk, mut uk1) = (p.convert(0), p.convert(1));
+                for i in (0..<$T>::BITS - n.leading_zeros()).rev() {
+                    let u2k = uk.clone() * (uk1.clone().double() - p.clone() * uk.clone());
+                    let u2k1 = uk1.clone().square() - q.clone() * uk.clone().square();
+                    if (n >> i) & 1 == 0 {
+                        uk = u2k;
+                        uk1 = u2k1;
+                    } else {
+                        let qk = q.clone() * uk;
+                        uk = u2k1;
+                        uk1 = uk1.clone() * (p.clone() * uk1 - qk.double());
+                    }
+                }
+                let vk = uk1.double() - p * uk.clone();
+                (uk.residue(), vk.residue())
+            }
+
+            impl LucasSequence for $T {
+                fn lucasm(p: &$T, q: &$T, n: &$T, m: &$T) -> ($T, $T) {
+                    assert!(*m > 0, "the modulus should not be zero");
+                    if m & 1 == 1 {
+                        let p = MontgomeryInt::<$T>::new(*p, m);
+                        let q = p.convert(*q);
+                        lucas_ladder(p, q, *n)
+                    } else {
+                        let p = VanillaInt::<$T>::new(*p, m);
+                        let q = p.convert(*q);
+                        lucas_ladder(p, q, *n)
+                    }
+                }
+
+                #[inline]
+                fn fibm(n: &$T, m: &$T) -> $T {
+                    assert!(*m > 0, "the modulus should not be zero");
+                    Self::lucasm(&1, &(m - 1), n, m).0
+                }
+            }
+        }
+    };
+}
+impl_lucas_for!(u8, u8_impl);
+impl_lucas_for!(u16, u16_impl);
+impl_lucas_for!(u32, u32_impl);
+impl_lucas_for!(u64, u64_impl);
+impl_lucas_for!(u128, u128_impl);
+impl_lucas_for!(usize, usize_impl);
+
+#[cfg(feature = "num-bigint")]
+mod _num_bigint {
+    use super::*;
+    use crate::{ModularCoreOps, ModularUnaryOps};
+    use num_bigint::BigUint;
+    use num_traits::{One, Zero};
+
+    impl LucasSequence for BigUint {
+        fn lucasm(p: &BigUint, q: &BigUint, n: &BigUint, m: &BigUint) -> (BigUint, BigUint) {
+            assert!(!m.is_zero(), "the modulus should not be zero");
+            let (p, q) = (p % m, q % m);
+            let (mut uk, mut uk1) = (BigUint::zero(), BigUint::one() % m);
+            for i in (0..n.bits()).rev() {
+                let u2k = (&uk).mulm((&uk1).dblm(m).subm((&p).mulm(&uk, m), m), m);
+                let u2k1 = (&uk1).sqm(m).subm((&q).mulm((&uk).sqm(m), m), m);
+                if n.bit(i) {
+                    let qk = (&q).mulm(&uk, m).dblm(m);
+                    uk1 = (&uk1).mulm((&p).mulm(&uk1, m).subm(qk, m), m);
+                    uk = u2k1;
+                } else {
+                    uk = u2k;
+                    uk1 = u2k1;
+                }
+            }
+            let vk = uk1.dblm(m).subm((&p).mulm(&uk, m), m);
+            (uk, vk)
+        }
+
+        #[inline]
+        fn fibm(n: &BigUint, m: &BigUint) -> BigUint {
+            assert!(!m.is_zero(), "the modulus should not be zero");
+            Self::lucasm(&BigUint::one(), &(m - 1u8), n, m).0
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use rand::random;
+
+        #[test]
+        fn biguint_lucas_test() {
+            for _ in 0..10 {
+                let (p, q, n) = (random::<u64>(), random::<u64>(), random::<u64>());
+                let m = random::<u64>().max(1);
+                let (u, v) = lucasm(&p, &q, &n, &m);
+                let big = |x: u64| BigUint::from(x);
+                assert_eq!(lucasm(&big(p), &big(q), &big(n), &big(m)), (big(u), big(v)));
+            }
+
+            // U_n(3, 2) = 2^n - 1 modulo a large prime
+            let m = (BigUint::one() << 255u8) - 19u8;
+            let n = BigUint::from(300u32);
+            let (u, v) = lucasm(&BigUint::from(3u8), &BigUint::from(2u8), &n, &m);
+            assert_eq!(u, ((BigUint::one() << 300u32) - 1u8) % &m);
+            assert_eq!(v, ((BigUint::one() << 300u32) + 1u8) % &m);
+
+            let n = random::<u64>();
+            assert_eq!(
+                fibm(&BigUint::from(n), &BigUint::from(1_000_000_007u64)),
+                BigUint::from(fibm(&n, &1_000_000_007))
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModularCoreOps, ModularUnaryOps};
+    use rand::random;
+
+    #[test]
+    fn against_recurrence_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..10 {
+                    let m = random::<$T>().max(1);
+                    let (p, q) = (random::<$T>(), random::<$T>());
+                    let (mut u, mut u1) = (0 % m, 1 % m);
+                    let (mut v, mut v1) = (2 % m, p % m);
+                    for n in 0..200 as $T {
+                        assert_eq!(lucasm(&p, &q, &n, &m), (u, v), "P={}, Q={}, n={}, m={}", p, q, n, m);
+                        let u2 = p.mulm(u1, &m).subm(q.mulm(u, &m), &m);
+                        let v2 = p.mulm(v1, &m).subm(q.mulm(v, &m), &m);
+                        u = u1;
+                        u1 = u2;
+                        v = v1;
+                        v1 = v2;
+                    }
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+    }
+
+    #[test]
+    fn known_values_test() {
+        // Fibonacci and Lucas numbers with m = 2^64 - 59 (a prime) and an even modulus
+        for &m in [18446744073709551557u64, 1 << 40].iter() {
+            let qm = 1u64.negm(&m);
+            assert_eq!(
+                lucasm(&1, &qm, &90, &m),
+                (2880067194370816120 % m, 6440026026380244498 % m)
+            );
+        }
+
+        // U_n(P, Q) = 0 mod p with n = p - (D|p) for a prime p and D = P^2 - 4Q
+        let p = 1_000_000_007u64;
+        for &(pp, q) in [(1u64, p - 1), (3, 1), (5, 3)].iter() {
+            let d = pp.mulm(pp, &p).subm(4u64.mulm(q, &p), &p);
+            let n = match crate::ModularSymbols::legendre(&d, &p) {
+                1 => p - 1,
+                -1 => p + 1,
+                _ => unreachable!(),
+            };
+            assert_eq!(lucasm(&pp, &q, &n, &p).0, 0);
+            // V_p = P mod p
+            assert_eq!(lucasm(&pp, &q, &p, &p).1, pp);
+        }
+
+        assert_eq!(lucasm(&5u32, &7, &100, &1), (0, 0));
+        assert_eq!(lucasm(&5u8, &7, &0, &2), (0, 0));
+    }
+
+    #[test]
+    fn fibm_test() {
+        let (mut f0, mut f1) = (0u128, 1u128);
+        for n in 0..180u32 {
+            for &m in [1u128, 2, 1000, 1 << 100, u128::MAX].iter() {
+                assert_eq!(fibm(&(n as u128), &m), f0 % m);
+            }
+            assert_eq!(
+                fibm(&(n as u64), &1_000_000_007),
+                (f0 % 1_000_000_007) as u64
+            );
+            let f2 = f0 + f1;
+            f0 = f1;
+            f1 = f2;
+        }
+        assert_eq!(fibm(&1_000_000_008u64, &1_000_000_007), 0); // p = 2 (mod 5)
+        assert_eq!(fibm(&255u8, &255), 70);
+    }
+
+    #[test]
+    fn pisano_period_test() {
+        // brute force the periods for small moduli
+        for m in 1..2000u64 {
+            let (mut f0, mut f1, mut period) = (0u64, 1 % m, 0u128);
+            loop {
+                let f2 = (f0 + f1) % m;
+                f0 = f1;
+                f1 = f2;
+                period += 1;
+                if f0 == 0 && f1 == 1 % m {
+                    break;
+                }
+            }
+            assert_eq!(pisano_period(m), period, "m = {}", m);
+        }
+
+        assert_eq!(pisano_period(5u64.pow(20)), 20 * 5u64.pow(19) as u128);
+        assert_eq!(pisano_period(2u64.pow(63)), 3 << 62);
+        let p = 1_000_000_009; // p = 4 (mod 5)
+        assert_eq!(pisano_period(p), 333333336);
+        assert_eq!(fibm(&333333336u64, &p), 0);
+
+        // primes above 2^63 where π(p) = 2(p + 1) is close to 2^66
+        assert_eq!(pisano_period(18446744073709551437), 36893488147419102876);
+        assert_eq!(pisano_period(18446744073709551427), 36893488147419102856);
+    }
+}
@@ -0,0 +1,790 @@
+//! Dense matrices over the modulo ring ℤ/mℤ
+
+use crate::{Montgomery, Reducer};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Errors of the matrix operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixError {
+    /// The dimensions of the operands (rows, columns) are not compatible
+    DimensionMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    /// The operation is only defined for square matrices
+    NotSquare,
+    /// The operands are defined with different moduli
+    ModulusMismatch,
+    /// The matrix is not invertible
+    Singular,
+    /// A nonzero entry without modular inverse is met during the elimination, which
+    /// happens only if the modulus is not a prime
+    ZeroDivisor,
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatrixError::DimensionMismatch { expected, found } => write!(
+                f,
+                "dimension mismatch: expected {}x{}, found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            MatrixError::NotSquare => f.write_str("the matrix is not square"),
+            MatrixError::ModulusMismatch => f.write_str("the moduli of the matrices are different"),
+            MatrixError::Singular => f.write_str("the matrix is singular"),
+            MatrixError::ZeroDivisor => f.write_str("a zero divisor is met in the elimination"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MatrixError {}
+
+/// A dense matrix with entries in the modulo ring ℤ/mℤ.
+///
+/// The entries are stored in row-major order in the reduced form of the reducer `R`,
+/// which is shared by all the entries. By default the [Montgomery] reducer is used,
+/// so that the multiplications don't involve divisions. Use [crate::Vanilla] as the
+/// reducer for even moduli.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::ModMatrix;
+///
+/// // the Fibonacci numbers by matrix exponentiation
+/// let m = 1_000_000_007u64;
+/// let fib = ModMatrix::<u64>::new(2, 2, vec![1, 1, 1, 0], &m).unwrap();
+/// assert_eq!(fib.pow(90).unwrap().get(0, 1), 2880067194370816120 % m);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModMatrix<T, R = Montgomery<T>> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+    r: R,
+}
+
+/// The solutions of a linear system `A·x = b`, which are `particular + Σ cᵢ·kernel[i]`
+/// for arbitrary coefficients `cᵢ`. The vectors are in normal form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinearSolution<T> {
+    /// A particular solution, where all the free variables are zero
+    pub particular: Vec<T>,
+    /// A basis of the null space of A, one vector for each free variable
+    pub kernel: Vec<Vec<T>>,
+}
+
+impl<T> LinearSolution<T> {
+    /// Whether the solution is unique
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        self.kernel.is_empty()
+    }
+}
+
+impl<T: Clone + PartialEq + From<u8>, R: Reducer<T> + Clone> ModMatrix<T, R> {
+    /// Create a matrix from the entries in row-major order. The entries don't need to be reduced.
+    pub fn new(rows: usize, cols: usize, data: Vec<T>, m: &T) -> Result<Self, MatrixError> {
+        if data.len() != rows * cols {
+            return Err(MatrixError::DimensionMismatch {
+                expected: (rows, cols),
+                found: (data.len() / cols.max(1), cols),
+            });
+        }
+        let r = R::new(m);
+        let data = data.into_iter().map(|v| r.transform(v)).collect();
+        Ok(Self {
+            rows,
+            cols,
+            data,
+            r,
+        })
+    }
+
+    /// Create a matrix with all entries being zero
+    pub fn zeros(rows: usize, cols: usize, m: &T) -> Self {
+        let r = R::new(m);
+        let zero = r.transform(T::from(0));
+        Self {
+            rows,
+            cols,
+            data: vec![zero; rows * cols],
+            r,
+        }
+    }
+
+    /// Create an identity matrix of size n
+    pub fn identity(n: usize, m: &T) -> Self {
+        let mut result = Self::zeros(n, n, m);
+        let one = result.r.transform(T::from(1));
+        for i in 0..n {
+            result.data[i * n + i] = one.clone();
+        }
+        result
+    }
+
+    /// Number of rows of the matrix
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns of the matrix
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The modulus of the entries
+    #[inline]
+    pub fn modulus(&self) -> T {
+        self.r.modulus()
+    }
+
+    /// Get the (normalized) entry at row i and column j
+    ///
+    /// # Panics
+    /// if the index is out of bound
+    #[inline]
+    pub fn get(&self, i: usize, j: usize) -> T {
+        assert!(i < self.rows && j < self.cols, "index out of bound");
+        self.r.residue(self.data[i * self.cols + j].clone())
+    }
+
+    /// Set the entry at row i and column j, the value doesn't need to be reduced
+    ///
+    /// # Panics
+    /// if the index is out of bound
+    #[inline]
+    pub fn set(&mut self, i: usize, j: usize, value: T) {
+        assert!(i < self.rows && j < self.cols, "index out of bound");
+        self.data[i * self.cols + j] = self.r.transform(value);
+    }
+
+    /// Get all the (normalized) entries in row-major order
+    pub fn to_vec(&self) -> Vec<T> {
+        self.data
+            .iter()
+            .map(|v| self.r.residue(v.clone()))
+            .collect()
+    }
+
+    #[inline]
+    fn check_modulus_eq(&self, rhs: &Self) -> Result<(), MatrixError> {
+        if self.r.modulus() != rhs.r.modulus() {
+            Err(MatrixError::ModulusMismatch)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Calculate self + rhs
+    pub fn add(&self, rhs: &Self) -> Result<Self, MatrixError> {
+        self.check_modulus_eq(rhs)?;
+        if (self.rows, self.cols) != (rhs.rows, rhs.cols) {
+            return Err(MatrixError::DimensionMismatch {
+                expected: (self.rows, self.cols),
+                found: (rhs.rows, rhs.cols),
+            });
+        }
+        let data = self
+            .data
+            .iter()
+            .zip(rhs.data.iter())
+            .map(|(a, b)| self.r.add(a, b))
+            .collect();
+        Ok(Self {
+            rows: self.rows,
+            cols: self.cols,
+            data,
+            r: self.r.clone(),
+        })
+    }
+
+    /// Calculate self * rhs
+    pub fn mul(&self, rhs: &Self) -> Result<Self, MatrixError> {
+        self.check_modulus_eq(rhs)?;
+        if self.cols != rhs.rows {
+            return Err(MatrixError::DimensionMismatch {
+                expected: (self.cols, rhs.cols),
+                found: (rhs.rows, rhs.cols),
+            });
+        }
+
+        let mut result = Self::zeros(self.rows, rhs.cols, &self.r.modulus());
+        for i in 0..self.rows {
+            let out = &mut result.data[i * rhs.cols..(i + 1) * rhs.cols];
+            for k in 0..self.cols {
+                let a = &self.data[i * self.cols + k];
+                let row = &rhs.data[k * rhs.cols..(k + 1) * rhs.cols];
+                for (o, b) in out.iter_mut().zip(row) {
+                    let prod = self.r.mul(a, b);
+                    self.r.add_in_place(o, &prod);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Calculate self ^ exp by binary exponentiation. Only square matrices are supported.
+    pub fn pow(&self, mut exp: u64) -> Result<Self, MatrixError> {
+        if self.rows != self.cols {
+            return Err(MatrixError::NotSquare);
+        }
+
+        let mut result = Self::identity(self.rows, &self.r.modulus());
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.mul(&base)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul(&base)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Transform self into the reduced row echelon form by Gauss-Jordan elimination, where
+    /// the pivots are only searched in the first `ncols` columns. Return the pivot columns
+    /// and the product of the pivots (negated for each row swap) in reduced form.
+    fn rref(&mut self, ncols: usize) -> Result<(Vec<usize>, T), MatrixError> {
+        let r = self.r.clone();
+        let cols = self.cols;
+        let mut det = r.transform(T::from(1));
+        let mut pivots = Vec::new();
+        for col in 0..ncols {
+            let row = pivots.len();
+            if row == self.rows {
+                break;
+            }
+
+            // find an invertible entry in the column as the pivot
+            let mut nonzero = false;
+            let mut found = None;
+            for i in row..self.rows {
+                let v = &self.data[i * cols + col];
+                if !r.is_zero(v) {
+                    nonzero = true;
+                    if let Some(inv) = r.inv(v.clone()) {
+                        found = Some((i, inv));
+                        break;
+                    }
+                }
+            }
+            let (pr, inv) = match found {
+                Some(v) => v,
+                None if nonzero => return Err(MatrixError::ZeroDivisor),
+                None => continue,
+            };
+            if pr != row {
+                for j in col..cols {
+                    self.data.swap(pr * cols + j, row * cols + j);
+                }
+                det = r.neg(det);
+            }
+            r.mul_in_place(&mut det, &self.data[row * cols + col]);
+
+            // normalize the pivot to one and eliminate the column in other rows
+            for v in &mut self.data[row * cols + col..(row + 1) * cols] {
+                r.mul_in_place(v, &inv);
+            }
+            for i in (0..self.rows).filter(|&i| i != row) {
+                let f = self.data[i * cols + col].clone();
+                if r.is_zero(&f) {
+                    continue;
+                }
+                for j in col..cols {
+                    let v = r.mul(&f, &self.data[row * cols + j]);
+                    r.sub_in_place(&mut self.data[i * cols + j], &v);
+                }
+            }
+            pivots.push(col);
+        }
+        Ok((pivots, det))
+    }
+
+    /// Calculate the determinant of the matrix by the fraction-free (Bareiss) elimination.
+    ///
+    /// The k-th step replaces the entries with the 2x2 minors against the pivot divided by
+    /// the previous pivot, `a_ij = (a_kk·a_ij - a_ik·a_kj) / a_(k-1)(k-1)`, and the division is
+    /// exact since the entries are the minors of the original matrix. So the pivots don't
+    /// need to be normalized, and the last pivot is the determinant. Only the pivots used
+    /// as the divisors are inverted, thus a composite modulus is supported as long as they
+    /// are invertible, otherwise [MatrixError::ZeroDivisor] is returned.
+    pub fn det(&self) -> Result<T, MatrixError> {
+        if self.rows != self.cols {
+            return Err(MatrixError::NotSquare);
+        }
+        let (n, r) = (self.rows, &self.r);
+        let mut a = self.data.clone();
+        let mut prev = r.transform(T::from(1));
+        let mut negate = false;
+        for k in 0..n {
+            // prefer an invertible pivot, so that the next division succeeds
+            let col = |i: &usize| &a[i * n + k];
+            let pr = (k..n)
+                .find(|i| r.inv(col(i).clone()).is_some())
+                .or_else(|| (k..n).find(|i| !r.is_zero(col(i))));
+            let pr = match pr {
+                Some(pr) => pr,
+                None => return Ok(T::from(0)),
+            };
+            if pr != k {
+                for j in k..n {
+                    a.swap(pr * n + j, k * n + j);
+                }
+                negate = !negate;
+            }
+
+            if k + 1 < n {
+                let prev_inv = r.inv(prev).ok_or(MatrixError::ZeroDivisor)?;
+                for i in k + 1..n {
+                    for j in k + 1..n {
+                        let v = r.sub(
+                            &r.mul(&a[k * n + k], &a[i * n + j]),
+                            &r.mul(&a[i * n + k], &a[k * n + j]),
+                        );
+                        a[i * n + j] = r.mul(&v, &prev_inv);
+                    }
+                }
+            }
+            prev = a[k * n + k].clone();
+        }
+        Ok(r.residue(if negate { r.neg(prev) } else { prev }))
+    }
+
+    /// Calculate the rank of the matrix by Gaussian elimination.
+    ///
+    /// The modulus is expected to be a prime, for a composite modulus the elimination
+    /// can fail with [MatrixError::ZeroDivisor].
+    pub fn rank(&self) -> Result<usize, MatrixError> {
+        Ok(self.clone().rref(self.cols)?.0.len())
+    }
+
+    /// Calculate the inverse of the matrix by Gauss-Jordan elimination, or return
+    /// [MatrixError::Singular] if the determinant is zero.
+    ///
+    /// The modulus is expected to be a prime, for a composite modulus the elimination
+    /// can fail with [MatrixError::ZeroDivisor].
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{MatrixError, ModMatrix};
+    ///
+    /// let m = 13u64;
+    /// let a = ModMatrix::<u64>::new(2, 2, vec![2, 3, 5, 7], &m).unwrap();
+    /// assert_eq!(a.det(), Ok(12)); // 2 * 7 - 3 * 5 = -1
+    /// let ai = a.inv().unwrap();
+    /// assert_eq!(a.mul(&ai).unwrap(), ModMatrix::identity(2, &m));
+    ///
+    /// let b = ModMatrix::<u64>::new(2, 2, vec![1, 2, 2, 4], &m).unwrap();
+    /// assert_eq!(b.rank(), Ok(1));
+    /// assert_eq!(b.inv(), Err(MatrixError::Singular));
+    /// ```
+    pub fn inv(&self) -> Result<Self, MatrixError> {
+        if self.rows != self.cols {
+            return Err(MatrixError::NotSquare);
+        }
+
+        // eliminate on [A | I]
+        let n = self.rows;
+        let one = self.r.transform(T::from(1));
+        let mut aug = Self::zeros(n, 2 * n, &self.r.modulus());
+        for i in 0..n {
+            aug.data[i * 2 * n..i * 2 * n + n].clone_from_slice(&self.data[i * n..(i + 1) * n]);
+            aug.data[i * 2 * n + n + i] = one.clone();
+        }
+        if aug.rref(n)?.0.len() < n {
+            return Err(MatrixError::Singular);
+        }
+
+        let mut data = Vec::with_capacity(n * n);
+        for i in 0..n {
+            data.extend_from_slice(&aug.data[i * 2 * n + n..(i + 1) * 2 * n]);
+        }
+        Ok(Self {
+            rows: n,
+            cols: n,
+            data,
+            r: self.r.clone(),
+        })
+    }
+
+    /// Solve the linear system `self·x = b` by Gauss-Jordan elimination. The entries of b
+    /// don't need to be reduced. Return [None] if the system has no solution, otherwise
+    /// return all the solutions parameterized by the free variables.
+    ///
+    /// The modulus is expected to be a prime, for a composite modulus the elimination
+    /// can fail with [MatrixError::ZeroDivisor].
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::ModMatrix;
+    ///
+    /// // x + 2y + 3z = 4 and 2x + 4y + 5z = 6 modulo 7
+    /// let a = ModMatrix::<u64>::new(2, 3, vec![1, 2, 3, 2, 4, 5], &7).unwrap();
+    /// let sol = a.solve(&[4, 6]).unwrap().unwrap();
+    /// assert_eq!(sol.particular, [5, 0, 2]);
+    /// assert_eq!(sol.kernel, [vec![5, 1, 0]]); // y is free, x = -2y
+    ///
+    /// let b = ModMatrix::<u64>::new(2, 2, vec![1, 1, 2, 2], &7).unwrap();
+    /// assert_eq!(b.solve(&[1, 3]), Ok(None));
+    /// ```
+    pub fn solve(&self, b: &[T]) -> Result<Option<LinearSolution<T>>, MatrixError> {
+        if b.len() != self.rows {
+            return Err(MatrixError::DimensionMismatch {
+                expected: (self.rows, 1),
+                found: (b.len(), 1),
+            });
+        }
+
+        // eliminate on [A | b]
+        let (n, w) = (self.cols, self.cols + 1);
+        let mut aug = Self::zeros(self.rows, w, &self.r.modulus());
+        for (i, bi) in b.iter().enumerate() {
+            aug.data[i * w..i * w + n].clone_from_slice(&self.data[i * n..(i + 1) * n]);
+            aug.data[i * w + n] = self.r.transform(bi.clone());
+        }
+        let (pivots, _) = aug.rref(n)?;
+
+        // the rows without pivots should have zero on the right side
+        if (pivots.len()..self.rows).any(|i| !self.r.is_zero(&aug.data[i * w + n])) {
+            return Ok(None);
+        }
+
+        let mut particular = vec![T::from(0); n];
+        for (i, &c) in pivots.iter().enumerate() {
+            particular[c] = self.r.residue(aug.data[i * w + n].clone());
+        }
+        let mut kernel = Vec::new();
+        let mut pivot_iter = pivots.iter().peekable();
+        for f in 0..n {
+            if pivot_iter.peek() == Some(&&f) {
+                pivot_iter.next();
+                continue;
+            }
+            let mut v = vec![T::from(0); n];
+            v[f] = T::from(1);
+            for (i, &c) in pivots.iter().enumerate() {
+                v[c] = self.r.residue(self.r.neg(aug.data[i * w + f].clone()));
+            }
+            kernel.push(v);
+        }
+        Ok(Some(LinearSolution { particular, kernel }))
+    }
+}
+
+impl<T: PartialEq, R: Reducer<T>> PartialEq for ModMatrix<T, R> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.rows == other.rows
+            && self.cols == other.cols
+            && self.r.modulus() == other.r.modulus()
+            && self.data == other.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModularCoreOps, Vanilla};
+    use rand::random;
+
+    fn random_matrix(rows: usize, cols: usize, m: u64) -> ModMatrix<u64> {
+        let data = (0..rows * cols).map(|_| random()).collect();
+        ModMatrix::new(rows, cols, data, &m).unwrap()
+    }
+
+    #[test]
+    fn construction_test() {
+        let m = 7u64;
+        let a = ModMatrix::<u64>::new(2, 3, vec![1, 2, 3, 14, 15, 16], &m).unwrap();
+        assert_eq!((a.rows(), a.cols(), a.modulus()), (2, 3, 7));
+        assert_eq!(a.to_vec(), [1, 2, 3, 0, 1, 2]);
+        assert_eq!(a.get(1, 2), 2);
+
+        let mut b = a.clone();
+        b.set(1, 2, 20);
+        assert_eq!(b.get(1, 2), 6);
+
+        assert_eq!(
+            ModMatrix::<u64>::new(2, 3, vec![1, 2, 3], &m),
+            Err(MatrixError::DimensionMismatch {
+                expected: (2, 3),
+                found: (1, 3)
+            })
+        );
+        assert_eq!(ModMatrix::<u64>::zeros(2, 2, &m).to_vec(), [0; 4]);
+        assert_eq!(ModMatrix::<u64>::identity(2, &m).to_vec(), [1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn arithmetic_test() {
+        let m = random::<u64>() | 1;
+        let a = random_matrix(3, 4, m);
+        let b = random_matrix(4, 2, m);
+        let c = random_matrix(2, 5, m);
+
+        // associativity and identity
+        let ab_c = a.mul(&b).unwrap().mul(&c).unwrap();
+        let a_bc = a.mul(&b.mul(&c).unwrap()).unwrap();
+        assert_eq!(ab_c, a_bc);
+        assert_eq!(ModMatrix::identity(3, &m).mul(&a).unwrap(), a);
+        assert_eq!(a.mul(&ModMatrix::identity(4, &m)).unwrap(), a);
+        assert_eq!(a.add(&ModMatrix::zeros(3, 4, &m)).unwrap(), a);
+
+        // against the scalar operations
+        let ab = a.mul(&b).unwrap();
+        for i in 0..3 {
+            for j in 0..2 {
+                let v = (0..4).fold(0u64, |acc, k| {
+                    acc.addm(a.get(i, k).mulm(b.get(k, j), &m), &m)
+                });
+                assert_eq!(ab.get(i, j), v);
+            }
+        }
+        let a2 = random_matrix(3, 4, m);
+        let sum = a.add(&a2).unwrap();
+        for (i, v) in sum.to_vec().into_iter().enumerate() {
+            assert_eq!(v, a.to_vec()[i].addm(a2.to_vec()[i], &m));
+        }
+
+        // errors
+        assert_eq!(
+            a.mul(&c),
+            Err(MatrixError::DimensionMismatch {
+                expected: (4, 5),
+                found: (2, 5)
+            })
+        );
+        assert!(matches!(
+            a.add(&b),
+            Err(MatrixError::DimensionMismatch { .. })
+        ));
+        assert_eq!(a.pow(2), Err(MatrixError::NotSquare));
+        assert_eq!(
+            a.add(&random_matrix(3, 4, m ^ 2)),
+            Err(MatrixError::ModulusMismatch)
+        );
+    }
+
+    #[test]
+    fn pow_test() {
+        let m = random::<u64>() | 1;
+        let a = random_matrix(4, 4, m);
+        let mut expected = ModMatrix::identity(4, &m);
+        for e in 0..20u64 {
+            assert_eq!(a.pow(e).unwrap(), expected);
+            expected = expected.mul(&a).unwrap();
+        }
+
+        // even modulus with the vanilla reducer
+        let m = 1u32 << 20;
+        let a = ModMatrix::<u32, Vanilla<u32>>::new(2, 2, vec![3, 1, 1, 0], &m).unwrap();
+        let mut expected = ModMatrix::identity(2, &m);
+        for e in 0..20u64 {
+            assert_eq!(a.pow(e).unwrap(), expected);
+            expected = expected.mul(&a).unwrap();
+        }
+    }
+
+    #[test]
+    fn linear_recurrence_test() {
+        // a(n) = 2a(n-1) + 3a(n-2) + 5a(n-3) + 7a(n-4) + 11a(n-5)
+        let coeffs = [2u64, 3, 5, 7, 11];
+        let init = [1u64, 1, 2, 3, 5]; // a(0) ~ a(4)
+        let m = 998244353u64;
+        let n = 1000usize;
+
+        // direct iteration
+        let mut seq = init.to_vec();
+        while seq.len() <= n {
+            let l = seq.len();
+            let next = coeffs
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (i, &c)| acc.addm(c.mulm(seq[l - 1 - i], &m), &m));
+            seq.push(next);
+        }
+
+        // companion matrix
+        let mut data = vec![0u64; 25];
+        data[..5].copy_from_slice(&coeffs);
+        for i in 1..5 {
+            data[i * 5 + i - 1] = 1;
+        }
+        let comp = ModMatrix::<u64>::new(5, 5, data, &m).unwrap();
+        let state = ModMatrix::<u64>::new(5, 1, init.iter().rev().cloned().collect(), &m).unwrap();
+        for &k in [0usize, 1, 17, 500, n - 4].iter() {
+            let v = comp.pow(k as u64).unwrap().mul(&state).unwrap();
+            assert_eq!(v.get(0, 0), seq[k + 4]);
+            assert_eq!(v.get(4, 0), seq[k]);
+        }
+    }
+
+    #[test]
+    fn det_inv_test() {
+        // against the cofactor expansion of 3x3 matrices
+        let m = 998244353u64;
+        for _ in 0..10 {
+            let a = random_matrix(3, 3, m);
+            let e = |i, j| a.get(i, j);
+            let minor = |i0, i1, j0, j1| {
+                e(i0, j0)
+                    .mulm(e(i1, j1), &m)
+                    .subm(e(i0, j1).mulm(e(i1, j0), &m), &m)
+            };
+            let det = e(0, 0)
+                .mulm(minor(1, 2, 1, 2), &m)
+                .subm(e(0, 1).mulm(minor(1, 2, 0, 2), &m), &m)
+                .addm(e(0, 2).mulm(minor(1, 2, 0, 1), &m), &m);
+            assert_eq!(a.det(), Ok(det));
+        }
+
+        for _ in 0..10 {
+            let a = random_matrix(6, 6, m);
+            let b = random_matrix(6, 6, m);
+            let ab = a.mul(&b).unwrap();
+            assert_eq!(
+                ab.det().unwrap(),
+                a.det().unwrap().mulm(b.det().unwrap(), &m)
+            );
+
+            // random matrices are invertible with high probability
+            let ai = a.inv().unwrap();
+            assert_eq!(a.mul(&ai).unwrap(), ModMatrix::identity(6, &m));
+            assert_eq!(ai.mul(&a).unwrap(), ModMatrix::identity(6, &m));
+            assert_eq!(ai.det().unwrap().mulm(a.det().unwrap(), &m), 1);
+            assert_eq!(a.rank(), Ok(6));
+        }
+
+        // low rank matrices
+        let a = random_matrix(5, 2, m).mul(&random_matrix(2, 4, m)).unwrap();
+        assert_eq!(a.rank(), Ok(2));
+        let sq = random_matrix(4, 3, m).mul(&random_matrix(3, 4, m)).unwrap();
+        assert_eq!(sq.rank(), Ok(3));
+        assert_eq!(sq.det(), Ok(0));
+        assert_eq!(sq.inv(), Err(MatrixError::Singular));
+        assert_eq!(ModMatrix::<u64>::zeros(3, 2, &m).rank(), Ok(0));
+        assert_eq!(a.det(), Err(MatrixError::NotSquare));
+        assert_eq!(a.inv(), Err(MatrixError::NotSquare));
+
+        // the pivots need swaps
+        let p = ModMatrix::<u64>::new(3, 3, vec![0, 1, 0, 0, 0, 1, 1, 0, 0], &m).unwrap();
+        assert_eq!(p.det(), Ok(1));
+        assert_eq!(p.inv().unwrap(), p.pow(2).unwrap());
+
+        // composite moduli
+        let m = 12u32;
+        let a = ModMatrix::<u32, Vanilla<u32>>::new(2, 2, vec![1, 2, 3, 5], &m).unwrap();
+        assert_eq!(a.det(), Ok(11));
+        assert_eq!(
+            a.mul(&a.inv().unwrap()).unwrap(),
+            ModMatrix::identity(2, &m)
+        );
+        let b = ModMatrix::<u32, Vanilla<u32>>::new(2, 2, vec![2, 0, 0, 1], &m).unwrap();
+        assert_eq!(b.det(), Ok(2)); // the last pivot is not inverted
+        assert_eq!(b.inv(), Err(MatrixError::ZeroDivisor));
+        let c = ModMatrix::<u32, Vanilla<u32>>::new(3, 3, vec![2, 0, 0, 0, 2, 0, 0, 0, 1], &m);
+        assert_eq!(c.unwrap().det(), Err(MatrixError::ZeroDivisor));
+
+        // against the cofactor expansion with the composite modulus 2^32 - 1 (3 * 5 * 17 * ...)
+        let m = u32::MAX;
+        for _ in 0..10 {
+            let a = ModMatrix::<u32, Vanilla<u32>>::new(
+                3,
+                3,
+                (0..9).map(|_| random::<u32>() | 1).collect(),
+                &m,
+            )
+            .unwrap();
+            let e = |i, j| a.get(i, j);
+            let minor = |i0, i1, j0, j1| {
+                e(i0, j0)
+                    .mulm(e(i1, j1), &m)
+                    .subm(e(i0, j1).mulm(e(i1, j0), &m), &m)
+            };
+            let det = e(0, 0)
+                .mulm(minor(1, 2, 1, 2), &m)
+                .subm(e(0, 1).mulm(minor(1, 2, 0, 2), &m), &m)
+                .addm(e(0, 2).mulm(minor(1, 2, 0, 1), &m), &m);
+            match a.det() {
+                Ok(d) => assert_eq!(d, det),
+                Err(err) => assert_eq!(err, MatrixError::ZeroDivisor),
+            }
+        }
+    }
+
+    #[test]
+    fn solve_test() {
+        let m = 998244353u64;
+        let check = |a: &ModMatrix<u64>, x: &[u64], b: &[u64]| {
+            let xm = ModMatrix::<u64>::new(x.len(), 1, x.to_vec(), &m).unwrap();
+            assert_eq!(a.mul(&xm).unwrap().to_vec(), b);
+        };
+
+        // unique solutions of random square systems
+        for _ in 0..10 {
+            let a = random_matrix(5, 5, m);
+            let x: Vec<u64> = (0..5).map(|_| random::<u64>() % m).collect();
+            let xm = ModMatrix::<u64>::new(5, 1, x.clone(), &m).unwrap();
+            let b = a.mul(&xm).unwrap().to_vec();
+            let sol = a.solve(&b).unwrap().unwrap();
+            assert!(sol.is_unique());
+            assert_eq!(sol.particular, x);
+        }
+
+        // underdetermined systems
+        for _ in 0..10 {
+            let a = random_matrix(3, 2, m).mul(&random_matrix(2, 5, m)).unwrap();
+            let x: Vec<u64> = (0..5).map(|_| random::<u64>() % m).collect();
+            let xm = ModMatrix::<u64>::new(5, 1, x, &m).unwrap();
+            let b = a.mul(&xm).unwrap().to_vec();
+            let sol = a.solve(&b).unwrap().unwrap();
+            assert_eq!(sol.kernel.len(), 3);
+            check(&a, &sol.particular, &b);
+            for v in sol.kernel.iter() {
+                check(&a, v, &[0, 0, 0]);
+            }
+
+            // a combination of the solutions
+            let c = random::<u64>() % m;
+            let y: Vec<u64> = (0..5)
+                .map(|j| sol.particular[j].addm(c.mulm(sol.kernel[1][j], &m), &m))
+                .collect();
+            check(&a, &y, &b);
+
+            // the right side out of the column space
+            let mut b2 = b.clone();
+            b2[0] = b2[0].addm(1, &m);
+            assert_eq!(a.solve(&b2), Ok(None));
+        }
+
+        // overdetermined and degenerated systems
+        let a = ModMatrix::<u64>::new(3, 2, vec![1, 1, 1, 2, 1, 3], &m).unwrap();
+        let sol = a.solve(&[2, 3, 4]).unwrap().unwrap();
+        assert_eq!(
+            sol,
+            LinearSolution {
+                particular: vec![1, 1],
+                kernel: vec![]
+            }
+        );
+        assert_eq!(a.solve(&[2, 3, 5]), Ok(None));
+        let z = ModMatrix::<u64>::zeros(2, 2, &m);
+        assert_eq!(z.solve(&[0, 0]).unwrap().unwrap().kernel, [[1, 0], [0, 1]]);
+        assert_eq!(z.solve(&[0, 1]), Ok(None));
+        assert_eq!(
+            a.solve(&[1, 2]),
+            Err(MatrixError::DimensionMismatch {
+                expected: (3, 1),
+                found: (2, 1)
+            })
+        );
+    }
+}
@@ -1,11 +1,26 @@
 use crate::reduced::impl_reduced_binary_pow;
-use crate::{udouble, umax, ModularUnaryOps, Reducer};
+use crate::{udouble, umax, ModularUnaryOps, Reducer, Vanilla};
 
 // FIXME: use unchecked operators to speed up calculation (after https://github.com/rust-lang/rust/issues/85122)
 /// A modular reducer for (pseudo) Mersenne numbers `2^P - K` as modulus. It supports `P` up to 127 and `K < 2^(P-1)`
 ///
 /// The `P` is limited to 127 so that it's not necessary to check overflow. This limit won't be a problem for any
 /// Mersenne primes within the range of [umax] (i.e. [u128]).
+///
+/// For `P < 64` the products are reduced within a single [umax] word, and for `K = 1`
+/// the reduction is a fixed sequence of shifts and additions without loops.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{FixedMersenneInt, ModularInteger};
+///
+/// // polynomial hashing modulo the Mersenne prime 2^61 - 1
+/// const P: u128 = (1 << 61) - 1;
+/// let base = FixedMersenneInt::<61, 1>::new(131, &P);
+/// let hash = b"hello".iter().fold(base.convert(0), |h, &c| h * base + base.convert(c as u128));
+/// let expected = b"hello".iter().fold(0, |h, &c| (h * 131 + c as u128) % P);
+/// assert_eq!(hash.residue(), expected);
+/// ```
 #[derive(Debug, Clone, Copy)]
 pub struct FixedMersenne<const P: u8, const K: umax>();
 
@@ -33,6 +48,21 @@ impl<const P: u8, const K: umax> FixedMersenne<P, K> {
         }
     }
 
+    // Calculate v % Self::MODULUS, where v < 2^(2P) is a product of two reduced integers.
+    // For the Mersenne numbers (K = 1), two folds are always enough so the loop is avoided.
+    const fn reduce_product(v: umax) -> umax {
+        if K != 1 {
+            return Self::reduce_single(v);
+        }
+        let t = (v & Self::BITMASK) + (v >> P); // < 2^(P+1)
+        let t = (t & Self::BITMASK) + (t >> P); // <= 2^P
+        if t >= Self::MODULUS {
+            t - Self::MODULUS
+        } else {
+            t
+        }
+    }
+
     // Calculate v % Self::MODULUS, where v is a udouble integer
     fn reduce_double(v: udouble) -> umax {
         // reduce modulo
@@ -130,7 +160,7 @@ impl<const P: u8, const K: umax> Reducer<umax> for FixedMersenne<P, K> {
     #[inline]
     fn mul(&self, lhs: &umax, rhs: &umax) -> umax {
         if (P as u32) < (umax::BITS / 2) {
-            Self::reduce_single(lhs * rhs)
+            Self::reduce_product(lhs * rhs)
         } else {
             Self::reduce_double(udouble::widening_mul(*lhs, *rhs))
         }
@@ -148,7 +178,7 @@ impl<const P: u8, const K: umax> Reducer<umax> for FixedMersenne<P, K> {
     #[inline]
     fn sqr(&self, target: umax) -> umax {
         if (P as u32) < (umax::BITS / 2) {
-            Self::reduce_single(target * target)
+            Self::reduce_product(target * target)
         } else {
             Self::reduce_double(udouble::widening_square(target))
         }
@@ -157,6 +187,159 @@ impl<const P: u8, const K: umax> Reducer<umax> for FixedMersenne<P, K> {
     impl_reduced_binary_pow!(umax);
 }
 
+/// A modular reducer for pseudo-Mersenne moduli `m = 2^n - c` determined at runtime, where
+/// the fold constant c is small (`c < 2^⌊n/2⌋`). It's the runtime counterpart of [FixedMersenne].
+///
+/// A double word `x = hi * 2^n + lo` is reduced by repeatedly folding it into `hi * c + lo`
+/// without any division, which takes at most three rounds for the products of two residues.
+/// The integers are stored in the normal form.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ModularInteger, PseudoMersenne, PseudoMersenneInt};
+///
+/// let m = (1u64 << 62) - 57; // a prime
+/// let r = PseudoMersenne::<u64>::new(m);
+/// assert_eq!(r.fold_constant(), (62, 57));
+/// assert!(PseudoMersenne::<u64>::try_new(1 << 62).is_none());
+///
+/// let x = PseudoMersenneInt::<u64>::new(u64::MAX, &m);
+/// assert_eq!((x * x).residue(), ((u64::MAX as u128).pow(2) % m as u128) as u64);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PseudoMersenne<T> {
+    m: T,
+    c: T,
+    n: u32,
+}
+
+macro_rules! impl_pseudo_mersenne_for {
+    ($t:ident, $ns:ident) => {
+        mod $ns {
+            use super::*;
+            use crate::word::$t::*;
+
+            impl PseudoMersenne<$t> {
+                /// Create the reducer for modulus m, or return [None] if m is not in the
+                /// form `2^n - c` with `c < 2^⌊n/2⌋`
+                pub const fn try_new(m: $t) -> Option<Self> {
+                    if m == 0 {
+                        return None;
+                    }
+                    // m > 2^(n-1), so n is the bit length of m
+                    let n = <$t>::BITS - m.leading_zeros();
+                    let c = ones(n) - m + 1;
+                    if c >> (n / 2) != 0 {
+                        return None;
+                    }
+                    Some(Self { m, c, n })
+                }
+
+                /// Create the reducer for modulus m
+                ///
+                /// # Panics
+                /// if m is not in the form `2^n - c` with `c < 2^⌊n/2⌋`
+                pub const fn new(m: $t) -> Self {
+                    match Self::try_new(m) {
+                        Some(r) => r,
+                        None => panic!("the modulus is not a pseudo-Mersenne number"),
+                    }
+                }
+
+                /// Get `(n, c)` such that the modulus is `2^n - c`
+                #[inline]
+                pub const fn fold_constant(&self) -> (u32, $t) {
+                    (self.n, self.c)
+                }
+
+                /// Calculate `x mod m` for a double word x
+                pub const fn reduce(&self, x: DoubleWord) -> $t {
+                    let mask = extend(ones(self.n));
+                    let mut lo = x & mask;
+                    let mut hi = x >> self.n;
+                    while hi > 0 {
+                        // hi * c doesn't overflow since hi < 2^(2N - n) and c < 2^⌊n/2⌋
+                        let sum = hi * extend(self.c) + lo;
+                        lo = sum & mask;
+                        hi = sum >> self.n;
+                    }
+
+                    let lo = lo as $t;
+                    if lo >= self.m {
+                        lo - self.m
+                    } else {
+                        lo
+                    }
+                }
+            }
+
+            impl Reducer<$t> for PseudoMersenne<$t> {
+                #[inline]
+                fn new(m: &$t) -> Self {
+                    PseudoMersenne::<$t>::new(*m)
+                }
+                #[inline]
+                fn transform(&self, target: $t) -> $t {
+                    self.reduce(extend(target))
+                }
+                #[inline]
+                fn check(&self, target: &$t) -> bool {
+                    *target < self.m
+                }
+                #[inline]
+                fn residue(&self, target: $t) -> $t {
+                    target
+                }
+                #[inline]
+                fn modulus(&self) -> $t {
+                    self.m
+                }
+                #[inline]
+                fn is_zero(&self, target: &$t) -> bool {
+                    *target == 0
+                }
+
+                #[inline(always)]
+                fn add(&self, lhs: &$t, rhs: &$t) -> $t {
+                    Vanilla::<$t>::add(&self.m, *lhs, *rhs)
+                }
+                #[inline(always)]
+                fn dbl(&self, target: $t) -> $t {
+                    Vanilla::<$t>::dbl(&self.m, target)
+                }
+                #[inline(always)]
+                fn sub(&self, lhs: &$t, rhs: &$t) -> $t {
+                    Vanilla::<$t>::sub(&self.m, *lhs, *rhs)
+                }
+                #[inline(always)]
+                fn neg(&self, target: $t) -> $t {
+                    Vanilla::<$t>::neg(&self.m, target)
+                }
+
+                #[inline(always)]
+                fn inv(&self, target: $t) -> Option<$t> {
+                    target.invm(&self.m)
+                }
+                #[inline]
+                fn mul(&self, lhs: &$t, rhs: &$t) -> $t {
+                    self.reduce(wmul(*lhs, *rhs))
+                }
+                #[inline]
+                fn sqr(&self, target: $t) -> $t {
+                    self.reduce(wsqr(target))
+                }
+
+                impl_reduced_binary_pow!($t);
+            }
+        }
+    };
+}
+impl_pseudo_mersenne_for!(u8, u8_impl);
+impl_pseudo_mersenne_for!(u16, u16_impl);
+impl_pseudo_mersenne_for!(u32, u32_impl);
+impl_pseudo_mersenne_for!(u64, u64_impl);
+impl_pseudo_mersenne_for!(usize, usize_impl);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,6 +391,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reduce_product_test() {
+        macro_rules! tests_for {
+            ($($M:ty)*) => ($({
+                let m = <$M>::MODULUS;
+                let edges = [0, 1, 2, m / 2, m - 2, m - 1];
+                for &a in edges.iter() {
+                    for &b in edges.iter() {
+                        assert_eq!(<$M>::reduce_product(a * b), a * b % m);
+                    }
+                }
+                for _ in 0..1000 {
+                    let (a, b) = (random::<umax>() % m, random::<umax>() % m);
+                    assert_eq!(<$M>::reduce_product(a * b), a * b % m);
+                }
+            })*);
+        }
+        tests_for!(M1 M2 M4 M5 FixedMersenne<7, 1> FixedMersenne<63, 25>);
+    }
+
+    #[test]
+    fn test_boundary() {
+        use crate::reduced::tests::ReducedTester;
+        ReducedTester::<umax>::test_boundary::<M1>(M1::MODULUS);
+        ReducedTester::<umax>::test_boundary::<M2>(M2::MODULUS);
+        ReducedTester::<umax>::test_boundary::<M3>(M3::MODULUS);
+        ReducedTester::<umax>::test_boundary::<M4>(M4::MODULUS);
+        ReducedTester::<umax>::test_boundary::<M5>(M5::MODULUS);
+        ReducedTester::<umax>::test_boundary::<M6>(M6::MODULUS);
+    }
+
     #[test]
     fn test_against_modops() {
         macro_rules! tests_for {
@@ -233,4 +447,40 @@ mod tests {
             tests_for!(a, b, e; M1 M2 M3 M4 M5 M6);
         }
     }
+
+    #[test]
+    fn pseudo_mersenne_test() {
+        use crate::reduced::tests::ReducedTester;
+        use crate::{ModularInteger, PseudoMersenneInt};
+
+        assert!(PseudoMersenne::<u8>::try_new(0).is_none());
+        assert!(PseudoMersenne::<u8>::try_new(128).is_none());
+        assert!(PseudoMersenne::<u8>::try_new(240).is_none());
+        assert_eq!(PseudoMersenne::<u8>::new(241).fold_constant(), (8, 15));
+        assert!(PseudoMersenne::<u32>::try_new(1).is_none());
+        assert_eq!(PseudoMersenne::<u32>::new(3).fold_constant(), (2, 1));
+
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..NRANDOM {
+                    let n = random::<u32>() % (<$T>::BITS - 1) + 2;
+                    let c = (random::<$T>() >> (<$T>::BITS - n / 2)).max(1);
+                    let m = (<$T>::MAX >> (<$T>::BITS - n)) - c + 1;
+                    ReducedTester::<$T>::test_boundary::<PseudoMersenne<$T>>(m);
+
+                    let (a, b, e) = (random::<$T>(), random::<$T>(), random::<$T>());
+                    let am = PseudoMersenneInt::<$T>::new(a, &m);
+                    let bm = am.convert(b);
+                    assert_eq!(am.residue(), a % m);
+                    assert_eq!((am * bm).residue(), a.mulm(b, &m));
+                    assert_eq!(am.square().residue(), a.sqm(&m));
+                    assert_eq!(am.pow(&e).residue(), a.powm(e, &m));
+                    assert_eq!(am.inv().map(|v| v.residue()), a.invm(&m));
+
+                    assert_eq!(am.convert(m - 1).square().residue(), 1 % m);
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 usize);
+    }
 }
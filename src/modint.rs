@@ -0,0 +1,179 @@
+//! A reducer selecting the algorithm by the modulus
+
+use crate::reduced::impl_reduced_binary_pow;
+use crate::{Barrett, Montgomery, Reducer};
+
+/// A modular reducer that inspects the modulus once and dispatches every operation to
+/// the better supported algorithm: [Montgomery] for odd moduli, and the Barrett reduction
+/// ([Barrett]) for even moduli. So any modulus `m >= 1` is accepted, which is used by the
+/// facade type [ModInt][crate::ModInt].
+///
+/// The dispatch costs one predictable branch for each operation. Because the reduced
+/// representations of the two variants are different, integers should only be combined
+/// when they are created with the same modulus.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ModularInteger, ModInt};
+///
+/// let x = ModInt::<u64>::new(12345, &1000); // even modulus
+/// let y = ModInt::<u64>::new(12345, &1001); // odd modulus
+/// assert_eq!((x * x).residue(), 12345 * 12345 % 1000);
+/// assert_eq!((y * y).residue(), 12345 * 12345 % 1001);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub enum AutoReducer<T> {
+    /// The reducer for odd moduli
+    Montgomery(Montgomery<T>),
+    /// The reducer for even moduli
+    Barrett(Barrett<T>),
+}
+
+macro_rules! dispatch {
+    ($self:ident, $r:ident => $e:expr) => {
+        match $self {
+            AutoReducer::Montgomery($r) => $e,
+            AutoReducer::Barrett($r) => $e,
+        }
+    };
+}
+
+macro_rules! impl_auto_reducer_for {
+    ($($T:ty)*) => {$(
+        impl AutoReducer<$T> {
+            /// Create the reducer for modulus m
+            ///
+            /// # Panics
+            /// if m is zero
+            pub const fn new(m: $T) -> Self {
+                assert!(m > 0, "the modulus should not be zero");
+                if m & 1 == 1 {
+                    AutoReducer::Montgomery(Montgomery::<$T>::new(m))
+                } else {
+                    AutoReducer::Barrett(Barrett::<$T>::new(m))
+                }
+            }
+        }
+
+        impl Reducer<$T> for AutoReducer<$T> {
+            #[inline]
+            fn new(m: &$T) -> Self {
+                Self::new(*m)
+            }
+            #[inline]
+            fn transform(&self, target: $T) -> $T {
+                dispatch!(self, r => r.transform(target))
+            }
+            #[inline]
+            fn check(&self, target: &$T) -> bool {
+                dispatch!(self, r => r.check(target))
+            }
+            #[inline]
+            fn modulus(&self) -> $T {
+                dispatch!(self, r => r.modulus())
+            }
+            #[inline]
+            fn residue(&self, target: $T) -> $T {
+                dispatch!(self, r => r.residue(target))
+            }
+            #[inline]
+            fn is_zero(&self, target: &$T) -> bool {
+                dispatch!(self, r => r.is_zero(target))
+            }
+            #[inline]
+            fn add(&self, lhs: &$T, rhs: &$T) -> $T {
+                dispatch!(self, r => r.add(lhs, rhs))
+            }
+            #[inline]
+            fn dbl(&self, target: $T) -> $T {
+                dispatch!(self, r => r.dbl(target))
+            }
+            #[inline]
+            fn sub(&self, lhs: &$T, rhs: &$T) -> $T {
+                dispatch!(self, r => r.sub(lhs, rhs))
+            }
+            #[inline]
+            fn neg(&self, target: $T) -> $T {
+                dispatch!(self, r => r.neg(target))
+            }
+            #[inline]
+            fn mul(&self, lhs: &$T, rhs: &$T) -> $T {
+                dispatch!(self, r => r.mul(lhs, rhs))
+            }
+            #[inline]
+            fn inv(&self, target: $T) -> Option<$T> {
+                dispatch!(self, r => r.inv(target))
+            }
+            #[inline]
+            fn sqr(&self, target: $T) -> $T {
+                dispatch!(self, r => r.sqr(target))
+            }
+
+            impl_reduced_binary_pow!($T);
+        }
+    )*};
+}
+impl_auto_reducer_for!(u8 u16 u32 u64 usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reduced::tests::ReducedTester;
+    use crate::{ModInt, ModularInteger, ModularPow};
+    use rand::random;
+
+    #[test]
+    fn test_against_modops() {
+        for _ in 0..10 {
+            for mode in 0..3 {
+                ReducedTester::<u8>::test_against_modops::<AutoReducer<u8>>(mode);
+                ReducedTester::<u16>::test_against_modops::<AutoReducer<u16>>(mode);
+                ReducedTester::<u32>::test_against_modops::<AutoReducer<u32>>(mode);
+                ReducedTester::<u64>::test_against_modops::<AutoReducer<u64>>(mode);
+                ReducedTester::<usize>::test_against_modops::<AutoReducer<usize>>(mode);
+            }
+        }
+    }
+
+    #[test]
+    fn test_boundary() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for &m in [<$T>::MAX, <$T>::MAX - 1, <$T>::MAX / 2 + 1, 3, 4].iter() {
+                    ReducedTester::<$T>::test_boundary::<AutoReducer<$T>>(m);
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 usize);
+    }
+
+    #[test]
+    fn dispatch_test() {
+        assert!(matches!(
+            AutoReducer::<u32>::new(7),
+            AutoReducer::Montgomery(_)
+        ));
+        assert!(matches!(
+            AutoReducer::<u32>::new(8),
+            AutoReducer::Barrett(_)
+        ));
+
+        // trivial moduli
+        for &m in [1u64, 2].iter() {
+            let a = random::<u64>();
+            let x = ModInt::new(a, &m);
+            assert_eq!(x.residue(), a % m);
+            assert_eq!((x + x.convert(1)).residue(), (a % m + 1) % m);
+            assert_eq!(x.convert(1).pow(&random()).residue(), 1 % m);
+        }
+
+        // moduli with large powers of two
+        for s in 1..64 {
+            let m = (random::<u64>() | 1) << s;
+            let (a, e) = (random::<u64>(), random::<u8>() as u64);
+            let x = ModInt::new(a, &m);
+            assert_eq!(x.modulus(), m);
+            assert_eq!(x.pow(&e).residue(), a.powm(e, &m));
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! Declaration of newtypes with a fixed modulus
+
+/// Declare a [Copy] newtype of integers modulo a constant, without carrying the modulus
+/// at runtime.
+///
+/// The syntax is `modtype!(<visibility> <name>: <integer type> = <modulus>)`, with optional
+/// attributes before it. The modulus can be any constant expression, and the precomputation
+/// of the reducer ([AutoReducer][crate::AutoReducer], so both odd and even moduli are
+/// supported) is done at compile time. The integer types [u8], [u16], [u32], [u64] and
+/// [usize] are supported.
+///
+/// The generated type implements [ModularInteger][crate::ModularInteger], the arithmetic
+/// operators (`+`, `-`, `*`, `/` and the assigning variants, and unary `-`), [Sum][core::iter::Sum],
+/// [Product][core::iter::Product], [Debug][core::fmt::Debug] and [Display][core::fmt::Display]
+/// (both printing the residue), and the conversions from and into the integer type.
+/// The division panics if the divisor is not invertible, use the `inv()` method to check it.
+/// With the `arbitrary` or `proptest` feature, the `Arbitrary` traits are also implemented.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{modtype, ModularInteger};
+///
+/// modtype!(pub Mod1e9p7: u32 = 1_000_000_007);
+///
+/// let x = Mod1e9p7::from(123456789);
+/// let y: Mod1e9p7 = (1..=20).map(Mod1e9p7::from).product();
+/// assert_eq!((x * y).residue(), (123456789 * 146326063u64 % 1_000_000_007) as u32);
+/// assert_eq!(u32::from(x / x), 1);
+/// assert_eq!(Mod1e9p7::MODULUS, 1_000_000_007);
+/// assert_eq!(core::mem::size_of::<Mod1e9p7>(), 4);
+/// ```
+#[macro_export]
+macro_rules! modtype {
+    ($(#[$attr:meta])* $vis:vis $name:ident: $T:ty = $m:expr) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        $vis struct $name($T);
+
+        impl $name {
+            /// The modulus of the type
+            pub const MODULUS: $T = $m;
+
+            const REDUCER: $crate::AutoReducer<$T> = $crate::AutoReducer::<$T>::new($m);
+
+            /// Calculate `self ^ exp`
+            #[inline]
+            pub fn pow(self, exp: &$T) -> Self {
+                Self($crate::Reducer::pow(&Self::REDUCER, self.0, exp))
+            }
+
+            /// Calculate the modular inverse, or return [None] if self is not a unit
+            #[inline]
+            pub fn inv(self) -> Option<Self> {
+                $crate::Reducer::inv(&Self::REDUCER, self.0).map(Self)
+            }
+        }
+
+        impl $crate::ModularInteger for $name {
+            type Base = $T;
+
+            #[inline]
+            fn modulus(&self) -> $T {
+                Self::MODULUS
+            }
+            #[inline]
+            fn residue(&self) -> $T {
+                $crate::Reducer::residue(&Self::REDUCER, self.0)
+            }
+            #[inline]
+            fn is_zero(&self) -> bool {
+                $crate::Reducer::is_zero(&Self::REDUCER, &self.0)
+            }
+            #[inline]
+            fn convert(&self, n: $T) -> Self {
+                Self::from(n)
+            }
+            #[inline]
+            fn double(self) -> Self {
+                Self($crate::Reducer::dbl(&Self::REDUCER, self.0))
+            }
+            #[inline]
+            fn square(self) -> Self {
+                Self($crate::Reducer::sqr(&Self::REDUCER, self.0))
+            }
+        }
+
+        impl From<$T> for $name {
+            #[inline]
+            fn from(n: $T) -> Self {
+                Self($crate::Reducer::transform(&Self::REDUCER, n))
+            }
+        }
+
+        impl From<$name> for $T {
+            #[inline]
+            fn from(n: $name) -> $T {
+                $crate::ModularInteger::residue(&n)
+            }
+        }
+
+        impl core::ops::Add for $name {
+            type Output = Self;
+            #[inline]
+            fn add(self, rhs: Self) -> Self {
+                Self($crate::Reducer::add(&Self::REDUCER, &self.0, &rhs.0))
+            }
+        }
+
+        impl core::ops::Sub for $name {
+            type Output = Self;
+            #[inline]
+            fn sub(self, rhs: Self) -> Self {
+                Self($crate::Reducer::sub(&Self::REDUCER, &self.0, &rhs.0))
+            }
+        }
+
+        impl core::ops::Mul for $name {
+            type Output = Self;
+            #[inline]
+            fn mul(self, rhs: Self) -> Self {
+                Self($crate::Reducer::mul(&Self::REDUCER, &self.0, &rhs.0))
+            }
+        }
+
+        impl core::ops::Div for $name {
+            type Output = Self;
+            #[inline]
+            fn div(self, rhs: Self) -> Self {
+                let inv = rhs.inv().expect("the modular inverse doesn't exist!");
+                Self($crate::Reducer::mul(&Self::REDUCER, &self.0, &inv.0))
+            }
+        }
+
+        impl core::ops::Neg for $name {
+            type Output = Self;
+            #[inline]
+            fn neg(self) -> Self {
+                Self($crate::Reducer::neg(&Self::REDUCER, self.0))
+            }
+        }
+
+        impl core::ops::AddAssign for $name {
+            #[inline]
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl core::ops::SubAssign for $name {
+            #[inline]
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl core::ops::MulAssign for $name {
+            #[inline]
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl core::ops::DivAssign for $name {
+            #[inline]
+            fn div_assign(&mut self, rhs: Self) {
+                *self = *self / rhs;
+            }
+        }
+
+        impl core::iter::Sum for $name {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::from(0), |acc, x| acc + x)
+            }
+        }
+
+        impl core::iter::Product for $name {
+            fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::from(1), |acc, x| acc * x)
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(
+                    f,
+                    "{}({})",
+                    stringify!($name),
+                    $crate::ModularInteger::residue(self)
+                )
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::Display::fmt(&$crate::ModularInteger::residue(self), f)
+            }
+        }
+
+        $crate::__modtype_arbitrary!($name, $T);
+        $crate::__modtype_proptest!($name, $T);
+    };
+}
+
+// The helpers of modtype! are selected by the features of this crate, since a #[cfg] emitted
+// by the macro would be evaluated against the features of the calling crate.
+
+#[cfg(feature = "arbitrary")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __modtype_arbitrary {
+    ($name:ident, $T:ty) => {
+        impl<'a> $crate::__arbitrary::Arbitrary<'a> for $name {
+            #[inline]
+            fn arbitrary(
+                u: &mut $crate::__arbitrary::Unstructured<'a>,
+            ) -> $crate::__arbitrary::Result<Self> {
+                <$T as $crate::__arbitrary::Arbitrary<'a>>::arbitrary(u).map(Self::from)
+            }
+
+            #[inline]
+            fn size_hint(depth: usize) -> (usize, Option<usize>) {
+                <$T as $crate::__arbitrary::Arbitrary<'a>>::size_hint(depth)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "arbitrary"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __modtype_arbitrary {
+    ($name:ident, $T:ty) => {};
+}
+
+#[cfg(feature = "proptest")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __modtype_proptest {
+    ($name:ident, $T:ty) => {
+        impl $crate::__proptest::arbitrary::Arbitrary for $name {
+            type Parameters = ();
+            type Strategy = $crate::__proptest::strategy::Map<
+                <$T as $crate::__proptest::arbitrary::Arbitrary>::Strategy,
+                fn($T) -> Self,
+            >;
+
+            fn arbitrary_with(_: ()) -> Self::Strategy {
+                $crate::__proptest::strategy::Strategy::prop_map(
+                    $crate::__proptest::arbitrary::any::<$T>(),
+                    Self::from as fn($T) -> Self,
+                )
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "proptest"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __modtype_proptest {
+    ($name:ident, $T:ty) => {};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ModularCoreOps, ModularInteger, ModularPow, ModularUnaryOps};
+    use rand::random;
+
+    modtype!(Mod1e9p7: u32 = 1_000_000_007);
+    modtype!(
+        /// An even modulus
+        pub(crate) ModEven: u64 = 1 << 40
+    );
+    modtype!(ModSmall: u8 = 255);
+
+    #[test]
+    fn modtype_test() {
+        macro_rules! tests_for {
+            ($($M:ident: $T:ty),*) => ($(
+                let m = $M::MODULUS;
+                for _ in 0..10 {
+                    let (a, b, e) = (random::<$T>(), random::<$T>(), random::<$T>());
+                    let (x, y) = ($M::from(a), $M::from(b));
+                    assert_eq!(x.residue(), a % m);
+                    assert_eq!((x + y).residue(), a.addm(b, &m));
+                    assert_eq!((x - y).residue(), a.subm(b, &m));
+                    assert_eq!((x * y).residue(), a.mulm(b, &m));
+                    assert_eq!((-x).residue(), a.negm(&m));
+                    assert_eq!(x.double().residue(), a.dblm(&m));
+                    assert_eq!(x.square().residue(), a.sqm(&m));
+                    assert_eq!(x.pow(&e).residue(), a.powm(e, &m));
+                    assert_eq!(x.inv().map(|v| v.residue()), a.invm(&m));
+                    if let Some(yi) = y.inv() {
+                        assert_eq!(x / y, x * yi);
+                    }
+
+                    let mut z = x;
+                    z += y;
+                    z *= y;
+                    z -= x;
+                    assert_eq!(z, (x + y) * y - x);
+                    assert_eq!(<$T>::from(z), z.residue());
+                    assert_eq!(x.convert(b), y);
+                }
+            )*);
+        }
+        tests_for!(Mod1e9p7: u32, ModEven: u64, ModSmall: u8);
+
+        let s: Mod1e9p7 = (1..=100).map(Mod1e9p7::from).sum();
+        assert_eq!(s.residue(), 5050);
+        assert_eq!(std::format!("{}", -Mod1e9p7::from(1)), "1000000006");
+        assert_eq!(
+            std::format!("{:?}", ModSmall::from(200) * ModSmall::from(2)),
+            "ModSmall(145)"
+        );
+        assert!(ModEven::from(1 << 40).is_zero());
+    }
+}
@@ -1,12 +1,17 @@
-use crate::reduced::impl_reduced_binary_pow;
-use crate::{ModularUnaryOps, Reducer, Vanilla};
+use crate::reduced::impl_reduced_window_pow;
+use crate::{ModularError, ReducedInt, Reducer, Vanilla};
+use core::convert::TryFrom;
+#[cfg(feature = "num-traits")]
+use num_traits::Pow;
+#[cfg(feature = "subtle")]
+use {crate::ct::CtSelect, subtle::Choice};
 
 /// Negated modular inverse on binary bases
 /// `neginv` calculates `-(m^-1) mod R`, `R = 2^k. If m is odd, then result of m + 1 will be returned.
-mod neg_mod_inv {
+pub(crate) mod neg_mod_inv {
     // Entry i contains (2i+1)^(-1) mod 256.
     #[rustfmt::skip]
-    const BINV_TABLE: [u8; 128] = [
+    pub(super) const BINV_TABLE: [u8; 128] = [
         0x01, 0xAB, 0xCD, 0xB7, 0x39, 0xA3, 0xC5, 0xEF, 0xF1, 0x1B, 0x3D, 0xA7, 0x29, 0x13, 0x35, 0xDF,
         0xE1, 0x8B, 0xAD, 0x97, 0x19, 0x83, 0xA5, 0xCF, 0xD1, 0xFB, 0x1D, 0x87, 0x09, 0xF3, 0x15, 0xBF,
         0xC1, 0x6B, 0x8D, 0x77, 0xF9, 0x63, 0x85, 0xAF, 0xB1, 0xDB, 0xFD, 0x67, 0xE9, 0xD3, 0xF5, 0x9F,
@@ -17,56 +22,42 @@ mod neg_mod_inv {
         0x21, 0xCB, 0xED, 0xD7, 0x59, 0xC3, 0xE5, 0x0F, 0x11, 0x3B, 0x5D, 0xC7, 0x49, 0x33, 0x55, 0xFF,
     ];
 
-    pub mod u8 {
-        use super::*;
-        pub const fn neginv(m: u8) -> u8 {
-            let i = BINV_TABLE[((m >> 1) & 0x7F) as usize];
-            i.wrapping_neg()
-        }
-    }
-
-    pub mod u16 {
-        use super::*;
-        pub const fn neginv(m: u16) -> u16 {
-            let mut i = BINV_TABLE[((m >> 1) & 0x7F) as usize] as u16;
-            // hensel lifting
-            i = 2u16.wrapping_sub(i.wrapping_mul(m)).wrapping_mul(i);
-            i.wrapping_neg()
-        }
-    }
-
-    pub mod u32 {
-        use super::*;
-        pub const fn neginv(m: u32) -> u32 {
-            let mut i = BINV_TABLE[((m >> 1) & 0x7F) as usize] as u32;
-            i = 2u32.wrapping_sub(i.wrapping_mul(m)).wrapping_mul(i);
-            i = 2u32.wrapping_sub(i.wrapping_mul(m)).wrapping_mul(i);
-            i.wrapping_neg()
+    // verify the table at compile time: (2i+1) * BINV_TABLE[i] = 1 mod 2^8
+    const _: () = {
+        let mut i = 0;
+        while i < 128 {
+            assert!(((2 * i + 1) as u8).wrapping_mul(BINV_TABLE[i]) == 1);
+            i += 1;
         }
-    }
+    };
 
-    pub mod u64 {
-        use super::*;
-        pub const fn neginv(m: u64) -> u64 {
-            let mut i = BINV_TABLE[((m >> 1) & 0x7F) as usize] as u64;
-            i = 2u64.wrapping_sub(i.wrapping_mul(m)).wrapping_mul(i);
-            i = 2u64.wrapping_sub(i.wrapping_mul(m)).wrapping_mul(i);
-            i = 2u64.wrapping_sub(i.wrapping_mul(m)).wrapping_mul(i);
-            i.wrapping_neg()
-        }
-    }
+    /// Generate `neginv` for a word type with the given number of Newton iterations.
+    /// Each iteration `i <- i * (2 - i * m)` doubles the number of correct low bits,
+    /// starting from the 8 bits given by the table.
+    macro_rules! impl_neginv {
+        ($T:ident, $steps:expr) => {
+            pub mod $T {
+                use super::*;
+                const STEPS: u32 = $steps;
+                const _: () = assert!(8 << STEPS >= $T::BITS);
 
-    pub mod u128 {
-        use super::*;
-        pub const fn neginv(m: u128) -> u128 {
-            let mut i = BINV_TABLE[((m >> 1) & 0x7F) as usize] as u128;
-            i = 2u128.wrapping_sub(i.wrapping_mul(m)).wrapping_mul(i);
-            i = 2u128.wrapping_sub(i.wrapping_mul(m)).wrapping_mul(i);
-            i = 2u128.wrapping_sub(i.wrapping_mul(m)).wrapping_mul(i);
-            i = 2u128.wrapping_sub(i.wrapping_mul(m)).wrapping_mul(i);
-            i.wrapping_neg()
-        }
+                pub const fn neginv(m: $T) -> $T {
+                    let mut i = BINV_TABLE[((m >> 1) & 0x7F) as usize] as $T;
+                    let mut k = 0;
+                    while k < STEPS {
+                        i = (2 as $T).wrapping_sub(i.wrapping_mul(m)).wrapping_mul(i);
+                        k += 1;
+                    }
+                    i.wrapping_neg()
+                }
+            }
+        };
     }
+    impl_neginv!(u8, 0);
+    impl_neginv!(u16, 1);
+    impl_neginv!(u32, 2);
+    impl_neginv!(u64, 3);
+    impl_neginv!(u128, 4);
 
     pub mod usize {
         #[inline]
@@ -92,6 +83,72 @@ pub struct Montgomery<T> {
     inv: T, // modular inverse of the modulus
 }
 
+/// An integer in the Montgomery form `aR mod m` of a [Montgomery] reducer.
+///
+/// The typed operations ([Montgomery::to_monty()], [MontyForm::mul()], etc.) only accept
+/// values in this wrapper, so that an integer that hasn't been transformed can't be passed
+/// where a Montgomery form is expected. They implement the arithmetic of [Montgomery], and
+/// the raw [Reducer] interface (thus [MontgomeryInt][crate::MontgomeryInt]) is a thin layer
+/// over them.
+///
+/// The [Reducer] trait itself doesn't take [MontyForm], because the representation type is
+/// the same `T` for all the reducers used by [ReducedInt], and changing it would break the
+/// other reducers and the users of [ReducedInt::repr()]. Instead, the representation of
+/// [MontgomeryInt][crate::MontgomeryInt] is exposed in this wrapper through `monty_form()`
+/// and `from_monty_form()`.
+///
+/// A Montgomery form is only created by [Montgomery::to_monty()] or the typed operations.
+/// Reinterpreting a raw integer as a Montgomery form must be explicit with
+/// [MontyForm::from_raw_unchecked()], while the raw value can be extracted with
+/// [MontyForm::raw()] or [Into].
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{Montgomery, MontyForm};
+///
+/// let r = Montgomery::<u64>::new(1_000_000_007);
+/// let (a, b) = (r.to_monty(12345), r.to_monty(67890));
+/// assert_eq!(r.from_monty(a.mul(&b, &r)), 12345 * 67890 % 1_000_000_007);
+/// ```
+///
+/// A raw integer is rejected:
+/// ```compile_fail
+/// use num_modular::Montgomery;
+///
+/// let r = Montgomery::<u64>::new(1_000_000_007);
+/// let a = r.to_monty(12345);
+/// a.mul(&67890u64, &r);
+/// ```
+///
+/// A raw integer is not converted implicitly:
+/// ```compile_fail
+/// use num_modular::{Montgomery, MontyForm};
+///
+/// let r = Montgomery::<u64>::new(1_000_000_007);
+/// let b: MontyForm<u64> = 67890u64.into();
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MontyForm<T>(T);
+
+impl<T> MontyForm<T> {
+    /// Treat the raw integer as a Montgomery form **without** transforming it. The caller
+    /// should make sure that the value is already the Montgomery form `aR mod m` for the
+    /// reducer it's used with (e.g. it's from [MontyForm::raw()] or deserialized data),
+    /// otherwise the results are silently wrong. Use [Montgomery::to_monty()] to transform
+    /// a normal integer.
+    #[inline(always)]
+    pub const fn from_raw_unchecked(v: T) -> Self {
+        MontyForm(v)
+    }
+
+    /// Get the reference to the raw representation
+    #[inline(always)]
+    pub const fn raw(&self) -> &T {
+        &self.0
+    }
+}
+
 macro_rules! impl_montgomery_for {
     ($t:ident, $ns:ident) => {
         mod $ns {
@@ -107,14 +164,96 @@ macro_rules! impl_montgomery_for {
                     );
                     Self { m, inv: neginv(m) }
                 }
-                const fn reduce(&self, monty: DoubleWord) -> $t {
-                    debug_assert!(high(monty) < self.m);
 
-                    // REDC algorithm
-                    let tm = low(monty).wrapping_mul(self.inv);
-                    let (t, overflow) = monty.overflowing_add(wmul(tm, self.m));
-                    let t = high(t);
+                /// Assemble the reducer from the modulus and the precomputed `-m^-1 mod R`,
+                /// e.g. from deserialized data. The invariants are checked with
+                /// [validate()][Self::validate] only in debug builds.
+                pub const fn from_raw_parts(m: $t, inv: $t) -> Self {
+                    let r = Self { m, inv };
+                    debug_assert!(r.validate().is_ok(), "invalid Montgomery reducer");
+                    r
+                }
+
+                /// Get the modulus and the precomputed `-m^-1 mod R`
+                #[inline]
+                pub const fn raw_parts(&self) -> ($t, $t) {
+                    (self.m, self.inv)
+                }
 
+                /// Check that the modulus is odd and the precomputed inverse satisfies
+                /// `m * inv ≡ -1 (mod R)`
+                pub const fn validate(&self) -> Result<(), ModularError> {
+                    if self.m & 1 == 0 {
+                        Err(ModularError::EvenModulus)
+                    } else if self.m.wrapping_mul(self.inv) != $t::MAX {
+                        Err(ModularError::InvalidInverse)
+                    } else {
+                        Ok(())
+                    }
+                }
+                #[inline(always)]
+                pub(crate) fn reduce(&self, monty: DoubleWord) -> $t {
+                    self.reduce_split(low(monty), high(monty))
+                }
+
+                /// Montgomery inverse in the Montgomery domain without leaving it (Kaliski's
+                /// algorithm), i.e. `(aR)^-1 R^2 = a^-1 R mod m`.
+                ///
+                /// The binary extended GCD gives the almost inverse `x^-1 2^k` with
+                /// `k <= 2B`, which is then corrected by doubling `2B - k` times.
+                /// REF: http://cetinkayakoc.net/docs/j82.pdf
+                fn monty_inv(&self, x: $t) -> Option<$t> {
+                    debug_assert!(x < self.m);
+                    let m = self.m;
+                    // r and s are kept reduced, since only their residues modulo m matter
+                    let (mut u, mut v, mut r, mut s) = (m, x, 0, 1 % m);
+                    let mut k = 0;
+                    while v > 0 {
+                        if u & 1 == 0 {
+                            u >>= 1;
+                            s = Vanilla::<$t>::dbl(&m, s);
+                        } else if v & 1 == 0 {
+                            v >>= 1;
+                            r = Vanilla::<$t>::dbl(&m, r);
+                        } else if u > v {
+                            u = (u - v) >> 1;
+                            r = Vanilla::<$t>::add(&m, r, s);
+                            s = Vanilla::<$t>::dbl(&m, s);
+                        } else {
+                            v = (v - u) >> 1;
+                            s = Vanilla::<$t>::add(&m, s, r);
+                            r = Vanilla::<$t>::dbl(&m, r);
+                        }
+                        k += 1;
+                    }
+                    if u != 1 {
+                        return None; // u = gcd(x, m)
+                    }
+
+                    let mut y = Vanilla::<$t>::neg(&m, r);
+                    while k < 2 * $t::BITS {
+                        y = Vanilla::<$t>::dbl(&m, y);
+                        k += 1;
+                    }
+                    Some(y)
+                }
+
+                /// REDC on the low and high words, the low word of `monty + tm * m` is always
+                /// zero, so there is a carry from it iff the low word of monty is not zero.
+                /// Return the result before the final subtraction, and whether it overflowed.
+                #[inline(always)]
+                const fn redc_split(&self, lo: $t, hi: $t) -> ($t, bool) {
+                    debug_assert!(hi < self.m);
+                    let tm = lo.wrapping_mul(self.inv);
+                    let (_, phi) = wmul_split(tm, self.m);
+                    let (t, o1) = hi.overflowing_add(phi);
+                    let (t, o2) = t.overflowing_add((lo != 0) as $t);
+                    (t, o1 || o2)
+                }
+
+                #[cfg(not(feature = "subtle"))]
+                fn reduce_split(&self, lo: $t, hi: $t) -> $t {
+                    let (t, overflow) = self.redc_split(lo, hi);
                     if overflow {
                         t + self.m.wrapping_neg()
                     } else if t >= self.m {
@@ -123,8 +262,23 @@ macro_rules! impl_montgomery_for {
                         t
                     }
                 }
+
+                #[cfg(feature = "subtle")]
+                fn reduce_split(&self, lo: $t, hi: $t) -> $t {
+                    // select t - m if overflow || t >= m, without a branch
+                    let (t, overflow) = self.redc_split(lo, hi);
+                    let (d, borrow) = t.overflowing_sub(self.m);
+                    let cond = Choice::from(overflow as u8) | !Choice::from(borrow as u8);
+                    <$t>::ct_select(t, d, cond)
+                }
             }
 
+            /// The raw interface over the typed operations, where the representations are
+            /// the Montgomery forms.
+            ///
+            /// It takes and returns the raw integers because [Reducer] is shared by all the
+            /// reducers of [ReducedInt], which stores the representation as the plain type `T`.
+            /// Use [MontyForm] when operating on the Montgomery forms directly.
             impl Reducer<$t> for Montgomery<$t> {
                 #[inline]
                 fn new(m: &$t) -> Self {
@@ -132,10 +286,7 @@ macro_rules! impl_montgomery_for {
                 }
                 #[inline]
                 fn transform(&self, target: $t) -> $t {
-                    if target == 0 {
-                        return 0;
-                    }
-                    nrem(merge(0, target), self.m)
+                    self.to_monty(target).0
                 }
                 #[inline]
                 fn check(&self, target: &$t) -> bool {
@@ -144,7 +295,7 @@ macro_rules! impl_montgomery_for {
 
                 #[inline]
                 fn residue(&self, target: $t) -> $t {
-                    self.reduce(extend(target))
+                    self.from_monty(MontyForm(target))
                 }
                 #[inline(always)]
                 fn modulus(&self) -> $t {
@@ -157,44 +308,202 @@ macro_rules! impl_montgomery_for {
 
                 #[inline(always)]
                 fn add(&self, lhs: &$t, rhs: &$t) -> $t {
-                    Vanilla::<$t>::add(&self.m, *lhs, *rhs)
+                    MontyForm(*lhs).add(&MontyForm(*rhs), self).0
                 }
 
                 #[inline(always)]
                 fn dbl(&self, target: $t) -> $t {
-                    Vanilla::<$t>::dbl(&self.m, target)
+                    MontyForm(target).dbl(self).0
                 }
 
                 #[inline(always)]
                 fn sub(&self, lhs: &$t, rhs: &$t) -> $t {
-                    Vanilla::<$t>::sub(&self.m, *lhs, *rhs)
+                    MontyForm(*lhs).sub(&MontyForm(*rhs), self).0
                 }
 
                 #[inline(always)]
                 fn neg(&self, target: $t) -> $t {
-                    Vanilla::<$t>::neg(&self.m, target)
+                    MontyForm(target).neg(self).0
                 }
 
                 #[inline]
                 fn mul(&self, lhs: &$t, rhs: &$t) -> $t {
-                    self.reduce(wmul(*lhs, *rhs))
+                    MontyForm(*lhs).mul(&MontyForm(*rhs), self).0
                 }
 
                 #[inline]
                 fn sqr(&self, target: $t) -> $t {
-                    self.reduce(wsqr(target))
+                    MontyForm(target).sqr(self).0
                 }
 
                 #[inline(always)]
                 fn inv(&self, target: $t) -> Option<$t> {
-                    // TODO: support direct montgomery inverse
-                    // REF: http://cetinkayakoc.net/docs/j82.pdf
-                    self.residue(target)
-                        .invm(&self.m)
-                        .map(|v| self.transform(v))
+                    MontyForm(target).inv(self).map(|v| v.0)
+                }
+
+                impl_reduced_window_pow!(Word);
+            }
+
+            impl ReducedInt<$t, Montgomery<$t>> {
+                /// Assemble the integer from the raw Montgomery representation and the reducer,
+                /// e.g. from deserialized data. The invariants are checked with
+                /// [validate()][Self::validate] only in debug builds.
+                pub fn from_raw(repr: $t, r: Montgomery<$t>) -> Self {
+                    let v = Self::from_parts(repr, r);
+                    debug_assert!(v.validate().is_ok(), "invalid Montgomery integer");
+                    v
+                }
+
+                /// Check the invariants of the reducer (see [Montgomery::validate()]),
+                /// and that the representation is smaller than the modulus.
+                pub fn validate(&self) -> Result<(), ModularError> {
+                    let r = self.reducer();
+                    r.validate()?;
+                    if *self.repr() >= r.m {
+                        return Err(ModularError::Unreduced);
+                    }
+                    Ok(())
+                }
+
+                /// Convert the integer to a [Montgomery] form based on a wider integer type with
+                /// the same modulus. The representation is transformed again because the
+                /// auxiliary modulus R differs between the widths.
+                ///
+                /// Example code:
+                /// ```rust
+                /// use num_modular::{ModularInteger, MontgomeryInt};
+                ///
+                /// let x = MontgomeryInt::<u32>::new(12345, &1_000_003);
+                /// let y = x.widen::<u64>();
+                /// assert_eq!((y * y).residue(), 12345 * 12345 % 1_000_003);
+                /// ```
+                pub fn widen<U>(&self) -> ReducedInt<U, Montgomery<U>>
+                where
+                    U: From<$t>,
+                    Montgomery<U>: Reducer<U>,
+                {
+                    let r = self.reducer();
+                    let residue = U::from(r.residue(*self.repr()));
+                    ReducedInt::new(residue, &U::from(r.m))
+                }
+
+                /// Convert the integer to a [Montgomery] form based on a narrower integer type
+                /// with the same modulus. [None] is returned if the modulus doesn't fit in U.
+                pub fn try_narrow<U>(&self) -> Option<ReducedInt<U, Montgomery<U>>>
+                where
+                    U: TryFrom<$t>,
+                    Montgomery<U>: Reducer<U>,
+                {
+                    let r = self.reducer();
+                    let m = U::try_from(r.m).ok()?;
+                    let residue = U::try_from(r.residue(*self.repr())).ok()?;
+                    Some(ReducedInt::new(residue, &m))
+                }
+
+                /// Get the Montgomery form of the integer
+                #[inline]
+                pub fn monty_form(&self) -> MontyForm<$t> {
+                    MontyForm(*self.repr())
+                }
+
+                /// Assemble the integer from the Montgomery form and its reducer
+                #[inline]
+                pub fn from_monty_form(x: MontyForm<$t>, r: Montgomery<$t>) -> Self {
+                    Self::from_raw(x.0, r)
+                }
+            }
+
+            impl From<MontyForm<$t>> for $t {
+                #[inline(always)]
+                fn from(v: MontyForm<$t>) -> Self {
+                    v.0
+                }
+            }
+
+            impl Montgomery<$t> {
+                /// Transform the integer into the Montgomery form
+                #[inline]
+                pub fn to_monty(&self, target: $t) -> MontyForm<$t> {
+                    if target == 0 {
+                        return MontyForm(0);
+                    }
+                    MontyForm(nrem(merge(0, target), self.m))
+                }
+
+                /// Transform the Montgomery form back to the normal integer in `[0, m)`
+                #[inline]
+                pub fn from_monty(&self, target: MontyForm<$t>) -> $t {
+                    self.reduce(extend(target.0))
+                }
+
+                /// Montgomery reduction of a double-width product of Montgomery forms (see
+                /// [MontyForm::mul_wide()]), whose high word should be less than the modulus.
+                #[inline]
+                pub fn redc(&self, target: MontyForm<DoubleWord>) -> MontyForm<$t> {
+                    MontyForm(self.reduce(target.0))
+                }
+            }
+
+            impl MontyForm<$t> {
+                /// Modular addition in the Montgomery form
+                #[inline]
+                pub fn add(&self, rhs: &Self, r: &Montgomery<$t>) -> Self {
+                    debug_assert!(self.0 < r.m && rhs.0 < r.m);
+                    MontyForm(Vanilla::<$t>::add(&r.m, self.0, rhs.0))
+                }
+
+                /// Modular doubling in the Montgomery form
+                #[inline]
+                pub fn dbl(&self, r: &Montgomery<$t>) -> Self {
+                    debug_assert!(self.0 < r.m);
+                    MontyForm(Vanilla::<$t>::dbl(&r.m, self.0))
+                }
+
+                /// Modular subtraction in the Montgomery form
+                #[inline]
+                pub fn sub(&self, rhs: &Self, r: &Montgomery<$t>) -> Self {
+                    debug_assert!(self.0 < r.m && rhs.0 < r.m);
+                    MontyForm(Vanilla::<$t>::sub(&r.m, self.0, rhs.0))
+                }
+
+                /// Modular negation in the Montgomery form
+                #[inline]
+                pub fn neg(&self, r: &Montgomery<$t>) -> Self {
+                    debug_assert!(self.0 < r.m);
+                    MontyForm(Vanilla::<$t>::neg(&r.m, self.0))
+                }
+
+                /// Modular multiplication in the Montgomery form
+                #[inline]
+                pub fn mul(&self, rhs: &Self, r: &Montgomery<$t>) -> Self {
+                    let (lo, hi) = wmul_split(self.0, rhs.0);
+                    MontyForm(r.reduce_split(lo, hi))
                 }
 
-                impl_reduced_binary_pow!(Word);
+                /// Widening multiplication without the reduction, see [Montgomery::redc()]
+                #[inline]
+                pub fn mul_wide(&self, rhs: &Self) -> MontyForm<DoubleWord> {
+                    MontyForm(wmul(self.0, rhs.0))
+                }
+
+                /// Modular squaring in the Montgomery form
+                #[inline]
+                pub fn sqr(&self, r: &Montgomery<$t>) -> Self {
+                    let (lo, hi) = wmul_split(self.0, self.0);
+                    MontyForm(r.reduce_split(lo, hi))
+                }
+
+                /// Modular exponentiation in the Montgomery form, the exponent is a normal integer
+                #[inline]
+                pub fn pow(&self, exp: &$t, r: &Montgomery<$t>) -> Self {
+                    MontyForm(Reducer::pow(r, self.0, exp))
+                }
+
+                /// Modular inverse in the Montgomery form
+                #[inline]
+                pub fn inv(&self, r: &Montgomery<$t>) -> Option<Self> {
+                    r.monty_inv(self.0).map(MontyForm)
+                }
             }
         }
     };
@@ -206,6 +515,765 @@ impl_montgomery_for!(u64, u64_impl);
 impl_montgomery_for!(u128, u128_impl);
 impl_montgomery_for!(usize, usize_impl);
 
+/// A [Montgomery] reducer for the odd modulus M fixed at compile time, so the integers
+/// don't carry the modulus and the precomputed inverse at runtime. It's used by the
+/// type [ConstMontgomeryInt][crate::ConstMontgomeryInt], which has the size of a [u64].
+///
+/// An even modulus is rejected at compile time.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ConstMontgomeryInt, ModularInteger};
+///
+/// type Mint = ConstMontgomeryInt<998244353>;
+/// let x = Mint::from(3);
+/// assert_eq!(x.pow(&(998244353 - 1)).residue(), 1);
+/// assert_eq!(core::mem::size_of::<Mint>(), 8);
+/// ```
+///
+/// ```compile_fail
+/// use num_modular::ConstMontgomeryInt;
+///
+/// let x = ConstMontgomeryInt::<1000>::from(3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConstMontgomery<const M: u64>;
+
+impl<const M: u64> ConstMontgomery<M> {
+    /// The equivalent runtime reducer, which is evaluated at compile time
+    pub const REDUCER: Montgomery<u64> = Montgomery::<u64>::new(M);
+
+    // R^2 mod M, so that the transform is a multiplication instead of a division
+    const R2: u64 = {
+        let r = (u64::MAX % M + 1) % M;
+        ((r as u128 * r as u128) % M as u128) as u64
+    };
+}
+
+impl<const M: u64> Reducer<u64> for ConstMontgomery<M> {
+    #[inline]
+    fn new(m: &u64) -> Self {
+        assert!(
+            *m == M,
+            "the given modulus doesn't match with the generic params"
+        );
+        Self
+    }
+    #[inline]
+    fn transform(&self, target: u64) -> u64 {
+        Self::REDUCER.mul(&target, &Self::R2)
+    }
+    #[inline]
+    fn check(&self, target: &u64) -> bool {
+        *target < M
+    }
+    #[inline]
+    fn residue(&self, target: u64) -> u64 {
+        Self::REDUCER.residue(target)
+    }
+    #[inline(always)]
+    fn modulus(&self) -> u64 {
+        M
+    }
+    #[inline(always)]
+    fn is_zero(&self, target: &u64) -> bool {
+        *target == 0
+    }
+
+    #[inline(always)]
+    fn add(&self, lhs: &u64, rhs: &u64) -> u64 {
+        Self::REDUCER.add(lhs, rhs)
+    }
+    #[inline(always)]
+    fn dbl(&self, target: u64) -> u64 {
+        Self::REDUCER.dbl(target)
+    }
+    #[inline(always)]
+    fn sub(&self, lhs: &u64, rhs: &u64) -> u64 {
+        Self::REDUCER.sub(lhs, rhs)
+    }
+    #[inline(always)]
+    fn neg(&self, target: u64) -> u64 {
+        Self::REDUCER.neg(target)
+    }
+    #[inline]
+    fn mul(&self, lhs: &u64, rhs: &u64) -> u64 {
+        Self::REDUCER.mul(lhs, rhs)
+    }
+    #[inline]
+    fn sqr(&self, target: u64) -> u64 {
+        Self::REDUCER.sqr(target)
+    }
+    #[inline]
+    fn inv(&self, target: u64) -> Option<u64> {
+        Self::REDUCER.inv(target)
+    }
+    #[inline]
+    fn pow(&self, base: u64, exp: &u64) -> u64 {
+        Self::REDUCER.pow(base, exp)
+    }
+}
+
+impl<const M: u64> From<u64> for ReducedInt<u64, ConstMontgomery<M>> {
+    #[inline]
+    fn from(n: u64) -> Self {
+        ReducedInt::from_reducer(n, ConstMontgomery)
+    }
+}
+
+/// Implement [Pow] on [MontgomeryInt][crate::MontgomeryInt] for exponents of the other widths,
+/// the exponent of the same width is supported by the generic implementation on [ReducedInt].
+/// Note that the inherent `pow(&T)` method takes precedence in the method call syntax,
+/// so call these as `Pow::pow(x, e)`.
+#[cfg(feature = "num-traits")]
+macro_rules! impl_monty_pow_for {
+    ($($t:ty: $($E:ty)*;)*) => {$($(
+        impl Pow<$E> for ReducedInt<$t, Montgomery<$t>> {
+            type Output = Self;
+            #[inline]
+            fn pow(self, exp: $E) -> Self {
+                Pow::pow(&self, exp)
+            }
+        }
+
+        impl Pow<$E> for &ReducedInt<$t, Montgomery<$t>> {
+            type Output = ReducedInt<$t, Montgomery<$t>>;
+            fn pow(self, exp: $E) -> Self::Output {
+                let r = self.reducer();
+                let a = match <$t>::try_from(exp) {
+                    Ok(e) => r.pow(*self.repr(), &e),
+                    Err(_) => {
+                        // the exponent is too large to be converted, fallback to the binary method
+                        let (mut result, mut base, mut exp) = (r.transform(1), *self.repr(), exp);
+                        while exp > 0 {
+                            if exp & 1 != 0 {
+                                result = r.mul(&result, &base);
+                            }
+                            base = r.sqr(base);
+                            exp >>= 1;
+                        }
+                        result
+                    }
+                };
+                ReducedInt::from_parts(a, r.clone())
+            }
+        }
+    )*)*};
+}
+#[cfg(feature = "num-traits")]
+impl_monty_pow_for! {
+    u8: u16 u32 u64 u128 usize;
+    u16: u8 u32 u64 u128 usize;
+    u32: u8 u16 u64 u128 usize;
+    u64: u8 u16 u32 u128 usize;
+    u128: u8 u16 u32 u64 usize;
+    usize: u8 u16 u32 u64 u128;
+}
+
+/// Calculate `a * b * R^-1` with the coarsely integrated operand scanning (CIOS) method on
+/// the n-word (little endian) operands, where `R = 2^(64n)`, `inv = -m^-1 mod 2^64` and
+/// a, b are less than m. The result is less than 2m, so it has n+1 words.
+#[cfg(any(feature = "num-bigint", feature = "ibig"))]
+fn mont_mul_words(a: &[u64], b: &[u64], m: &[u64], inv: u64) -> alloc::vec::Vec<u64> {
+    let n = m.len();
+
+    // t < 2m is kept during the iteration, which needs n+2 words
+    let mut t = alloc::vec![0u64; n + 2];
+    for &bi in b {
+        // t += a * b[i]
+        let mut carry = 0u64;
+        for (tj, &aj) in t.iter_mut().zip(a) {
+            let s = *tj as u128 + aj as u128 * bi as u128 + carry as u128;
+            *tj = s as u64;
+            carry = (s >> 64) as u64;
+        }
+        let s = t[n] as u128 + carry as u128;
+        t[n] = s as u64;
+        t[n + 1] = (s >> 64) as u64;
+
+        // t = (t + q * m) / 2^64, where q is selected to make the lowest word zero
+        let q = t[0].wrapping_mul(inv);
+        let s = t[0] as u128 + q as u128 * m[0] as u128;
+        let mut carry = (s >> 64) as u64;
+        for j in 1..n {
+            let s = t[j] as u128 + q as u128 * m[j] as u128 + carry as u128;
+            t[j - 1] = s as u64;
+            carry = (s >> 64) as u64;
+        }
+        let s = t[n] as u128 + carry as u128;
+        t[n - 1] = s as u64;
+        t[n] = t[n + 1] + (s >> 64) as u64;
+    }
+    t.truncate(n + 1);
+    t
+}
+
+#[cfg(feature = "num-bigint")]
+mod _num_bigint {
+    use super::*;
+    #[cfg(feature = "alloc")]
+    use crate::pow::{reducer_pow_wnaf, WNAF_MIN_BITS, WNAF_WIDTH};
+    use crate::ModularUnaryOps;
+    #[cfg(feature = "alloc")]
+    use crate::SignedDigits;
+    use alloc::vec::Vec;
+    use num_bigint::BigUint;
+    use num_traits::{One, Zero};
+
+    /// Convert the 64-bit words (little endian) into a [BigUint]
+    fn from_u64_digits(digits: &[u64]) -> BigUint {
+        BigUint::new(
+            digits
+                .iter()
+                .flat_map(|&d| [d as u32, (d >> 32) as u32])
+                .collect(),
+        )
+    }
+
+    /// Get the 64-bit words (little endian) of a [BigUint], padded to length n
+    fn to_u64_digits(x: &BigUint, n: usize) -> Vec<u64> {
+        let mut digits = x.to_u64_digits();
+        digits.resize(n, 0);
+        digits
+    }
+
+    impl Montgomery<BigUint> {
+        /// Number of 64-bit words of the modulus, the auxiliary modulus `R` is `2^(64n)`
+        #[inline]
+        fn words(&self) -> usize {
+            ((self.m.bits() + 63) / 64) as usize
+        }
+
+        /// Calculate `a * b * R^-1 mod m` where a and b are less than m
+        fn mont_mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+            let n = self.words();
+            let inv = self.inv.iter_u64_digits().next().unwrap_or(0);
+            let t = from_u64_digits(&mont_mul_words(
+                &to_u64_digits(a, n),
+                &to_u64_digits(b, n),
+                &to_u64_digits(&self.m, n),
+                inv,
+            ));
+            if t >= self.m {
+                t - &self.m
+            } else {
+                t
+            }
+        }
+    }
+
+    /// Montgomery reducer for [BigUint], where the auxiliary modulus `R` is `2^(64n)`
+    /// for an n-word modulus. The multiplications are done with the word-level CIOS method,
+    /// which avoids the multi-precision divisions in [mulm][crate::ModularCoreOps::mulm].
+    impl Reducer<BigUint> for Montgomery<BigUint> {
+        fn new(m: &BigUint) -> Self {
+            assert!(
+                m.bit(0),
+                "Only odd modulus are supported by the Montgomery form"
+            );
+            let r = BigUint::one() << (64 * ((m.bits() + 63) / 64));
+            let inv = &r - m.invm(&r).unwrap(); // m is odd, so it's coprime to R
+            Self { m: m.clone(), inv }
+        }
+        #[inline]
+        fn transform(&self, target: BigUint) -> BigUint {
+            (target << (64 * self.words())) % &self.m
+        }
+        #[inline]
+        fn check(&self, target: &BigUint) -> bool {
+            target < &self.m
+        }
+        #[inline]
+        fn residue(&self, target: BigUint) -> BigUint {
+            self.mont_mul(&target, &BigUint::one())
+        }
+        #[inline]
+        fn modulus(&self) -> BigUint {
+            self.m.clone()
+        }
+        #[inline]
+        fn is_zero(&self, target: &BigUint) -> bool {
+            target.is_zero()
+        }
+
+        #[inline]
+        fn add(&self, lhs: &BigUint, rhs: &BigUint) -> BigUint {
+            let sum = lhs + rhs;
+            if sum >= self.m {
+                sum - &self.m
+            } else {
+                sum
+            }
+        }
+        #[inline]
+        fn dbl(&self, target: BigUint) -> BigUint {
+            self.add(&target, &target)
+        }
+        #[inline]
+        fn sub(&self, lhs: &BigUint, rhs: &BigUint) -> BigUint {
+            if lhs >= rhs {
+                lhs - rhs
+            } else {
+                &self.m - rhs + lhs
+            }
+        }
+        #[inline]
+        fn neg(&self, target: BigUint) -> BigUint {
+            if target.is_zero() {
+                target
+            } else {
+                &self.m - target
+            }
+        }
+        #[inline]
+        fn mul(&self, lhs: &BigUint, rhs: &BigUint) -> BigUint {
+            self.mont_mul(lhs, rhs)
+        }
+        #[inline]
+        fn sqr(&self, target: BigUint) -> BigUint {
+            self.mont_mul(&target, &target)
+        }
+        #[inline]
+        fn inv(&self, target: BigUint) -> Option<BigUint> {
+            self.residue(target)
+                .invm(&self.m)
+                .map(|v| self.transform(v))
+        }
+        fn pow(&self, base: BigUint, exp: &BigUint) -> BigUint {
+            // the inverse costs about as much as dozens of multiplications, which is paid off
+            // by the sparser wNAF digits of long exponents
+            #[cfg(feature = "alloc")]
+            if exp.bits() >= WNAF_MIN_BITS {
+                if let Some(inv) = self.inv(base.clone()) {
+                    let digits = exp.wnaf(WNAF_WIDTH);
+                    return reducer_pow_wnaf(self, base, inv, &digits, WNAF_WIDTH);
+                }
+            }
+
+            let mut result = self.transform(BigUint::one());
+            for i in (0..exp.bits()).rev() {
+                result = self.sqr(result);
+                if exp.bit(i) {
+                    result = self.mul(&result, &base);
+                }
+            }
+            result
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::{ModularCoreOps, ModularInteger, ModularPow, MontgomeryInt};
+        use rand::random;
+
+        fn random_biguint(words: usize) -> BigUint {
+            from_u64_digits(&(0..words).map(|_| random::<u64>()).collect::<Vec<_>>())
+        }
+
+        #[test]
+        fn biguint_montgomery_test() {
+            for &words in &[1usize, 2, 16, 32, 64] {
+                let m = random_biguint(words) | BigUint::one();
+                let r = Montgomery::<BigUint>::new(&m);
+                for _ in 0..3 {
+                    let (a, b) = (random_biguint(words) % &m, random_biguint(words) % &m);
+                    let (ma, mb) = (r.transform(a.clone()), r.transform(b.clone()));
+                    assert!(r.check(&ma));
+                    assert_eq!(r.residue(ma.clone()), a);
+                    assert_eq!(r.residue(r.add(&ma, &mb)), (&a).addm(&b, &m));
+                    assert_eq!(r.residue(r.sub(&ma, &mb)), (&a).subm(&b, &m));
+                    assert_eq!(r.residue(r.mul(&ma, &mb)), (&a).mulm(&b, &m));
+                    assert_eq!(r.residue(r.sqr(ma.clone())), (&a).mulm(&a, &m));
+                    assert_eq!(r.residue(r.dbl(ma.clone())), (&a).dblm(&m));
+                    assert_eq!(r.residue(r.neg(ma.clone())), (&a).negm(&m));
+                    assert_eq!(r.inv(ma.clone()).map(|v| r.residue(v)), (&a).invm(&m));
+                }
+
+                // the boundary values
+                let max = &m - 1u8;
+                let mmax = r.transform(max.clone());
+                assert_eq!(r.residue(r.sqr(mmax.clone())), BigUint::one());
+                assert!(r.is_zero(&r.add(&mmax, &r.transform(BigUint::one()))));
+                assert!(r.is_zero(&r.neg(r.transform(BigUint::zero()))));
+            }
+        }
+
+        #[test]
+        fn biguint_montgomery_int_test() {
+            for &words in &[16usize, 32, 64] {
+                // 1024, 2048 and 4096 bits
+                let m =
+                    random_biguint(words) | BigUint::one() | (BigUint::one() << (64 * words - 1));
+                let (a, b, e) = (
+                    random_biguint(words),
+                    random_biguint(words),
+                    random_biguint(2),
+                );
+                let x = MontgomeryInt::new(a.clone(), &m);
+                let y = x.convert(b.clone());
+                assert_eq!((x.clone() + y.clone()).residue(), (&a).addm(&b, &m));
+                assert_eq!((x.clone() - y.clone()).residue(), (&a).subm(&b, &m));
+                assert_eq!((x.clone() * y.clone()).residue(), (&a).mulm(&b, &m));
+                assert_eq!(x.clone().pow(&e).residue(), (&a).powm(&e, &m));
+
+                // long exponents go through the wNAF digits
+                let e = random_biguint(words);
+                assert_eq!(x.clone().pow(&e).residue(), (&a).powm(&e, &m));
+            }
+
+            // a non-invertible base with a long exponent falls back to the binary method
+            let m = BigUint::from(3u8).pow(200u32);
+            let e = random_biguint(8);
+            let x = MontgomeryInt::new(BigUint::from(6u8), &m);
+            assert_eq!(x.pow(&e).residue(), BigUint::zero());
+        }
+    }
+}
+
+#[cfg(feature = "ibig")]
+mod _ibig {
+    use super::*;
+    use crate::ModularUnaryOps;
+    use alloc::vec::Vec;
+    use ibig::UBig;
+
+    /// Convert the 64-bit words (little endian) into a [UBig]
+    fn from_u64_digits(digits: &[u64]) -> UBig {
+        let bytes: Vec<u8> = digits.iter().flat_map(|d| d.to_le_bytes()).collect();
+        UBig::from_le_bytes(&bytes)
+    }
+
+    /// Get the 64-bit words (little endian) of a [UBig], padded to length n
+    fn to_u64_digits(x: &UBig, n: usize) -> Vec<u64> {
+        let mut digits: Vec<u64> = x
+            .to_le_bytes()
+            .chunks(8)
+            .map(|c| {
+                let mut word = [0u8; 8];
+                word[..c.len()].copy_from_slice(c);
+                u64::from_le_bytes(word)
+            })
+            .collect();
+        digits.resize(n, 0);
+        digits
+    }
+
+    impl Montgomery<UBig> {
+        /// Number of 64-bit words of the modulus, the auxiliary modulus `R` is `2^(64n)`
+        #[inline]
+        fn words(&self) -> usize {
+            (self.m.bit_len() + 63) / 64
+        }
+
+        /// Calculate `a * b * R^-1 mod m` where a and b are less than m
+        fn mont_mul(&self, a: &UBig, b: &UBig) -> UBig {
+            let n = self.words();
+            let inv = to_u64_digits(&self.inv, 1)[0];
+            let t = from_u64_digits(&mont_mul_words(
+                &to_u64_digits(a, n),
+                &to_u64_digits(b, n),
+                &to_u64_digits(&self.m, n),
+                inv,
+            ));
+            if t >= self.m {
+                t - &self.m
+            } else {
+                t
+            }
+        }
+    }
+
+    /// Montgomery reducer for [UBig], where the auxiliary modulus `R` is `2^(64n)` for an
+    /// n-word modulus. It shares the word-level CIOS multiplication with the reducer for
+    /// `BigUint`.
+    impl Reducer<UBig> for Montgomery<UBig> {
+        fn new(m: &UBig) -> Self {
+            assert!(
+                m.bit(0),
+                "Only odd modulus are supported by the Montgomery form"
+            );
+            let r = UBig::from(1u8) << (64 * ((m.bit_len() + 63) / 64));
+            let inv = &r - m.invm(&r).unwrap(); // m is odd, so it's coprime to R
+            Self { m: m.clone(), inv }
+        }
+        #[inline]
+        fn transform(&self, target: UBig) -> UBig {
+            (target << (64 * self.words())) % &self.m
+        }
+        #[inline]
+        fn check(&self, target: &UBig) -> bool {
+            target < &self.m
+        }
+        #[inline]
+        fn residue(&self, target: UBig) -> UBig {
+            self.mont_mul(&target, &UBig::from(1u8))
+        }
+        #[inline]
+        fn modulus(&self) -> UBig {
+            self.m.clone()
+        }
+        #[inline]
+        fn is_zero(&self, target: &UBig) -> bool {
+            target == &UBig::from(0u8)
+        }
+
+        #[inline]
+        fn add(&self, lhs: &UBig, rhs: &UBig) -> UBig {
+            let sum = lhs + rhs;
+            if sum >= self.m {
+                sum - &self.m
+            } else {
+                sum
+            }
+        }
+        #[inline]
+        fn dbl(&self, target: UBig) -> UBig {
+            self.add(&target, &target)
+        }
+        #[inline]
+        fn sub(&self, lhs: &UBig, rhs: &UBig) -> UBig {
+            if lhs >= rhs {
+                lhs - rhs
+            } else {
+                &self.m - rhs + lhs
+            }
+        }
+        #[inline]
+        fn neg(&self, target: UBig) -> UBig {
+            if self.is_zero(&target) {
+                target
+            } else {
+                &self.m - target
+            }
+        }
+        #[inline]
+        fn mul(&self, lhs: &UBig, rhs: &UBig) -> UBig {
+            self.mont_mul(lhs, rhs)
+        }
+        #[inline]
+        fn sqr(&self, target: UBig) -> UBig {
+            self.mont_mul(&target, &target)
+        }
+        #[inline]
+        fn inv(&self, target: UBig) -> Option<UBig> {
+            self.residue(target)
+                .invm(&self.m)
+                .map(|v| self.transform(v))
+        }
+        fn pow(&self, base: UBig, exp: &UBig) -> UBig {
+            let mut result = self.transform(UBig::from(1u8));
+            for i in (0..exp.bit_len()).rev() {
+                result = self.sqr(result);
+                if exp.bit(i) {
+                    result = self.mul(&result, &base);
+                }
+            }
+            result
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::{ModularCoreOps, ModularInteger, ModularPow, MontgomeryInt};
+        use rand::random;
+
+        fn random_ubig(words: usize) -> UBig {
+            from_u64_digits(&(0..words).map(|_| random::<u64>()).collect::<Vec<_>>())
+        }
+
+        #[test]
+        fn ubig_montgomery_test() {
+            for &words in &[1usize, 2, 16, 32] {
+                let m = random_ubig(words) | UBig::from(1u8);
+                let r = Montgomery::<UBig>::new(&m);
+                for _ in 0..3 {
+                    let (a, b) = (random_ubig(words) % &m, random_ubig(words) % &m);
+                    let (ma, mb) = (r.transform(a.clone()), r.transform(b.clone()));
+                    assert!(r.check(&ma));
+                    assert_eq!(r.residue(ma.clone()), a);
+                    assert_eq!(r.residue(r.add(&ma, &mb)), (&a).addm(&b, &m));
+                    assert_eq!(r.residue(r.sub(&ma, &mb)), (&a).subm(&b, &m));
+                    assert_eq!(r.residue(r.mul(&ma, &mb)), (&a).mulm(&b, &m));
+                    assert_eq!(r.residue(r.sqr(ma.clone())), (&a).mulm(&a, &m));
+                    assert_eq!(r.residue(r.neg(ma.clone())), (&a).negm(&m));
+                    assert_eq!(r.inv(ma.clone()).map(|v| r.residue(v)), (&a).invm(&m));
+                }
+
+                // the boundary values
+                let mmax = r.transform(&m - UBig::from(1u8));
+                assert_eq!(r.residue(r.sqr(mmax.clone())), UBig::from(1u8));
+                assert!(r.is_zero(&r.add(&mmax, &r.transform(UBig::from(1u8)))));
+            }
+        }
+
+        #[test]
+        fn ubig_montgomery_int_test() {
+            // 1024 bits
+            let m = random_ubig(16) | UBig::from(1u8) | (UBig::from(1u8) << 1023);
+            let (a, b, e) = (random_ubig(16), random_ubig(16), random_ubig(2));
+            let x = MontgomeryInt::new(a.clone(), &m);
+            let y = x.convert(b.clone());
+            assert_eq!((x.clone() + y.clone()).residue(), (&a).addm(&b, &m));
+            assert_eq!((x.clone() * y.clone()).residue(), (&a).mulm(&b, &m));
+            assert_eq!(x.pow(&e).residue(), (&a).powm(&e, &m));
+        }
+    }
+}
+
+#[cfg(feature = "primitive-types")]
+mod _primitive_types {
+    use super::*;
+    use crate::ptypes::{add_reduced, sub_reduced};
+    use crate::{ModularCoreOps, ModularUnaryOps};
+    use primitive_types::{U128, U256, U512};
+
+    /// Calculate `a * b * R^-1` with the CIOS method on the N-word (little endian) operands,
+    /// where `R = 2^(64N)`, `inv = -m^-1 mod 2^64` and a, b are less than m. The result
+    /// is less than 2m, it's returned as the lower N words and the carry.
+    fn mont_mul_fixed<const N: usize>(
+        a: &[u64; N],
+        b: &[u64; N],
+        m: &[u64; N],
+        inv: u64,
+    ) -> ([u64; N], bool) {
+        // t < 2m is kept during the iteration, with t_n as the word above the lower N words
+        let mut t = [0u64; N];
+        let mut t_n = 0u64;
+        for &bi in b {
+            // t += a * b[i]
+            let mut carry = 0u64;
+            for (tj, &aj) in t.iter_mut().zip(a) {
+                let s = *tj as u128 + aj as u128 * bi as u128 + carry as u128;
+                *tj = s as u64;
+                carry = (s >> 64) as u64;
+            }
+            let s = t_n as u128 + carry as u128;
+            t_n = s as u64;
+            let t_n1 = (s >> 64) as u64;
+
+            // t = (t + q * m) / 2^64, where q is selected to make the lowest word zero
+            let q = t[0].wrapping_mul(inv);
+            let s = t[0] as u128 + q as u128 * m[0] as u128;
+            let mut carry = (s >> 64) as u64;
+            for j in 1..N {
+                let s = t[j] as u128 + q as u128 * m[j] as u128 + carry as u128;
+                t[j - 1] = s as u64;
+                carry = (s >> 64) as u64;
+            }
+            let s = t_n as u128 + carry as u128;
+            t[N - 1] = s as u64;
+            t_n = t_n1 + (s >> 64) as u64;
+        }
+        (t, t_n != 0)
+    }
+
+    macro_rules! impl_ptypes_montgomery_for {
+        ($T:ident, $N:literal) => {
+            impl Montgomery<$T> {
+                /// Calculate `a * b * R^-1 mod m` with `R = 2^(64N)`, where a, b < m
+                fn mont_mul(&self, a: &$T, b: &$T) -> $T {
+                    let (t, carry) = mont_mul_fixed(&a.0, &b.0, &self.m.0, self.inv.low_u64());
+                    let r = $T(t);
+                    if carry || r >= self.m {
+                        r.overflowing_sub(self.m).0
+                    } else {
+                        r
+                    }
+                }
+            }
+
+            impl Reducer<$T> for Montgomery<$T> {
+                fn new(m: &$T) -> Self {
+                    assert!(
+                        m.bit(0),
+                        "Only odd modulus are supported by the Montgomery form"
+                    );
+                    // m^-1 mod 2^64 from the table, lifted by Newton iterations i <- i * (2 - i * m)
+                    let mut i = $T::from(neg_mod_inv::u64::neginv(m.low_u64()).wrapping_neg());
+                    let two = $T::from(2u8);
+                    for _ in 0..($N as u32).trailing_zeros() {
+                        i = i
+                            .overflowing_mul(two.overflowing_sub(i.overflowing_mul(*m).0).0)
+                            .0;
+                    }
+                    Self {
+                        m: *m,
+                        inv: $T::zero().overflowing_sub(i).0,
+                    }
+                }
+                #[inline]
+                fn transform(&self, target: $T) -> $T {
+                    // R mod m = (2^(64N) - 1) mod m + 1, which might equal m
+                    let r = ($T::MAX % self.m + 1) % self.m;
+                    target.mulm(&r, &self.m)
+                }
+                #[inline]
+                fn check(&self, target: &$T) -> bool {
+                    *target < self.m
+                }
+                #[inline]
+                fn residue(&self, target: $T) -> $T {
+                    self.mont_mul(&target, &$T::one())
+                }
+                #[inline(always)]
+                fn modulus(&self) -> $T {
+                    self.m
+                }
+                #[inline(always)]
+                fn is_zero(&self, target: &$T) -> bool {
+                    target.is_zero()
+                }
+
+                #[inline]
+                fn add(&self, lhs: &$T, rhs: &$T) -> $T {
+                    add_reduced(*lhs, *rhs, self.m)
+                }
+                #[inline]
+                fn dbl(&self, target: $T) -> $T {
+                    add_reduced(target, target, self.m)
+                }
+                #[inline]
+                fn sub(&self, lhs: &$T, rhs: &$T) -> $T {
+                    sub_reduced(*lhs, *rhs, self.m)
+                }
+                #[inline]
+                fn neg(&self, target: $T) -> $T {
+                    sub_reduced($T::zero(), target, self.m)
+                }
+                #[inline]
+                fn mul(&self, lhs: &$T, rhs: &$T) -> $T {
+                    self.mont_mul(lhs, rhs)
+                }
+                #[inline]
+                fn sqr(&self, target: $T) -> $T {
+                    self.mont_mul(&target, &target)
+                }
+                #[inline]
+                fn inv(&self, target: $T) -> Option<$T> {
+                    self.residue(target)
+                        .invm(&self.m)
+                        .map(|v| self.transform(v))
+                }
+                fn pow(&self, base: $T, exp: &$T) -> $T {
+                    let mut result = self.transform($T::one());
+                    for i in (0..exp.bits()).rev() {
+                        result = self.sqr(result);
+                        if exp.bit(i) {
+                            result = self.mul(&result, &base);
+                        }
+                    }
+                    result
+                }
+            }
+        };
+    }
+    impl_ptypes_montgomery_for!(U128, 2);
+    impl_ptypes_montgomery_for!(U256, 4);
+    impl_ptypes_montgomery_for!(U512, 8);
+}
+
 // TODO(v0.6.x): accept even numbers by removing 2 factors from m and store the exponent
 // Requirement: 1. A separate class to perform modular arithmetics with 2^n as modulus
 //              2. Algorithm for construct residue from two components (see http://koclab.cs.ucsb.edu/teaching/cs154/docx/Notes7-Montgomery.pdf)
@@ -214,6 +1282,7 @@ impl_montgomery_for!(usize, usize_impl);
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{ModularCoreOps, ModularPow, ModularUnaryOps, MontgomeryInt};
     use rand::random;
 
     const NRANDOM: u32 = 10;
@@ -263,6 +1332,322 @@ mod tests {
         }
     }
 
+    #[test]
+    fn neginv_test() {
+        use neg_mod_inv::BINV_TABLE;
+        for (i, &v) in BINV_TABLE.iter().enumerate() {
+            assert_eq!((2 * i as u8 + 1).wrapping_mul(v), 1);
+        }
+
+        for m in (1..=u8::MAX).step_by(2) {
+            assert_eq!(m.wrapping_mul(neg_mod_inv::u8::neginv(m)), u8::MAX);
+        }
+        for m in (1..=u16::MAX).step_by(2) {
+            assert_eq!(m.wrapping_mul(neg_mod_inv::u16::neginv(m)), u16::MAX);
+        }
+        for _ in 0..1000 {
+            let m = random::<u32>() | 1;
+            assert_eq!(m.wrapping_mul(neg_mod_inv::u32::neginv(m)), u32::MAX);
+            let m = random::<u64>() | 1;
+            assert_eq!(m.wrapping_mul(neg_mod_inv::u64::neginv(m)), u64::MAX);
+            let m = random::<u128>() | 1;
+            assert_eq!(m.wrapping_mul(neg_mod_inv::u128::neginv(m)), u128::MAX);
+            let m = random::<usize>() | 1;
+            assert_eq!(m.wrapping_mul(neg_mod_inv::usize::neginv(m)), usize::MAX);
+        }
+        for &m in [1u128, 3, u128::MAX, u128::MAX - 2, (1 << 127) + 1].iter() {
+            assert_eq!(m.wrapping_mul(neg_mod_inv::u128::neginv(m)), u128::MAX);
+        }
+    }
+
+    #[test]
+    fn validate_test() {
+        use crate::ModularError;
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                let m = random::<$T>() | 1;
+                let r = Montgomery::<$T>::new(m);
+                let (rm, rinv) = r.raw_parts();
+                assert_eq!(rm, m);
+                assert_eq!(Montgomery::<$T>::from_raw_parts(rm, rinv).validate(), Ok(()));
+
+                let x = MontgomeryInt::<$T>::new(random(), &m);
+                assert_eq!(x.validate(), Ok(()));
+                let y = MontgomeryInt::<$T>::from_raw(*x.repr(), r);
+                assert_eq!(y.validate(), Ok(()));
+                assert_eq!(y, x);
+
+                // corrupt each invariant independently
+                let bad = Montgomery::<$T> { m: m & !1, inv: rinv };
+                assert_eq!(bad.validate(), Err(ModularError::EvenModulus));
+                let bad = Montgomery::<$T> { m, inv: rinv ^ 2 };
+                assert_eq!(bad.validate(), Err(ModularError::InvalidInverse));
+                let bad = Montgomery::<$T> { m, inv: rinv.wrapping_add(1) };
+                assert_eq!(bad.validate(), Err(ModularError::InvalidInverse));
+                let bad = MontgomeryInt::<$T>::from_parts(m, r);
+                assert_eq!(bad.validate(), Err(ModularError::Unreduced));
+                let bad = MontgomeryInt::<$T>::from_parts(<$T>::MAX, r);
+                assert_eq!(bad.validate(), Err(ModularError::Unreduced));
+                let bad = MontgomeryInt::<$T>::from_parts(0, Montgomery::<$T> { m, inv: 0 });
+                assert_eq!(bad.validate(), Err(ModularError::InvalidInverse));
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+    }
+
+    #[test]
+    fn narrow_mul_test() {
+        macro_rules! tests_for {
+            ($($T:ident)*) => ($({
+                use crate::word::$T::{split, wmul, wmul_halves};
+                let edges = [0, 1, 2, <$T>::MAX, <$T>::MAX - 1, <$T>::MAX >> 1, 1 << (<$T>::BITS / 2)];
+                for &a in edges.iter() {
+                    for &b in edges.iter() {
+                        assert_eq!(wmul_halves(a, b), split(wmul(a, b)));
+                    }
+                }
+                for _ in 0..1000 {
+                    let (a, b) = (random::<$T>(), random::<$T>());
+                    assert_eq!(wmul_halves(a, b), split(wmul(a, b)));
+                }
+            })*);
+        }
+        tests_for!(u32 u64 usize);
+
+        // all odd u16 moduli with the u32 reducer, against the widening implementation
+        for m in (1..=u16::MAX as u32).step_by(2) {
+            let r = Montgomery::<u32>::new(m);
+            let (a, b) = (random::<u32>() % m, random::<u32>() % m);
+            let (am, bm) = (r.transform(a), r.transform(b));
+            let expected = (a as u64 * b as u64 % m as u64) as u32;
+            assert_eq!(r.residue(r.mul(&am, &bm)), expected);
+            assert_eq!(
+                r.residue(r.sqr(am)),
+                (a as u64 * a as u64 % m as u64) as u32
+            );
+            assert_eq!(r.mul(&am, &bm), r.reduce(crate::word::u32::wmul(am, bm)));
+        }
+        for _ in 0..NRANDOM * 100 {
+            let m = random::<u32>() | 1;
+            let r = Montgomery::<u32>::new(m);
+            let (a, b) = (random::<u32>() % m, random::<u32>() % m);
+            let (am, bm) = (r.transform(a), r.transform(b));
+            assert_eq!(r.residue(r.mul(&am, &bm)), a.mulm(b, &m));
+            assert_eq!(r.mul(&am, &bm), r.reduce(crate::word::u32::wmul(am, bm)));
+
+            let m = random::<u64>() | 1;
+            let r = Montgomery::<u64>::new(m);
+            let (a, b) = (random::<u64>() % m, random::<u64>() % m);
+            let (am, bm) = (r.transform(a), r.transform(b));
+            assert_eq!(r.residue(r.mul(&am, &bm)), a.mulm(b, &m));
+            assert_eq!(r.mul(&am, &bm), r.reduce(crate::word::u64::wmul(am, bm)));
+        }
+    }
+
+    #[test]
+    fn monty_inv_test() {
+        // exhaustive on u8, including the unit modulus
+        for m in (1..=u8::MAX).step_by(2) {
+            let r = Montgomery::<u8>::new(m);
+            for a in 0..m {
+                let inv = r.inv(r.transform(a)).map(|v| r.residue(v));
+                assert_eq!(inv, a.invm(&m), "{}^-1 mod {}", a, m);
+            }
+        }
+
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                let ms = [<$T>::MAX, <$T>::MAX - 2, <$T>::MAX / 2, random::<$T>() | 1, 3];
+                for &m in ms.iter() {
+                    let r = Montgomery::<$T>::new(m);
+                    for _ in 0..NRANDOM {
+                        let a = random::<$T>() % m;
+                        let inv = r.inv(r.transform(a)).map(|v| r.residue(v));
+                        assert_eq!(inv, a.invm(&m), "{}^-1 mod {}", a, m);
+                    }
+                    assert_eq!(r.inv(r.transform(m - 1)).map(|v| r.residue(v)), Some(m - 1));
+                }
+            )*);
+        }
+        tests_for!(u16 u32 u64 u128 usize);
+
+        // division with and without panicking
+        let m = 3u64 * 5 * 1_000_000_007;
+        let (x, y) = (
+            MontgomeryInt::new(12345u64, &m),
+            MontgomeryInt::new(67u64, &m),
+        );
+        assert_eq!(x.checked_div(&y).map(|q| q * y), Some(x));
+        assert_eq!(x.checked_div(&MontgomeryInt::new(10, &m)), None);
+        assert_eq!((x / y) * y, x);
+    }
+
+    #[test]
+    #[should_panic]
+    fn monty_div_non_unit_test() {
+        let m = 3u64 * 5 * 1_000_000_007;
+        let _ = MontgomeryInt::new(1u64, &m) / MontgomeryInt::new(6, &m);
+    }
+
+    #[test]
+    fn monty_form_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..NRANDOM {
+                    let m = random::<$T>() | 1;
+                    let r = Montgomery::<$T>::new(m);
+                    let (a, b, e) = (random::<$T>() % m, random::<$T>() % m, random::<$T>());
+                    let (am, bm) = (r.to_monty(a), r.to_monty(b));
+                    assert_eq!(r.from_monty(am.add(&bm, &r)), a.addm(b, &m));
+                    assert_eq!(r.from_monty(am.sub(&bm, &r)), a.subm(b, &m));
+                    assert_eq!(r.from_monty(am.neg(&r)), a.negm(&m));
+                    assert_eq!(r.from_monty(am.mul(&bm, &r)), a.mulm(b, &m));
+                    assert_eq!(r.from_monty(am.sqr(&r)), a.mulm(a, &m));
+                    assert_eq!(r.from_monty(am.pow(&e, &r)), a.powm(e, &m));
+                    assert_eq!(am.inv(&r).map(|v| r.from_monty(v)), a.invm(&m));
+
+                    // interop with the raw representation and MontgomeryInt
+                    let x = MontgomeryInt::new(a, &m);
+                    assert_eq!(x.monty_form(), am);
+                    assert_eq!(<$T>::from(am), *x.repr());
+                    assert_eq!(MontyForm::from_raw_unchecked(*x.repr()), am);
+                    assert_eq!(MontgomeryInt::<$T>::from_monty_form(am, r), x);
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+
+        // REDC of a double-width product
+        let m = random::<u64>() | 1;
+        let r = Montgomery::<u64>::new(m);
+        let (a, b) = (r.to_monty(random::<u64>()), r.to_monty(random::<u64>()));
+        assert_eq!(*a.mul_wide(&b).raw(), *a.raw() as u128 * *b.raw() as u128);
+        assert_eq!(r.redc(a.mul_wide(&b)), a.mul(&b, &r));
+    }
+
+    #[test]
+    fn sub_neg_boundary_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for &m in [1, 3, <$T>::MAX, <$T>::MAX - 2, <$T>::MAX / 2 + 1 | 1].iter() {
+                    let r = Montgomery::<$T>::new(m);
+                    let values = [0, 1 % m, m - 1, m / 2];
+                    for &a in values.iter() {
+                        let am = r.transform(a);
+                        assert_eq!(r.residue(r.neg(am)), a.negm(&m));
+                        assert!(r.check(&r.neg(am)));
+                        for &b in values.iter() {
+                            let bm = r.transform(b);
+                            assert_eq!(r.residue(r.sub(&am, &bm)), a.subm(b, &m), "{} - {} mod {}", a, b, m);
+                            assert!(r.check(&r.sub(&am, &bm)));
+                        }
+                    }
+                    assert_eq!(r.neg(0), 0);
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn unreduced_operand_test() {
+        let r = Montgomery::<u32>::new(7);
+        r.sub(&9, &1);
+    }
+
+    #[test]
+    fn width_conversion_test() {
+        use crate::{ModularInteger, MontgomeryInt};
+        macro_rules! tests_for {
+            ($($S:ty => $($L:ty)*;)*) => ($($(
+                let m = random::<$S>() | 1;
+                let a = random::<$S>();
+                let x = MontgomeryInt::<$S>::new(a, &m);
+                let y = x.widen::<$L>();
+                assert_eq!(y.modulus(), m as $L);
+                assert_eq!(y.residue(), (a % m) as $L);
+                assert_eq!(y.validate(), Ok(()));
+                assert_eq!(y.try_narrow::<$S>(), Some(x));
+
+                // narrowing fails if the modulus is too large
+                let m = (random::<$L>() | 1).max(<$S>::MAX as $L + 2);
+                let x = MontgomeryInt::<$L>::new(random(), &m);
+                assert_eq!(x.try_narrow::<$S>(), None);
+            )*)*);
+        }
+        tests_for!(
+            u8 => u16 u32 u64 u128;
+            u16 => u32 u64 u128;
+            u32 => u64 u128;
+            u64 => u128;
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "num-traits")]
+    fn pow_trait_test() {
+        use crate::ModularInteger;
+        use num_traits::Pow;
+
+        // Fermat's little theorem reduces the exponents of any width
+        macro_rules! tests_for {
+            ($($t:ty: $p:expr => $($E:ty)*;)*) => ($($(
+                let p: $t = $p;
+                for _ in 0..NRANDOM {
+                    let a = random::<$t>() % (p - 1) + 1;
+                    let x = MontgomeryInt::<$t>::new(a, &p);
+                    let e = random::<$E>();
+                    let expected = a.powm((e as u128 % (p as u128 - 1)) as $t, &p);
+                    assert_eq!(Pow::pow(x, e).residue(), expected, "{}^{} mod {}", a, e, p);
+                    assert_eq!(Pow::pow(&x, e), Pow::pow(x, e));
+                    assert_eq!(Pow::pow(x, 0 as $E).residue(), 1);
+                }
+            )*)*);
+        }
+        tests_for!(
+            u8: 251 => u16 u32 u64 u128 usize;
+            u16: 65521 => u8 u32 u64 u128 usize;
+            u32: 4294967291 => u8 u16 u64 u128 usize;
+            u64: 0xffffffff00000001 => u8 u16 u32 u128 usize;
+            usize: 65537 => u8 u16 u32 u64 u128;
+        );
+
+        let x = MontgomeryInt::<u128>::new(3, &((1 << 127) - 1));
+        assert_eq!(Pow::pow(x, u64::MAX), x.pow(&(u64::MAX as u128)));
+        assert_eq!(Pow::pow(x, 7u8).residue(), 2187);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn from_raw_debug_test() {
+        let r = Montgomery::<u64>::new(13);
+        let _ = crate::MontgomeryInt::<u64>::from_raw(13, r);
+    }
+
+    #[test]
+    fn window_pow_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..NRANDOM {
+                    let m = random::<$T>() | 1;
+                    let r = Montgomery::<$T>::new(m);
+                    let v = Vanilla::<$T>::new(&m);
+                    let a = random::<$T>();
+                    // exponents of all lengths, covering each window width
+                    let e = random::<$T>() >> (random::<u32>() % <$T>::BITS);
+                    for &e in [e, 0, 1, 2, 3, <$T>::MAX, <$T>::MAX >> 1, 1 << (<$T>::BITS - 1)].iter() {
+                        let expected = v.pow(a % m, &e);
+                        assert_eq!(r.residue(r.pow(r.transform(a), &e)), expected, "{}^{} mod {}", a, e, m);
+                    }
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+    }
+
     #[test]
     fn test_against_modops() {
         use crate::reduced::tests::ReducedTester;
@@ -275,4 +1660,36 @@ mod tests {
             ReducedTester::<usize>::test_against_modops::<Montgomery<usize>>(1);
         }
     }
+
+    #[test]
+    fn const_montgomery_test() {
+        use crate::reduced::tests::ReducedTester;
+        use crate::{ConstMontgomeryInt, ModularInteger, MontgomeryInt};
+
+        macro_rules! tests_for {
+            ($($m:expr),*) => ($(
+                ReducedTester::<u64>::test_boundary::<ConstMontgomery<{ $m }>>($m);
+                for _ in 0..NRANDOM {
+                    let (a, b, e) = (random::<u64>(), random::<u64>(), random::<u64>());
+                    let (x, y) = (ConstMontgomeryInt::<{ $m }>::from(a), ConstMontgomeryInt::<{ $m }>::from(b));
+                    let (xr, yr) = (MontgomeryInt::new(a, &$m), MontgomeryInt::new(b, &$m));
+                    assert_eq!(x.repr(), xr.repr());
+                    assert_eq!((x * y).residue(), (xr * yr).residue());
+                    assert_eq!((x + y).residue(), (xr + yr).residue());
+                    assert_eq!((x - y).residue(), (xr - yr).residue());
+                    assert_eq!(x.pow(&e).residue(), xr.pow(&e).residue());
+                    assert_eq!(x.inv().map(|v| v.residue()), xr.inv().map(|v| v.residue()));
+                }
+            )*);
+        }
+        tests_for!(
+            3,
+            998244353,
+            1000000007,
+            0xffffffff00000001,
+            18446744073709551557,
+            u64::MAX
+        );
+        assert_eq!(ConstMontgomeryInt::<1>::from(5).residue(), 0);
+    }
 }
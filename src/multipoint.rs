@@ -0,0 +1,190 @@
+//! Multipoint evaluation and interpolation of polynomials with subproduct trees
+
+use crate::ntt::convert_u64;
+use crate::{invm_batch_or_factor, ModularInteger, Poly};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Segments with at most this many points are evaluated directly by Horner's method
+const DIRECT_EVAL_LEN: usize = 16;
+
+/// The subproduct tree of the points `x_0, ..., x_(n-1)`, where each node stores the product
+/// of `t - x_i` over its segment. The children of the node k are `2k + 1` and `2k + 2`.
+struct SubproductTree<'a, M> {
+    points: &'a [M],
+    nodes: Vec<Poly<M>>,
+}
+
+impl<'a, M: ModularInteger + Clone> SubproductTree<'a, M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    fn new(points: &'a [M]) -> Self {
+        let zero = convert_u64(&points[0], 0);
+        let mut tree = Self {
+            points,
+            nodes: alloc::vec![Poly::zero(&zero); 4 * points.len()],
+        };
+        tree.build(0, 0, points.len());
+        tree
+    }
+
+    fn build(&mut self, k: usize, l: usize, r: usize) {
+        self.nodes[k] = if r - l == 1 {
+            let x = &self.points[l];
+            Poly::new(alloc::vec![-x.clone(), convert_u64(x, 1)], x)
+        } else {
+            let mid = (l + r) / 2;
+            self.build(2 * k + 1, l, mid);
+            self.build(2 * k + 2, mid, r);
+            &self.nodes[2 * k + 1] * &self.nodes[2 * k + 2]
+        };
+    }
+
+    /// Evaluate f at the points in `[l, r)`, where f has been reduced by the node k
+    fn eval(&self, f: &Poly<M>, k: usize, l: usize, r: usize, out: &mut Vec<M>) {
+        if r - l <= DIRECT_EVAL_LEN {
+            out.extend(self.points[l..r].iter().map(|x| f.eval(x)));
+            return;
+        }
+        let mid = (l + r) / 2;
+        self.eval(&(f % &self.nodes[2 * k + 1]), 2 * k + 1, l, mid, out);
+        self.eval(&(f % &self.nodes[2 * k + 2]), 2 * k + 2, mid, r, out);
+    }
+
+    /// Calculate `sum w_i * prod_(j != i) (t - x_j)` over the points in `[l, r)`
+    fn combine(&self, weights: &[M], k: usize, l: usize, r: usize) -> Poly<M> {
+        if r - l == 1 {
+            return Poly::monomial(weights[l].clone(), 0);
+        }
+        let mid = (l + r) / 2;
+        let left = self.combine(weights, 2 * k + 1, l, mid);
+        let right = self.combine(weights, 2 * k + 2, mid, r);
+        &(&left * &self.nodes[2 * k + 2]) + &(&right * &self.nodes[2 * k + 1])
+    }
+}
+
+impl<M: ModularInteger + Clone> Poly<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    /// Evaluate the polynomial at all the points. The polynomial is reduced down the
+    /// subproduct tree of the points, so it takes `O(n log^2 n)` operations for n points
+    /// and a polynomial of degree `O(n)` when the modulus is NTT-friendly.
+    ///
+    /// The modulus should be a prime, see [div_rem()][Poly::div_rem].
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{ModularInteger, MontgomeryInt, Poly};
+    ///
+    /// let x = MontgomeryInt::new(0u64, &998244353);
+    /// let f = Poly::from_residues([1, 0, 1], &x); // 1 + t^2
+    /// let points: Vec<_> = (0..5).map(|v| x.convert(v)).collect();
+    /// let values: Vec<_> = f.eval_points(&points).iter().map(|v| v.residue()).collect();
+    /// assert_eq!(values, [1, 2, 5, 10, 17]);
+    /// ```
+    pub fn eval_points(&self, points: &[M]) -> Vec<M> {
+        if points.is_empty() {
+            return Vec::new();
+        }
+        let tree = SubproductTree::new(points);
+        let mut out = Vec::with_capacity(points.len());
+        tree.eval(&(self % &tree.nodes[0]), 0, 0, points.len(), &mut out);
+        out
+    }
+
+    /// Find the polynomial of degree less than n passing through the n points
+    /// `(points[i], values[i])`, with the Lagrange formula evaluated on the subproduct tree
+    /// in `O(n log^2 n)` operations when the modulus is NTT-friendly. The denominators
+    /// `P'(x_i)` (where `P = prod (t - x_i)`) are inverted at once by
+    /// [invm_batch_or_factor()].
+    ///
+    /// It returns [None] if the points are not distinct, or more generally, if a difference
+    /// of two points is not invertible. The modulus should be a prime.
+    ///
+    /// # Panics
+    /// if the points are empty or the numbers of points and values differ
+    ///
+    /// Example code:
+    /// ```rust
+    /// use num_modular::{ModularInteger, MontgomeryInt, Poly};
+    ///
+    /// let x = MontgomeryInt::new(0u64, &998244353);
+    /// let points: Vec<_> = [0, 1, 2].iter().map(|&v| x.convert(v)).collect();
+    /// let values: Vec<_> = [1, 2, 5].iter().map(|&v| x.convert(v)).collect();
+    /// let f = Poly::interpolate(&points, &values).unwrap();
+    /// assert_eq!(f.residues(), [1, 0, 1]);
+    /// assert!(Poly::interpolate(&[x.convert(1), x.convert(1)], &values[..2]).is_none());
+    /// ```
+    pub fn interpolate(points: &[M], values: &[M]) -> Option<Self> {
+        assert!(!points.is_empty(), "at least one point is required");
+        assert_eq!(
+            points.len(),
+            values.len(),
+            "the numbers of points and values should be the same"
+        );
+
+        let tree = SubproductTree::new(points);
+        let mut dp = Vec::with_capacity(points.len());
+        tree.eval(&tree.nodes[0].derivative(), 0, 0, points.len(), &mut dp);
+
+        let m: u64 = points[0].modulus().into();
+        let residues: Vec<u64> = dp.iter().map(|v| v.residue().into()).collect();
+        let dp_inv = invm_batch_or_factor(&residues, &m).ok()?;
+        let weights: Vec<M> = values
+            .iter()
+            .zip(dp_inv)
+            .map(|(y, v)| y.clone() * convert_u64(y, v))
+            .collect();
+        Some(tree.combine(&weights, 0, 0, points.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MontgomeryInt;
+    use rand::random;
+
+    type Mint = MontgomeryInt<u64>;
+
+    fn random_points(x: &Mint, n: usize) -> Vec<Mint> {
+        (0..n).map(|_| x.convert(random())).collect()
+    }
+
+    #[test]
+    fn eval_points_test() {
+        for &p in [998244353u64, 1_000_000_007].iter() {
+            let x = Mint::new(0, &p);
+            for &(deg, n) in [(0usize, 1usize), (5, 3), (10, 40), (300, 100), (50, 500)].iter() {
+                let f = Poly::new(random_points(&x, deg + 1), &x);
+                let points = random_points(&x, n);
+                let values = f.eval_points(&points);
+                let expected: Vec<Mint> = points.iter().map(|v| f.eval(v)).collect();
+                assert_eq!(values, expected, "p = {}, deg = {}, n = {}", p, deg, n);
+            }
+            assert!(Poly::zero(&x).eval_points(&[]).is_empty());
+        }
+    }
+
+    #[test]
+    fn interpolate_test() {
+        for &p in [998244353u64, 1_000_000_007].iter() {
+            let x = Mint::new(0, &p);
+            for &n in [1usize, 2, 17, 100, 300].iter() {
+                let f = Poly::new(random_points(&x, n), &x);
+                let points: Vec<Mint> = (0..n as u64).map(|i| x.convert(i * 7 + 3)).collect();
+                let values = f.eval_points(&points);
+                assert_eq!(Poly::interpolate(&points, &values).unwrap(), f);
+            }
+        }
+
+        // repeated points
+        let x = Mint::new(0, &998244353);
+        let mut points = random_points(&x, 50);
+        points[40] = points[3];
+        let values = random_points(&x, 50);
+        assert!(Poly::interpolate(&points, &values).is_none());
+    }
+}
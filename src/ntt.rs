@@ -0,0 +1,515 @@
+//! Number-theoretic transforms over [ModularInteger] with an NTT-friendly prime modulus
+
+#[cfg(feature = "alloc")]
+use crate::{ConstMontgomeryInt, ModularCoreOps, ModularUnaryOps};
+use crate::{FindQnr, ModularInteger};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Calculate x^exp with the operators of [ModularInteger]
+pub(crate) fn pow_u64<M: ModularInteger + Clone>(x: &M, mut exp: u64) -> M
+where
+    M::Base: TryFrom<u64>,
+{
+    let mut result = convert_u64(x, 1);
+    let mut base = x.clone();
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base.clone();
+        }
+        base = base.square();
+        exp >>= 1;
+    }
+    result
+}
+
+#[inline]
+pub(crate) fn convert_u64<M: ModularInteger>(x: &M, n: u64) -> M
+where
+    M::Base: TryFrom<u64>,
+{
+    x.convert(
+        M::Base::try_from(n)
+            .ok()
+            .expect("the value should fit in the base type"),
+    )
+}
+
+/// Get the largest power of two dividing `p - 1`, which is the maximum length of the
+/// transforms modulo the prime p (the ring of x).
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ntt_max_len, MontgomeryInt};
+///
+/// let x = MontgomeryInt::new(0u64, &998244353); // p - 1 = 2^23 * 7 * 17
+/// assert_eq!(ntt_max_len(&x), 1 << 23);
+/// ```
+pub fn ntt_max_len<M: ModularInteger>(x: &M) -> u64
+where
+    M::Base: Into<u64>,
+{
+    let p: u64 = x.modulus().into();
+    1 << (p - 1).trailing_zeros()
+}
+
+/// Find a primitive n-th root of unity modulo the prime p (the ring of x), where n is a
+/// power of two. It returns [None] if n is not a power of two or n doesn't divide `p - 1`.
+///
+/// The root is `z^((p-1)/n)` for the smallest quadratic non-residue z (see [find_qnr()][crate::find_qnr()]),
+/// whose order is exactly n since `z^((p-1)/2) = -1`. The primality of p is not checked.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{root_of_unity, ModularInteger, MontgomeryInt};
+///
+/// let x = MontgomeryInt::new(0u64, &998244353);
+/// let w = root_of_unity(&x, 1 << 20).unwrap();
+/// assert_eq!(w.pow(&(1 << 20)).residue(), 1);
+/// assert_ne!(w.pow(&(1 << 19)).residue(), 1);
+/// assert!(root_of_unity(&x, 1 << 24).is_none());
+/// ```
+pub fn root_of_unity<M: ModularInteger + Clone>(x: &M, n: u64) -> Option<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    let p: u64 = x.modulus().into();
+    if !n.is_power_of_two() || (p - 1) % n != 0 {
+        return None;
+    }
+    if n == 1 {
+        return Some(convert_u64(x, 1));
+    }
+    let z = convert_u64(x, u64::find_qnr(&p));
+    Some(pow_u64(&z, (p - 1) / n))
+}
+
+/// Reorder the slice by the bit-reversed indices
+fn bit_reverse<T>(a: &mut [T]) {
+    let n = a.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j ^= bit;
+        if i < j {
+            a.swap(i, j);
+        }
+    }
+}
+
+/// Iterative Cooley-Tukey transform with a primitive n-th root of unity
+fn transform<M: ModularInteger + Clone>(a: &mut [M], root: M)
+where
+    M::Base: TryFrom<u64>,
+{
+    let n = a.len();
+    bit_reverse(a);
+    let mut len = 2;
+    while len <= n {
+        let wlen = pow_u64(&root, (n / len) as u64);
+        for chunk in a.chunks_mut(len) {
+            let (lo, hi) = chunk.split_at_mut(len / 2);
+            let mut w = convert_u64(&wlen, 1);
+            for (u, v) in lo.iter_mut().zip(hi.iter_mut()) {
+                let t = v.clone() * w.clone();
+                *v = u.clone() - t.clone();
+                *u = u.clone() + t;
+                w = w * wlen.clone();
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Find the root of unity for the transform of the slice
+fn transform_root<M: ModularInteger + Clone>(a: &[M]) -> Option<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    let n = a.len();
+    if n <= 1 {
+        return None;
+    }
+    assert!(n.is_power_of_two(), "the length should be a power of two");
+    Some(
+        root_of_unity(&a[0], n as u64)
+            .expect("the length should divide p - 1 for the prime modulus p"),
+    )
+}
+
+/// Calculate the number-theoretic transform `A[k] = sum(a[j] * w^(jk))` in place, where w is
+/// the primitive n-th root of unity from [root_of_unity()]. All the entries should have the
+/// same prime modulus p, and the results are in the natural order.
+///
+/// The length n should be a power of two dividing `p - 1` (e.g. up to `2^23` for 998244353),
+/// so the inputs should be padded with zeros to a power of two, e.g. with
+/// [usize::next_power_of_two()]. Slices of length 0 or 1 are unchanged.
+///
+/// # Panics
+/// if the length is not a power of two or doesn't divide `p - 1`
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{intt, ntt, ModularInteger, MontgomeryInt};
+///
+/// // (1 + 2x)(3 + 4x) = 3 + 10x + 8x^2
+/// let x = MontgomeryInt::new(0u64, &998244353);
+/// let mut a = [1, 2, 0, 0].map(|v| x.convert(v));
+/// let mut b = [3, 4, 0, 0].map(|v| x.convert(v));
+/// ntt(&mut a);
+/// ntt(&mut b);
+/// let mut c = [a[0] * b[0], a[1] * b[1], a[2] * b[2], a[3] * b[3]];
+/// intt(&mut c);
+/// assert_eq!(c.map(|v| v.residue()), [3, 10, 8, 0]);
+/// ```
+pub fn ntt<M: ModularInteger + Clone>(a: &mut [M])
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    if let Some(root) = transform_root(a) {
+        transform(a, root);
+    }
+}
+
+/// Calculate the inverse of [ntt()] in place, i.e. `a[j] = sum(A[k] * w^(-jk)) / n`.
+///
+/// # Panics
+/// if the length is not a power of two or doesn't divide `p - 1`
+pub fn intt<M: ModularInteger + Clone>(a: &mut [M])
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    if let Some(root) = transform_root(a) {
+        let n = a.len() as u64;
+        // w^-1 = w^(n-1), n^-1 = p - (p-1)/n
+        transform(a, pow_u64(&root, n - 1));
+        let p: u64 = a[0].modulus().into();
+        let ninv = convert_u64(&a[0], p - (p - 1) / n);
+        for v in a.iter_mut() {
+            *v = v.clone() * ninv.clone();
+        }
+    }
+}
+
+/// Calculate the convolution `c[k] = sum(a[i] * b[k-i])` of two sequences modulo an
+/// NTT-friendly prime (e.g. [ModInt998244353][crate::ModInt998244353]), which are the
+/// coefficients of the product of two polynomials. The result has the length
+/// `a.len() + b.len() - 1`, or it's empty if any input is empty.
+///
+/// The product is calculated by the schoolbook multiplication if any input is short,
+/// otherwise by the transforms of the length `(a.len() + b.len() - 1).next_power_of_two()`.
+///
+/// # Panics
+/// if the transform length is needed but doesn't divide `p - 1` for the prime modulus p
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{convolution, ModInt998244353, ModularInteger};
+///
+/// let a: Vec<_> = [1u64, 2, 3].iter().map(|&v| ModInt998244353::from(v)).collect();
+/// let b: Vec<_> = [4u64, 5].iter().map(|&v| ModInt998244353::from(v)).collect();
+/// let c: Vec<u64> = convolution(&a, &b).iter().map(|v| v.residue()).collect();
+/// assert_eq!(c, [4, 13, 22, 15]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn convolution<M: ModularInteger + Clone>(a: &[M], b: &[M]) -> Vec<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let zero = convert_u64(&a[0], 0);
+    let len = a.len() + b.len() - 1;
+
+    if a.len().min(b.len()) <= 32 {
+        let mut c = alloc::vec![zero; len];
+        for (i, u) in a.iter().enumerate() {
+            for (j, v) in b.iter().enumerate() {
+                c[i + j] = c[i + j].clone() + u.clone() * v.clone();
+            }
+        }
+        return c;
+    }
+
+    let n = len.next_power_of_two();
+    let pad = |v: &[M]| {
+        let mut r = v.to_vec();
+        r.resize(n, zero.clone());
+        ntt(&mut r);
+        r
+    };
+    let (fa, fb) = (pad(a), pad(b));
+    let mut c: Vec<M> = fa.into_iter().zip(fb).map(|(u, v)| u * v).collect();
+    intt(&mut c);
+    c.truncate(len);
+    c
+}
+
+/// The NTT-friendly primes for [convolution_mod()], with `p - 1` divisible by `2^54`
+#[cfg(feature = "alloc")]
+const CRT_PRIMES: [u64; 3] = [
+    4179340454199820289, // 29 * 2^57 + 1
+    2485986994308513793, // 69 * 2^55 + 1
+    3188548536178311169, // 177 * 2^54 + 1
+];
+
+/// Calculate the convolution of the residues modulo the const prime P
+#[cfg(feature = "alloc")]
+fn convolution_prime<const P: u64>(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let a: Vec<_> = a
+        .iter()
+        .map(|&v| ConstMontgomeryInt::<P>::from(v))
+        .collect();
+    let b: Vec<_> = b
+        .iter()
+        .map(|&v| ConstMontgomeryInt::<P>::from(v))
+        .collect();
+    convolution(&a, &b).iter().map(|v| v.residue()).collect()
+}
+
+/// Calculate the convolution `c[k] = sum(a[i] * b[k-i]) mod m` of two sequences for an
+/// arbitrary modulus m, which doesn't need to be a prime or NTT-friendly. The result has the
+/// length `a.len() + b.len() - 1`, or it's empty if any input is empty.
+///
+/// The exact convolution of the residues is calculated modulo three 62-bit NTT-friendly
+/// primes (with [convolution()]) and then combined by the Chinese remainder theorem (in the
+/// Garner's form), since the coefficients are less than `min(a.len(), b.len()) * m^2`,
+/// which is below the product of the primes. It takes three times the transforms of a
+/// single prime, and the lengths up to `2^54` are supported.
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::convolution_mod;
+///
+/// let m = 1_000_000_007;
+/// let a: Vec<u64> = (1..=100).collect();
+/// let b = vec![m - 1; 100];
+/// let c = convolution_mod(&a, &b, m);
+/// assert_eq!(c.len(), 199);
+/// assert_eq!(c[99], m - 5050); // -(1 + 2 + ... + 100)
+/// ```
+#[cfg(feature = "alloc")]
+pub fn convolution_mod(a: &[u64], b: &[u64], m: u64) -> Vec<u64> {
+    assert!(m > 0, "the modulus should not be zero");
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let a: Vec<u64> = a.iter().map(|v| v % m).collect();
+    let b: Vec<u64> = b.iter().map(|v| v % m).collect();
+
+    if a.len().min(b.len()) <= 32 {
+        let mut c = alloc::vec![0u64; a.len() + b.len() - 1];
+        for (i, &u) in a.iter().enumerate() {
+            for (j, &v) in b.iter().enumerate() {
+                c[i + j] = c[i + j].addm(u.mulm(v, &m), &m);
+            }
+        }
+        return c;
+    }
+
+    let [p0, p1, p2] = CRT_PRIMES;
+    let c0 = convolution_prime::<{ CRT_PRIMES[0] }>(&a, &b);
+    let c1 = convolution_prime::<{ CRT_PRIMES[1] }>(&a, &b);
+    let c2 = convolution_prime::<{ CRT_PRIMES[2] }>(&a, &b);
+
+    // c = r0 + p0 * t1 + p0 * p1 * t2 with t1 < p1, t2 < p2
+    let p0_inv = p0.invm(&p1).unwrap();
+    let p01 = p0.mulm(p1, &p2);
+    let p01_inv = p01.invm(&p2).unwrap();
+    let (p0_m, p01_m) = (p0 % m, p0.mulm(p1, &m));
+    c0.into_iter()
+        .zip(c1)
+        .zip(c2)
+        .map(|((r0, r1), r2)| {
+            let t1 = r1.subm(r0, &p1).mulm(p0_inv, &p1);
+            let t2 = r2
+                .subm(r0, &p2)
+                .subm(p0.mulm(t1, &p2), &p2)
+                .mulm(p01_inv, &p2);
+            (r0 % m)
+                .addm(p0_m.mulm(t1, &m), &m)
+                .addm(p01_m.mulm(t2, &m), &m)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConstMontgomeryInt, ModularCoreOps, MontgomeryInt, VanillaInt};
+    use rand::random;
+    use std::vec::Vec;
+
+    // the transform by definition
+    fn naive_ntt(a: &[u64], w: u64, p: u64) -> Vec<u64> {
+        (0..a.len())
+            .map(|k| {
+                let wk = crate::ModularPow::powm(w, k as u64, &p);
+                a.iter()
+                    .rev()
+                    .fold(0u64, |acc, &v| acc.mulm(wk, &p).addm(v, &p))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn root_of_unity_test() {
+        for &p in [998244353u64, 469762049, 167772161, 7340033, 65537, 17].iter() {
+            let x = MontgomeryInt::new(0, &p);
+            let max = ntt_max_len(&x);
+            assert_eq!((p - 1) % max, 0);
+            assert_eq!((p - 1) / max % 2, 1);
+            let mut n = 1;
+            while n <= max {
+                let w = root_of_unity(&x, n).unwrap();
+                assert_eq!(pow_u64(&w, n).residue(), 1);
+                if n > 1 {
+                    assert_eq!(pow_u64(&w, n / 2).residue(), p - 1);
+                }
+                n <<= 1;
+            }
+            assert!(root_of_unity(&x, max * 2).is_none());
+            assert!(root_of_unity(&x, 6).is_none());
+        }
+
+        let x = VanillaInt::new(0u32, &1_000_000_007);
+        assert_eq!(ntt_max_len(&x), 2);
+        assert_eq!(root_of_unity(&x, 2).unwrap().residue(), 1_000_000_006);
+    }
+
+    #[test]
+    fn ntt_test() {
+        let p = 998244353u64;
+        let x = MontgomeryInt::new(0, &p);
+        for k in 0..10 {
+            let n = 1 << k;
+            let raw: Vec<u64> = (0..n).map(|_| random::<u64>() % p).collect();
+            let mut a: Vec<_> = raw.iter().map(|&v| x.convert(v)).collect();
+            ntt(&mut a);
+            if n > 1 {
+                let w = root_of_unity(&x, n as u64).unwrap().residue();
+                let expected = naive_ntt(&raw, w, p);
+                assert_eq!(a.iter().map(|v| v.residue()).collect::<Vec<_>>(), expected);
+            }
+            intt(&mut a);
+            assert_eq!(a.iter().map(|v| v.residue()).collect::<Vec<_>>(), raw);
+        }
+
+        // other representations
+        type Mint = ConstMontgomeryInt<998244353>;
+        let raw: Vec<u64> = (0..64).map(|_| random::<u64>() % p).collect();
+        let mut a: Vec<Mint> = raw.iter().map(|&v| Mint::from(v)).collect();
+        let mut b: Vec<_> = raw.iter().map(|&v| x.convert(v)).collect();
+        ntt(&mut a);
+        ntt(&mut b);
+        assert!(a
+            .iter()
+            .zip(b.iter())
+            .all(|(u, v)| u.residue() == v.residue()));
+
+        let raw: Vec<u32> = (0..256).map(|_| random::<u32>() % 7340033).collect();
+        let mut a: Vec<_> = raw.iter().map(|&v| VanillaInt::new(v, &7340033)).collect();
+        ntt(&mut a);
+        intt(&mut a);
+        assert_eq!(a.iter().map(|v| v.residue()).collect::<Vec<_>>(), raw);
+    }
+
+    #[test]
+    fn convolution_test() {
+        let p = 998244353u64;
+        let x = MontgomeryInt::new(0, &p);
+        for &(la, lb) in [(1, 1), (5, 3), (33, 40), (100, 1), (200, 300)].iter() {
+            let a: Vec<u64> = (0..la).map(|_| random::<u64>() % p).collect();
+            let b: Vec<u64> = (0..lb).map(|_| random::<u64>() % p).collect();
+            let mut expected = std::vec![0u64; la + lb - 1];
+            for (i, &u) in a.iter().enumerate() {
+                for (j, &v) in b.iter().enumerate() {
+                    expected[i + j] = expected[i + j].addm(u.mulm(v, &p), &p);
+                }
+            }
+
+            let ma: Vec<_> = a.iter().map(|&v| x.convert(v)).collect();
+            let mb: Vec<_> = b.iter().map(|&v| x.convert(v)).collect();
+            #[cfg(feature = "alloc")]
+            {
+                let c: Vec<u64> = convolution(&ma, &mb).iter().map(|v| v.residue()).collect();
+                assert_eq!(c, expected);
+                let ma: Vec<_> = a.iter().map(|&v| crate::ModInt998244353::from(v)).collect();
+                let mb: Vec<_> = b.iter().map(|&v| crate::ModInt998244353::from(v)).collect();
+                let c: Vec<u64> = convolution(&ma, &mb).iter().map(|v| v.residue()).collect();
+                assert_eq!(c, expected);
+            }
+
+            // by the transforms directly
+            let n = (la + lb - 1).next_power_of_two();
+            let (mut fa, mut fb) = (ma, mb);
+            fa.resize(n, x);
+            fb.resize(n, x);
+            ntt(&mut fa);
+            ntt(&mut fb);
+            let mut c: Vec<_> = fa.iter().zip(fb.iter()).map(|(&u, &v)| u * v).collect();
+            intt(&mut c);
+            let c: Vec<u64> = c.iter().take(la + lb - 1).map(|v| v.residue()).collect();
+            assert_eq!(c, expected);
+        }
+
+        #[cfg(feature = "alloc")]
+        {
+            let empty: [MontgomeryInt<u64>; 0] = [];
+            assert!(convolution(&empty, &[x]).is_empty());
+
+            // short inputs are fine for the moduli without large roots of unity
+            let a: Vec<_> = (1..=100u64).map(crate::ModInt1000000007::from).collect();
+            let b = [crate::ModInt1000000007::from(1_000_000_006); 2];
+            let c = convolution(&a, &b);
+            assert_eq!(c.len(), 101);
+            assert_eq!(c[0].residue(), 1_000_000_006);
+            assert_eq!(c[50].residue(), 1_000_000_007 - 101);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn convolution_mod_test() {
+        for &m in [1u64, 2, 1_000_000_007, 998244353, 1 << 63, u64::MAX].iter() {
+            for &(la, lb) in [(1, 1), (3, 50), (40, 33), (150, 300)].iter() {
+                let a: Vec<u64> = (0..la).map(|_| random::<u64>()).collect();
+                let b: Vec<u64> = (0..lb).map(|_| random::<u64>()).collect();
+                let mut expected = std::vec![0u64; la + lb - 1];
+                for (i, &u) in a.iter().enumerate() {
+                    for (j, &v) in b.iter().enumerate() {
+                        expected[i + j] = expected[i + j].addm(u.mulm(v, &m), &m);
+                    }
+                }
+                assert_eq!(convolution_mod(&a, &b, m), expected, "m = {}", m);
+            }
+        }
+
+        // the worst case for the magnitude of the coefficients
+        let m = u64::MAX;
+        let a = std::vec![m - 1; 1000];
+        let c = convolution_mod(&a, &a, m);
+        for (k, v) in c.iter().enumerate() {
+            let count = (k.min(1998 - k) + 1) as u64;
+            assert_eq!(*v, count % m);
+        }
+        assert!(convolution_mod(&[], &a, m).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn ntt_unsupported_length_test() {
+        let x = MontgomeryInt::new(0u64, &1_000_000_007);
+        let mut a = [x; 4];
+        ntt(&mut a);
+    }
+}
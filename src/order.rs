@@ -0,0 +1,454 @@
+//! Multiplicative orders and the Carmichael function
+
+use crate::powm_fast;
+use crate::prim::gcd;
+use crate::root::prime_powers;
+
+/// A prime power factorization `[(p1, k1), (p2, k2), ...]` with a fixed capacity, which is
+/// enough for any integer below 2^128 (the product of the first 28 primes exceeds it)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Factors {
+    list: [(u128, u32); 28],
+    len: usize,
+}
+
+impl Factors {
+    pub(crate) const fn new() -> Self {
+        Self {
+            list: [(0, 0); 28],
+            len: 0,
+        }
+    }
+
+    /// Replace the factorized integer n with `lcm(n, p^k)`
+    pub(crate) fn lcm_assign(&mut self, p: u128, k: u32) {
+        if k == 0 {
+            return;
+        }
+        match self.list[..self.len].iter_mut().find(|(q, _)| *q == p) {
+            Some((_, j)) => *j = (*j).max(k),
+            None => {
+                self.list[self.len] = (p, k);
+                self.len += 1;
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn as_slice(&self) -> &[(u128, u32)] {
+        &self.list[..self.len]
+    }
+}
+
+/// Factorize the Carmichael function λ(m) given the factorization of m, the factors of
+/// `p - 1` for each odd prime p are found by [prime_powers()]
+pub(crate) fn carmichael_factors(m_factors: impl Iterator<Item = (u128, u32)>) -> Factors {
+    let mut result = Factors::new();
+    for (p, k) in m_factors {
+        if k == 0 {
+            continue;
+        }
+        if p == 2 {
+            // λ(2) = 1, λ(4) = 2, λ(2^k) = 2^(k-2) for k >= 3
+            result.lcm_assign(2, if k <= 2 { k - 1 } else { k - 2 });
+        } else {
+            // λ(p^k) = p^(k-1) * (p - 1)
+            result.lcm_assign(p, k - 1);
+            for (q, j) in prime_powers(p - 1) {
+                result.lcm_assign(q, j);
+            }
+        }
+    }
+    result
+}
+
+/// Integer types supported by [ord()] and [ord_with_factors()]
+pub trait MultiplicativeOrder: Sized {
+    /// Return the multiplicative order of self modulo m, see [ord()] for details
+    fn ord(&self, m: &Self) -> Option<Self>;
+
+    /// Return the multiplicative order of self modulo m with the factorization of a multiple
+    /// of the order, see [ord_with_factors()] for details
+    fn ord_with_factors(&self, m: &Self, factors: &[(Self, u32)]) -> Option<Self>;
+}
+
+/// Calculate the multiplicative order of a modulo m, i.e. the smallest positive k such that
+/// `a^k ≡ 1 (mod m)`, or return [None] if a is not coprime to m.
+///
+/// The order divides the Carmichael function λ(m), which is factorized by factorizing m and
+/// `p - 1` for the prime factors p of m. Then the prime factors are removed from λ(m) as long
+/// as the power remains one. The factorization is fast for moduli up to 64 bits, but it falls
+/// back to trial division for larger ones, which is slow if m has two large prime factors. In
+/// that case the factorization of λ(m) should be provided to [ord_with_factors()].
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::ord;
+///
+/// assert_eq!(ord(&2u32, &7), Some(3));
+/// assert_eq!(ord(&3u32, &7), Some(6)); // 3 is a primitive root modulo 7
+/// assert_eq!(ord(&7u32, &12), Some(2));
+/// assert_eq!(ord(&2u32, &12), None);
+/// ```
+#[inline]
+pub fn ord<T: MultiplicativeOrder>(a: &T, m: &T) -> Option<T> {
+    a.ord(m)
+}
+
+/// Calculate the multiplicative order of a modulo m (see [ord()]), given the factorization
+/// `factors = [(p1, k1), (p2, k2), ...]` of a multiple `n = p1^k1 * p2^k2 * ...` of the
+/// order, such as λ(m) or φ(m). For a prime modulus p, it's the factorization of `p - 1`.
+///
+/// It takes `O(sum(k_i) * log(m))` multiplications. [None] is also returned if `a^n ≠ 1`,
+/// i.e. a is not coprime to m or n is not a multiple of the order.
+///
+/// # Panics
+/// if m is zero, or if the product of the factors overflows
+///
+/// Example code:
+/// ```rust
+/// use num_modular::ord_with_factors;
+///
+/// let p = 1_000_000_007u64; // p - 1 = 2 * 500000003
+/// let factors = [(2, 1), (500_000_003, 1)];
+/// assert_eq!(ord_with_factors(&5u64, &p, &factors), Some(p - 1));
+/// assert_eq!(ord_with_factors(&4u64, &p, &factors), Some((p - 1) / 2));
+/// assert_eq!(ord_with_factors(&(p - 1), &p, &factors), Some(2));
+/// ```
+#[inline]
+pub fn ord_with_factors<T: MultiplicativeOrder>(a: &T, m: &T, factors: &[(T, u32)]) -> Option<T> {
+    a.ord_with_factors(m, factors)
+}
+
+macro_rules! impl_order_for {
+    ($($T:ty)*) => {$(
+        impl MultiplicativeOrder for $T {
+            fn ord(&self, m: &$T) -> Option<$T> {
+                assert!(*m > 0, "the modulus should be positive");
+                let lambda = carmichael_factors(prime_powers(*m as u128));
+                // λ(m) <= m, so the factors fit in the type
+                let mut factors = [(0 as $T, 0u32); 28];
+                for (f, &(p, k)) in factors.iter_mut().zip(lambda.as_slice()) {
+                    *f = (p as $T, k);
+                }
+                self.ord_with_factors(m, &factors[..lambda.as_slice().len()])
+            }
+
+            fn ord_with_factors(&self, m: &$T, factors: &[($T, u32)]) -> Option<$T> {
+                let (a, m) = (*self % *m, *m);
+                let one = 1 % m;
+                let mut n = factors.iter().fold(1 as $T, |acc, &(p, k)| {
+                    p.checked_pow(k)
+                        .and_then(|pk| acc.checked_mul(pk))
+                        .expect("the product of the factors should fit in the integer type")
+                });
+                if powm_fast(a, n, m) != one {
+                    return None;
+                }
+                for &(p, k) in factors {
+                    for _ in 0..k {
+                        if powm_fast(a, n / p, m) != one {
+                            break;
+                        }
+                        n /= p;
+                    }
+                }
+                Some(n)
+            }
+        }
+    )*};
+}
+impl_order_for!(u8 u16 u32 u64 u128 usize);
+
+/// Integer types supported by [carmichael()] and [reduce_exp()]
+pub trait Carmichael: Sized {
+    /// Return the Carmichael function λ(m) from the factorization of m, see [carmichael()]
+    fn carmichael(factors: &[(Self, u32)]) -> Self;
+
+    /// Reduce the exponent for the modulus with the given factorization, see [reduce_exp()]
+    fn reduce_exp(exp: &Self, factors: &[(Self, u32)]) -> Self;
+}
+
+/// Calculate the Carmichael function λ(m), i.e. the exponent of the multiplicative group modulo
+/// m, from the factorization `factors = [(p1, k1), (p2, k2), ...]` of `m = p1^k1 * p2^k2 * ...`
+/// with distinct primes. It's the lcm of `λ(p^k) = p^(k-1) * (p - 1)` for odd primes, and
+/// `λ(2) = 1, λ(4) = 2, λ(2^k) = 2^(k-2)` for `k >= 3`. It divides Euler's totient φ(m),
+/// and `a^λ(m) ≡ 1 (mod m)` for all a coprime to m.
+///
+/// # Panics
+/// if the modulus overflows
+///
+/// Example code:
+/// ```rust
+/// use num_modular::carmichael;
+///
+/// assert_eq!(carmichael(&[(2u64, 3), (3, 1), (5, 1)]), 4); // λ(120)
+/// assert_eq!(carmichael(&[(3u64, 1), (11, 1), (17, 1)]), 80); // λ(561)
+/// assert_eq!(carmichael::<u64>(&[]), 1);
+/// ```
+#[inline]
+pub fn carmichael<T: Carmichael>(factors: &[(T, u32)]) -> T {
+    T::carmichael(factors)
+}
+
+/// Reduce an exponent for the modulus m given as the factorization `[(p1, k1), (p2, k2), ...]`
+/// (see [carmichael()]), such that `b^reduce_exp(e) ≡ b^e (mod m)` for all bases b, so that a
+/// huge exponent can be passed to [powm()][crate::ModularPow::powm] after the reduction.
+///
+/// For the bases coprime to m, the power only depends on the exponent modulo λ(m). This is not
+/// the case for the other bases (e.g. `2^2 ≡ 0 (mod 4)`, but `2^(2 mod λ(4)) ≡ 1`), where the
+/// powers become periodic only when the exponent is at least the largest multiplicity
+/// `k = max(k_i)`. So the exponent is kept if `e < k`, otherwise it's reduced into the range
+/// `[k, k + λ(m))` instead of `[0, λ(m))`. The result is never larger than e.
+///
+/// # Panics
+/// if the modulus overflows
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{reduce_exp, ModularPow};
+///
+/// let factors = [(2u64, 2), (3, 1)]; // m = 12, λ(12) = 2
+/// let e = reduce_exp(&u64::MAX, &factors);
+/// assert_eq!(e, 3);
+/// for b in 0..12u64 {
+///     assert_eq!(b.powm(e, &12), b.powm(u64::MAX, &12));
+/// }
+/// ```
+#[inline]
+pub fn reduce_exp<T: Carmichael>(exp: &T, factors: &[(T, u32)]) -> T {
+    T::reduce_exp(exp, factors)
+}
+
+macro_rules! impl_carmichael_for {
+    ($($T:ty)*) => {$(
+        impl Carmichael for $T {
+            fn carmichael(factors: &[($T, u32)]) -> $T {
+                const OVERFLOW: &str = "the modulus should fit in the integer type";
+                factors.iter().fold(1, |acc, &(p, k)| {
+                    let lambda = match (p, k) {
+                        (_, 0) => 1,
+                        (2, 1) => 1,
+                        (2, 2) => 2,
+                        (2, _) => 1 << (k - 2),
+                        _ => p.checked_pow(k - 1).expect(OVERFLOW) * (p - 1),
+                    };
+
+                    // lcm(acc, lambda)
+                    acc / gcd(acc, lambda) * lambda
+                })
+            }
+
+            fn reduce_exp(exp: &$T, factors: &[($T, u32)]) -> $T {
+                let kmax = factors.iter().map(|&(_, k)| k).max().unwrap_or(0) as $T;
+                if *exp < kmax {
+                    return *exp;
+                }
+                kmax + (*exp - kmax) % Self::carmichael(factors)
+            }
+        }
+    )*};
+}
+impl_carmichael_for!(u8 u16 u32 u64 u128 usize);
+
+#[cfg(feature = "num-bigint")]
+mod _num_bigint {
+    use super::*;
+    use num_bigint::BigUint;
+    use num_integer::Integer;
+    use num_traits::One;
+
+    impl Carmichael for BigUint {
+        fn carmichael(factors: &[(BigUint, u32)]) -> BigUint {
+            factors.iter().fold(BigUint::one(), |acc, (p, k)| {
+                let lambda = match *k {
+                    0 => BigUint::one(),
+                    1 | 2 if p == &BigUint::from(2u8) => BigUint::from(*k),
+                    _ if p == &BigUint::from(2u8) => BigUint::one() << (k - 2),
+                    _ => p.pow(k - 1) * (p - 1u8),
+                };
+                acc.lcm(&lambda)
+            })
+        }
+
+        fn reduce_exp(exp: &BigUint, factors: &[(BigUint, u32)]) -> BigUint {
+            let kmax = BigUint::from(factors.iter().map(|(_, k)| *k).max().unwrap_or(0));
+            if exp < &kmax {
+                return exp.clone();
+            }
+            (exp - &kmax) % Self::carmichael(factors) + kmax
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::ModularPow;
+        use core::convert::TryInto;
+        use rand::random;
+
+        #[test]
+        fn biguint_carmichael_test() {
+            let factors = [(2u64, 5), (3, 2), (7, 1), (65537, 1)];
+            let big_factors: std::vec::Vec<_> = factors
+                .iter()
+                .map(|&(p, k)| (BigUint::from(p), k))
+                .collect();
+            assert_eq!(
+                carmichael(&big_factors),
+                BigUint::from(carmichael(&factors))
+            );
+
+            // an exponent with hundreds of bits
+            let m: u64 = factors.iter().map(|&(p, k)| p.pow(k)).product();
+            let e = BigUint::from_bytes_le(&random::<[u8; 32]>());
+            let r: u64 = reduce_exp(&e, &big_factors).try_into().unwrap();
+            for _ in 0..10 {
+                let b = random::<u64>() % m;
+                for &b in [b, b & !1, b * 6 % m, 0].iter() {
+                    let expected = BigUint::from(b).modpow(&e, &BigUint::from(m));
+                    assert_eq!(BigUint::from(b.powm(r, &m)), expected);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModularPow;
+    use rand::random;
+
+    // the multiplicative order by brute force
+    fn naive_ord(a: u32, m: u32) -> Option<u32> {
+        let mut x = a % m;
+        for k in 1..=m {
+            if x == 1 % m {
+                return Some(k);
+            }
+            x = (x as u64 * a as u64 % m as u64) as u32;
+        }
+        None
+    }
+
+    #[test]
+    fn carmichael_factors_test() {
+        let lambda = |m: u128| -> u128 {
+            carmichael_factors(prime_powers(m))
+                .as_slice()
+                .iter()
+                .map(|&(p, k)| p.pow(k))
+                .product()
+        };
+        assert_eq!(lambda(1), 1);
+        assert_eq!(lambda(2), 1);
+        assert_eq!(lambda(4), 2);
+        assert_eq!(lambda(8), 2);
+        assert_eq!(lambda(1 << 20), 1 << 18);
+        assert_eq!(lambda(15), 4);
+        assert_eq!(lambda(561), 80); // a Carmichael number
+        assert_eq!(lambda(1_000_000_007), 1_000_000_006);
+        assert_eq!(lambda(2 * 3 * 5 * 7 * 11 * 13), 60);
+    }
+
+    #[test]
+    fn ord_test() {
+        for m in 1..300u32 {
+            for a in 0..m {
+                assert_eq!(ord(&a, &m), naive_ord(a, m), "ord({}) mod {}", a, m);
+            }
+        }
+
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                let m = (random::<u16>() as $T).max(1);
+                let a = random::<$T>() % m;
+                if let Some(k) = ord(&a, &m) {
+                    assert_eq!(a.powm(k, &m), 1 % m);
+                }
+                assert_eq!(ord(&a, &m), naive_ord(a as u32, m as u32).map(|v| v as $T));
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+    }
+
+    #[test]
+    fn carmichael_test() {
+        let lambda = |m: u64| -> u64 {
+            let factors: std::vec::Vec<_> = prime_powers(m as u128)
+                .map(|(p, k)| (p as u64, k))
+                .collect();
+            carmichael(&factors)
+        };
+        for m in 1..1000u64 {
+            // λ(m) is the largest order
+            let expected = (0..m).filter_map(|a| ord(&a, &m)).max().unwrap();
+            assert_eq!(lambda(m), expected, "λ({})", m);
+        }
+        assert_eq!(carmichael(&[(2u8, 7)]), 32);
+        assert_eq!(carmichael(&[(2u8, 0), (3, 0)]), 1);
+        assert_eq!(
+            carmichael(&[(18446744073709551557u128, 1), (18446744073709551533, 1)]),
+            (18446744073709551556 / 4) * 18446744073709551532
+        );
+    }
+
+    #[test]
+    fn reduce_exp_test() {
+        for m in 1..200u32 {
+            let factors: std::vec::Vec<_> = prime_powers(m as u128)
+                .map(|(p, k)| (p as u32, k))
+                .collect();
+            for _ in 0..10 {
+                let e = random::<u32>() >> (random::<u32>() % 32);
+                let r = reduce_exp(&e, &factors);
+                assert!(r <= e);
+                for b in 0..m {
+                    assert_eq!(b.powm(r, &m), b.powm(e, &m), "{}^{} mod {}", b, e, m);
+                }
+            }
+        }
+
+        // 72 = 2^3 * 3^2, λ(72) = 6
+        let factors = [(2u64, 3), (3, 2)];
+        assert_eq!(reduce_exp(&2, &factors), 2);
+        assert_eq!(reduce_exp(&3, &factors), 3);
+        assert_eq!(reduce_exp(&9, &factors), 3);
+        assert_eq!(reduce_exp(&10, &factors), 4);
+    }
+
+    #[test]
+    fn ord_with_factors_test() {
+        let p = 998244353u64; // p - 1 = 2^23 * 7 * 17
+        let factors = [(2, 23), (7, 1), (17, 1)];
+        assert_eq!(ord_with_factors(&3, &p, &factors), Some(p - 1));
+        assert_eq!(ord_with_factors(&1, &p, &factors), Some(1));
+        assert_eq!(ord_with_factors(&0, &p, &factors), None);
+        for _ in 0..10 {
+            let a = random::<u64>() % (p - 1) + 1;
+            let k = ord_with_factors(&a, &p, &factors).unwrap();
+            assert_eq!(a.powm(k, &p), 1);
+            assert_eq!(ord(&a, &p), Some(k));
+            for &(q, _) in factors.iter() {
+                if k % q == 0 {
+                    assert_ne!(a.powm(k / q, &p), 1);
+                }
+            }
+        }
+
+        // φ(m) as the multiple of the order
+        let m = 3u32.pow(10) * 5; // φ(m) = 2 * 3^9 * 4
+        let factors = [(2, 3), (3, 9)];
+        for a in 1..1000u32 {
+            assert_eq!(ord_with_factors(&a, &m, &factors), ord(&a, &m));
+        }
+
+        // the product is not a multiple of the order
+        assert_eq!(ord_with_factors(&3u64, &p, &[(2, 23)]), None);
+        assert_eq!(ord_with_factors(&3u64, &1, &[]), Some(1));
+    }
+}
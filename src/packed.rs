@@ -0,0 +1,250 @@
+//! Word-parallel arithmetic on small residues packed in one [u64], each lane having its own modulus
+
+/// Lane-wise subtraction without borrows between lanes, returning the wrapped differences
+/// and the most significant bits of the lanes where a borrow happened.
+#[inline(always)]
+const fn swar_sub(x: u64, y: u64, high: u64) -> (u64, u64) {
+    let diff = ((x | high) - (y & !high)) ^ ((x ^ !y) & high);
+    let borrow = ((!x & y) | (!(x ^ y) & diff)) & high;
+    (diff, borrow)
+}
+
+/// Lane-wise addition without carries between lanes, the sums are wrapped
+#[inline(always)]
+const fn swar_add(x: u64, y: u64, high: u64) -> u64 {
+    ((x & !high) + (y & !high)) ^ ((x ^ y) & high)
+}
+
+macro_rules! impl_packed_residues {
+    ($Packed:ident, $T:ty, $N:literal, $doc_lanes:literal) => {
+        #[doc = concat!("Residues of ", $doc_lanes, " packed in one [u64], each lane with its own modulus.")]
+        ///
+        /// The lane-wise modular addition and subtraction are done with a few word operations
+        /// (SIMD within a register), which is useful for sieving with many small moduli at once.
+        /// Lane i occupies the i-th least significant bits of the word. Other operations (like
+        /// multiplications) can be done on the unpacked residues.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $Packed {
+            v: u64, // packed residues
+            m: u64, // packed moduli
+        }
+
+        impl $Packed {
+            const BITS: u32 = <$T>::BITS;
+            const LOW: u64 = u64::MAX / <$T>::MAX as u64; // the lowest bit of each lane
+            const HIGH: u64 = Self::LOW << (Self::BITS - 1); // the highest bit of each lane
+
+            #[inline]
+            const fn pack_lanes(lanes: [$T; $N]) -> u64 {
+                let mut v = 0;
+                let mut i = 0;
+                while i < $N {
+                    v |= (lanes[i] as u64) << (i as u32 * Self::BITS);
+                    i += 1;
+                }
+                v
+            }
+
+            #[inline]
+            const fn unpack_lanes(v: u64) -> [$T; $N] {
+                let mut lanes = [0; $N];
+                let mut i = 0;
+                while i < $N {
+                    lanes[i] = (v >> (i as u32 * Self::BITS)) as $T;
+                    i += 1;
+                }
+                lanes
+            }
+
+            /// Pack the values reduced by the moduli of the corresponding lanes
+            ///
+            /// # Panics
+            /// if any of the moduli is zero
+            pub fn pack(values: [$T; $N], moduli: [$T; $N]) -> Self {
+                let mut reduced = [0; $N];
+                for i in 0..$N {
+                    assert!(moduli[i] > 0, "the modulus should not be zero");
+                    reduced[i] = values[i] % moduli[i];
+                }
+                Self {
+                    v: Self::pack_lanes(reduced),
+                    m: Self::pack_lanes(moduli),
+                }
+            }
+
+            /// Pack zeros with the given moduli
+            ///
+            /// # Panics
+            /// if any of the moduli is zero
+            #[inline]
+            pub fn zeros(moduli: [$T; $N]) -> Self {
+                Self::pack([0; $N], moduli)
+            }
+
+            /// Get the residues in all lanes
+            #[inline]
+            pub const fn unpack(&self) -> [$T; $N] {
+                Self::unpack_lanes(self.v)
+            }
+
+            /// Get the moduli of all lanes
+            #[inline]
+            pub const fn moduli(&self) -> [$T; $N] {
+                Self::unpack_lanes(self.m)
+            }
+
+            /// Get the residue in the i-th lane
+            ///
+            /// # Panics
+            /// if i is out of range
+            #[inline]
+            pub const fn lane(&self, i: usize) -> $T {
+                assert!(i < $N, "the lane index is out of range");
+                (self.v >> (i as u32 * Self::BITS)) as $T
+            }
+
+            /// Expand the highest bit of each lane to the whole lane
+            #[inline(always)]
+            const fn lane_mask(high: u64) -> u64 {
+                (high >> (Self::BITS - 1)) * (<$T>::MAX as u64)
+            }
+
+            /// Lane-wise `(self - rhs) mod m`
+            #[inline]
+            pub const fn subm(&self, rhs: &Self) -> Self {
+                debug_assert!(self.m == rhs.m, "the moduli of the lanes should be the same");
+                let (diff, borrow) = swar_sub(self.v, rhs.v, Self::HIGH);
+                let fix = self.m & Self::lane_mask(borrow);
+                Self {
+                    v: swar_add(diff, fix, Self::HIGH),
+                    m: self.m,
+                }
+            }
+
+            /// Lane-wise `(self + rhs) mod m`, evaluated as `self - (m - rhs)`
+            #[inline]
+            pub const fn addm(&self, rhs: &Self) -> Self {
+                debug_assert!(self.m == rhs.m, "the moduli of the lanes should be the same");
+                let (neg, _) = swar_sub(self.m, rhs.v, Self::HIGH);
+                let (diff, borrow) = swar_sub(self.v, neg, Self::HIGH);
+                let fix = self.m & Self::lane_mask(borrow);
+                Self {
+                    v: swar_add(diff, fix, Self::HIGH),
+                    m: self.m,
+                }
+            }
+
+            /// Lane-wise `(-self) mod m`
+            #[inline]
+            pub const fn negm(&self) -> Self {
+                Self { v: 0, m: self.m }.subm(self)
+            }
+
+            /// Get a bit mask where the i-th bit is set if the residue in the i-th lane is zero
+            #[inline]
+            pub const fn zero_lanes(&self) -> u32 {
+                // the highest bit is set iff the lane is zero
+                let (_, borrow) = swar_sub(self.v, Self::LOW, Self::HIGH);
+                let zeros = borrow & !self.v;
+                let mut mask = 0;
+                let mut i = 0;
+                while i < $N {
+                    mask |= ((zeros >> ((i + 1) * Self::BITS - 1)) as u32 & 1) << i;
+                    i += 1;
+                }
+                mask
+            }
+        }
+    };
+}
+impl_packed_residues!(PackedResidues8, u8, 8, "eight u8 lanes");
+impl_packed_residues!(PackedResidues16, u16, 4, "four u16 lanes");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModularCoreOps, ModularUnaryOps};
+    use rand::random;
+
+    #[test]
+    fn packed_ops_test() {
+        macro_rules! tests_for {
+            ($($Packed:ident: $T:ty, $N:literal;)*) => ($(
+                for _ in 0..1000 {
+                    let mut moduli = [0 as $T; $N];
+                    let (mut a, mut b) = ([0 as $T; $N], [0 as $T; $N]);
+                    for i in 0..$N {
+                        moduli[i] = match random::<u8>() % 4 {
+                            0 => <$T>::MAX,
+                            1 => 1,
+                            _ => random::<$T>().max(1),
+                        };
+                        // include the boundary residues m-1 and 0
+                        a[i] = match random::<u8>() % 4 {
+                            0 => moduli[i] - 1,
+                            1 => 0,
+                            _ => random::<$T>() % moduli[i],
+                        };
+                        b[i] = match random::<u8>() % 4 {
+                            0 => moduli[i] - 1,
+                            1 => 0,
+                            _ => random::<$T>() % moduli[i],
+                        };
+                    }
+
+                    let pa = $Packed::pack(a, moduli);
+                    let pb = $Packed::pack(b, moduli);
+                    assert_eq!(pa.unpack(), a);
+                    assert_eq!(pa.moduli(), moduli);
+
+                    let (sum, diff, neg) = (pa.addm(&pb), pa.subm(&pb), pa.negm());
+                    let mut zeros = 0;
+                    for i in 0..$N {
+                        let m = moduli[i];
+                        assert_eq!(sum.lane(i), a[i].addm(b[i], &m), "{} + {} mod {}", a[i], b[i], m);
+                        assert_eq!(diff.unpack()[i], a[i].subm(b[i], &m), "{} - {} mod {}", a[i], b[i], m);
+                        assert_eq!(neg.unpack()[i], a[i].negm(&m), "-{} mod {}", a[i], m);
+                        zeros |= ((a[i] == 0) as u32) << i;
+                    }
+                    assert_eq!(pa.zero_lanes(), zeros);
+                    assert_eq!(sum.moduli(), moduli);
+                }
+
+                // values are reduced when packed
+                let p = $Packed::pack([<$T>::MAX; $N], [7; $N]);
+                assert_eq!(p.unpack(), [<$T>::MAX % 7; $N]);
+                assert_eq!($Packed::zeros([3; $N]).zero_lanes(), (1 << $N) - 1);
+            )*);
+        }
+        tests_for!(
+            PackedResidues8: u8, 8;
+            PackedResidues16: u16, 4;
+        );
+    }
+
+    #[test]
+    fn sieve_test() {
+        // mark the multiples of the small primes by stepping the packed offsets
+        const PRIMES: [u8; 8] = [3, 5, 7, 11, 13, 17, 19, 23];
+        let start = random::<u32>() >> 1;
+        let mut offsets = [0u8; 8];
+        for i in 0..8 {
+            offsets[i] = (start % PRIMES[i] as u32) as u8;
+        }
+        let mut x = PackedResidues8::pack(offsets, PRIMES);
+        let one = PackedResidues8::pack([1; 8], PRIMES);
+        for n in start..start + 1000 {
+            let expected = PRIMES.iter().enumerate().fold(0, |mask, (i, &p)| {
+                mask | (((n % p as u32 == 0) as u32) << i)
+            });
+            assert_eq!(x.zero_lanes(), expected);
+            x = x.addm(&one);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_modulus_test() {
+        PackedResidues16::pack([1, 2, 3, 4], [5, 0, 5, 5]);
+    }
+}
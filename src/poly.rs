@@ -0,0 +1,479 @@
+//! Dense polynomials over the modulo ring ℤ/pℤ
+
+use crate::ntt::{convert_u64, convolution, convolution_mod, pow_u64, root_of_unity};
+use crate::{ModularInteger, ModularUnaryOps};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+/// A dense polynomial with coefficients of a [ModularInteger] type, whose base type should
+/// be at most 64 bits (e.g. [`MontgomeryInt<u64>`][crate::MontgomeryInt] or
+/// [ModInt998244353][crate::ModInt998244353]).
+///
+/// The coefficients are stored from the lowest degree without trailing zeros, and they are
+/// kept in the form of the [ModularInteger] type (e.g. the Montgomery form) throughout all
+/// the operations. All the coefficients should have the same modulus, otherwise the
+/// operations panic (same as the [ModularInteger] operators). The zero polynomial still
+/// keeps the modulus, so that it can be created with [zero()][Poly::zero].
+///
+/// The product is calculated by the schoolbook multiplication for short operands, by the
+/// transforms ([convolution()]) if the modulus is NTT-friendly, and by the three-prime
+/// transforms ([convolution_mod()]) otherwise, so any modulus is supported. The division
+/// requires the leading coefficient of the divisor to be invertible, which always holds for
+/// a prime modulus, and long divisions are reduced to a product with the power series
+/// inverse of the reversed divisor.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ModularInteger, MontgomeryInt, Poly};
+///
+/// let x = MontgomeryInt::new(0u64, &998244353);
+/// let f = Poly::from_residues([1, 2, 1], &x); // (1 + t)^2
+/// let g = Poly::from_residues([1, 1], &x); // 1 + t
+/// let (q, r) = f.div_rem(&g);
+/// assert_eq!(q, g);
+/// assert!(r.is_zero());
+/// assert_eq!((&f * &g).residues(), [1, 3, 3, 1]);
+/// assert_eq!(f.eval(&x.convert(3)).residue(), 16);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Poly<M> {
+    coeffs: Vec<M>,
+    zero: M,
+}
+
+impl<M: ModularInteger + Clone> Poly<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    /// Create a polynomial from the coefficients (from the lowest degree), with the same
+    /// modulus as x. The trailing zeros are removed.
+    pub fn new(coeffs: Vec<M>, x: &M) -> Self {
+        let mut p = Self {
+            coeffs,
+            zero: convert_u64(x, 0),
+        };
+        p.normalize();
+        p
+    }
+
+    /// Create a polynomial from the residues of the coefficients (from the lowest degree),
+    /// with the same modulus as x
+    pub fn from_residues<I: IntoIterator<Item = M::Base>>(residues: I, x: &M) -> Self {
+        let coeffs = residues.into_iter().map(|v| x.convert(v)).collect();
+        Self::new(coeffs, x)
+    }
+
+    /// Create the zero polynomial, with the same modulus as x
+    #[inline]
+    pub fn zero(x: &M) -> Self {
+        Self::new(Vec::new(), x)
+    }
+
+    /// Create the monomial `c * t^k`
+    pub fn monomial(c: M, k: usize) -> Self {
+        let zero = convert_u64(&c, 0);
+        let mut coeffs = alloc::vec![zero.clone(); k];
+        coeffs.push(c);
+        Self::new(coeffs, &zero)
+    }
+
+    /// Remove the trailing zeros
+    fn normalize(&mut self) {
+        while self.coeffs.last().map_or(false, |c| c.is_zero()) {
+            self.coeffs.pop();
+        }
+    }
+
+    /// Get the degree of the polynomial, which is [None] for the zero polynomial
+    #[inline]
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+
+    /// Check whether the polynomial is zero
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    /// Get the coefficients from the lowest degree, without trailing zeros
+    #[inline]
+    pub fn coeffs(&self) -> &[M] {
+        &self.coeffs
+    }
+
+    /// Take the coefficients from the lowest degree, without trailing zeros
+    #[inline]
+    pub fn into_coeffs(self) -> Vec<M> {
+        self.coeffs
+    }
+
+    /// Get the coefficient of `t^i`, which is zero if i is larger than the degree
+    #[inline]
+    pub fn coeff(&self, i: usize) -> M {
+        self.coeffs.get(i).unwrap_or(&self.zero).clone()
+    }
+
+    /// Get the coefficient of the highest degree, which is [None] for the zero polynomial
+    #[inline]
+    pub fn leading(&self) -> Option<&M> {
+        self.coeffs.last()
+    }
+
+    /// Get the residues of the coefficients from the lowest degree
+    pub fn residues(&self) -> Vec<M::Base> {
+        self.coeffs.iter().map(|c| c.residue()).collect()
+    }
+
+    /// Evaluate the polynomial at x by Horner's method
+    pub fn eval(&self, x: &M) -> M {
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(self.zero.clone(), |acc, c| acc * x.clone() + c.clone())
+    }
+
+    /// Calculate the formal derivative of the polynomial
+    pub fn derivative(&self) -> Self {
+        let coeffs = self
+            .coeffs
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, c)| c.clone() * convert_u64(c, i as u64))
+            .collect();
+        Self::new(coeffs, &self.zero)
+    }
+
+    /// Multiply all the coefficients by c
+    pub fn scale(&self, c: &M) -> Self {
+        let coeffs = self.coeffs.iter().map(|v| v.clone() * c.clone()).collect();
+        Self::new(coeffs, &self.zero)
+    }
+
+    /// Calculate the quotient and the remainder of the division by the divisor, such that
+    /// `self = q * divisor + r` and `deg(r) < deg(divisor)`.
+    ///
+    /// # Panics
+    /// if the divisor is zero or its leading coefficient is not invertible
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let d = divisor.degree().expect("the divisor should not be zero");
+        if self.coeffs.len() <= d {
+            return (Self::zero(&self.zero), self.clone());
+        }
+
+        let lc_inv = inv(&divisor.coeffs[d]).expect("the leading coefficient should be invertible");
+        if d > 32 && self.coeffs.len() - d > 32 {
+            return self.div_rem_newton(divisor);
+        }
+        let mut r = self.coeffs.clone();
+        let mut q = alloc::vec![self.zero.clone(); r.len() - d];
+        for i in (0..q.len()).rev() {
+            let c = r[i + d].clone() * lc_inv.clone();
+            for (rj, bj) in r[i..i + d].iter_mut().zip(divisor.coeffs.iter()) {
+                *rj = rj.clone() - c.clone() * bj.clone();
+            }
+            q[i] = c;
+        }
+        r.truncate(d);
+        (Self::new(q, &self.zero), Self::new(r, &self.zero))
+    }
+
+    /// Divide the polynomial by its leading coefficient, the zero polynomial is unchanged.
+    ///
+    /// # Panics
+    /// if the leading coefficient is not invertible
+    pub fn monic(&self) -> Self {
+        match self.leading() {
+            Some(lc) => self.scale(&inv(lc).expect("the leading coefficient should be invertible")),
+            None => self.clone(),
+        }
+    }
+
+    /// Calculate the monic greatest common divisor by the Euclidean algorithm, which is zero
+    /// only if both polynomials are zero.
+    ///
+    /// # Panics
+    /// if a remainder without invertible leading coefficient is met, which happens only if
+    /// the modulus is not a prime
+    pub fn gcd(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let r = &a % &b;
+            a = core::mem::replace(&mut b, r);
+        }
+        a.monic()
+    }
+
+    /// Calculate the division with the reversed quotient `rev(q) = rev(self) / rev(divisor)
+    /// mod t^(n-d)`, where the series inverse is found by [inv_series()][Poly::inv_series].
+    fn div_rem_newton(&self, divisor: &Self) -> (Self, Self) {
+        let d = divisor.coeffs.len() - 1;
+        let k = self.coeffs.len() - d;
+        let ra: Vec<M> = self.coeffs.iter().rev().take(k).cloned().collect();
+        let rb = Self::new(divisor.coeffs.iter().rev().cloned().collect(), &self.zero);
+        let rb_inv = rb.inv_series(k).unwrap();
+
+        let mut q = mul_coeffs(&ra, rb_inv.coeffs());
+        q.resize(k, self.zero.clone());
+        q.reverse();
+        let q = Self::new(q, &self.zero);
+        let mut r = (self - &(&q * divisor)).coeffs;
+        r.truncate(d);
+        (q, Self::new(r, &self.zero))
+    }
+}
+
+/// Calculate the modular inverse of x
+pub(crate) fn inv<M: ModularInteger>(x: &M) -> Option<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    let (v, m): (u64, u64) = (x.residue().into(), x.modulus().into());
+    v.invm(&m).map(|v| convert_u64(x, v))
+}
+
+/// Calculate the product of the coefficient sequences, see [Poly] for the strategy
+pub(crate) fn mul_coeffs<M: ModularInteger + Clone>(a: &[M], b: &[M]) -> Vec<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    if a.len().min(b.len()) <= 32 {
+        return convolution(a, b);
+    }
+
+    // the transforms are valid iff w^(n/2) = -1 for the root w, which could fail only
+    // for a composite modulus
+    let n = (a.len() + b.len() - 1).next_power_of_two() as u64;
+    let friendly =
+        root_of_unity(&a[0], n).map_or(false, |w| pow_u64(&w, n / 2) == -convert_u64(&a[0], 1));
+    if friendly {
+        convolution(a, b)
+    } else {
+        let residues = |v: &[M]| -> Vec<u64> { v.iter().map(|c| c.residue().into()).collect() };
+        let m: u64 = a[0].modulus().into();
+        convolution_mod(&residues(a), &residues(b), m)
+            .into_iter()
+            .map(|c| convert_u64(&a[0], c))
+            .collect()
+    }
+}
+
+impl<M: ModularInteger + Clone> Neg for Poly<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    type Output = Self;
+    fn neg(mut self) -> Self {
+        for c in self.coeffs.iter_mut() {
+            *c = -c.clone();
+        }
+        self
+    }
+}
+
+impl<M: ModularInteger + Clone> Add for &Poly<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    type Output = Poly<M>;
+    fn add(self, rhs: Self) -> Poly<M> {
+        let n = self.coeffs.len().max(rhs.coeffs.len());
+        let coeffs = (0..n).map(|i| self.coeff(i) + rhs.coeff(i)).collect();
+        Poly::new(coeffs, &self.zero)
+    }
+}
+
+impl<M: ModularInteger + Clone> Sub for &Poly<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    type Output = Poly<M>;
+    fn sub(self, rhs: Self) -> Poly<M> {
+        let n = self.coeffs.len().max(rhs.coeffs.len());
+        let coeffs = (0..n).map(|i| self.coeff(i) - rhs.coeff(i)).collect();
+        Poly::new(coeffs, &self.zero)
+    }
+}
+
+impl<M: ModularInteger + Clone> Mul for &Poly<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    type Output = Poly<M>;
+    #[inline]
+    fn mul(self, rhs: Self) -> Poly<M> {
+        Poly::new(mul_coeffs(&self.coeffs, &rhs.coeffs), &self.zero)
+    }
+}
+
+impl<M: ModularInteger + Clone> Div for &Poly<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    type Output = Poly<M>;
+    #[inline]
+    fn div(self, rhs: Self) -> Poly<M> {
+        self.div_rem(rhs).0
+    }
+}
+
+impl<M: ModularInteger + Clone> Rem for &Poly<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    type Output = Poly<M>;
+    #[inline]
+    fn rem(self, rhs: Self) -> Poly<M> {
+        self.div_rem(rhs).1
+    }
+}
+
+macro_rules! impl_owned_op {
+    ($op:ident, $method:ident) => {
+        impl<M: ModularInteger + Clone> $op for Poly<M>
+        where
+            M::Base: Into<u64> + TryFrom<u64>,
+        {
+            type Output = Self;
+            #[inline]
+            fn $method(self, rhs: Self) -> Self {
+                (&self).$method(&rhs)
+            }
+        }
+    };
+}
+impl_owned_op!(Add, add);
+impl_owned_op!(Sub, sub);
+impl_owned_op!(Mul, mul);
+impl_owned_op!(Div, div);
+impl_owned_op!(Rem, rem);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModularCoreOps, MontgomeryInt, VanillaInt};
+    use rand::random;
+
+    fn random_poly(x: &MontgomeryInt<u64>, len: usize) -> Poly<MontgomeryInt<u64>> {
+        Poly::from_residues((0..len).map(|_| random::<u64>()), x)
+    }
+
+    fn naive_mul(a: &[u64], b: &[u64], m: u64) -> Vec<u64> {
+        let mut c = std::vec![0u64; a.len() + b.len() - 1];
+        for (i, &u) in a.iter().enumerate() {
+            for (j, &v) in b.iter().enumerate() {
+                c[i + j] = c[i + j].addm(u.mulm(v, &m), &m);
+            }
+        }
+        c
+    }
+
+    #[test]
+    fn basic_test() {
+        let x = MontgomeryInt::new(0u64, &1_000_000_007);
+        let f = Poly::from_residues([3, 0, 2, 0, 0], &x);
+        assert_eq!(f.degree(), Some(2));
+        assert_eq!(f.residues(), [3, 0, 2]);
+        assert_eq!(f.coeff(2).residue(), 2);
+        assert_eq!(f.coeff(10).residue(), 0);
+        assert_eq!(f.leading().map(|c| c.residue()), Some(2));
+        assert_eq!(f.derivative().residues(), [0, 4]);
+        assert_eq!(f.scale(&x.convert(3)).residues(), [9, 0, 6]);
+        assert_eq!(f.eval(&x.convert(10)).residue(), 203);
+
+        let zero = Poly::zero(&x);
+        assert!(zero.is_zero());
+        assert_eq!(zero.degree(), None);
+        assert_eq!(zero.eval(&x.convert(5)).residue(), 0);
+        assert_eq!(&f - &f, zero);
+        assert_eq!(&f + &zero, f);
+        assert!((&f * &zero).is_zero());
+        assert_eq!((-f.clone()).residues(), [1_000_000_004, 0, 1_000_000_005]);
+
+        let t3 = Poly::monomial(x.convert(5), 3);
+        assert_eq!(t3.residues(), [0, 0, 0, 5]);
+        assert_eq!((&t3 + &f).residues(), [3, 0, 2, 5]);
+        assert!(Poly::monomial(x.convert(0), 3).is_zero());
+    }
+
+    #[test]
+    fn mul_test() {
+        // 998244353 uses the transforms, 1e9+7 falls back to the three-prime transforms
+        for &m in [998244353u64, 1_000_000_007, (1 << 62) + 1].iter() {
+            let x = MontgomeryInt::new(0u64, &m);
+            for &(la, lb) in [(1, 1), (10, 20), (40, 100), (300, 257)].iter() {
+                let (a, b) = (random_poly(&x, la), random_poly(&x, lb));
+                let expected = naive_mul(&a.residues(), &b.residues(), m);
+                assert_eq!((&a * &b).residues(), expected, "m = {}", m);
+            }
+        }
+
+        // even modulus
+        let x = VanillaInt::new(0u32, &(1 << 30));
+        let a = Poly::from_residues((0..100).map(|_| random::<u32>()), &x);
+        let b = Poly::from_residues((0..70).map(|_| random::<u32>()), &x);
+        let ra: Vec<u64> = a.residues().iter().map(|&v| v as u64).collect();
+        let rb: Vec<u64> = b.residues().iter().map(|&v| v as u64).collect();
+        let expected = naive_mul(&ra, &rb, 1 << 30);
+        let c: Vec<u64> = (a * b).residues().iter().map(|&v| v as u64).collect();
+        assert_eq!(c, expected);
+    }
+
+    #[test]
+    fn div_rem_test() {
+        let x = MontgomeryInt::new(0u64, &998244353);
+        for &(la, lb) in [(1, 1), (5, 10), (50, 7), (200, 100), (64, 64)].iter() {
+            let (a, mut b) = (random_poly(&x, la), random_poly(&x, lb));
+            if b.is_zero() {
+                b = Poly::monomial(x.convert(1), 0);
+            }
+            let (q, r) = a.div_rem(&b);
+            assert!(r.degree() < b.degree() || r.is_zero());
+            assert_eq!(&(&q * &b) + &r, a);
+            assert_eq!(&a / &b, q);
+            assert_eq!(a % b, r);
+        }
+
+        // exact division
+        let (a, b) = (random_poly(&x, 80), random_poly(&x, 30));
+        let (q, r) = (&a * &b).div_rem(&b);
+        assert_eq!(q, a);
+        assert!(r.is_zero());
+
+        // long division by the series inverse, with a non NTT-friendly modulus
+        let x = MontgomeryInt::new(0u64, &1_000_000_007);
+        let (a, b) = (random_poly(&x, 500), random_poly(&x, 120));
+        let (q, r) = a.div_rem(&b);
+        assert!(r.degree() < b.degree());
+        assert_eq!(&(&q * &b) + &r, a);
+    }
+
+    #[test]
+    fn gcd_test() {
+        let x = MontgomeryInt::new(0u64, &1_000_000_007);
+        let (a, b, c) = (
+            random_poly(&x, 30),
+            random_poly(&x, 20),
+            random_poly(&x, 10),
+        );
+        let g = (&a * &c).gcd(&(&b * &c));
+        assert_eq!(g.leading().map(|v| v.residue()), Some(1));
+        assert_eq!(&g % &c, Poly::zero(&x));
+        assert_eq!(g.degree(), c.degree()); // a and b are coprime with high probability
+        assert_eq!(c.gcd(&Poly::zero(&x)), c.monic());
+        assert!(Poly::zero(&x).gcd(&Poly::zero(&x)).is_zero());
+        assert_eq!(c.scale(&x.convert(5)).monic(), c.monic());
+    }
+
+    #[test]
+    #[should_panic]
+    fn div_zero_test() {
+        let x = MontgomeryInt::new(0u64, &998244353);
+        let _ = random_poly(&x, 5).div_rem(&Poly::zero(&x));
+    }
+}
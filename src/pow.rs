@@ -0,0 +1,1444 @@
+//! Exponentiation utilities built on top of the reducers
+
+use crate::monty::neg_mod_inv;
+use crate::root::prime_powers;
+use crate::{
+    Carmichael, ModularCoreOps, ModularInteger, ModularPow, ModularUnaryOps, Montgomery,
+    ReducedInt, Reducer, Vanilla,
+};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::ops::{BitAnd, Shr};
+
+/// Integer types supported by [powm_fast()]
+pub trait PowmFast: Sized {
+    /// Return (base ^ exp) % m, see [powm_fast()] for details
+    fn powm_fast(base: Self, exp: Self, m: Self) -> Self;
+}
+
+/// Modular exponentiation with the algorithm selected by the form of the modulus.
+///
+/// The modulus is inspected only once:
+/// - odd modulus: exponentiation in the Montgomery form
+/// - power of two: exponentiation with wrapping multiplications and a bit mask
+/// - other even modulus `m = q * 2^s`: the two cases above are combined by
+///   the Chinese Remainder Theorem
+///
+/// # Panics
+/// if the modulus is zero
+#[inline]
+pub fn powm_fast<T: PowmFast>(base: T, exp: T, m: T) -> T {
+    T::powm_fast(base, exp, m)
+}
+
+macro_rules! impl_powm_fast_for {
+    ($($T:ident)*) => {$(
+        impl PowmFast for $T {
+            fn powm_fast(base: $T, exp: $T, m: $T) -> $T {
+                assert!(m > 0, "the modulus should not be zero");
+
+                // (base ^ exp) mod 2^s, where mask = 2^s - 1
+                let pow_masked = |mut base: $T, mut exp: $T, mask: $T| {
+                    let mut result: $T = 1;
+                    while exp > 0 {
+                        if exp & 1 != 0 {
+                            result = result.wrapping_mul(base);
+                        }
+                        base = base.wrapping_mul(base);
+                        exp >>= 1;
+                    }
+                    result & mask
+                };
+
+                if m & 1 == 1 {
+                    return ReducedInt::<$T, Montgomery<$T>>::new(base, &m)
+                        .pow(&exp)
+                        .residue();
+                }
+
+                let s = m.trailing_zeros();
+                let q = m >> s;
+                let mask = (1 << s) - 1;
+                let x2 = pow_masked(base, exp, mask);
+                if q == 1 {
+                    return x2;
+                }
+
+                // combine x1 = base^exp mod q and x2 = base^exp mod 2^s by
+                // x = x1 + q * ((x2 - x1) * q^-1 mod 2^s)
+                let x1 = ReducedInt::<$T, Montgomery<$T>>::new(base, &q)
+                    .pow(&exp)
+                    .residue();
+                let qinv = neg_mod_inv::$T::neginv(q).wrapping_neg();
+                let t = x2.wrapping_sub(x1).wrapping_mul(qinv) & mask;
+                x1 + q * t
+            }
+        }
+    )*};
+}
+impl_powm_fast_for!(u8 u16 u32 u64 u128 usize);
+
+/// Errors of parsing the exponent string in [powm_str_exp()] and [powm_hex_exp()]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The exponent string is empty
+    Empty,
+    /// The character at the given byte position is not a digit of the radix
+    InvalidDigit(usize),
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseError::Empty => f.write_str("the exponent is empty"),
+            ParseError::InvalidDigit(pos) => write!(f, "invalid digit at position {}", pos),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Integer types supported by [powm_str_exp()] and [powm_hex_exp()]
+pub trait PowmStrExp: Sized {
+    /// Return (base ^ exp) % m with a decimal exponent, see [powm_str_exp()] for details
+    fn powm_str_exp(
+        base: Self,
+        exp_decimal: &str,
+        m: &Self,
+        lambda: Option<Self>,
+    ) -> Result<Self, ParseError>;
+
+    /// Return (base ^ exp) % m with a hexadecimal exponent, see [powm_hex_exp()] for details
+    fn powm_hex_exp(
+        base: Self,
+        exp_hex: &str,
+        m: &Self,
+        lambda: Option<Self>,
+    ) -> Result<Self, ParseError>;
+}
+
+/// Modular exponentiation with the exponent given as a decimal string, which can be
+/// too large to be represented by any integer type (e.g. millions of digits).
+///
+/// The string is consumed digit by digit and the exponent is reduced modulo `lambda` on the fly,
+/// so the exponent is never materialized:
+/// - If `lambda` is given, it must be a positive multiple of the Carmichael function λ(m)
+///   (e.g. Euler's totient φ(m)).
+/// - If `lambda` is [None], λ(m) is computed from the factorization of m (see
+///   [carmichael()][crate::carmichael]). It's fast when m without its small prime factors
+///   fits in u64, otherwise it falls back to trial division, so λ(m) should be supplied.
+///
+/// The reduction is also valid for bases not coprime to m: when the true exponent is not
+/// smaller than the bit length of m (which bounds the multiplicities of the prime factors
+/// of m), the reduced exponent is raised by multiples of `lambda` until it's above that
+/// bound as well.
+///
+/// # Panics
+/// if m is zero, or `lambda` is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::powm_str_exp;
+///
+/// // 2^(10^30) mod 1000, with λ(1000) = 100
+/// let exp = "1000000000000000000000000000000";
+/// assert_eq!(powm_str_exp(2u32, exp, &1000, Some(100)), Ok(376));
+/// assert_eq!(powm_str_exp(2u32, exp, &1000, None), Ok(376));
+/// ```
+#[inline]
+pub fn powm_str_exp<T: PowmStrExp>(
+    base: T,
+    exp_decimal: &str,
+    m: &T,
+    lambda: Option<T>,
+) -> Result<T, ParseError> {
+    T::powm_str_exp(base, exp_decimal, m, lambda)
+}
+
+/// Modular exponentiation with the exponent given as a hexadecimal string (case-insensitive,
+/// without the `0x` prefix), e.g. the test vectors of cryptographic standards.
+///
+/// It's the same as [powm_str_exp()] except for the radix: the string is consumed digit by
+/// digit and the exponent is reduced modulo `lambda` (or λ(m) if it's [None]) on the fly.
+///
+/// # Panics
+/// if m is zero, or `lambda` is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{powm_hex_exp, ModularPow};
+///
+/// let m = 1_000_000_007u64;
+/// assert_eq!(powm_hex_exp(3u64, "DEADbeef", &m, None), Ok(3u64.powm(0xdeadbeef, &m)));
+/// // 3^(2^256 - 1) mod p, with λ(p) = p - 1
+/// let exp = "f".repeat(64);
+/// assert_eq!(powm_hex_exp(3u64, &exp, &m, Some(m - 1)), powm_hex_exp(3u64, &exp, &m, None));
+/// ```
+#[inline]
+pub fn powm_hex_exp<T: PowmStrExp>(
+    base: T,
+    exp_hex: &str,
+    m: &T,
+    lambda: Option<T>,
+) -> Result<T, ParseError> {
+    T::powm_hex_exp(base, exp_hex, m, lambda)
+}
+
+macro_rules! impl_powm_str_exp_for {
+    ($T:ty, $m:ident) => {
+        mod $m {
+            use super::*;
+
+            /// Exponentiation with the exponent string of digits in the radix (at most 16)
+            fn powm_radix_exp(
+                base: $T,
+                exp: &str,
+                radix: u8,
+                m: &$T,
+                lambda: Option<$T>,
+            ) -> Result<$T, ParseError> {
+                assert!(*m > 0, "the modulus should not be zero");
+                if exp.is_empty() {
+                    return Err(ParseError::Empty);
+                }
+                let digits = exp.bytes().enumerate().map(|(i, c)| {
+                    match (c as char).to_digit(radix as u32) {
+                        Some(d) => Ok(d as $T),
+                        None => Err(ParseError::InvalidDigit(i)),
+                    }
+                });
+                let base = base % m;
+
+                let lambda = match lambda {
+                    Some(l) => l,
+                    None => {
+                        // λ(m) <= m, so the factors fit in the type
+                        let mut factors = [(0 as $T, 0u32); 28];
+                        let mut len = 0;
+                        for (f, (p, k)) in factors.iter_mut().zip(prime_powers(*m as u128)) {
+                            *f = (p as $T, k);
+                            len += 1;
+                        }
+                        <$T>::carmichael(&factors[..len])
+                    }
+                };
+                assert!(lambda > 0, "lambda should not be zero");
+
+                // the exponent is kept exactly as long as it's smaller than the threshold
+                let threshold = (<$T>::BITS - m.leading_zeros()) as u8;
+                let mut small = 0u8;
+                let mut reduced: $T = 0;
+                for d in digits {
+                    let d = d?;
+                    reduced = reduced.mulm(radix as $T, &lambda).addm(d % lambda, &lambda);
+                    small = small.saturating_mul(radix).saturating_add(d as u8);
+                }
+
+                if small < threshold {
+                    return Ok(base.powm(small as $T, m));
+                }
+                let mut result = base.powm(reduced, m);
+                if reduced < threshold as $T {
+                    let k = (threshold as $T - reduced - 1) / lambda + 1;
+                    result = result.mulm(base.powm(lambda, m).powm(k, m), m);
+                }
+                Ok(result)
+            }
+
+            impl PowmStrExp for $T {
+                #[inline]
+                fn powm_str_exp(
+                    base: $T,
+                    exp_decimal: &str,
+                    m: &$T,
+                    lambda: Option<$T>,
+                ) -> Result<$T, ParseError> {
+                    powm_radix_exp(base, exp_decimal, 10, m, lambda)
+                }
+
+                #[inline]
+                fn powm_hex_exp(
+                    base: $T,
+                    exp_hex: &str,
+                    m: &$T,
+                    lambda: Option<$T>,
+                ) -> Result<$T, ParseError> {
+                    powm_radix_exp(base, exp_hex, 16, m, lambda)
+                }
+            }
+        }
+    };
+}
+impl_powm_str_exp_for!(u8, u8_str_exp);
+impl_powm_str_exp_for!(u16, u16_str_exp);
+impl_powm_str_exp_for!(u32, u32_str_exp);
+impl_powm_str_exp_for!(u64, u64_str_exp);
+impl_powm_str_exp_for!(u128, u128_str_exp);
+impl_powm_str_exp_for!(usize, usize_str_exp);
+
+/// Modular exponentiation with the exponent reduced by Euler's theorem, see [powm_reduced()]
+pub trait PowmReduced<Exp = Self>: Sized {
+    /// Return (self ^ exp) % m with φ(m) given, see [powm_reduced()] for details
+    fn powm_reduced(self, exp: &Exp, m: &Self, phi: &Self) -> Self;
+}
+
+/// Modular exponentiation with a huge exponent, reduced modulo Euler's totient `phi = φ(m)`
+/// given by the caller.
+///
+/// If the base is coprime to m, the power only depends on `exp mod φ(m)` by Euler's theorem.
+/// Otherwise, the generalized Euler's theorem `b^e ≡ b^(e mod φ(m) + φ(m)) (mod m)` is applied,
+/// which holds for `e >= log2(m)`, and the smaller exponents are evaluated directly. Besides
+/// the exponents of the same type as the base, the primitive integers also accept [BigUint]
+/// exponents with the `num-bigint` feature.
+///
+/// Instead of φ(m), a positive multiple of the Carmichael function λ(m) (see
+/// [carmichael()][crate::carmichael()]) also works, as long as it's not smaller than `log2(m)`.
+///
+/// [BigUint]: https://docs.rs/num-bigint/latest/num_bigint/struct.BigUint.html
+///
+/// # Panics
+/// if m or phi is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{powm_reduced, ModularPow};
+///
+/// // φ(100) = 40, and 2 is not coprime to 100
+/// assert_eq!(powm_reduced(2u64, &u64::MAX, &100, &40), 2u64.powm(u64::MAX, &100));
+/// assert_eq!(powm_reduced(3u64, &u64::MAX, &100, &40), 3u64.powm(u64::MAX, &100));
+/// assert_eq!(powm_reduced(2u64, &5, &100, &40), 32);
+/// ```
+#[inline]
+pub fn powm_reduced<T: PowmReduced<E>, E>(base: T, exp: &E, m: &T, phi: &T) -> T {
+    base.powm_reduced(exp, m, phi)
+}
+
+macro_rules! impl_powm_reduced_for {
+    ($T:ty, $m:ident) => {
+        mod $m {
+            use super::*;
+
+            /// Calculate base^(r + k * phi) with k = 0 if the base is invertible, otherwise k = 1
+            fn pow_corrected(base: $T, r: $T, m: $T, phi: $T) -> $T {
+                let base = base % m;
+                let result = powm_fast(base, r, m);
+                if m == 1 || base.invm(&m).is_some() {
+                    result
+                } else {
+                    result.mulm(powm_fast(base, phi, m), &m)
+                }
+            }
+
+            impl PowmReduced for $T {
+                fn powm_reduced(self, exp: &$T, m: &$T, phi: &$T) -> $T {
+                    assert!(*phi > 0, "phi should not be zero");
+                    let threshold = (<$T>::BITS - m.leading_zeros()) as $T;
+                    if *exp < threshold {
+                        return powm_fast(self, *exp, *m);
+                    }
+                    pow_corrected(self, *exp % *phi, *m, *phi)
+                }
+            }
+
+            #[cfg(feature = "num-bigint")]
+            impl PowmReduced<num_bigint::BigUint> for $T {
+                fn powm_reduced(self, exp: &num_bigint::BigUint, m: &$T, phi: &$T) -> $T {
+                    use core::convert::TryFrom;
+                    assert!(*phi > 0, "phi should not be zero");
+                    let threshold = (<$T>::BITS - m.leading_zeros()) as u64;
+                    if exp.bits() < 64 && u64::try_from(exp).unwrap() < threshold {
+                        return powm_fast(self, <$T>::try_from(exp).unwrap(), *m);
+                    }
+                    let r = <$T>::try_from(exp % num_bigint::BigUint::from(*phi)).unwrap();
+                    pow_corrected(self, r, *m, *phi)
+                }
+            }
+        }
+    };
+}
+impl_powm_reduced_for!(u8, u8_impl);
+impl_powm_reduced_for!(u16, u16_impl);
+impl_powm_reduced_for!(u32, u32_impl);
+impl_powm_reduced_for!(u64, u64_impl);
+impl_powm_reduced_for!(u128, u128_impl);
+impl_powm_reduced_for!(usize, usize_impl);
+
+/// Fixed 4-bit window exponentiation with an exponent of little-endian 64-bit limbs
+fn pow_limbs<T: Copy + From<u8>, R: Reducer<T>>(r: &R, base: T, exp: &[u64]) -> T {
+    let one = r.transform(T::from(1));
+    let mut table = [one; 16];
+    table[1] = r.transform(base);
+    for i in 2..16 {
+        table[i] = r.mul(&table[i - 1], &table[1]);
+    }
+
+    let mut result = one;
+    let mut leading = true;
+    for &limb in exp.iter().rev() {
+        for i in (0..16).rev() {
+            let w = (limb >> (4 * i)) & 0xF;
+            if leading {
+                // skip the leading zeros
+                if w == 0 {
+                    continue;
+                }
+                leading = false;
+            } else {
+                for _ in 0..4 {
+                    result = r.sqr(result);
+                }
+            }
+            if w != 0 {
+                result = r.mul(&result, &table[w as usize]);
+            }
+        }
+    }
+    r.residue(result)
+}
+
+macro_rules! impl_powm_limbs_for {
+    ($($T:ty)*) => {$(
+        /// Modular exponentiation with the exponent given as the little-endian 64-bit limbs, so
+        /// that the exponent can have thousands of bits without a big integer type. The
+        /// calculation is performed in the Montgomery form for odd moduli.
+        impl ModularPow<&[u64], &$T> for $T {
+            type Output = $T;
+            fn powm(self, exp: &[u64], m: &$T) -> $T {
+                if m & 1 == 1 {
+                    pow_limbs(&Montgomery::<$T>::new(*m), self % m, exp)
+                } else {
+                    pow_limbs(&Vanilla::<$T>::new(m), self, exp)
+                }
+            }
+        }
+    )*};
+}
+impl_powm_limbs_for!(u8 u16 u32 u64 u128 usize);
+
+/// Integer types supported by [tetration()]
+pub trait Tetration: Sized {
+    /// Return the power tower of self with the given height modulo m, see [tetration()]
+    fn tetration(self, height: u64, m: &Self) -> Self;
+}
+
+/// Calculate the power tower `a^a^...^a` (with `height` copies of a, evaluated from the top)
+/// modulo m, by the recursive reduction of the exponents modulo Euler's totient.
+///
+/// The exponent `a↑↑(h-1)` is needed modulo φ(m), the exponent of that is needed modulo
+/// φ(φ(m)) and so on, until the modulus becomes one. The generalized Euler's theorem
+/// `a^e ≡ a^(e mod φ(m) + φ(m)) (mod m)` is valid only for exponents `e >= log2(m)`, so the
+/// exponents are also evaluated exactly while they fit in the integer type. Since the chain
+/// of totients reaches one in `O(log(m))` steps and the towers of `a >= 2` overflow in at most
+/// 5 levels, the height is effectively bounded, and huge heights are fine. The totients are
+/// calculated by factorizing the moduli, which is slow for a modulus beyond 64 bits with two
+/// large prime factors.
+///
+/// Conventionally `a↑↑0 = 1`, and `0^0 = 1`, so the towers of zero alternate between 1 and 0.
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{tetration, ModularPow};
+///
+/// assert_eq!(tetration(3u64, 3, &1000), 3u64.powm(27, &1000)); // 3^3^3
+/// assert_eq!(tetration(2u64, 5, &1_000_000_000), 719156736); // 2^65536 mod 10^9
+/// assert_eq!(tetration(3u64, u64::MAX, &1000), 387); // stable for large heights
+/// ```
+#[inline]
+pub fn tetration<T: Tetration>(a: T, height: u64, m: &T) -> T {
+    a.tetration(height, m)
+}
+
+macro_rules! impl_tetration_for {
+    ($T:ty, $m:ident) => {
+        mod $m {
+            use super::*;
+            use crate::root::prime_powers;
+
+            fn totient(n: $T) -> $T {
+                prime_powers(n as u128).fold(n, |acc, (p, _)| acc / p as $T * (p as $T - 1))
+            }
+
+            /// The tower `a↑↑h` modulo n, and its exact value if it fits in the type (a >= 2)
+            fn tower(a: $T, h: u64, n: $T) -> ($T, Option<$T>) {
+                if h == 0 {
+                    return (1 % n, Some(1));
+                }
+                let phi = totient(n);
+                let (e, exact) = tower(a, h - 1, phi);
+                let exact = exact.and_then(|e| {
+                    if e < <$T>::BITS as $T {
+                        a.checked_pow(e as u32)
+                    } else {
+                        None
+                    }
+                });
+                let residue = match exact {
+                    Some(v) => v % n,
+                    // the exponent is huge, e >= log2(n) holds certainly
+                    None => powm_fast(a, e, n).mulm(powm_fast(a, phi, n), &n),
+                };
+                (residue, exact)
+            }
+
+            impl Tetration for $T {
+                fn tetration(self, height: u64, m: &$T) -> $T {
+                    assert!(*m > 0, "the modulus should not be zero");
+                    let m = *m;
+                    match self {
+                        0 => return (height % 2 == 0) as $T % m,
+                        1 => return 1 % m,
+                        _ => {}
+                    }
+
+                    // the levels above the chain of totients and the overflow don't matter
+                    let mut depth = 5;
+                    let mut n = m;
+                    while n > 1 {
+                        n = totient(n);
+                        depth += 1;
+                    }
+                    tower(self, height.min(depth), m).0
+                }
+            }
+        }
+    };
+}
+impl_tetration_for!(u8, u8_tetration);
+impl_tetration_for!(u16, u16_tetration);
+impl_tetration_for!(u32, u32_tetration);
+impl_tetration_for!(u64, u64_tetration);
+impl_tetration_for!(u128, u128_tetration);
+impl_tetration_for!(usize, usize_tetration);
+
+#[cfg(feature = "num-bigint")]
+impl PowmReduced for num_bigint::BigUint {
+    fn powm_reduced(self, exp: &Self, m: &Self, phi: &Self) -> Self {
+        use num_integer::Integer;
+        use num_traits::{One, Zero};
+        assert!(!phi.is_zero(), "phi should not be zero");
+        if exp < &Self::from(m.bits()) {
+            return self.modpow(exp, m);
+        }
+        let result = self.modpow(&(exp % phi), m);
+        if m.is_one() || self.gcd(m).is_one() {
+            result
+        } else {
+            result * self.modpow(phi, m) % m
+        }
+    }
+}
+
+/// Integer types supported by [powm_crt()]
+pub trait PowmCrt: Sized {
+    /// Return (base ^ exp) % m with the factorization of m, see [powm_crt()] for details
+    fn powm_crt(base: &Self, exp: &Self, factors: &[(Self, u32)]) -> Self;
+}
+
+/// Modular exponentiation over a composite modulus with known factorization, by the
+/// Chinese Remainder Theorem.
+///
+/// The modulus is `m = p1^k1 * p2^k2 * ...` given as `factors = [(p1, k1), (p2, k2), ...]`
+/// with distinct primes. For each prime power, the exponent is reduced modulo φ(p^k) and the
+/// power is evaluated with [powm_fast()] (i.e. in Montgomery form for odd primes), then the
+/// results are recombined with Garner's algorithm. Since the moduli are about half as wide,
+/// this is several times faster than a direct exponentiation modulo m, like in RSA.
+///
+/// Bases not coprime to some prime p are handled correctly: the power is zero modulo `p^k`
+/// if the exponent is at least k, and it's evaluated directly otherwise. An empty list of
+/// factors represents `m = 1`, where the result is always zero.
+///
+/// # Panics
+/// if the product of the factors overflows, or if the primes are not pairwise distinct
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{powm_crt, ModularPow};
+///
+/// let (p, q) = (1000000007u64, 998244353u64);
+/// let (b, e) = (123456789, 987654321);
+/// assert_eq!(powm_crt(&b, &e, &[(p, 1), (q, 1)]), b.powm(e, &(p * q)));
+/// ```
+#[inline]
+pub fn powm_crt<T: PowmCrt>(base: &T, exp: &T, factors: &[(T, u32)]) -> T {
+    T::powm_crt(base, exp, factors)
+}
+
+macro_rules! impl_powm_crt_for {
+    ($($T:ty)*) => {$(
+        impl PowmCrt for $T {
+            fn powm_crt(base: &$T, exp: &$T, factors: &[($T, u32)]) -> $T {
+                const OVERFLOW: &str = "the product of the factors should fit in the integer type";
+                let (base, exp) = (*base, *exp);
+
+                // x is the result modulo the product of the processed prime powers
+                let (mut x, mut modulus): ($T, $T) = (0, 1);
+                for &(p, k) in factors {
+                    if k == 0 {
+                        continue;
+                    }
+                    let pk1 = p.checked_pow(k - 1).expect(OVERFLOW);
+                    let pk = pk1.checked_mul(p).expect(OVERFLOW);
+                    let b = base % pk;
+                    let r = if b % p != 0 {
+                        powm_fast(b, exp % (pk - pk1), pk)
+                    } else if exp >= k as $T {
+                        0
+                    } else {
+                        powm_fast(b, exp, pk)
+                    };
+
+                    // x + modulus * ((r - x) * modulus^-1 mod p^k), modulo the new modulus
+                    let minv = (modulus % pk)
+                        .invm(&pk)
+                        .expect("the primes in the factorization should be distinct");
+                    let t = r.subm(x % pk, &pk).mulm(minv, &pk);
+                    let next = modulus.checked_mul(pk).expect(OVERFLOW);
+                    x = x.addm(modulus.mulm(t, &next), &next);
+                    modulus = next;
+                }
+                x
+            }
+        }
+    )*};
+}
+impl_powm_crt_for!(u8 u16 u32 u64 u128 usize);
+
+/// Integer types supported by [multi_powm()]
+pub trait MultiPowm: Sized {
+    /// Return the product of (base ^ exp) % m over the pairs, see [multi_powm()] for details
+    fn multi_powm(pairs: &[(Self, Self)], m: &Self) -> Self;
+}
+
+/// Simultaneous multi-exponentiation, calculating `a1^e1 * a2^e2 * ... mod m` for the pairs
+/// `[(a1, e1), (a2, e2), ...]`, like `a^x * b^y mod m` in the signature verifications.
+///
+/// The exponents are scanned together from the most significant bit (Straus' algorithm,
+/// or Shamir's trick for two bases), so the squarings are shared by the bases and at most one
+/// multiplication is needed for each bit, by the precomputed product of the bases whose
+/// exponent bit is set. The bases are processed in groups of four to bound the tables to
+/// 16 entries. For k bases in a group, this needs about `1/k` of the squarings of separate
+/// [powm()][ModularPow::powm] calls, and fewer multiplications as well.
+///
+/// The calculation is performed in the Montgomery form for odd moduli. An empty list of pairs
+/// evaluates to `1 % m`.
+///
+/// # Panics
+/// if the modulus is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{multi_powm, ModularCoreOps, ModularPow};
+///
+/// let m = 1000000007u64;
+/// let (a, x, b, y) = (2, 123456789, 3, 987654321);
+/// let expected = a.powm(x, &m).mulm(b.powm(y, &m), &m);
+/// assert_eq!(multi_powm(&[(a, x), (b, y)], &m), expected);
+/// ```
+#[inline]
+pub fn multi_powm<T: MultiPowm>(pairs: &[(T, T)], m: &T) -> T {
+    T::multi_powm(pairs, m)
+}
+
+/// Straus' algorithm over a reducer, `bit_len` returns the number of significant bits
+fn multi_pow_reduced<T, R: Reducer<T>>(r: &R, pairs: &[(T, T)], bit_len: fn(&T) -> u32) -> T
+where
+    T: Copy + PartialEq + From<u8> + Shr<u32, Output = T> + BitAnd<Output = T>,
+{
+    let one = r.transform(T::from(1));
+    let mut result = one;
+    for group in pairs.chunks(4) {
+        // the products of the subsets of the bases, indexed by the bit masks
+        let mut table = [one; 16];
+        for (i, (b, _)) in group.iter().enumerate() {
+            let b = r.transform(*b);
+            for mask in 0..(1 << i) {
+                table[mask | 1 << i] = r.mul(&table[mask], &b);
+            }
+        }
+
+        let bits = group.iter().map(|(_, e)| bit_len(e)).max().unwrap_or(0);
+        let mut acc = one;
+        for i in (0..bits).rev() {
+            acc = r.sqr(acc);
+            let mask = group.iter().enumerate().fold(0, |mask, (j, (_, e))| {
+                if (*e >> i) & T::from(1) != T::from(0) {
+                    mask | 1 << j
+                } else {
+                    mask
+                }
+            });
+            if mask != 0 {
+                r.mul_in_place(&mut acc, &table[mask]);
+            }
+        }
+        r.mul_in_place(&mut result, &acc);
+    }
+    r.residue(result)
+}
+
+macro_rules! impl_multi_powm_for {
+    ($($T:ty)*) => {$(
+        impl MultiPowm for $T {
+            fn multi_powm(pairs: &[($T, $T)], m: &$T) -> $T {
+                let bit_len: fn(&$T) -> u32 = |e| <$T>::BITS - e.leading_zeros();
+                if m & 1 == 1 {
+                    multi_pow_reduced(&Montgomery::<$T>::new(*m), pairs, bit_len)
+                } else {
+                    multi_pow_reduced(&Vanilla::<$T>::new(m), pairs, bit_len)
+                }
+            }
+        }
+    )*};
+}
+impl_multi_powm_for!(u8 u16 u32 u64 u128 usize);
+
+/// Signed-digit recoding of exponents.
+///
+/// The digits are returned in little-endian order (least significant digit first),
+/// such that `self = sum(digits[i] * 2^i)`. Trailing zero digits are trimmed.
+#[cfg(feature = "alloc")]
+pub trait SignedDigits {
+    /// Calculate the non-adjacent form (NAF) of the integer, where each digit is
+    /// in `{-1, 0, 1}` and no two consecutive digits are non-zero.
+    #[inline]
+    fn naf(&self) -> Vec<i8> {
+        self.wnaf(2)
+    }
+
+    /// Calculate the width-w non-adjacent form of the integer, where each non-zero
+    /// digit is odd with absolute value less than `2^(w-1)`, and among any `w`
+    /// consecutive digits at most one is non-zero.
+    ///
+    /// # Panics
+    /// if w is not in range `[2, 8]`
+    fn wnaf(&self, w: u8) -> Vec<i8>;
+}
+
+/// Calculate the non-adjacent form of an exponent, see [SignedDigits::naf()]
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn naf<T: SignedDigits>(e: &T) -> Vec<i8> {
+    e.naf()
+}
+
+/// Calculate the width-w non-adjacent form of an exponent, see [SignedDigits::wnaf()]
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn wnaf<T: SignedDigits>(e: &T, w: u8) -> Vec<i8> {
+    e.wnaf(w)
+}
+
+/// wNAF recoding on little-endian 64-bit limbs
+#[cfg(feature = "alloc")]
+fn wnaf_limbs(mut limbs: Vec<u64>, w: u8) -> Vec<i8> {
+    assert!(
+        (2..=8).contains(&w),
+        "the window width should be in range [2, 8]"
+    );
+    limbs.push(0); // reserve space for the carry
+    let nbits = limbs.len() * 64;
+    let bit = |limbs: &[u64], i: usize| (limbs[i / 64] >> (i % 64)) & 1;
+
+    let mut digits = Vec::with_capacity(nbits);
+    let mut i = 0;
+    while i < nbits {
+        if bit(&limbs, i) == 0 {
+            digits.push(0);
+            i += 1;
+            continue;
+        }
+
+        // read the window of w bits starting from position i
+        let mut window = 0u64;
+        for j in (i..(i + w as usize).min(nbits)).rev() {
+            window = (window << 1) | bit(&limbs, j);
+        }
+        let d = if window >= 1 << (w - 1) {
+            window as i16 - (1 << w)
+        } else {
+            window as i16
+        };
+        digits.push(d as i8);
+
+        // subtract d * 2^i from the integer, which clears the window and
+        // results in a carry at position i + w if d is negative
+        for j in i..(i + w as usize).min(nbits) {
+            limbs[j / 64] &= !(1 << (j % 64));
+        }
+        if d < 0 {
+            let mut j = i + w as usize;
+            while j < nbits {
+                let (v, carry) = limbs[j / 64].overflowing_add(1 << (j % 64));
+                limbs[j / 64] = v;
+                if !carry {
+                    break;
+                }
+                j = (j / 64 + 1) * 64;
+            }
+        }
+        i += 1;
+    }
+
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    digits
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_signed_digits_for {
+    ($($T:ty)*) => {$(
+        impl SignedDigits for $T {
+            fn wnaf(&self, w: u8) -> Vec<i8> {
+                let mut limbs = Vec::new();
+                let mut v = *self as u128;
+                while v > 0 {
+                    limbs.push(v as u64);
+                    v >>= 32;
+                    v >>= 32;
+                }
+                wnaf_limbs(limbs, w)
+            }
+        }
+    )*};
+}
+#[cfg(feature = "alloc")]
+impl_signed_digits_for!(u8 u16 u32 u64 u128 usize);
+
+#[cfg(all(feature = "alloc", feature = "num-bigint"))]
+impl SignedDigits for num_bigint::BigUint {
+    fn wnaf(&self, w: u8) -> Vec<i8> {
+        wnaf_limbs(self.to_u64_digits(), w)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: PartialEq + Clone + From<u8> + SignedDigits, R: Reducer<T> + Clone> ReducedInt<T, R> {
+    /// Calculate self ^ exp from the width-w non-adjacent form of the exponent (see [wnaf()]),
+    /// which is a signed-digit method rather than a sliding window over the binary digits.
+    ///
+    /// The nonzero digits are odd and in `(-2^(w-1), 2^(w-1))`, and they are followed by at
+    /// least `w - 1` zeros, so there are about `bits / (w + 1)` multiplications besides the
+    /// squarings. Negative digits are handled with the powers of the modular inverse of self,
+    /// so two tables of `2^(w-2)` odd powers are needed, compared to one table of `2^(w-1)`
+    /// odd powers for the same digit density in the unsigned sliding window used by
+    /// [ReducedInt::pow()] with the Montgomery reducers.
+    ///
+    /// Since the inversion costs about as much as dozens of multiplications, this method is
+    /// better only for long exponents that don't fit in a machine word (e.g. with a [BigUint]
+    /// as T), or when the inverse is cheap. For primitive exponents, [ReducedInt::pow()] is
+    /// usually faster. If self is not invertible, the calculation falls back to
+    /// [ReducedInt::pow()]. The [Montgomery][crate::Montgomery] reducer of [BigUint] already
+    /// uses this method in [ReducedInt::pow()] for the exponents of at least 256 bits.
+    ///
+    /// [BigUint]: https://docs.rs/num-bigint/latest/num_bigint/struct.BigUint.html
+    pub fn pow_wnaf(self, exp: &T, w: u8) -> Self {
+        let inv = match self.clone().inv() {
+            Some(v) => v,
+            None => return self.pow(exp),
+        };
+        let r = self.reducer().clone();
+        let a = reducer_pow_wnaf(&r, self.repr().clone(), inv.repr().clone(), &exp.wnaf(w), w);
+        Self::from_parts(a, r)
+    }
+}
+
+/// The exponent length from which the Montgomery reducers of the multi-precision integers
+/// switch to the wNAF exponentiation (see [ReducedInt::pow_wnaf()]) when the base is invertible
+#[cfg(all(feature = "alloc", feature = "num-bigint"))]
+pub(crate) const WNAF_MIN_BITS: u64 = 256;
+
+/// The width of the wNAF digits used with [WNAF_MIN_BITS]
+#[cfg(all(feature = "alloc", feature = "num-bigint"))]
+pub(crate) const WNAF_WIDTH: u8 = 5;
+
+/// Calculate base ^ e in the reduced form, where e is given by its width-w NAF digits and inv
+/// is the inverse of base
+#[cfg(feature = "alloc")]
+pub(crate) fn reducer_pow_wnaf<T: Clone + From<u8>, R: Reducer<T>>(
+    r: &R,
+    base: T,
+    inv: T,
+    digits: &[i8],
+    w: u8,
+) -> T {
+    // precompute odd powers: x, x^3, ..., x^(2^(w-1) - 1)
+    let odd_powers = |x: T| {
+        let sq = r.sqr(x.clone());
+        let mut table = Vec::with_capacity(1 << (w - 2));
+        table.push(x);
+        for _ in 1..(1 << (w - 2)) {
+            let next = r.mul(table.last().unwrap(), &sq);
+            table.push(next);
+        }
+        table
+    };
+    let pos_table = odd_powers(base);
+    let neg_table = odd_powers(inv);
+
+    let mut result = r.transform(T::from(1));
+    for &d in digits.iter().rev() {
+        result = r.sqr(result);
+        if d > 0 {
+            result = r.mul(&result, &pos_table[(d as usize - 1) / 2]);
+        } else if d < 0 {
+            result = r.mul(&result, &neg_table[((-d) as usize - 1) / 2]);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModularPow;
+    use rand::random;
+
+    const NRANDOM: u32 = 10;
+
+    #[test]
+    fn powm_fast_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                let ms: [$T; 9] = [
+                    1, 2, 3, 4, 12, 1 << (<$T>::BITS - 1),
+                    <$T>::MAX, <$T>::MAX - 1, random::<$T>() | 1,
+                ];
+                for &m in ms.iter() {
+                    for _ in 0..NRANDOM {
+                        let (b, e) = (random::<$T>(), random::<$T>());
+                        assert_eq!(powm_fast(b, e, m), b.powm(e, &m), "{}^{} mod {}", b, e, m);
+
+                        // odd, power of two and general even moduli
+                        let m = random::<$T>().saturating_add(1);
+                        let m2 = 1 << (random::<u32>() % <$T>::BITS);
+                        let m3 = (m >> 1).max(1) << 1;
+                        for &m in [m | 1, m2, m3].iter() {
+                            assert_eq!(powm_fast(b, e, m), b.powm(e, &m), "{}^{} mod {}", b, e, m);
+                        }
+                    }
+                    assert_eq!(powm_fast(0, 0, m), 1 % m);
+                    assert_eq!(powm_fast(<$T>::MAX, 1, m), <$T>::MAX % m);
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+    }
+
+    #[test]
+    fn powm_crt_test() {
+        const PRIMES: [u64; 8] = [2, 3, 5, 7, 11, 101, 65537, 1000003];
+        for _ in 0..100 {
+            // random factored modulus with repeated prime factors
+            let mut factors = std::vec::Vec::new();
+            let mut m = 1u64;
+            for &p in PRIMES.iter() {
+                let k = random::<u32>() % 4;
+                match p.checked_pow(k).and_then(|pk| m.checked_mul(pk)) {
+                    Some(v) if k > 0 => {
+                        m = v;
+                        factors.push((p, k));
+                    }
+                    _ => {}
+                }
+            }
+
+            for _ in 0..NRANDOM {
+                let (b, e) = (random::<u64>(), random::<u64>());
+                assert_eq!(
+                    powm_crt(&b, &e, &factors),
+                    b.powm(e, &m),
+                    "{}^{} mod {}",
+                    b,
+                    e,
+                    m
+                );
+                let e = random::<u64>() % 8;
+                assert_eq!(
+                    powm_crt(&b, &e, &factors),
+                    b.powm(e, &m),
+                    "{}^{} mod {}",
+                    b,
+                    e,
+                    m
+                );
+
+                // bases divisible by one of the primes
+                if let Some(&(p, _)) = factors.get(random::<usize>() % (factors.len() + 1)) {
+                    let b = p * (random::<u64>() >> 32);
+                    for &e in [0, 1, 2, 3, random::<u64>()].iter() {
+                        assert_eq!(
+                            powm_crt(&b, &e, &factors),
+                            b.powm(e, &m),
+                            "{}^{} mod {}",
+                            b,
+                            e,
+                            m
+                        );
+                    }
+                }
+            }
+        }
+
+        assert_eq!(powm_crt(&3u32, &5, &[]), 0);
+        assert_eq!(powm_crt(&0u32, &0, &[(7, 2)]), 1);
+        assert_eq!(
+            powm_crt(&200u8, &3, &[(2, 2), (3, 1), (5, 1)]),
+            200u8.powm(3, &60)
+        );
+
+        // the product of the moduli close to u64::MAX
+        let cases: [&[(u64, u32)]; 3] = [
+            &[(4294967291, 1), (4294967279, 1)],
+            &[(2097143, 1), (2097133, 1), (2097131, 1)],
+            &[(2, 1), (3, 1), (3074457345618258599, 1)],
+        ];
+        for factors in cases.iter() {
+            let m: u64 = factors.iter().map(|&(p, _)| p).product();
+            for _ in 0..NRANDOM {
+                let (b, e) = (random::<u64>(), random::<u64>());
+                assert_eq!(powm_crt(&b, &e, factors), b.powm(e, &m));
+                assert_eq!(powm_crt(&u64::MAX, &e, factors), u64::MAX.powm(e, &m));
+            }
+        }
+
+        // an RSA-like modulus with two 64-bit primes
+        let (p, q) = (18446744073709551557u128, 18446744073709551533u128);
+        for _ in 0..NRANDOM {
+            let (b, e) = (random::<u128>(), random::<u128>());
+            assert_eq!(powm_crt(&b, &e, &[(p, 1), (q, 1)]), b.powm(e, &(p * q)));
+        }
+    }
+
+    #[test]
+    fn multi_powm_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..NRANDOM {
+                    for &m in [random::<$T>() | 1, (random::<$T>() >> 1).max(1) << 1, 1, 2].iter() {
+                        let n = random::<usize>() % 10;
+                        let pairs: std::vec::Vec<($T, $T)> = (0..n)
+                            .map(|_| (random(), random::<$T>() >> (random::<u32>() % <$T>::BITS)))
+                            .collect();
+                        let expected = pairs
+                            .iter()
+                            .fold(1 % m, |acc, &(b, e)| acc.mulm(b.powm(e, &m), &m));
+                        assert_eq!(multi_powm(&pairs, &m), expected, "{:?} mod {}", pairs, m);
+                    }
+                }
+                assert_eq!(multi_powm::<$T>(&[], &7), 1);
+                assert_eq!(multi_powm::<$T>(&[(0, 0), (5, 0)], &7), 1);
+                assert_eq!(multi_powm::<$T>(&[(2, 3), (3, 2)], &100), 72);
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+    }
+
+    // Euler's totient function by trial division
+    fn totient(mut m: u64) -> u64 {
+        let mut result = m;
+        let mut p = 2;
+        while p * p <= m {
+            if m % p == 0 {
+                while m % p == 0 {
+                    m /= p;
+                }
+                result -= result / p;
+            }
+            p += 1;
+        }
+        if m > 1 {
+            result -= result / m;
+        }
+        result
+    }
+
+    #[test]
+    fn powm_str_exp_test() {
+        assert_eq!(powm_str_exp(2u32, "", &7, None), Err(ParseError::Empty));
+        assert_eq!(
+            powm_str_exp(2u32, "12a4", &7, None),
+            Err(ParseError::InvalidDigit(2))
+        );
+        assert_eq!(
+            powm_str_exp(2u32, "-1", &7, Some(6)),
+            Err(ParseError::InvalidDigit(0))
+        );
+        assert_eq!(powm_str_exp(0u32, "0", &7, None), Ok(1));
+        assert_eq!(powm_str_exp(0u32, "0000", &7, Some(6)), Ok(1));
+        assert_eq!(powm_str_exp(5u32, "123", &1, Some(1)), Ok(0));
+
+        // non-coprime bases, where the naive reduction e mod λ(m) fails
+        // λ(8) = 2, but 2^4 = 0 mod 8 while 2^(4 mod 2) = 1
+        assert_eq!(powm_str_exp(2u8, "4", &8, Some(2)), Ok(0));
+        assert_eq!(powm_str_exp(2u8, "3", &8, Some(2)), Ok(0));
+        assert_eq!(powm_str_exp(2u8, "2", &8, Some(2)), Ok(4));
+        assert_eq!(powm_str_exp(2u8, "1000000000000", &8, Some(2)), Ok(0));
+        // 72 = 2^3 * 3^2, so the powers of 6 are periodic only from k = 3
+        for k in 1..30u32 {
+            let e = std::format!("{}", k);
+            let expected = 6u64.powm(k as u64, &72);
+            assert_eq!(powm_str_exp(6u64, &e, &72, Some(6)), Ok(expected));
+            assert_eq!(powm_str_exp(6u64, &e, &72, Some(24)), Ok(expected));
+            assert_eq!(powm_str_exp(6u64, &e, &72, None), Ok(expected));
+        }
+
+        // lambda close to the maximum of the type, where the exponent is kept above the threshold
+        let m = u64::MAX - 58;
+        assert_eq!(
+            powm_str_exp(3u64, "18446744073709551559", &m, Some(m - 1)),
+            Ok(3u64.powm(18446744073709551559, &m))
+        );
+        assert_eq!(powm_str_exp(2u8, "250", &251, Some(250)), Ok(1));
+
+        // exponents fitting in u64
+        for _ in 0..NRANDOM {
+            let m = random::<u32>().max(1) as u64;
+            let (b, e) = (random::<u64>(), random::<u64>() >> (random::<u32>() % 64));
+            let exp = std::format!("{}", e);
+            let expected = b.powm(e, &m);
+            assert_eq!(powm_str_exp(b, &exp, &m, None), Ok(expected));
+            assert_eq!(powm_str_exp(b, &exp, &m, Some(totient(m))), Ok(expected));
+            assert_eq!(
+                powm_str_exp(b, &exp, &m, Some(totient(m) * 3)),
+                Ok(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn powm_hex_exp_test() {
+        assert_eq!(powm_hex_exp(2u32, "", &7, None), Err(ParseError::Empty));
+        assert_eq!(
+            powm_hex_exp(2u32, "0x12", &7, None),
+            Err(ParseError::InvalidDigit(1))
+        );
+        assert_eq!(
+            powm_hex_exp(2u32, "fg", &7, Some(6)),
+            Err(ParseError::InvalidDigit(1))
+        );
+        assert_eq!(powm_hex_exp(0u32, "00", &7, None), Ok(1));
+        assert_eq!(powm_hex_exp(2u8, "A", &8, Some(2)), Ok(0));
+        assert_eq!(
+            powm_hex_exp(6u64, "1c", &72, Some(6)),
+            Ok(6u64.powm(28, &72))
+        );
+
+        // exponent near u64::MAX with lambda = m - 1
+        let m = u64::MAX - 58;
+        assert_eq!(
+            powm_hex_exp(3u64, "ffffffffffffffc7", &m, Some(m - 1)),
+            Ok(3u64.powm(0xffffffffffffffc7, &m))
+        );
+        assert_eq!(powm_hex_exp(2u8, "fa", &251, Some(250)), Ok(1));
+
+        for _ in 0..NRANDOM {
+            let m = random::<u32>().max(1) as u64;
+            let (b, e) = (random::<u64>(), random::<u64>() >> (random::<u32>() % 64));
+            let expected = b.powm(e, &m);
+            for exp in [
+                std::format!("{:x}", e),
+                std::format!("{:X}", e),
+                std::format!("000{:x}", e),
+            ]
+            .iter()
+            {
+                assert_eq!(powm_hex_exp(b, exp, &m, None), Ok(expected));
+                assert_eq!(powm_hex_exp(b, exp, &m, Some(totient(m))), Ok(expected));
+            }
+
+            // the hexadecimal and the decimal strings of a 128-bit exponent
+            let e = random::<u128>();
+            let (hex, dec) = (std::format!("{:x}", e), std::format!("{}", e));
+            let expected = (b as u128).powm(e, &(m as u128)) as u64;
+            assert_eq!(powm_hex_exp(b, &hex, &m, None), Ok(expected));
+            assert_eq!(powm_hex_exp(b, &hex, &m, Some(totient(m))), Ok(expected));
+            assert_eq!(powm_str_exp(b, &dec, &m, Some(totient(m))), Ok(expected));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "num-bigint")]
+    fn powm_str_exp_biguint_test() {
+        use num_bigint::BigUint;
+        use std::string::String;
+
+        for &ndigits in [20usize, 100, 1000, 3000].iter() {
+            let mut exp: String = (0..ndigits)
+                .map(|_| (b'0' + random::<u8>() % 10) as char)
+                .collect();
+            if random() {
+                exp.insert(0, '0'); // leading zeros are allowed
+            }
+            let e = BigUint::parse_bytes(exp.as_bytes(), 10).unwrap();
+
+            // small moduli with many repeated factors
+            let ms = [
+                1u64 << 40,
+                3u64.pow(20) * 4,
+                2 * 3 * 5 * 7 * 11 * 13 * 17 * 19 * 23 * 29 * 31 * 37,
+                random::<u32>().max(1) as u64,
+            ];
+            for &m in ms.iter() {
+                let b = random::<u64>() % m;
+                for &b in [b, b & !1, 6, 0, 1].iter() {
+                    let expected = BigUint::from(b).modpow(&e, &BigUint::from(m));
+                    let actual = powm_str_exp(b, &exp, &m, Some(totient(m)));
+                    assert_eq!(BigUint::from(actual.unwrap()), expected);
+                    let actual = powm_str_exp(b, &exp, &m, None);
+                    assert_eq!(BigUint::from(actual.unwrap()), expected);
+                }
+            }
+
+            // λ(m) is computed for a u128 modulus whose large cofactor fits in u64
+            let m = ((u64::MAX - 58) as u128 * 3u128.pow(5)) << 20;
+            for &b in [random::<u128>() % m, 3, 0].iter() {
+                let expected = BigUint::from(b).modpow(&e, &BigUint::from(m));
+                let actual = powm_str_exp(b, &exp, &m, None);
+                assert_eq!(BigUint::from(actual.unwrap()), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn powm_reduced_test() {
+        for m in 1..300u64 {
+            let phi = totient(m);
+            for b in 0..m {
+                for _ in 0..3 {
+                    let e = random::<u64>() >> (random::<u32>() % 64);
+                    assert_eq!(
+                        powm_reduced(b, &e, &m, &phi),
+                        b.powm(e, &m),
+                        "{}^{} mod {}",
+                        b,
+                        e,
+                        m
+                    );
+                }
+                for e in 0..20u64 {
+                    assert_eq!(
+                        powm_reduced(b, &e, &m, &phi),
+                        b.powm(e, &m),
+                        "{}^{} mod {}",
+                        b,
+                        e,
+                        m
+                    );
+                }
+            }
+        }
+
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..NRANDOM {
+                    let m = random::<u16>().max(1) as $T;
+                    let phi = totient(m as u64) as $T;
+                    let (b, e) = (random::<$T>(), random::<$T>());
+                    assert_eq!(powm_reduced(b, &e, &m, &phi), b.powm(e, &m));
+                    let b = b & !1;
+                    assert_eq!(powm_reduced(b, &e, &m, &phi), b.powm(e, &m));
+                    let phi3 = phi.checked_mul(3).unwrap_or(phi);
+                    assert_eq!(powm_reduced(b, &e, &m, &phi3), b.powm(e, &m));
+                }
+            )*);
+        }
+        tests_for!(u16 u32 u64 u128 usize);
+    }
+
+    #[test]
+    fn powm_limbs_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..NRANDOM {
+                    let ms = [random::<$T>() | 1, (random::<$T>() >> 1).max(1) << 1, 1];
+                    for &m in ms.iter() {
+                        let b = random::<$T>();
+                        let (b128, m128) = (b as u128, m as u128);
+                        let e = random::<u64>();
+                        assert_eq!(b.powm(&[e][..], &m), b128.powm(e as u128, &m128) as $T);
+                        let e = random::<u128>();
+                        let limbs = [e as u64, (e >> 64) as u64];
+                        assert_eq!(b.powm(&limbs[..], &m), b128.powm(e, &m128) as $T);
+                        assert_eq!(b.powm(&[e as u64, (e >> 64) as u64, 0, 0][..], &m), b.powm(&limbs[..], &m));
+                    }
+                    assert_eq!(random::<$T>().powm(&[][..], &7), 1);
+                    assert_eq!(random::<$T>().powm(&[0, 0][..], &7), 1);
+                    assert_eq!((0 as $T).powm(&[0, 1][..], &7), 0);
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+
+        // x^(2^k) by repeated squaring
+        let (b, m) = (random::<u64>(), random::<u64>() | 1);
+        let mut expected = b % m;
+        let mut limbs = [0u64; 40];
+        for k in 0..64 * 40 {
+            limbs[k / 64] = 1 << (k % 64);
+            assert_eq!(
+                b.powm(&limbs[..], &m),
+                expected,
+                "{}^(2^{}) mod {}",
+                b,
+                k,
+                m
+            );
+            limbs[k / 64] = 0;
+            expected = expected.mulm(expected, &m);
+        }
+    }
+
+    #[test]
+    fn tetration_test() {
+        // exact towers
+        for m in 1..500u64 {
+            assert_eq!(tetration(0u64, 0, &m), 1 % m);
+            assert_eq!(tetration(0u64, 3, &m), 0);
+            assert_eq!(tetration(0u64, 4, &m), 1 % m);
+            assert_eq!(tetration(1u64, 100, &m), 1 % m);
+            assert_eq!(tetration(2u64, 1, &m), 2 % m);
+            assert_eq!(tetration(2u64, 4, &m), 65536 % m);
+            assert_eq!(tetration(3u64, 3, &m), 7625597484987 % m);
+            assert_eq!(tetration(5u64, 2, &m), 3125 % m);
+            assert_eq!(tetration(4u64, 3, &m), 4u64.powm(256, &m));
+            assert_eq!(tetration(2u64, 5, &m), 2u64.powm(65536, &m));
+            assert_eq!(tetration(6u64, 3, &m), 6u64.powm(46656, &m));
+        }
+
+        // towers with huge exponents, against the precomputed values
+        assert_eq!(tetration(2u64, 6, &1_000_000_007), 528011107);
+        assert_eq!(tetration(2u64, 6, &1_000_000_000), 437428736);
+        assert_eq!(tetration(3u32, 4, &999999), 110592);
+        assert_eq!(tetration(3u64, 4, &(1 << 40)), 486993987899);
+        assert_eq!(tetration(10u32, 3, &1234567), 277843);
+        assert_eq!(tetration(7u64, 3, &10_000_000_000), 2870132343);
+        assert_eq!(tetration(7u128, 3, &10_000_000_000), 2870132343);
+        assert_eq!(tetration(2u8, 6, &255), 1);
+
+        // the towers stabilize modulo m
+        for _ in 0..NRANDOM {
+            let (a, m) = (random::<u16>() as u64, random::<u16>().max(1) as u64);
+            let t = tetration(a.max(2), 30, &m);
+            assert_eq!(tetration(a.max(2), 31, &m), t);
+            assert_eq!(tetration(a.max(2), u64::MAX, &m), t);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "num-bigint")]
+    fn powm_reduced_biguint_test() {
+        use num_bigint::BigUint;
+        let ms = [
+            1u64 << 40,
+            3u64.pow(20) * 4,
+            2 * 3 * 5 * 7 * 11 * 13 * 17 * 19 * 23,
+            1_000_000_007,
+        ];
+        for &m in ms.iter() {
+            let phi = totient(m);
+            for _ in 0..NRANDOM {
+                let e = BigUint::from_bytes_le(&random::<[u8; 32]>()) << 200u32;
+                let b = random::<u64>() % m;
+                for &b in [b, b & !1, 6, 0, 1].iter() {
+                    let expected = BigUint::from(b).modpow(&e, &BigUint::from(m));
+                    assert_eq!(BigUint::from(powm_reduced(b, &e, &m, &phi)), expected);
+                    let actual =
+                        powm_reduced(BigUint::from(b), &e, &BigUint::from(m), &BigUint::from(phi));
+                    assert_eq!(actual, expected);
+                }
+                for e in 0..50u64 {
+                    let expected = b.powm(e, &m);
+                    assert_eq!(powm_reduced(b, &BigUint::from(e), &m, &phi), expected);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn reconstruct(digits: &[i8]) -> i128 {
+        digits
+            .iter()
+            .rev()
+            .fold(0i128, |acc, &d| acc * 2 + d as i128)
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn naf_test() {
+        assert!(naf(&0u32).is_empty());
+        assert_eq!(naf(&7u8), [-1, 0, 0, 1]);
+        assert_eq!(wnaf(&7u8, 3), [-1, 0, 0, 1]);
+        assert_eq!(wnaf(&7u8, 4), [7]);
+        assert_eq!(naf(&u8::MAX), [-1, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+        for _ in 0..NRANDOM {
+            for w in 2..=8 {
+                let e = random::<u64>();
+                let digits = wnaf(&e, w);
+                assert_eq!(reconstruct(&digits), e as i128);
+                assert!(digits.last().map_or(true, |&d| d > 0));
+                for (i, &d) in digits.iter().enumerate() {
+                    if d != 0 {
+                        assert!(d % 2 != 0 && (d as i16).abs() < 1 << (w - 1));
+                        let window = &digits[i + 1..(i + w as usize).min(digits.len())];
+                        assert!(window.iter().all(|&d| d == 0));
+                    }
+                }
+
+                let e = random::<u128>() >> 1;
+                assert_eq!(reconstruct(&wnaf(&e, w)), e as i128);
+            }
+        }
+
+        // digits beyond the width of u128
+        let digits = naf(&u128::MAX);
+        assert_eq!(digits.len(), 129);
+        assert_eq!(digits[0], -1);
+        assert_eq!(digits[128], 1);
+    }
+
+    #[test]
+    #[cfg(all(feature = "alloc", feature = "num-bigint"))]
+    fn naf_biguint_test() {
+        use num_bigint::{BigInt, BigUint};
+        for _ in 0..NRANDOM {
+            let e = BigUint::from_bytes_le(&random::<[u8; 32]>());
+            for w in 2..=8 {
+                let digits = wnaf(&e, w);
+                let r = digits
+                    .iter()
+                    .rev()
+                    .fold(BigInt::from(0), |acc, &d| acc * 2 + d);
+                assert_eq!(r, BigInt::from(e.clone()));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn pow_wnaf_test() {
+        use crate::{MontgomeryInt, VanillaInt};
+        for _ in 0..NRANDOM {
+            let m = random::<u64>() | 1;
+            let (b, e) = (random::<u64>(), random::<u64>());
+            for w in 2..=8 {
+                let x = MontgomeryInt::new(b, &m);
+                assert_eq!(x.pow_wnaf(&e, w).residue(), b.powm(e, &m));
+                let x = VanillaInt::new(b, &m);
+                assert_eq!(x.pow_wnaf(&e, w).residue(), b.powm(e, &m));
+            }
+
+            // non-invertible base
+            let m = (random::<u32>() & !1).max(2);
+            let b = random::<u32>() & !1;
+            let e = random::<u32>();
+            let x = VanillaInt::new(b, &m);
+            assert_eq!(x.pow_wnaf(&e, 5).residue(), b.powm(e, &m));
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Deterministic primality certificates by Pratt
+
+use crate::ModularPow;
+use alloc::vec::Vec;
+
+/// A primality certificate by [Pratt](https://en.wikipedia.org/wiki/Pratt_certificate).
+///
+/// A number `p > 2` is prime if and only if there is a witness `a` of order `p - 1`, that is
+/// `a^(p-1) = 1 (mod p)` and `a^((p-1)/q) != 1 (mod p)` for each prime factor q of `p - 1`.
+/// The certificate stores the witness and the certificates of all the prime factors of
+/// `p - 1` recursively, and the number 2 is certified by itself.
+///
+/// Generating a certificate requires factoring, which is supplied by the caller, while
+/// [verify()][PrattCertificate::verify] only needs modular exponentiations and trial
+/// divisions by the listed factors, and it doesn't trust the generator in any way.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::PrattCertificate;
+///
+/// // factor by trial division
+/// let factor = |mut n: u64| {
+///     let mut factors = Vec::new();
+///     let mut d = 2;
+///     while d * d <= n {
+///         while n % d == 0 {
+///             factors.push(d);
+///             n /= d;
+///         }
+///         d += 1;
+///     }
+///     if n > 1 {
+///         factors.push(n);
+///     }
+///     factors
+/// };
+///
+/// let cert = PrattCertificate::generate(1_000_000_007, &factor).unwrap();
+/// assert_eq!(cert.witness(), 5);
+/// assert!(cert.verify());
+/// assert!(PrattCertificate::generate(561, &factor).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrattCertificate {
+    p: u64,
+    witness: u64,
+    factors: Vec<PrattCertificate>,
+}
+
+impl PrattCertificate {
+    /// Assemble a certificate claiming that p is prime with the given witness, and the
+    /// certificates of the distinct prime factors of `p - 1`. The claim is not checked here.
+    #[inline]
+    pub fn new(p: u64, witness: u64, factors: Vec<PrattCertificate>) -> Self {
+        Self {
+            p,
+            witness,
+            factors,
+        }
+    }
+
+    /// Generate the certificate for p, where `factor(n)` should return the prime factors
+    /// of n (repetitions and order don't matter). The smallest witness is selected.
+    ///
+    /// Return [None] if p is not a prime, or if the factorization is not correct.
+    pub fn generate<F: Fn(u64) -> Vec<u64>>(p: u64, factor: &F) -> Option<Self> {
+        if p < 2 {
+            return None;
+        }
+        if p == 2 {
+            return Some(Self::new(2, 1, Vec::new()));
+        }
+
+        let mut primes = factor(p - 1);
+        primes.sort_unstable();
+        primes.dedup();
+        let mut rest = p - 1;
+        for &q in primes.iter() {
+            if q < 2 || rest % q != 0 {
+                return None;
+            }
+            while rest % q == 0 {
+                rest /= q;
+            }
+        }
+        if rest != 1 {
+            return None;
+        }
+
+        let mut factors = Vec::with_capacity(primes.len());
+        for &q in primes.iter() {
+            factors.push(Self::generate(q, factor)?);
+        }
+
+        for a in 2..p {
+            if a.powm(p - 1, &p) != 1 {
+                return None; // a is a Fermat witness of compositeness
+            }
+            if primes.iter().all(|&q| a.powm((p - 1) / q, &p) != 1) {
+                return Some(Self::new(p, a, factors));
+            }
+        }
+        None
+    }
+
+    /// The certified prime
+    #[inline]
+    pub fn prime(&self) -> u64 {
+        self.p
+    }
+
+    /// The witness with the multiplicative order `p - 1`
+    #[inline]
+    pub fn witness(&self) -> u64 {
+        self.witness
+    }
+
+    /// The certificates of the distinct prime factors of `p - 1`
+    #[inline]
+    pub fn factors(&self) -> &[PrattCertificate] {
+        &self.factors
+    }
+
+    /// Check the certificate recursively, return true if it proves that the number is prime.
+    pub fn verify(&self) -> bool {
+        let p = self.p;
+        if p == 2 {
+            return true;
+        }
+        if p < 2 || self.witness < 2 || self.witness >= p {
+            return false;
+        }
+
+        // the factors should be distinct, and their product should cover p - 1
+        let mut rest = p - 1;
+        for (i, f) in self.factors.iter().enumerate() {
+            let q = f.p;
+            if q < 2 || rest % q != 0 || self.factors[..i].iter().any(|g| g.p == q) {
+                return false;
+            }
+            while rest % q == 0 {
+                rest /= q;
+            }
+        }
+        if rest != 1 {
+            return false;
+        }
+
+        let a = self.witness;
+        a.powm(p - 1, &p) == 1
+            && self.factors.iter().all(|f| a.powm((p - 1) / f.p, &p) != 1)
+            && self.factors.iter().all(|f| f.verify())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+
+    fn factor(mut n: u64) -> Vec<u64> {
+        let mut factors = Vec::new();
+        let mut d = 2;
+        while d * d <= n {
+            while n % d == 0 {
+                factors.push(d);
+                n /= d;
+            }
+            d += 1;
+        }
+        if n > 1 {
+            factors.push(n);
+        }
+        factors
+    }
+
+    #[test]
+    fn generate_test() {
+        const PRIMES: [u64; 10] = [
+            2,
+            3,
+            5,
+            7,
+            65537,
+            998244353,
+            1000000007,
+            2147483647,
+            4294967291,
+            1099511627791,
+        ];
+        for &p in PRIMES.iter() {
+            let cert = PrattCertificate::generate(p, &factor).unwrap();
+            assert_eq!(cert.prime(), p);
+            assert!(cert.verify(), "{}", p);
+        }
+        assert_eq!(
+            PrattCertificate::generate(998244353, &factor)
+                .unwrap()
+                .witness(),
+            3
+        );
+
+        // composites, including Carmichael numbers
+        for &n in [0u64, 1, 4, 9, 91, 561, 1105, 41041, 1000000007 * 3].iter() {
+            assert!(PrattCertificate::generate(n, &factor).is_none(), "{}", n);
+        }
+
+        // wrong factorization
+        assert!(PrattCertificate::generate(101, &|_| vec![2, 5, 7]).is_none());
+        assert!(PrattCertificate::generate(101, &|_| vec![2]).is_none());
+    }
+
+    #[test]
+    fn tamper_test() {
+        let cert = PrattCertificate::generate(1000000007, &factor).unwrap();
+        assert!(cert.verify());
+
+        // wrong witnesses
+        for &a in [0, 1, 4, 1000000006, 1000000007, 1000000012].iter() {
+            let mut c = cert.clone();
+            c.witness = a;
+            assert!(!c.verify(), "{}", a);
+        }
+
+        // missing, duplicated or wrong factors
+        let mut c = cert.clone();
+        c.factors.pop();
+        assert!(!c.verify());
+        let mut c = cert.clone();
+        c.factors.push(c.factors[0].clone());
+        assert!(!c.verify());
+        let mut c = cert.clone();
+        c.factors
+            .push(PrattCertificate::generate(3, &factor).unwrap());
+        assert!(!c.verify());
+
+        // a tampered sub-certificate
+        let mut c = cert.clone();
+        let last = c.factors.len() - 1;
+        c.factors[last].witness = c.factors[last].p - 1; // of order 2
+        assert!(!c.verify());
+
+        // a composite disguised with a fake factor certificate: 341 - 1 = 2^2 * 5 * 17
+        let fake = PrattCertificate::new(
+            341,
+            2,
+            vec![
+                PrattCertificate::new(2, 1, Vec::new()),
+                PrattCertificate::new(5, 2, vec![PrattCertificate::new(2, 1, Vec::new())]),
+                PrattCertificate::new(17, 3, Vec::new()),
+            ],
+        );
+        assert!(!fake.verify());
+        let fake = PrattCertificate::new(9, 2, vec![PrattCertificate::new(8, 3, Vec::new())]);
+        assert!(!fake.verify());
+    }
+}
@@ -2,6 +2,17 @@
 
 use crate::{udouble, Reducer, Vanilla};
 use crate::{DivExact, ModularAbs, ModularCoreOps, ModularPow, ModularSymbols, ModularUnaryOps};
+use core::ops::Rem;
+
+/// Greatest common divisor of two primitive integers by the Euclidean algorithm
+pub(crate) fn gcd<T: Copy + PartialEq + Rem<Output = T> + From<u8>>(mut a: T, mut b: T) -> T {
+    while b != T::from(0) {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
 
 // FIXME: implement the modular functions as const after https://github.com/rust-lang/rust/pull/68847
 
@@ -196,7 +207,7 @@ macro_rules! impl_symbols_iprim {
                         }
                     }
                     0 => {
-                        if self == &1 {
+                        if self == &1 || self == &-1 {
                             1
                         } else {
                             0
@@ -213,7 +224,12 @@ macro_rules! impl_symbols_iprim {
                         }
                     },
                     i if i < &-1 => {
-                        self.kronecker(&-1) * self.kronecker(&-i)
+                        // -i could overflow, so the odd part is extracted from the magnitude
+                        let u = i.unsigned_abs();
+                        let f = u.trailing_zeros();
+                        self.kronecker(&-1)
+                            * self.kronecker(&2).pow(f)
+                            * self.jacobi(&((u >> f) as $T))
                     },
                     _ => {
                         let f = n.trailing_zeros();
@@ -379,6 +395,7 @@ macro_rules! impl_mod_ops_by_deref {
 }
 
 impl_mod_ops_by_deref!(u8 u16 u32 u64 u128 usize);
+impl_mod_ops_by_deref!([u64; 2]); // implemented in the limb module
 
 macro_rules! impl_absm_for_prim {
     ($($signed:ty => $unsigned:ty;)*) => {$(
@@ -387,7 +404,7 @@ macro_rules! impl_absm_for_prim {
                 if self >= 0 {
                     (self as $unsigned) % m
                 } else {
-                    (-self as $unsigned).negm(m)
+                    (self as $unsigned).wrapping_neg().negm(m)
                 }
             }
         }
@@ -398,6 +415,81 @@ impl_absm_for_prim! {
     i8 => u8; i16 => u16; i32 => u32; i64 => u64; i128 => u128; isize => usize;
 }
 
+// signed operands are normalized into [0, m) and the operations are forwarded
+// to the unsigned implementation of the same width, the modulus must be positive
+macro_rules! impl_mod_ops_iprim {
+    ($($T:ty => $U:ty;)*) => {$(
+        impl ModularCoreOps<$T, &$T> for $T {
+            type Output = $T;
+            #[inline]
+            fn addm(self, rhs: $T, m: &$T) -> $T {
+                assert!(*m > 0, "the modulus should be positive");
+                let m = *m as $U;
+                self.absm(&m).addm(rhs.absm(&m), &m) as $T
+            }
+            #[inline]
+            fn subm(self, rhs: $T, m: &$T) -> $T {
+                assert!(*m > 0, "the modulus should be positive");
+                let m = *m as $U;
+                self.absm(&m).subm(rhs.absm(&m), &m) as $T
+            }
+            #[inline]
+            fn mulm(self, rhs: $T, m: &$T) -> $T {
+                assert!(*m > 0, "the modulus should be positive");
+                let m = *m as $U;
+                self.absm(&m).mulm(rhs.absm(&m), &m) as $T
+            }
+        }
+
+        impl ModularPow<$T, &$T> for $T {
+            type Output = $T;
+            // negative exponents are supported only if self is invertible
+            #[inline]
+            fn powm(self, exp: $T, m: &$T) -> $T {
+                assert!(*m > 0, "the modulus should be positive");
+                let m = *m as $U;
+                let base = self.absm(&m);
+                if exp >= 0 {
+                    base.powm(exp as $U, &m) as $T
+                } else {
+                    base.invm(&m)
+                        .expect("the base should be invertible for negative exponents")
+                        .powm((exp as $U).wrapping_neg(), &m) as $T
+                }
+            }
+        }
+
+        impl ModularUnaryOps<&$T> for $T {
+            type Output = $T;
+            #[inline]
+            fn negm(self, m: &$T) -> $T {
+                assert!(*m > 0, "the modulus should be positive");
+                let m = *m as $U;
+                self.absm(&m).negm(&m) as $T
+            }
+            #[inline]
+            fn invm(self, m: &$T) -> Option<$T> {
+                assert!(*m > 0, "the modulus should be positive");
+                let m = *m as $U;
+                self.absm(&m).invm(&m).map(|v| v as $T)
+            }
+            #[inline]
+            fn dblm(self, m: &$T) -> $T {
+                self.addm(self, m)
+            }
+            #[inline]
+            fn sqm(self, m: &$T) -> $T {
+                self.mulm(self, m)
+            }
+        }
+    )*};
+}
+
+impl_mod_ops_iprim! {
+    i8 => u8; i16 => u16; i32 => u32; i64 => u64; i128 => u128; isize => usize;
+}
+impl_mod_ops_by_deref!(i8 i16 i32 i64 i128 isize);
+
 macro_rules! impl_div_exact_for_prim {
     ($($t:ty)*) => {$(
         impl DivExact<$t, ()> for $t {
@@ -415,7 +507,7 @@ macro_rules! impl_div_exact_for_prim {
     )*};
 }
 
-impl_div_exact_for_prim!(u8 u16 u32 u64 u128);
+impl_div_exact_for_prim!(u8 u16 u32 u64 u128 usize);
 
 #[cfg(test)]
 mod tests {
@@ -472,6 +564,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn addm_boundary_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($({
+                let ms: [$T; 4] = [<$T>::MAX, <$T>::MAX - 1, <$T>::MAX / 2 + 1, random::<$T>() | 1];
+                for &m in ms.iter() {
+                    let cases: [($T, $T); 7] = [
+                        (1, m - 1),           // sum equals m
+                        (m - 1, 1),
+                        (m - 1, m - 1),       // sum equals 2m - 2
+                        (m - 1, m),           // sum equals 2m - 1
+                        (m, m),
+                        (<$T>::MAX, <$T>::MAX),
+                        (<$T>::MAX, 1),
+                    ];
+                    for &(a, b) in cases.iter() {
+                        let expected = ((a as u128 % m as u128 + b as u128 % m as u128)
+                            % m as u128) as $T;
+                        let r = a.addm(b, &m);
+                        assert!(r < m);
+                        assert_eq!(r, expected, "{} + {} mod {}", a, b, m);
+                        assert_eq!(a.dblm(&m), ((a as u128 % m as u128) * 2 % m as u128) as $T);
+                    }
+                }
+            })*);
+        }
+        tests_for!(u8 u16 u32 u64 usize);
+
+        // u128 cannot be checked with a wider type
+        let m = u128::MAX - 1;
+        assert_eq!(1u128.addm(m - 1, &m), 0);
+        assert_eq!((m - 1).addm(m, &m), m - 1);
+        assert_eq!(u128::MAX.addm(u128::MAX, &m), 2);
+        assert_eq!(u128::MAX.addm(u128::MAX, &u128::MAX), 0);
+        assert_eq!(
+            (u128::MAX - 1).addm(u128::MAX - 1, &u128::MAX),
+            u128::MAX - 2
+        );
+        assert_eq!(u128::MAX.dblm(&m), 2);
+    }
+
     #[test]
     fn subm_test() {
         // fixed cases
@@ -564,6 +697,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn subm_negm_exhaustive_test() {
+        // all triples of u8, including the unreduced operands
+        for m in 1..=u8::MAX {
+            for a in 0..=u8::MAX {
+                let r = a.negm(&m);
+                assert!(r < m);
+                assert_eq!(
+                    r as i32,
+                    (-(a as i32)).rem_euclid(m as i32),
+                    "-{} mod {}",
+                    a,
+                    m
+                );
+                for b in 0..=u8::MAX {
+                    let r = a.subm(b, &m);
+                    assert!(r < m);
+                    assert_eq!(r as i32, (a as i32 - b as i32).rem_euclid(m as i32));
+                }
+            }
+        }
+
+        // boundary cases of u64 and u128
+        macro_rules! tests_for {
+            ($($T:ty => $S:ty;)*) => ($(
+                let ms = [1, 2, 3, <$T>::MAX, <$T>::MAX - 1, <$T>::MAX / 2, <$T>::MAX / 2 + 1];
+                for &m in ms.iter() {
+                    let values = [0, 1, m - 1, m, m.wrapping_add(1), <$T>::MAX, <$T>::MAX - 1, 2 * (m / 2)];
+                    for &a in values.iter() {
+                        let expected = (-((a % m) as $S)).rem_euclid(m as $S) as $T;
+                        assert_eq!(a.negm(&m), expected, "-{} mod {}", a, m);
+                        for &b in values.iter() {
+                            let expected = ((a % m) as $S - (b % m) as $S).rem_euclid(m as $S) as $T;
+                            assert_eq!(a.subm(b, &m), expected, "{} - {} mod {}", a, b, m);
+                        }
+                    }
+                }
+            )*);
+        }
+        tests_for!(u8 => i16; u16 => i32; u32 => i64; u64 => i128;);
+
+        // u128 without a wider signed type: -x = m - x for 0 < x < m
+        let ms = [1, 2, 3, u128::MAX, u128::MAX - 1, u128::MAX / 2 + 1];
+        for &m in ms.iter() {
+            let values = [0, 1, m - 1, m, m.wrapping_add(1), u128::MAX, u128::MAX - 1];
+            for &a in values.iter() {
+                let x = a % m;
+                assert_eq!(a.negm(&m), if x == 0 { 0 } else { m - x });
+                for &b in values.iter() {
+                    let y = b % m;
+                    let expected = if x >= y { x - y } else { m - (y - x) };
+                    assert_eq!(a.subm(b, &m), expected, "{} - {} mod {}", a, b, m);
+                }
+            }
+        }
+    }
+
     #[test]
     fn mulm_test() {
         // fixed cases
@@ -698,6 +888,106 @@ mod tests {
         }
     }
 
+    #[test]
+    fn pointer_width_test() {
+        // usize and isize agree with the fixed-width types of the same width
+        macro_rules! tests_for {
+            ($($T:ty, $S:ty => $U:ty;)*) => ($(
+                if <$T>::BITS == usize::BITS {
+                    for _ in 0..NRANDOM {
+                        let (a, b, m) = (random::<$T>(), random::<$T>(), random::<$T>().max(1));
+                        let (ua, ub, um) = (a as usize, b as usize, m as usize);
+                        assert_eq!(ua.addm(ub, &um), a.addm(b, &m) as usize);
+                        assert_eq!(ua.subm(&ub, &um), a.subm(b, &m) as usize);
+                        assert_eq!((&ua).mulm(&ub, &um), a.mulm(b, &m) as usize);
+                        assert_eq!(ua.powm(ub, &um), a.powm(b, &m) as usize);
+                        assert_eq!(ua.invm(&um), a.invm(&m).map(|v| v as usize));
+                        assert_eq!(DivExact::div_exact(ua, um, &()), DivExact::div_exact(a, m, &()).map(|v| v as usize));
+
+                        // signed operands are normalized into [0, m)
+                        let (sa, sb) = (a as $S as isize, b as $S as isize);
+                        let sm = (m >> 1).max(1) as isize;
+                        let (ra, rb) = (sa.rem_euclid(sm) as $U, sb.rem_euclid(sm) as $U);
+                        let um = sm as $U;
+                        assert_eq!(sa.addm(sb, &sm), ra.addm(rb, &um) as isize);
+                        assert_eq!(sa.subm(&sb, &sm), ra.subm(rb, &um) as isize);
+                        assert_eq!((&sa).mulm(&sb, &sm), ra.mulm(rb, &um) as isize);
+                        assert_eq!(sa.negm(&sm), ra.negm(&um) as isize);
+                        assert_eq!(sa.dblm(&sm), ra.dblm(&um) as isize);
+                        assert_eq!(sa.sqm(&sm), ra.sqm(&um) as isize);
+                        assert_eq!(sa.invm(&sm), ra.invm(&um).map(|v| v as isize));
+                        assert_eq!(sa.powm(sb.abs() % 1000, &sm), ra.powm((sb.abs() % 1000) as $U, &um) as isize);
+                        if let Some(inv) = sa.invm(&sm) {
+                            assert_eq!(sa.powm(-3, &sm), inv.powm(3, &sm));
+                        }
+                    }
+                }
+            )*);
+        }
+        tests_for!(u16, i16 => u16; u32, i32 => u32; u64, i64 => u64;);
+
+        assert_eq!(
+            isize::MIN.absm(&3),
+            (isize::MIN as i128).rem_euclid(3) as usize
+        );
+        assert_eq!(
+            isize::MIN.addm(isize::MIN, &7),
+            ((isize::MIN as i128) * 2).rem_euclid(7) as isize
+        );
+        assert_eq!((-1isize).powm(isize::MIN, &5), 1);
+    }
+
+    #[test]
+    fn signed_ops_test() {
+        fn assert_ref_ops<T: crate::ModularRefOps>() {}
+        assert_ref_ops::<i8>();
+        assert_ref_ops::<i64>();
+        assert_ref_ops::<i128>();
+
+        // against the Euclidean remainder of the products in i128
+        macro_rules! tests_for {
+            ($($T:ty => $U:ty;)*) => ($(
+                for _ in 0..NRANDOM {
+                    let (a, b) = (random::<$T>(), random::<$T>());
+                    let m = (random::<$T>() >> 1).max(1);
+                    let (wa, wb, wm) = (a as i128, b as i128, m as i128);
+                    assert_eq!(a.addm(b, &m) as i128, (wa + wb).rem_euclid(wm));
+                    assert_eq!(a.subm(b, &m) as i128, (wa - wb).rem_euclid(wm));
+                    assert_eq!(a.mulm(&b, &m) as i128, (wa * wb).rem_euclid(wm));
+                    assert_eq!((&a).negm(&m) as i128, (-wa).rem_euclid(wm));
+                    assert_eq!(a.dblm(&m) as i128, (2 * wa).rem_euclid(wm));
+                    assert_eq!(a.sqm(&m) as i128, (wa * wa).rem_euclid(wm));
+                    assert_eq!(a.powm(3, &m) as i128, (wa * wa % wm * wa).rem_euclid(wm));
+
+                    let r = a.rem_euclid(m) as $U;
+                    assert_eq!(a.invm(&m), r.invm(&(m as $U)).map(|v| v as $T));
+                    if let Some(inv) = a.invm(&m) {
+                        assert_eq!(a.mulm(inv, &m), 1 % m);
+                        assert_eq!(a.powm(-2, &m), inv.sqm(&m));
+                    }
+                }
+                assert_eq!(<$T>::MIN.addm(<$T>::MIN, &<$T>::MAX), (<$T>::MIN as i128 * 2).rem_euclid(<$T>::MAX as i128) as $T);
+                assert_eq!((-1 as $T).powm(<$T>::MIN, &3), 1);
+            )*);
+        }
+        tests_for!(i8 => u8; i16 => u16; i32 => u32; i64 => u64;);
+
+        // i128 doesn't fit the products, so test with small operands
+        for _ in 0..NRANDOM {
+            let (a, b) = (random::<i64>() as i128, random::<i64>() as i128);
+            let m = (random::<i128>() >> 1).abs().max(1);
+            assert_eq!(a.mulm(b, &m), (a * b).rem_euclid(m));
+            assert_eq!(a.subm(b, &m), (a - b).rem_euclid(m));
+        }
+        assert_eq!(i128::MIN.negm(&7), i128::MIN.rem_euclid(7).negm(&7));
+    }
+
+    #[test]
+    #[should_panic]
+    fn isize_negative_modulus_test() {
+        1isize.addm(1, &-5);
+    }
+
     #[test]
     fn legendre_test() {
         const CASES: [(u8, u8, i8); 18] = [
@@ -890,4 +1180,66 @@ mod tests {
             assert_eq!((a as i128).kronecker(&(n as i128)), res);
         }
     }
+
+    #[test]
+    fn kronecker_degenerate_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for &a in [0, 1, 2, 5, <$T>::MAX - 1, <$T>::MAX].iter() {
+                    let a: $T = a;
+                    assert_eq!(a.kronecker(&1), 1);
+                    assert_eq!(a.kronecker(&0), (a == 1) as i8);
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+
+        macro_rules! tests_for_signed {
+            ($($T:ty)*) => ($(
+                for &a in [0, 1, -1, 2, -2, 5, <$T>::MIN, <$T>::MAX].iter() {
+                    let a: $T = a;
+                    assert_eq!(a.kronecker(&1), 1);
+                    assert_eq!(a.kronecker(&0), (a == 1 || a == -1) as i8);
+                    assert_eq!(a.kronecker(&-1), if a < 0 { -1 } else { 1 });
+                }
+
+                // (a|MIN) = (a|-1) * (a|2)^(bits-1)
+                let f = <$T>::BITS - 1;
+                assert_eq!((3 as $T).kronecker(&<$T>::MIN), (-1i8).pow(f));
+                assert_eq!((-3 as $T).kronecker(&<$T>::MIN), -(-1i8).pow(f));
+                assert_eq!((4 as $T).kronecker(&<$T>::MIN), 0);
+            )*);
+        }
+        tests_for_signed!(i8 i16 i32 i64 i128 isize);
+    }
+
+    #[test]
+    fn kronecker_multiplicativity_test() {
+        // (ab|n) = (a|n)(b|n) and (a|mn) = (a|m)(a|n)
+        for _ in 0..100000 {
+            let (a, b, n) = (
+                random::<u32>() as u64,
+                random::<u32>() as u64,
+                random::<u32>() as u64,
+            );
+            let m = random::<u32>() as u64;
+            assert_eq!((a * b).kronecker(&n), a.kronecker(&n) * b.kronecker(&n));
+            assert_eq!(a.kronecker(&(m * n)), a.kronecker(&m) * a.kronecker(&n));
+
+            // keep small factors to exercise the even part and the zero cases
+            let (a, b) = (random::<u8>() as u64, random::<u8>() as u64);
+            let (m, n) = (random::<u8>() as u64, random::<u8>() as u64);
+            assert_eq!((a * b).kronecker(&n), a.kronecker(&n) * b.kronecker(&n));
+            if m != 0 && n != 0 {
+                assert_eq!(a.kronecker(&(m * n)), a.kronecker(&m) * a.kronecker(&n));
+            }
+
+            let (a, b) = (random::<i32>() as i64, random::<i32>() as i64);
+            let (m, n) = (random::<i32>() as i64, random::<i32>() as i64);
+            assert_eq!((a * b).kronecker(&n), a.kronecker(&n) * b.kronecker(&n));
+            if m != 0 && n != 0 {
+                assert_eq!(a.kronecker(&(m * n)), a.kronecker(&m) * a.kronecker(&n));
+            }
+        }
+    }
 }
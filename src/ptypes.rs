@@ -0,0 +1,375 @@
+//! Implementations of the modular operations for the fixed-width integers of `primitive-types`
+//!
+//! The multiplications of [U128] and [U256] are done with the full (double-width) product,
+//! and the multiplication of [U512] falls back to the double-and-add method since there is
+//! no wider type for it. [Montgomery][crate::Montgomery] is also implemented for [U128],
+//! [U256] and [U512] with `R = 2^128`, `R = 2^256` and `R = 2^512` respectively, where the
+//! products are reduced word by word so that no wider type is needed.
+
+use crate::{ModularCoreOps, ModularPow, ModularSymbols, ModularUnaryOps};
+use core::convert::TryFrom;
+use primitive_types::{U128, U256, U512};
+
+/// Calculate `(a + b) mod m`, where a, b < m
+#[inline]
+pub(crate) fn add_reduced<T: Copy + PartialOrd + OverflowingOps>(a: T, b: T, m: T) -> T {
+    let (sum, overflow) = a.overflowing_add(b);
+    if overflow || sum >= m {
+        sum.overflowing_sub(m).0
+    } else {
+        sum
+    }
+}
+
+/// Calculate `(a - b) mod m`, where a, b < m
+#[inline]
+pub(crate) fn sub_reduced<T: Copy + PartialOrd + OverflowingOps>(a: T, b: T, m: T) -> T {
+    if a >= b {
+        a.overflowing_sub(b).0
+    } else {
+        m.overflowing_sub(b.overflowing_sub(a).0).0
+    }
+}
+
+/// The wrapping arithmetics shared by the types of `primitive-types`
+pub(crate) trait OverflowingOps: Sized {
+    fn overflowing_add(self, rhs: Self) -> (Self, bool);
+    fn overflowing_sub(self, rhs: Self) -> (Self, bool);
+}
+
+macro_rules! impl_overflowing_ops {
+    ($($T:ident)*) => {$(
+        impl OverflowingOps for $T {
+            #[inline(always)]
+            fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+                $T::overflowing_add(self, rhs)
+            }
+            #[inline(always)]
+            fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+                $T::overflowing_sub(self, rhs)
+            }
+        }
+    )*};
+}
+impl_overflowing_ops!(U128 U256 U512);
+
+/// Calculate `a * b mod m` with the full product, where a, b < m
+macro_rules! mul_reduced_wide {
+    ($T:ident, $Wide:ident) => {
+        #[inline]
+        fn mul_reduced(a: $T, b: $T, m: $T) -> $T {
+            $T::try_from(a.full_mul(b) % $Wide::from(m)).unwrap()
+        }
+    };
+}
+
+macro_rules! impl_ptypes_ops {
+    ($T:ident) => {
+        impl ModularCoreOps<&$T, &$T> for $T {
+            type Output = $T;
+            #[inline]
+            fn addm(self, rhs: &$T, m: &$T) -> $T {
+                add_reduced(self % m, rhs % m, *m)
+            }
+            #[inline]
+            fn subm(self, rhs: &$T, m: &$T) -> $T {
+                sub_reduced(self % m, rhs % m, *m)
+            }
+            #[inline]
+            fn mulm(self, rhs: &$T, m: &$T) -> $T {
+                mul_reduced(self % m, rhs % m, *m)
+            }
+        }
+
+        impl ModularCoreOps<&$T, &$T> for &$T {
+            type Output = $T;
+            #[inline]
+            fn addm(self, rhs: &$T, m: &$T) -> $T {
+                (*self).addm(rhs, m)
+            }
+            #[inline]
+            fn subm(self, rhs: &$T, m: &$T) -> $T {
+                (*self).subm(rhs, m)
+            }
+            #[inline]
+            fn mulm(self, rhs: &$T, m: &$T) -> $T {
+                (*self).mulm(rhs, m)
+            }
+        }
+
+        impl ModularUnaryOps<&$T> for $T {
+            type Output = $T;
+            #[inline]
+            fn negm(self, m: &$T) -> $T {
+                sub_reduced($T::zero(), self % m, *m)
+            }
+
+            fn invm(self, m: &$T) -> Option<$T> {
+                let (mut last_r, mut r) = (*m, self % m);
+                let (mut last_t, mut t) = ($T::zero(), $T::one() % m);
+
+                while !r.is_zero() {
+                    let (quo, rem) = last_r.div_mod(r);
+                    last_r = r;
+                    r = rem;
+
+                    let new_t = sub_reduced(last_t, mul_reduced(quo % m, t, *m), *m);
+                    last_t = t;
+                    t = new_t;
+                }
+
+                // if r = gcd(self, m) > 1, then inverse doesn't exist
+                if last_r == $T::one() {
+                    Some(last_t)
+                } else {
+                    None
+                }
+            }
+
+            #[inline]
+            fn dblm(self, m: &$T) -> $T {
+                let x = self % m;
+                add_reduced(x, x, *m)
+            }
+            #[inline]
+            fn sqm(self, m: &$T) -> $T {
+                let x = self % m;
+                mul_reduced(x, x, *m)
+            }
+        }
+
+        impl ModularUnaryOps<&$T> for &$T {
+            type Output = $T;
+            #[inline]
+            fn negm(self, m: &$T) -> $T {
+                (*self).negm(m)
+            }
+            #[inline]
+            fn invm(self, m: &$T) -> Option<$T> {
+                (*self).invm(m)
+            }
+            #[inline]
+            fn dblm(self, m: &$T) -> $T {
+                (*self).dblm(m)
+            }
+            #[inline]
+            fn sqm(self, m: &$T) -> $T {
+                (*self).sqm(m)
+            }
+        }
+
+        impl ModularPow<&$T, &$T> for $T {
+            type Output = $T;
+            fn powm(self, exp: &$T, m: &$T) -> $T {
+                let base = self % m;
+                let mut result = $T::one() % m;
+                for i in (0..exp.bits()).rev() {
+                    result = mul_reduced(result, result, *m);
+                    if exp.bit(i) {
+                        result = mul_reduced(result, base, *m);
+                    }
+                }
+                result
+            }
+        }
+
+        impl ModularPow<&$T, &$T> for &$T {
+            type Output = $T;
+            #[inline]
+            fn powm(self, exp: &$T, m: &$T) -> $T {
+                (*self).powm(exp, m)
+            }
+        }
+
+        impl ModularSymbols<&$T> for $T {
+            #[inline]
+            fn checked_legendre(&self, n: &$T) -> Option<i8> {
+                let r = self.powm(&(n.overflowing_sub($T::one()).0 >> 1), n);
+                if r.is_zero() {
+                    Some(0)
+                } else if r == $T::one() {
+                    Some(1)
+                } else if r.overflowing_add($T::one()).0 == *n {
+                    Some(-1)
+                } else {
+                    None
+                }
+            }
+
+            fn checked_jacobi(&self, n: &$T) -> Option<i8> {
+                if !n.bit(0) {
+                    return None;
+                }
+                if *n == $T::one() {
+                    return Some(1);
+                }
+
+                let mut a = self % n;
+                let mut n = *n;
+                let mut t = 1;
+                while !a.is_zero() {
+                    while !a.bit(0) {
+                        a >>= 1;
+                        if n.low_u64() & 7 == 3 || n.low_u64() & 7 == 5 {
+                            t = -t;
+                        }
+                    }
+                    core::mem::swap(&mut a, &mut n);
+                    if a.low_u64() & 3 == 3 && n.low_u64() & 3 == 3 {
+                        t = -t;
+                    }
+                    a %= n;
+                }
+                Some(if n == $T::one() { t } else { 0 })
+            }
+
+            fn kronecker(&self, n: &$T) -> i8 {
+                if n.is_zero() {
+                    return if *self == $T::one() { 1 } else { 0 };
+                }
+                if *n == $T::one() {
+                    return 1;
+                }
+
+                // (a/2) = 0 for even a, 1 for a = ±1 (mod 8) and -1 otherwise
+                let k2: i8 = match self.low_u64() & 7 {
+                    1 | 7 => 1,
+                    3 | 5 => -1,
+                    _ => 0,
+                };
+                let f = n.trailing_zeros();
+                k2.pow(f) * self.jacobi(&(*n >> f as usize))
+            }
+        }
+    };
+}
+
+mod u128_impl {
+    use super::*;
+    mul_reduced_wide!(U128, U256);
+    impl_ptypes_ops!(U128);
+}
+
+mod u256_impl {
+    use super::*;
+    mul_reduced_wide!(U256, U512);
+    impl_ptypes_ops!(U256);
+}
+
+mod u512_impl {
+    use super::*;
+
+    /// Calculate `a * b mod m` with the double-and-add method, where a, b < m
+    fn mul_reduced(a: U512, b: U512, m: U512) -> U512 {
+        let mut r = U512::zero();
+        for i in (0..b.bits()).rev() {
+            r = add_reduced(r, r, m);
+            if b.bit(i) {
+                r = add_reduced(r, a, m);
+            }
+        }
+        r
+    }
+    impl_ptypes_ops!(U512);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModularInteger, MontgomeryInt};
+    use rand::random;
+
+    const NRANDOM: u32 = 10;
+
+    #[test]
+    fn ops_against_prim_test() {
+        macro_rules! tests_for {
+            ($($T:ident)*) => ($(
+                for _ in 0..NRANDOM {
+                    let (a, b, e) = (random::<u128>(), random::<u128>(), random::<u16>() as u128);
+                    let m = (random::<u128>() >> (random::<u8>() % 128)).max(1);
+                    let (pa, pb, pe, pm) = ($T::from(a), $T::from(b), $T::from(e), $T::from(m));
+                    assert_eq!(pa.addm(&pb, &pm), $T::from(a.addm(b, &m)));
+                    assert_eq!(pa.subm(&pb, &pm), $T::from(a.subm(b, &m)));
+                    assert_eq!(pa.mulm(&pb, &pm), $T::from(a.mulm(b, &m)));
+                    assert_eq!(pa.negm(&pm), $T::from(a.negm(&m)));
+                    assert_eq!(pa.dblm(&pm), $T::from(a.dblm(&m)));
+                    assert_eq!(pa.sqm(&pm), $T::from(a.sqm(&m)));
+                    assert_eq!(pa.powm(&pe, &pm), $T::from(a.powm(e, &m)));
+                    assert_eq!(pa.invm(&pm), a.invm(&m).map($T::from));
+                    assert_eq!(pa.kronecker(&pm), a.kronecker(&m));
+                    assert_eq!(pa.kronecker(&pb), a.kronecker(&b));
+                }
+
+                // the moduli with the highest bit set
+                let m = $T::MAX;
+                let x = m.overflowing_sub($T::one()).0;
+                assert_eq!(x.mulm(&x, &m), $T::one());
+                assert_eq!(x.addm(&x, &m), m.overflowing_sub($T::from(2u8)).0);
+                assert_eq!(x.dblm(&m), x.addm(&x, &m));
+                assert_eq!(x.invm(&m), Some(x));
+                assert_eq!(x.powm(&m, &m), x);
+            )*);
+        }
+        tests_for!(U128 U256 U512);
+
+        let p = U256::from(1_000_000_007u64);
+        assert_eq!(U256::from(2u8).legendre(&p), 1);
+        assert_eq!(U256::from(5u8).legendre(&p), -1);
+    }
+
+    #[test]
+    fn montgomery_test() {
+        // the prime 2^255 - 19
+        let p = (U256::one() << 255) - 19;
+        for _ in 0..NRANDOM {
+            let (a, b) = (
+                U256::from(random::<u128>()) << 100,
+                U256::from(random::<u128>()),
+            );
+            let x = MontgomeryInt::new(a, &p);
+            let y = x.convert(b);
+            assert_eq!(x.residue(), a % p);
+            assert_eq!((x + y).residue(), a.addm(&b, &p));
+            assert_eq!((x - y).residue(), a.subm(&b, &p));
+            assert_eq!((x * y).residue(), a.mulm(&b, &p));
+            assert_eq!((-x).residue(), a.negm(&p));
+            assert_eq!(x.square().residue(), a.sqm(&p));
+            assert_eq!(x.pow(&(p - 2)).residue(), a.invm(&p).unwrap());
+            assert_eq!((x / y * y).residue(), a % p);
+
+            let (a, b, m) = (random::<u128>(), random::<u128>(), random::<u128>() | 1);
+            let x = MontgomeryInt::new(U128::from(a), &U128::from(m));
+            let y = x.convert(U128::from(b));
+            assert_eq!((x * y).residue(), U128::from(a.mulm(b, &m)));
+            assert_eq!(x.pow(&U128::from(b)).residue(), U128::from(a.powm(b, &m)));
+        }
+
+        let m = U256::MAX;
+        let x = MontgomeryInt::new(m - 1, &m);
+        assert_eq!(x.square().residue(), U256::one());
+
+        // the prime 2^511 + 111
+        let p = (U512::one() << 511) + 111;
+        for _ in 0..NRANDOM {
+            let (a, b) = (
+                (U512::from(random::<u128>()) << 384) | U512::from(random::<u128>()),
+                U512::from(random::<u128>()) << 200,
+            );
+            let x = MontgomeryInt::new(a, &p);
+            let y = x.convert(b);
+            assert_eq!(x.residue(), a % p);
+            assert_eq!((x + y).residue(), a.addm(&b, &p));
+            assert_eq!((x - y).residue(), a.subm(&b, &p));
+            assert_eq!((x * y).residue(), a.mulm(&b, &p));
+            assert_eq!(x.square().residue(), a.sqm(&p));
+            assert_eq!(x.pow(&(p - 2)).residue(), a.invm(&p).unwrap());
+            assert_eq!((x / y * y).residue(), a % p);
+        }
+        let x = MontgomeryInt::new(p - 1, &p);
+        assert_eq!(x.square().residue(), U512::one());
+        let m = U512::MAX;
+        let x = MontgomeryInt::new(m - 2, &m);
+        assert_eq!(x.square().residue(), U512::from(4u8));
+    }
+}
@@ -0,0 +1,358 @@
+//! Searching for quadratic non-residues, and sampling the quadratic residues
+
+use crate::ModularSymbols;
+#[cfg(feature = "rand")]
+use crate::ModularUnaryOps;
+
+/// Integer types supported by [find_qnr()]
+pub trait FindQnr: Sized {
+    /// Find the smallest quadratic non-residue modulo p, see [find_qnr()] for details
+    fn find_qnr(p: &Self) -> Self;
+
+    /// Find a random quadratic non-residue modulo p, see [find_qnr_random()] for details
+    #[cfg(feature = "rand")]
+    fn find_qnr_random<R: rand::Rng + ?Sized>(p: &Self, rng: &mut R) -> Self;
+
+    /// Sample a random nonzero quadratic residue modulo p, see [random_qr()] for details
+    #[cfg(feature = "rand")]
+    fn random_qr<R: rand::Rng + ?Sized>(p: &Self, rng: &mut R) -> Self;
+}
+
+/// Find the smallest quadratic non-residue modulo an odd prime p.
+///
+/// The candidates 2, 3, 5, 7, ... are tested with the Jacobi symbol. Under the
+/// generalized Riemann hypothesis the result is smaller than `2 * ln(p)^2`, so the
+/// search is fast for all primes in practice.
+///
+/// The result for other moduli is not meaningful, but it's guaranteed to terminate:
+/// - If p is even or `p < 3` (including p = 2, which has no non-residue), 0 is returned.
+/// - If p is an odd composite, either 0 is returned when a candidate sharing a factor
+///   with p is met first, or a number `a` with Jacobi symbol `(a|p) = -1` is returned,
+///   which is indeed a non-residue, though not necessarily the smallest one.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::find_qnr;
+///
+/// assert_eq!(find_qnr(&7u32), 3);
+/// assert_eq!(find_qnr(&23u32), 5);
+/// ```
+#[inline]
+pub fn find_qnr<T: FindQnr>(p: &T) -> T {
+    T::find_qnr(p)
+}
+
+/// Find a quadratic non-residue modulo an odd prime p by random sampling.
+///
+/// Each sample is a non-residue with probability about 1/2, so the expected number
+/// of Jacobi symbol evaluations is 2 regardless of the structure of p. This avoids the
+/// worst case of [find_qnr()] on primes chosen to have a large least non-residue.
+///
+/// If no non-residue is found after a fixed number of samples (which happens with
+/// negligible probability for primes), the deterministic search is used as the fallback,
+/// therefore the behavior for moduli other than odd primes is the same as [find_qnr()].
+#[cfg(feature = "rand")]
+#[inline]
+pub fn find_qnr_random<T: FindQnr, R: rand::Rng + ?Sized>(p: &T, rng: &mut R) -> T {
+    T::find_qnr_random(p, rng)
+}
+
+/// Sample a nonzero quadratic residue modulo an odd prime p uniformly at random.
+///
+/// The result is the square of a uniformly random integer in `[1, p)`. Since every nonzero
+/// residue has exactly two square roots modulo an odd prime, the result is uniform over the
+/// `(p-1)/2` quadratic residues. Use [find_qnr_random()] to sample the non-residues.
+///
+/// For an odd composite p, the result is still a square modulo p but it's not uniform
+/// and it can share factors with p. If p is even or `p < 3`, 0 is returned (consistent with
+/// [find_qnr()]).
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{random_qr, ModularSymbols};
+///
+/// let p = 1_000_000_007u64;
+/// let a = random_qr(&p, &mut rand::thread_rng());
+/// assert_eq!(a.legendre(&p), 1);
+/// ```
+#[cfg(feature = "rand")]
+#[inline]
+pub fn random_qr<T: FindQnr, R: rand::Rng + ?Sized>(p: &T, rng: &mut R) -> T {
+    T::random_qr(p, rng)
+}
+
+#[cfg(feature = "rand")]
+const RANDOM_TRIALS: usize = 64;
+
+macro_rules! impl_find_qnr_for {
+    ($($T:ty)*) => {$(
+        impl FindQnr for $T {
+            fn find_qnr(p: &$T) -> $T {
+                let p = *p;
+                if p < 3 || p % 2 == 0 {
+                    return 0;
+                }
+
+                // the smallest non-residue is always a prime, so only 2 and odd candidates are tested
+                let mut a: $T = 2;
+                while a < p {
+                    match a.jacobi(&p) {
+                        -1 => return a,
+                        0 => return 0,
+                        _ => {}
+                    }
+                    a = if a == 2 { 3 } else { a + 2 };
+                }
+                0
+            }
+
+            #[cfg(feature = "rand")]
+            fn find_qnr_random<R: rand::Rng + ?Sized>(p: &$T, rng: &mut R) -> $T {
+                if *p < 3 || *p % 2 == 0 {
+                    return 0;
+                }
+                for _ in 0..RANDOM_TRIALS {
+                    let a = rng.gen_range(2..*p);
+                    match a.jacobi(p) {
+                        -1 => return a,
+                        0 => return 0,
+                        _ => {}
+                    }
+                }
+                Self::find_qnr(p)
+            }
+
+            #[cfg(feature = "rand")]
+            fn random_qr<R: rand::Rng + ?Sized>(p: &$T, rng: &mut R) -> $T {
+                if *p < 3 || *p % 2 == 0 {
+                    return 0;
+                }
+                rng.gen_range(1..*p).sqm(p)
+            }
+        }
+    )*};
+}
+impl_find_qnr_for!(u8 u16 u32 u64 u128 usize);
+
+#[cfg(feature = "num-bigint")]
+impl FindQnr for num_bigint::BigUint {
+    fn find_qnr(p: &Self) -> Self {
+        use num_integer::Integer;
+        use num_traits::Zero;
+
+        if p < &Self::from(3u8) || p.is_even() {
+            return Self::zero();
+        }
+
+        let mut a = Self::from(2u8);
+        while &a < p {
+            match a.jacobi(p) {
+                -1 => return a,
+                0 => return Self::zero(),
+                _ => {}
+            }
+            a += if a == Self::from(2u8) { 1u8 } else { 2u8 };
+        }
+        Self::zero()
+    }
+
+    #[cfg(feature = "rand")]
+    fn find_qnr_random<R: rand::Rng + ?Sized>(p: &Self, rng: &mut R) -> Self {
+        use num_integer::Integer;
+        use num_traits::Zero;
+
+        if p < &Self::from(3u8) || p.is_even() {
+            return Self::zero();
+        }
+
+        // sample from 64 extra bits to make the bias of the reduction negligible
+        let limbs = (p.bits() + 127) / 64;
+        for _ in 0..RANDOM_TRIALS {
+            let mut a = Self::zero();
+            for _ in 0..limbs {
+                a = (a << 64u8) + rng.next_u64();
+            }
+            let a = a % p;
+            if a < Self::from(2u8) {
+                continue;
+            }
+            match a.jacobi(p) {
+                -1 => return a,
+                0 => return Self::zero(),
+                _ => {}
+            }
+        }
+        Self::find_qnr(p)
+    }
+
+    #[cfg(feature = "rand")]
+    fn random_qr<R: rand::Rng + ?Sized>(p: &Self, rng: &mut R) -> Self {
+        use num_integer::Integer;
+        use num_traits::Zero;
+
+        if p < &Self::from(3u8) || p.is_even() {
+            return Self::zero();
+        }
+
+        // sample from 64 extra bits to make the bias of the reduction negligible
+        let limbs = (p.bits() + 127) / 64;
+        loop {
+            let mut a = Self::zero();
+            for _ in 0..limbs {
+                a = (a << 64u8) + rng.next_u64();
+            }
+            let a = a % p;
+            if !a.is_zero() {
+                return a.sqm(p);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (p, least quadratic non-residue of p)
+    const LEAST_QNR: [(u64, u64); 16] = [
+        (3, 2),
+        (5, 2),
+        (7, 3),
+        (11, 2),
+        (13, 2),
+        (17, 3),
+        (23, 5),
+        (41, 3),
+        (71, 7),
+        (311, 11),
+        (479, 13),
+        (1559, 17),
+        (5711, 19),
+        (10559, 23),
+        (18191, 29),
+        (31391, 31),
+    ];
+
+    const PRIMES: [u64; 8] = [
+        101,
+        65521,
+        2147483647,
+        4294967291,
+        1000000007,
+        998244353,
+        18446744073709551557,
+        2305843009213693951,
+    ];
+
+    #[test]
+    fn find_qnr_test() {
+        for &(p, q) in LEAST_QNR.iter() {
+            assert_eq!(find_qnr(&p), q);
+            assert_eq!(find_qnr(&(p as u128)), q as u128);
+            if p < 256 {
+                assert_eq!(find_qnr(&(p as u8)), q as u8);
+            }
+            if p < 65536 {
+                assert_eq!(find_qnr(&(p as u16)), q as u16);
+            }
+            assert_eq!(find_qnr(&(p as u32)), q as u32);
+            assert_eq!(find_qnr(&(p as usize)), q as usize);
+        }
+
+        for &p in PRIMES.iter() {
+            let q = find_qnr(&p);
+            assert_eq!(q.jacobi(&p), -1);
+            assert_eq!(q.legendre(&p), -1);
+        }
+        let p = 170141183460469231731687303715884105727u128; // 2^127 - 1
+        assert_eq!(find_qnr(&p).legendre(&p), -1);
+
+        // moduli other than odd primes
+        assert_eq!(find_qnr(&0u32), 0);
+        assert_eq!(find_qnr(&1u32), 0);
+        assert_eq!(find_qnr(&2u32), 0);
+        assert_eq!(find_qnr(&100u32), 0);
+        assert_eq!(find_qnr(&9u32), 0);
+        assert_eq!(find_qnr(&15u32), 0);
+        assert_eq!(find_qnr(&21u32), 2); // (2|21) = -1
+        assert_eq!(find_qnr(&u8::MAX), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn find_qnr_random_test() {
+        let mut rng = rand::thread_rng();
+        for &p in PRIMES.iter() {
+            for _ in 0..10 {
+                let q = find_qnr_random(&p, &mut rng);
+                assert!(q >= 2 && q < p);
+                assert_eq!(q.jacobi(&p), -1);
+            }
+        }
+        for &(p, _) in LEAST_QNR.iter() {
+            let q = find_qnr_random(&(p as u32), &mut rng);
+            assert_eq!(q.jacobi(&(p as u32)), -1);
+        }
+        assert_eq!(find_qnr_random(&2u64, &mut rng), 0);
+        assert_eq!(find_qnr_random(&9u64, &mut rng), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn random_qr_test() {
+        let mut rng = rand::thread_rng();
+        for &p in PRIMES.iter() {
+            for _ in 0..10 {
+                let a = random_qr(&p, &mut rng);
+                assert!(a >= 1 && a < p);
+                assert_eq!(a.legendre(&p), 1);
+            }
+        }
+
+        // all the quadratic residues modulo 23 are sampled evenly
+        let mut counts = [0u32; 23];
+        for _ in 0..11000 {
+            counts[random_qr(&23usize, &mut rng)] += 1;
+        }
+        for (a, &c) in counts.iter().enumerate() {
+            if a > 0 && a.legendre(&23) == 1 {
+                // the expectation is 1000 and the standard deviation is about 30
+                assert!((850..1150).contains(&c), "count of {} is {}", a, c);
+            } else {
+                assert_eq!(c, 0);
+            }
+        }
+
+        assert_eq!(random_qr(&2u8, &mut rng), 0);
+        assert_eq!(random_qr(&100u32, &mut rng), 0);
+        assert_eq!(random_qr(&3u8, &mut rng), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "num-bigint")]
+    fn find_qnr_biguint_test() {
+        use num_bigint::BigUint;
+
+        for &(p, q) in LEAST_QNR.iter() {
+            assert_eq!(find_qnr(&BigUint::from(p)), BigUint::from(q));
+        }
+
+        // 2^255 - 19 and 2^521 - 1
+        let p25519 = (BigUint::from(1u8) << 255u32) - 19u8;
+        let p521 = (BigUint::from(1u8) << 521u32) - 1u8;
+        for p in [p25519, p521].iter() {
+            let q = find_qnr(p);
+            assert_eq!(q.jacobi(p), -1);
+            #[cfg(feature = "rand")]
+            {
+                let q = find_qnr_random(p, &mut rand::thread_rng());
+                assert!(&q < p);
+                assert_eq!(q.jacobi(p), -1);
+                let a = random_qr(p, &mut rand::thread_rng());
+                assert!(&a < p);
+                assert_eq!(a.legendre(p), 1);
+            }
+        }
+        assert_eq!(find_qnr(&BigUint::from(2u8)), BigUint::from(0u8));
+    }
+}
@@ -0,0 +1,449 @@
+//! Arithmetic in quadratic extensions of the modulo rings
+
+use crate::Reducer;
+use core::ops::*;
+
+/// The operations of the base ring used by the arithmetic of the quadratic extensions,
+/// so that the formulas are shared by [QuadExt] and [Fp2][crate::Fp2]
+pub(crate) trait QuadBase<T> {
+    fn add(&self, lhs: &T, rhs: &T) -> T;
+    fn sub(&self, lhs: &T, rhs: &T) -> T;
+    fn mul(&self, lhs: &T, rhs: &T) -> T;
+    fn dbl(&self, target: T) -> T;
+    fn neg(&self, target: T) -> T;
+    fn sqr(&self, target: T) -> T;
+    fn inv(&self, target: T) -> Option<T>;
+}
+
+/// [QuadBase] on the raw integers in the reduced form of a [Reducer]
+struct ReducerOps<'a, R>(&'a R);
+
+impl<T, R: Reducer<T>> QuadBase<T> for ReducerOps<'_, R> {
+    #[inline]
+    fn add(&self, lhs: &T, rhs: &T) -> T {
+        self.0.add(lhs, rhs)
+    }
+    #[inline]
+    fn sub(&self, lhs: &T, rhs: &T) -> T {
+        self.0.sub(lhs, rhs)
+    }
+    #[inline]
+    fn mul(&self, lhs: &T, rhs: &T) -> T {
+        self.0.mul(lhs, rhs)
+    }
+    #[inline]
+    fn dbl(&self, target: T) -> T {
+        self.0.dbl(target)
+    }
+    #[inline]
+    fn neg(&self, target: T) -> T {
+        self.0.neg(target)
+    }
+    #[inline]
+    fn sqr(&self, target: T) -> T {
+        self.0.sqr(target)
+    }
+    #[inline]
+    fn inv(&self, target: T) -> Option<T> {
+        self.0.inv(target)
+    }
+}
+
+/// Calculate `(a + bω)(c + eω) = (ac + d·be) + (ae + bc)ω`
+#[inline]
+pub(crate) fn mul_parts<T, B: QuadBase<T>>(r: &B, d: &T, lhs: (&T, &T), rhs: (&T, &T)) -> (T, T) {
+    let ((a, b), (c, e)) = (lhs, rhs);
+    let bd = r.mul(b, d);
+    let re = r.add(&r.mul(a, c), &r.mul(&bd, e));
+    let im = r.add(&r.mul(a, e), &r.mul(b, c));
+    (re, im)
+}
+
+/// Calculate `(a + bω)² = (a² + d·b²) + 2abω`
+#[inline]
+pub(crate) fn sqr_parts<T: Clone, B: QuadBase<T>>(r: &B, d: &T, x: (&T, &T)) -> (T, T) {
+    let (a, b) = x;
+    let db2 = r.mul(&r.sqr(b.clone()), d);
+    let re = r.add(&r.sqr(a.clone()), &db2);
+    (re, r.dbl(r.mul(a, b)))
+}
+
+/// Calculate the norm `(a + bω)(a - bω) = a² - d·b²`
+#[inline]
+pub(crate) fn norm_parts<T: Clone, B: QuadBase<T>>(r: &B, d: &T, x: (&T, &T)) -> T {
+    let (a, b) = x;
+    let db2 = r.mul(&r.sqr(b.clone()), d);
+    r.sub(&r.sqr(a.clone()), &db2)
+}
+
+/// Calculate the inverse `(a - bω) / (a² - d·b²)`, or [None] if the norm is not invertible
+#[inline]
+pub(crate) fn inv_parts<T: Clone, B: QuadBase<T>>(r: &B, d: &T, x: (&T, &T)) -> Option<(T, T)> {
+    let ninv = r.inv(norm_parts(r, d, x))?;
+    let (a, b) = x;
+    Some((r.mul(a, &ninv), r.neg(r.mul(b, &ninv))))
+}
+
+/// An element `a + b·ω` of the quadratic extension `(ℤ/mℤ)[ω]/(ω² - d)`.
+///
+/// Similar to [ReducedInt][crate::ReducedInt], both components are stored in the reduced
+/// form together with the reducer, so the context `(m, d)` is carried by every element.
+/// When m is a prime p and d is a quadratic non-residue modulo p, this is the finite
+/// field `F_p²`, which is the basis of Cipolla's algorithm and the Lucas sequences.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::MontgomeryQuadExt;
+///
+/// // (1 + 2ω)(3 + 4ω) with ω² = 5 mod 7
+/// let x = MontgomeryQuadExt::<u64>::new(1, 2, 5, &7);
+/// let y = x.convert(3, 4);
+/// assert_eq!((x * y).residue(), ((3 + 8 * 5) % 7, 10 % 7));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct QuadExt<T, R: Reducer<T>> {
+    a: T, // the rational part
+    b: T, // the coefficient of ω
+    d: T, // ω² in reduced form
+    r: R,
+}
+
+impl<T: PartialEq + Clone, R: Reducer<T> + Clone> QuadExt<T, R> {
+    /// Create the element `a + b·ω` with `ω² = d (mod m)`
+    #[inline]
+    pub fn new(a: T, b: T, d: T, m: &T) -> Self {
+        let r = R::new(m);
+        Self {
+            a: r.transform(a),
+            b: r.transform(b),
+            d: r.transform(d),
+            r,
+        }
+    }
+
+    /// Create the element `a + b·ω` in the same extension as self
+    #[inline]
+    pub fn convert(&self, a: T, b: T) -> Self {
+        Self {
+            a: self.r.transform(a),
+            b: self.r.transform(b),
+            d: self.d.clone(),
+            r: self.r.clone(),
+        }
+    }
+
+    /// Get the components `(a, b)` of `a + b·ω` in normal form
+    #[inline]
+    pub fn residue(&self) -> (T, T) {
+        (
+            self.r.residue(self.a.clone()),
+            self.r.residue(self.b.clone()),
+        )
+    }
+
+    /// Get the modulus m
+    #[inline]
+    pub fn modulus(&self) -> T {
+        self.r.modulus()
+    }
+
+    /// Get the value of `ω² = d` in normal form
+    #[inline]
+    pub fn non_residue(&self) -> T {
+        self.r.residue(self.d.clone())
+    }
+
+    #[inline(always)]
+    fn check_context_eq(&self, rhs: &Self) {
+        if cfg!(debug_assertions) && (self.r.modulus() != rhs.r.modulus() || self.d != rhs.d) {
+            panic!("The extensions of two operators should be the same!");
+        }
+    }
+
+    #[inline]
+    fn with_parts(&self, a: T, b: T) -> Self {
+        Self {
+            a,
+            b,
+            d: self.d.clone(),
+            r: self.r.clone(),
+        }
+    }
+
+    #[inline]
+    fn mul_parts(&self, rhs: &Self) -> (T, T) {
+        let (x, y) = ((&self.a, &self.b), (&rhs.a, &rhs.b));
+        mul_parts(&ReducerOps(&self.r), &self.d, x, y)
+    }
+
+    /// Calculate the conjugate `a - b·ω`
+    #[inline]
+    pub fn conj(&self) -> Self {
+        self.with_parts(self.a.clone(), self.r.neg(self.b.clone()))
+    }
+
+    /// Calculate the norm `(a + b·ω)(a - b·ω) = a² - d·b²` in normal form
+    #[inline]
+    pub fn norm(&self) -> T {
+        let norm = norm_parts(&ReducerOps(&self.r), &self.d, (&self.a, &self.b));
+        self.r.residue(norm)
+    }
+
+    /// Calculate self * self
+    #[inline]
+    pub fn square(&self) -> Self {
+        let (a, b) = sqr_parts(&ReducerOps(&self.r), &self.d, (&self.a, &self.b));
+        self.with_parts(a, b)
+    }
+
+    /// Calculate the multiplicative inverse as `conj(x) / norm(x)`.
+    /// [None] is returned if the norm is not invertible modulo m.
+    pub fn inv(&self) -> Option<Self> {
+        let (a, b) = inv_parts(&ReducerOps(&self.r), &self.d, (&self.a, &self.b))?;
+        Some(self.with_parts(a, b))
+    }
+
+    /// Test whether the element is zero
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.r.is_zero(&self.a) && self.r.is_zero(&self.b)
+    }
+}
+
+macro_rules! impl_quad_ext_pow_for {
+    ($($T:ty)*) => {$(
+        impl<R: Reducer<$T> + Clone> QuadExt<$T, R> {
+            /// Calculate self ^ exp by binary exponentiation
+            pub fn pow(&self, exp: &$T) -> Self {
+                let mut result = self.convert(1, 0);
+                let mut bit = <$T>::BITS - exp.leading_zeros();
+                while bit > 0 {
+                    bit -= 1;
+                    result = result.square();
+                    if (exp >> bit) & 1 == 1 {
+                        result = result * self;
+                    }
+                }
+                result
+            }
+        }
+    )*};
+}
+impl_quad_ext_pow_for!(u8 u16 u32 u64 u128 usize);
+
+impl<T: PartialEq, R: Reducer<T>> PartialEq for QuadExt<T, R> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.a == other.a && self.b == other.b && self.d == other.d
+    }
+}
+
+macro_rules! impl_binops {
+    ($method:ident, impl $op:ident) => {
+        impl<T: PartialEq + Clone, R: Reducer<T> + Clone> $op for QuadExt<T, R> {
+            type Output = Self;
+            #[inline]
+            fn $method(self, rhs: Self) -> Self::Output {
+                self.check_context_eq(&rhs);
+                let a = self.r.$method(&self.a, &rhs.a);
+                let b = self.r.$method(&self.b, &rhs.b);
+                Self { a, b, ..self }
+            }
+        }
+
+        impl<T: PartialEq + Clone, R: Reducer<T> + Clone> $op<&Self> for QuadExt<T, R> {
+            type Output = Self;
+            #[inline]
+            fn $method(self, rhs: &Self) -> Self::Output {
+                self.check_context_eq(rhs);
+                let a = self.r.$method(&self.a, &rhs.a);
+                let b = self.r.$method(&self.b, &rhs.b);
+                Self { a, b, ..self }
+            }
+        }
+
+        impl<T: PartialEq + Clone, R: Reducer<T> + Clone> $op<&QuadExt<T, R>> for &QuadExt<T, R> {
+            type Output = QuadExt<T, R>;
+            #[inline]
+            fn $method(self, rhs: &QuadExt<T, R>) -> Self::Output {
+                self.check_context_eq(rhs);
+                let a = self.r.$method(&self.a, &rhs.a);
+                let b = self.r.$method(&self.b, &rhs.b);
+                self.with_parts(a, b)
+            }
+        }
+    };
+}
+impl_binops!(add, impl Add);
+impl_binops!(sub, impl Sub);
+
+impl<T: PartialEq + Clone, R: Reducer<T> + Clone> Mul for QuadExt<T, R> {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.check_context_eq(&rhs);
+        let (a, b) = self.mul_parts(&rhs);
+        Self { a, b, ..self }
+    }
+}
+
+impl<T: PartialEq + Clone, R: Reducer<T> + Clone> Mul<&Self> for QuadExt<T, R> {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: &Self) -> Self::Output {
+        self.check_context_eq(rhs);
+        let (a, b) = self.mul_parts(rhs);
+        Self { a, b, ..self }
+    }
+}
+
+impl<T: PartialEq + Clone, R: Reducer<T> + Clone> Mul<&QuadExt<T, R>> for &QuadExt<T, R> {
+    type Output = QuadExt<T, R>;
+    #[inline]
+    fn mul(self, rhs: &QuadExt<T, R>) -> Self::Output {
+        self.check_context_eq(rhs);
+        let (a, b) = self.mul_parts(rhs);
+        self.with_parts(a, b)
+    }
+}
+
+impl<T: PartialEq + Clone, R: Reducer<T> + Clone> Neg for QuadExt<T, R> {
+    type Output = Self;
+    #[inline]
+    fn neg(self) -> Self::Output {
+        let a = self.r.neg(self.a.clone());
+        let b = self.r.neg(self.b.clone());
+        Self { a, b, ..self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ModularCoreOps, ModularPow, ModularSymbols, ModularUnaryOps, Montgomery, MontgomeryQuadExt,
+        VanillaQuadExt,
+    };
+    use rand::random;
+
+    const NRANDOM: u32 = 10;
+    const PRIMES: [u64; 6] = [3, 7, 13, 65537, 1_000_000_007, 18446744073709551557];
+
+    #[test]
+    fn mul_rule_test() {
+        for &p in PRIMES.iter() {
+            for _ in 0..NRANDOM {
+                let (a, b, c, e, d) = (
+                    random::<u64>() % p,
+                    random::<u64>() % p,
+                    random::<u64>() % p,
+                    random::<u64>() % p,
+                    random::<u64>() % p,
+                );
+                let x = MontgomeryQuadExt::<u64>::new(a, b, d, &p);
+                let y = x.convert(c, e);
+                let expected = (
+                    a.mulm(c, &p).addm(b.mulm(e, &p).mulm(d, &p), &p),
+                    a.mulm(e, &p).addm(b.mulm(c, &p), &p),
+                );
+                assert_eq!((x * y).residue(), expected);
+                assert_eq!(x.square(), x * x);
+                assert_eq!((x + y).residue(), (a.addm(c, &p), b.addm(e, &p)));
+                assert_eq!((x - y).residue(), (a.subm(c, &p), b.subm(e, &p)));
+                assert_eq!((-x).residue(), (a.negm(&p), b.negm(&p)));
+                assert_eq!(x.norm(), (x * x.conj()).residue().0);
+                assert_eq!((x * x.conj()).residue().1, 0);
+                assert_eq!(x.non_residue(), d);
+
+                // the reducers agree
+                let z = VanillaQuadExt::<u64>::new(a, b, d, &p);
+                assert_eq!((z * z.convert(c, e)).residue(), expected);
+                assert_eq!(z.pow(&p).residue(), x.pow(&p).residue());
+            }
+        }
+    }
+
+    #[test]
+    fn frobenius_test() {
+        for &p in PRIMES.iter() {
+            let d = (2..p).find(|d| d.legendre(&p) == -1).unwrap();
+            for _ in 0..NRANDOM {
+                let x = MontgomeryQuadExt::<u64>::new(random(), random(), d, &p);
+                assert_eq!(x.pow(&p), x.conj());
+                assert_eq!(x.pow(&p) * x, x.convert(x.norm(), 0));
+            }
+        }
+    }
+
+    #[test]
+    fn inv_test() {
+        for &p in PRIMES.iter() {
+            let d = (2..p).find(|d| d.legendre(&p) == -1).unwrap();
+            for _ in 0..NRANDOM {
+                let x = MontgomeryQuadExt::<u64>::new(random(), random(), d, &p);
+                if x.is_zero() {
+                    assert_eq!(x.inv(), None);
+                    continue;
+                }
+                // every non-zero element is invertible in the field
+                let xinv = x.inv().unwrap();
+                assert_eq!((x * xinv).residue(), (1, 0));
+                assert_eq!(xinv.inv(), Some(x));
+            }
+        }
+
+        // zero divisors when d is a quadratic residue: (2 + ω)(2 - ω) = 0 with ω² = 4
+        let x = MontgomeryQuadExt::<u32>::new(2, 1, 4, &7);
+        assert_eq!(x.norm(), 0);
+        assert_eq!(x.inv(), None);
+        assert!((x * x.conj()).is_zero());
+    }
+
+    // square root modulo an odd prime by the Tonelli-Shanks algorithm
+    fn tonelli_shanks(n: u64, p: u64) -> u64 {
+        let s = (p - 1).trailing_zeros();
+        let q = (p - 1) >> s;
+        let z = (2..p).find(|z| z.legendre(&p) == -1).unwrap();
+        let (mut m, mut c) = (s, z.powm(q, &p));
+        let (mut t, mut r) = (n.powm(q, &p), n.powm((q + 1) / 2, &p));
+        while t != 1 {
+            let mut i = 0;
+            let mut t2 = t;
+            while t2 != 1 {
+                t2 = t2.mulm(t2, &p);
+                i += 1;
+            }
+            let b = c.powm(1 << (m - i - 1), &p);
+            m = i;
+            c = b.mulm(b, &p);
+            t = t.mulm(c, &p);
+            r = r.mulm(b, &p);
+        }
+        r
+    }
+
+    #[test]
+    fn cipolla_test() {
+        for &p in PRIMES.iter() {
+            for _ in 0..NRANDOM {
+                let n = random::<u64>() % p;
+                if n == 0 || n.legendre(&p) != 1 {
+                    continue;
+                }
+
+                // find a such that a² - n is a non-residue, then (a + ω)^((p+1)/2) is a root
+                let a = (0..p)
+                    .find(|&a| a.mulm(a, &p).subm(n, &p).legendre(&p) == -1)
+                    .unwrap();
+                let d = a.mulm(a, &p).subm(n, &p);
+                let x = QuadExt::<u64, Montgomery<u64>>::new(a, 1, d, &p);
+                let (root, zero) = x.pow(&(p / 2 + 1)).residue();
+                assert_eq!(zero, 0);
+                assert_eq!(root.mulm(root, &p), n);
+
+                let expected = tonelli_shanks(n, p);
+                assert!(root == expected || root == expected.negm(&p));
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Polynomial arithmetic in the quotient ring ℤ/pℤ\[t\]/(f(t))
+
+use crate::ntt::convert_u64;
+use crate::poly::inv;
+use crate::{ModularInteger, Poly};
+use core::convert::TryFrom;
+
+/// The quotient ring `ℤ/pℤ[t]/(f(t))` of the polynomials modulo a fixed polynomial f, whose
+/// elements are represented by the [Poly] of degree less than `deg(f)`.
+///
+/// The operations reduce the results by f with [Poly::div_rem()], so the leading coefficient
+/// of f should be invertible. When p is a prime and f is irreducible, the ring is the finite
+/// field `GF(p^deg(f))`, where all the nonzero elements are invertible. The exponentiation
+/// supports the polynomial identity tests like the AKS test, which checks
+/// `(t + a)^n = t^n + a` modulo `(t^r - 1, n)`.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ModularInteger, MontgomeryInt, Poly, QuotientRing};
+///
+/// // GF(7^3) = ℤ/7ℤ[t]/(t^3 - 2)
+/// let x = MontgomeryInt::new(0u32, &7);
+/// let ring = QuotientRing::new(Poly::from_residues([5, 0, 0, 1], &x));
+/// let a = Poly::from_residues([1, 2, 3], &x);
+/// assert_eq!(ring.pow(&a, 343), a); // Frobenius: a^(p^3) = a
+/// let b = ring.inv(&a).unwrap();
+/// assert_eq!(ring.mul(&a, &b).residues(), [1]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotientRing<M> {
+    f: Poly<M>,
+}
+
+impl<M: ModularInteger + Clone> QuotientRing<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    /// Create the quotient ring modulo the polynomial f
+    ///
+    /// # Panics
+    /// if f is a constant or its leading coefficient is not invertible
+    pub fn new(f: Poly<M>) -> Self {
+        match (f.degree(), f.leading()) {
+            (Some(d), Some(lc)) if d > 0 => {
+                assert!(
+                    inv(lc).is_some(),
+                    "the leading coefficient should be invertible"
+                );
+            }
+            _ => panic!("the modulus polynomial should not be a constant"),
+        }
+        Self { f }
+    }
+
+    /// The modulus polynomial f
+    #[inline]
+    pub fn modulus(&self) -> &Poly<M> {
+        &self.f
+    }
+
+    /// The degree of the modulus polynomial f
+    #[inline]
+    pub fn degree(&self) -> usize {
+        self.f.degree().unwrap()
+    }
+
+    /// The zero of the ring
+    #[inline]
+    pub fn zero(&self) -> Poly<M> {
+        Poly::zero(&self.f.coeff(0))
+    }
+
+    /// The one of the ring
+    #[inline]
+    pub fn one(&self) -> Poly<M> {
+        Poly::monomial(convert_u64(&self.f.coeff(0), 1), 0)
+    }
+
+    /// Reduce a polynomial modulo f
+    #[inline]
+    pub fn reduce(&self, a: &Poly<M>) -> Poly<M> {
+        a.div_rem(&self.f).1
+    }
+
+    /// Calculate `a + b mod f`
+    #[inline]
+    pub fn add(&self, a: &Poly<M>, b: &Poly<M>) -> Poly<M> {
+        self.reduce(&(a + b))
+    }
+
+    /// Calculate `a - b mod f`
+    #[inline]
+    pub fn sub(&self, a: &Poly<M>, b: &Poly<M>) -> Poly<M> {
+        self.reduce(&(a - b))
+    }
+
+    /// Calculate `a * b mod f`
+    #[inline]
+    pub fn mul(&self, a: &Poly<M>, b: &Poly<M>) -> Poly<M> {
+        self.reduce(&(a * b))
+    }
+
+    /// Calculate `a^2 mod f`
+    #[inline]
+    pub fn square(&self, a: &Poly<M>) -> Poly<M> {
+        self.mul(a, a)
+    }
+
+    /// Calculate `base^exp mod f` by binary exponentiation
+    pub fn pow(&self, base: &Poly<M>, exp: u64) -> Poly<M> {
+        let base = self.reduce(base);
+        let mut result = self.one();
+        for i in (0..64 - exp.leading_zeros()).rev() {
+            result = self.square(&result);
+            if (exp >> i) & 1 == 1 {
+                result = self.mul(&result, &base);
+            }
+        }
+        result
+    }
+
+    /// Calculate the inverse of a modulo f by the extended Euclidean algorithm, it returns
+    /// [None] if a and f are not coprime.
+    ///
+    /// # Panics
+    /// if a remainder without invertible leading coefficient is met, which happens only if
+    /// the modulus of the coefficients is not a prime
+    pub fn inv(&self, a: &Poly<M>) -> Option<Poly<M>> {
+        // invariant: s_i * a = r_i mod f
+        let (mut r0, mut r1) = (self.f.clone(), self.reduce(a));
+        let (mut s0, mut s1) = (self.zero(), self.one());
+        while !r1.is_zero() {
+            let (q, r) = r0.div_rem(&r1);
+            r0 = core::mem::replace(&mut r1, r);
+            let s = &s0 - &(&q * &s1);
+            s0 = core::mem::replace(&mut s1, s);
+        }
+        if r0.degree() != Some(0) {
+            return None;
+        }
+        let c = inv(&r0.coeff(0)).expect("the leading coefficient should be invertible");
+        Some(self.reduce(&s0.scale(&c)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MontgomeryInt, VanillaInt};
+    use rand::random;
+
+    #[test]
+    fn arithmetic_test() {
+        let x = MontgomeryInt::new(0u64, &998244353);
+        let f = Poly::from_residues((0..20).map(|_| random::<u64>()).chain([1]), &x);
+        let ring = QuotientRing::new(f.clone());
+        assert_eq!(ring.degree(), 20);
+        assert_eq!(ring.modulus(), &f);
+        assert!(ring.reduce(&f).is_zero());
+
+        let rand_poly = |len| Poly::from_residues((0..len).map(|_| random::<u64>()), &x);
+        let (a, b, c) = (rand_poly(50), rand_poly(20), rand_poly(10));
+        let ra = ring.reduce(&a);
+        assert!(ra.degree() < Some(20));
+        assert_eq!(ring.reduce(&(&ra - &a)), ring.zero());
+        assert_eq!(
+            ring.mul(&ring.mul(&a, &b), &c),
+            ring.mul(&a, &ring.mul(&b, &c))
+        );
+        assert_eq!(
+            ring.mul(&a, &ring.add(&b, &c)),
+            ring.add(&ring.mul(&a, &b), &ring.mul(&a, &c))
+        );
+        assert_eq!(ring.sub(&ring.add(&a, &b), &b), ra);
+
+        let mut expected = ring.one();
+        for e in 0..30u64 {
+            assert_eq!(ring.pow(&a, e), expected);
+            expected = ring.mul(&expected, &a);
+        }
+    }
+
+    #[test]
+    fn field_test() {
+        // t^3 - 2 is irreducible modulo 7 since 2 is not a cube
+        let x = VanillaInt::new(0u8, &7);
+        let ring = QuotientRing::new(Poly::from_residues([5, 0, 0, 1], &x));
+        let order = 7u64.pow(3);
+        for v in 1..order {
+            let a = Poly::from_residues([v % 7, v / 7 % 7, v / 49].map(|d| d as u8), &x);
+            assert_eq!(ring.pow(&a, order - 1), ring.one());
+            let b = ring.inv(&a).unwrap();
+            assert_eq!(ring.mul(&a, &b), ring.one());
+        }
+        assert_eq!(ring.inv(&ring.zero()), None);
+
+        // a zero divisor in ℤ/pℤ[t]/(t^2 - 1)
+        let x = MontgomeryInt::new(0u64, &1_000_000_007);
+        let ring = QuotientRing::new(Poly::from_residues([1_000_000_006, 0, 1], &x));
+        assert_eq!(ring.inv(&Poly::from_residues([1, 1], &x)), None);
+        let a = Poly::from_residues([3, 5], &x);
+        assert_eq!(ring.mul(&a, &ring.inv(&a).unwrap()), ring.one());
+    }
+
+    #[test]
+    fn aks_identity_test() {
+        // (t + a)^n = t^n + a modulo (t^r - 1, n) holds for all a iff n is a prime (for r large enough)
+        let check = |n: u64, r: usize, a: u64| {
+            let x = MontgomeryInt::new(0u64, &n);
+            let mut f = std::vec![n - 1];
+            f.resize(r, 0);
+            f.push(1);
+            let ring = QuotientRing::new(Poly::from_residues(f, &x));
+            let lhs = ring.pow(&Poly::from_residues([a, 1], &x), n);
+            let rhs = ring.reduce(
+                &(&Poly::monomial(x.convert(1), (n % r as u64) as usize)
+                    + &Poly::from_residues([a], &x)),
+            );
+            lhs == rhs
+        };
+        for &p in [1_000_000_007u64, 998244353, 65537].iter() {
+            assert!((1..5).all(|a| check(p, 31, a)));
+        }
+        assert!(!(1..5).all(|a| check(561, 31, a))); // Carmichael number
+        assert!(!(1..5).all(|a| check(1_000_000_007 * 3, 31, a)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn constant_modulus_test() {
+        let x = MontgomeryInt::new(0u64, &998244353);
+        QuotientRing::new(Poly::from_residues([3], &x));
+    }
+}
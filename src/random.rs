@@ -0,0 +1,519 @@
+//! Sampling random elements of the modulo rings
+
+use crate::{udouble, Montgomery, ReducedInt, Reducer};
+use rand::distributions::Distribution;
+use rand::Rng;
+
+/// Integer types supported by [random_residue()]
+pub trait RandomResidue: Sized {
+    /// Sample a random residue modulo m, see [random_residue()] for details
+    fn random_residue<R: Rng + ?Sized>(rng: &mut R, m: &Self) -> Self;
+}
+
+/// Sample an integer in `[0, m)` uniformly at random.
+///
+/// The sampling is done by rejection, so that there is no modulo bias like `random::<u64>() % m`,
+/// which favors the small residues when m doesn't divide `2^64`.
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::random_residue;
+///
+/// let mut rng = rand::thread_rng();
+/// assert!(random_residue(&mut rng, &1000u32) < 1000);
+/// ```
+#[inline]
+pub fn random_residue<T: RandomResidue, R: Rng + ?Sized>(rng: &mut R, m: &T) -> T {
+    T::random_residue(rng, m)
+}
+
+/// The modulus of the uniform distributions, which is checked to be nonzero on creation
+#[derive(Debug, Clone, Copy)]
+struct NonZeroModulus<T>(T);
+
+impl<T> NonZeroModulus<T> {
+    /// # Panics
+    /// if m is zero
+    #[inline]
+    fn new(m: &T) -> Self
+    where
+        T: Clone + PartialEq + Default,
+    {
+        assert!(m != &T::default(), "the modulus should not be zero");
+        Self(m.clone())
+    }
+
+    #[inline]
+    const fn get(&self) -> &T {
+        &self.0
+    }
+}
+
+/// The uniform distribution over the modulo ring ℤ/mℤ, which samples the [ReducedInt]
+/// with the given reducer type, or the raw residues in `[0, m)`.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ModularInteger, MontgomeryInt, UniformResidue};
+/// use rand::distributions::Distribution;
+///
+/// let dist = UniformResidue::new(&1_000_000_007u64);
+/// let x: MontgomeryInt<u64> = dist.sample(&mut rand::thread_rng());
+/// assert_eq!(x.modulus(), 1_000_000_007);
+/// assert!(x.residue() < 1_000_000_007);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct UniformResidue<T> {
+    m: NonZeroModulus<T>,
+}
+
+impl<T: RandomResidue> UniformResidue<T> {
+    /// Create the distribution with the modulus m
+    ///
+    /// # Panics
+    /// if m is zero
+    #[inline]
+    pub fn new(m: &T) -> Self
+    where
+        T: Clone + PartialEq + Default,
+    {
+        Self {
+            m: NonZeroModulus::new(m),
+        }
+    }
+
+    /// Get the modulus of the distribution
+    #[inline]
+    pub const fn modulus(&self) -> &T {
+        self.m.get()
+    }
+}
+
+impl<T: RandomResidue> Distribution<T> for UniformResidue<T> {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> T {
+        T::random_residue(rng, self.m.get())
+    }
+}
+
+impl<T: RandomResidue, Red: Reducer<T>> Distribution<ReducedInt<T, Red>> for UniformResidue<T> {
+    /// Sample a random integer in the ring, note that the reducer is initialized for every sample,
+    /// it's preferred to sample the raw residues and convert them with
+    /// [ModularInteger::convert()][crate::ModularInteger::convert] in a hot loop.
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ReducedInt<T, Red> {
+        ReducedInt::new(T::random_residue(rng, self.m.get()), self.m.get())
+    }
+}
+
+/// Integer types supported by [random_unit()]
+pub trait RandomUnit: Sized {
+    /// Sample a random unit modulo m, see [random_unit()] for details
+    fn random_unit<R: Rng + ?Sized>(rng: &mut R, m: &Self) -> Self;
+}
+
+/// Sample an integer in `[0, m)` coprime to m (i.e. a unit of ℤ/mℤ) uniformly at random.
+///
+/// The candidates are drawn uniformly from `[0, m)` without modulo bias and rejected until
+/// one is coprime to m, so the expected number of draws is `m/φ(m)`, which grows only as
+/// `O(log log m)` (e.g. about 5.8 for m = 30030). For m = 1, the only residue 0 is returned.
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::random_unit;
+///
+/// let mut rng = rand::thread_rng();
+/// let u = random_unit(&mut rng, &30030u32);
+/// assert!(u % 2 != 0 && u % 3 != 0 && u % 5 != 0);
+/// ```
+#[inline]
+pub fn random_unit<T: RandomUnit, R: Rng + ?Sized>(rng: &mut R, m: &T) -> T {
+    T::random_unit(rng, m)
+}
+
+/// The uniform distribution over the units (the integers coprime to m) of ℤ/mℤ, which samples
+/// the [ReducedInt] with the given reducer type, or the raw residues. See [random_unit()] for
+/// the details of the sampling.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ModularInteger, MontgomeryInt, UniformUnit};
+/// use rand::distributions::Distribution;
+///
+/// let mut rng = rand::thread_rng();
+/// let dist = UniformUnit::new(&(1u64 << 40 | 1));
+/// let x: MontgomeryInt<u64> = dist.sample(&mut rng);
+/// assert!(x.inv().is_some());
+///
+/// // blinding a value with a random unit
+/// let secret = x.convert(123456789);
+/// let r: MontgomeryInt<u64> = dist.sample(&mut rng);
+/// let blinded = secret * r;
+/// assert_eq!(blinded * r.inv().unwrap(), secret);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct UniformUnit<T> {
+    m: NonZeroModulus<T>,
+}
+
+impl<T: RandomUnit> UniformUnit<T> {
+    /// Create the distribution with the modulus m
+    ///
+    /// # Panics
+    /// if m is zero
+    #[inline]
+    pub fn new(m: &T) -> Self
+    where
+        T: Clone + PartialEq + Default,
+    {
+        Self {
+            m: NonZeroModulus::new(m),
+        }
+    }
+
+    /// Get the modulus of the distribution
+    #[inline]
+    pub const fn modulus(&self) -> &T {
+        self.m.get()
+    }
+}
+
+impl<T: RandomUnit> Distribution<T> for UniformUnit<T> {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> T {
+        T::random_unit(rng, self.m.get())
+    }
+}
+
+impl<T: RandomUnit, Red: Reducer<T>> Distribution<ReducedInt<T, Red>> for UniformUnit<T> {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ReducedInt<T, Red> {
+        ReducedInt::new(T::random_unit(rng, self.m.get()), self.m.get())
+    }
+}
+
+macro_rules! impl_random_unit_for {
+    ($($T:ty)*) => {$(
+        impl RandomResidue for $T {
+            #[inline]
+            fn random_residue<R: Rng + ?Sized>(rng: &mut R, m: &$T) -> $T {
+                assert!(*m > 0, "the modulus should not be zero");
+                rng.gen_range(0..*m)
+            }
+        }
+
+        impl RandomUnit for $T {
+            fn random_unit<R: Rng + ?Sized>(rng: &mut R, m: &$T) -> $T {
+                let m = *m;
+                assert!(m > 0, "the modulus should not be zero");
+                if m <= 2 {
+                    return m - 1;
+                }
+
+                // binary gcd with the odd part of m, and the candidate should be odd if m is even
+                let even = m & 1 == 0;
+                let modd = m >> m.trailing_zeros();
+                let is_coprime = |a: $T| {
+                    if a == 0 || (even && a & 1 == 0) {
+                        return false;
+                    }
+                    let (mut a, mut b) = (a >> a.trailing_zeros(), modd);
+                    while a != b {
+                        if a > b {
+                            a -= b;
+                            a >>= a.trailing_zeros();
+                        } else {
+                            b -= a;
+                            b >>= b.trailing_zeros();
+                        }
+                    }
+                    a == 1
+                };
+
+                loop {
+                    let a = rng.gen_range(0..m);
+                    if is_coprime(a) {
+                        return a;
+                    }
+                }
+            }
+        }
+
+        impl ReducedInt<$T, Montgomery<$T>> {
+            /// Sample a unit modulo m uniformly at random, see [random_unit()] for details
+            ///
+            /// # Panics
+            /// if m is even
+            #[inline]
+            pub fn random_unit<R: Rng + ?Sized>(rng: &mut R, m: &$T) -> Self {
+                Self::new(<$T>::random_unit(rng, m), m)
+            }
+        }
+    )*};
+}
+impl_random_unit_for!(u8 u16 u32 u64 u128 usize);
+
+/// Exponentiation with a blinded exponent, see [powm_blinded()] for details
+pub trait PowmBlinded<T> {
+    /// Calculate self ^ exp with a randomly blinded exponent, see [powm_blinded()]
+    fn powm_blinded<R: Rng + ?Sized>(&self, exp: &T, group_order: &T, rng: &mut R) -> Self;
+}
+
+/// Calculate `base ^ exp` as `base ^ (exp + k * group_order)` for a random k, and evaluate
+/// the power with the Montgomery ladder.
+///
+/// Exponent blinding randomizes the sequence of operations between calls with the same
+/// exponent, which is a cheap mitigation against simple power analysis. The multiple k
+/// has the same width as T, and the blinded exponent is evaluated in a double-width
+/// integer so it never overflows. The ladder performs one multiplication and one squaring
+/// for each bit of the double-width exponent without branching on the bits, although the
+/// reducer itself is not guaranteed to run in constant time.
+///
+/// The caller should supply the group order (e.g. λ(m), or p - 1 for a prime p) or a
+/// multiple of it, since the crate can't factor the modulus. The result is correct only
+/// if base is invertible modulo m.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{powm_blinded, ModularInteger, MontgomeryInt};
+///
+/// let p = 1_000_000_007u64;
+/// let x = MontgomeryInt::new(12345, &p);
+/// let y = powm_blinded(&x, &(p - 2), &(p - 1), &mut rand::thread_rng());
+/// assert_eq!(y, x.inv().unwrap());
+/// ```
+#[inline]
+pub fn powm_blinded<T, R: Rng + ?Sized>(
+    base: &ReducedInt<T, Montgomery<T>>,
+    exp: &T,
+    group_order: &T,
+    rng: &mut R,
+) -> ReducedInt<T, Montgomery<T>>
+where
+    Montgomery<T>: Reducer<T>,
+    ReducedInt<T, Montgomery<T>>: PowmBlinded<T>,
+{
+    base.powm_blinded(exp, group_order, rng)
+}
+
+macro_rules! impl_powm_blinded_for {
+    ($($T:ty)*) => {$(
+        impl PowmBlinded<$T> for ReducedInt<$T, Montgomery<$T>> {
+            fn powm_blinded<R: Rng + ?Sized>(
+                &self,
+                exp: &$T,
+                group_order: &$T,
+                rng: &mut R,
+            ) -> Self {
+                let k = rng.gen::<$T>();
+                let blinded = udouble::widening_mul(k as u128, *group_order as u128)
+                    .overflowing_add(udouble { hi: 0, lo: *exp as u128 })
+                    .0;
+                let bit = |i: u32| {
+                    if i >= 128 {
+                        (blinded.hi >> (i - 128)) as $T & 1
+                    } else {
+                        (blinded.lo >> i) as $T & 1
+                    }
+                };
+
+                // Montgomery ladder with the invariant r1 = r0 * base
+                let r = self.reducer();
+                let mut r0 = r.transform(1);
+                let mut r1 = *self.repr();
+                for i in (0..(2 * <$T>::BITS + 1).min(256)).rev() {
+                    let mask = (0 as $T).wrapping_sub(bit(i));
+                    let t = mask & (r0 ^ r1);
+                    r0 ^= t;
+                    r1 ^= t;
+                    r1 = r.mul(&r0, &r1);
+                    r0 = r.sqr(r0);
+                    let t = mask & (r0 ^ r1);
+                    r0 ^= t;
+                    r1 ^= t;
+                }
+                Self::from_parts(r0, *r)
+            }
+        }
+    )*};
+}
+impl_powm_blinded_for!(u8 u16 u32 u64 u128 usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prim::gcd;
+    use crate::{ModularInteger, MontgomeryInt};
+
+    const NRANDOM: u32 = 10;
+
+    #[test]
+    fn random_unit_test() {
+        let mut rng = rand::thread_rng();
+        assert_eq!(random_unit(&mut rng, &1u32), 0);
+        assert_eq!(random_unit(&mut rng, &2u32), 1);
+        assert!(random_unit(&mut rng, &3u8) % 3 != 0);
+
+        for &m in [30030u64, 510510, 9699690, 1 << 40, 3u64.pow(30), u64::MAX].iter() {
+            for _ in 0..1000 {
+                let u = random_unit(&mut rng, &m);
+                assert!(u < m);
+                assert_eq!(gcd(u, m), 1, "{} is not a unit modulo {}", u, m);
+            }
+        }
+
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..100 {
+                    let m = <$T>::MAX - 1; // even and with small factors for all widths
+                    let u = random_unit(&mut rng, &m);
+                    assert_eq!(gcd(u as u64, m as u64), 1);
+                    let x = MontgomeryInt::<$T>::random_unit(&mut rng, &(m - 1 | 1));
+                    assert!(x.inv().is_some());
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 usize);
+    }
+
+    #[test]
+    fn powm_blinded_test() {
+        use rand::rngs::mock::StepRng;
+        use rand::{random, rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(random());
+        // the largest multiplier k = T::MAX, where the blinded exponent overflows T
+        let mut max_rng = StepRng::new(u64::MAX, 0);
+        let mut zero_rng = StepRng::new(0, 0);
+
+        macro_rules! tests_for {
+            ($($T:ty: $($p:expr),*;)*) => ($($(
+                let p: $T = $p;
+                for _ in 0..NRANDOM {
+                    let x = MontgomeryInt::<$T>::new(random::<$T>() % (p - 1) + 1, &p);
+                    let e = random::<$T>();
+                    let expected = x.pow(&e);
+                    assert_eq!(powm_blinded(&x, &e, &(p - 1), &mut rng), expected);
+                    assert_eq!(powm_blinded(&x, &e, &(p - 1), &mut max_rng), expected);
+                    assert_eq!(powm_blinded(&x, &e, &(p - 1), &mut zero_rng), expected);
+                    assert_eq!(powm_blinded(&x, &0, &(p - 1), &mut rng).residue(), 1);
+                    assert_eq!(powm_blinded(&x, &<$T>::MAX, &(p - 1), &mut max_rng), x.pow(&<$T>::MAX));
+                }
+            )*)*);
+        }
+        tests_for!(
+            u8: 251, 3;
+            u16: 65521;
+            u32: 4294967291, 1000000007;
+            u64: 18446744073709551557, 998244353;
+            u128: u128::MAX - 158;
+            usize: 65537;
+        );
+
+        // a composite modulus with a multiple of λ(m) as the group order
+        let (p, q) = (1000000007u64, 998244353u64);
+        let m = p * q;
+        for _ in 0..NRANDOM {
+            let x = MontgomeryInt::new(random::<u64>(), &m);
+            if x.inv().is_none() {
+                continue;
+            }
+            let e = random::<u64>();
+            let phi = (p - 1) * (q - 1);
+            assert_eq!(powm_blinded(&x, &e, &phi, &mut rng), x.pow(&e));
+            assert_eq!(powm_blinded(&x, &e, &phi, &mut max_rng), x.pow(&e));
+        }
+    }
+
+    #[test]
+    fn random_residue_test() {
+        let mut rng = rand::thread_rng();
+        assert_eq!(random_residue(&mut rng, &1u8), 0);
+
+        // m = 3 * 2^62, where `random::<u64>() % m` picks [0, 2^62) twice as often
+        let m = 3u64 << 62;
+        let n = 30000;
+        let small = (0..n)
+            .filter(|_| random_residue(&mut rng, &m) < 1 << 62)
+            .count();
+        // the expectation is 10000 and the standard deviation is about 82
+        assert!((9500..10500).contains(&small), "{} small samples", small);
+
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                let m = <$T>::MAX / 3 * 2 + 1;
+                let dist = UniformResidue::new(&m);
+                for _ in 0..NRANDOM {
+                    assert!(random_residue(&mut rng, &m) < m);
+                    let x: $T = dist.sample(&mut rng);
+                    assert!(x < m);
+                    let x: MontgomeryInt<$T> = dist.sample(&mut rng);
+                    assert_eq!(x.modulus(), m);
+                    let x: crate::VanillaInt<$T> = dist.sample(&mut rng);
+                    assert!(x.residue() < m);
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+    }
+
+    #[test]
+    fn uniform_unit_test() {
+        let mut rng = rand::thread_rng();
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                // odd moduli divisible by 3, 5 and 17 for all widths
+                let m = <$T>::MAX;
+                let dist = UniformUnit::new(&m);
+                for _ in 0..NRANDOM {
+                    let u: $T = dist.sample(&mut rng);
+                    assert!(u % 3 != 0 && u % 5 != 0 && u % 17 != 0);
+                    let x: MontgomeryInt<$T> = dist.sample(&mut rng);
+                    assert!(x.inv().is_some());
+                    let x: crate::BarrettInt<$T> = dist.sample(&mut rng);
+                    assert!(x.inv().is_some());
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 usize);
+        assert_eq!(*UniformUnit::new(&15u8).modulus(), 15);
+    }
+
+    #[should_panic]
+    #[test]
+    fn uniform_residue_zero_test() {
+        UniformResidue::new(&0u32);
+    }
+
+    #[test]
+    fn distribution_test() {
+        // the units modulo 20 are 1, 3, 7, 9, 11, 13, 17, 19
+        let mut rng = rand::thread_rng();
+        let mut counts = [0u32; 20];
+        for _ in 0..16000 {
+            counts[random_unit(&mut rng, &20usize)] += 1;
+        }
+        for (i, &c) in counts.iter().enumerate() {
+            if gcd(i as u64, 20) == 1 {
+                // the expectation is 2000 and the standard deviation is about 42
+                assert!((1700..2300).contains(&c), "count of {} is {}", i, c);
+            } else {
+                assert_eq!(c, 0);
+            }
+        }
+
+        let mut counts = [0u32; 7];
+        for _ in 0..6000 {
+            let x = MontgomeryInt::<u32>::random_unit(&mut rng, &7);
+            counts[x.residue() as usize] += 1;
+        }
+        assert_eq!(counts[0], 0);
+        assert!(counts[1..].iter().all(|c| (800..1200).contains(c)));
+    }
+}
@@ -0,0 +1,304 @@
+//! Rational number reconstruction from modular images
+
+use crate::prim::gcd;
+
+/// Rational number reconstruction, see [ratrecon()] and [ratrecon_bounded()] for details
+pub trait RationalReconstruction: Sized {
+    /// The signed type representing the numerator
+    type Signed;
+
+    /// Reconstruct a fraction with the default bounds, see [ratrecon()]
+    fn ratrecon(&self, m: &Self) -> Option<(Self::Signed, Self)>;
+
+    /// Reconstruct a fraction with the given bounds, see [ratrecon_bounded()]
+    fn ratrecon_bounded(
+        &self,
+        m: &Self,
+        num_bound: &Self,
+        den_bound: &Self,
+    ) -> Option<(Self::Signed, Self)>;
+}
+
+/// Find a fraction n/d such that `n ≡ r * d (mod m)` with `|n| <= N` and `0 < d <= D`,
+/// where both bounds are `N = D = floor(sqrt((m - 1) / 2))` (Wang's bounds).
+///
+/// Under these bounds the fraction is unique if it exists. See [ratrecon_bounded()]
+/// for choosing the bounds independently.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ratrecon, ModularCoreOps, ModularUnaryOps};
+///
+/// let m = 1_000_000_007u64;
+/// let r = m - 3.mulm(7.invm(&m).unwrap(), &m); // -3/7 mod m
+/// assert_eq!(ratrecon(&r, &m), Some((-3, 7)));
+/// ```
+#[inline]
+pub fn ratrecon<T: RationalReconstruction>(r: &T, m: &T) -> Option<(T::Signed, T)> {
+    r.ratrecon(m)
+}
+
+/// Find a fraction n/d such that `n ≡ r * d (mod m)` with `|n| <= num_bound` and
+/// `0 < d <= den_bound`, and `gcd(n, d) = 1`.
+///
+/// The fraction is found by the extended Euclidean algorithm on `(m, r)`, which computes
+/// the continued fraction convergents of `r / m`. The expansion is truncated at the first
+/// remainder not larger than `num_bound`, and the result is accepted only if the denominator
+/// is within `den_bound`. If `2 * num_bound * den_bound < m`, then there is at most one
+/// such fraction and it will be found if it exists.
+///
+/// [None] is returned if there is no such fraction, or the numerator cannot be represented
+/// by the signed type.
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ratrecon_bounded, ModularCoreOps, ModularUnaryOps};
+///
+/// let m = 1_000_000_007u64;
+/// let r = 31415.mulm(27.invm(&m).unwrap(), &m); // 31415/27 mod m
+/// assert_eq!(ratrecon_bounded(&r, &m, &40000, &30), Some((31415, 27)));
+/// assert_eq!(ratrecon_bounded(&r, &m, &40000, &26), None);
+/// ```
+#[inline]
+pub fn ratrecon_bounded<T: RationalReconstruction>(
+    r: &T,
+    m: &T,
+    num_bound: &T,
+    den_bound: &T,
+) -> Option<(T::Signed, T)> {
+    r.ratrecon_bounded(m, num_bound, den_bound)
+}
+
+macro_rules! impl_ratrecon_for {
+    ($($T:ty => $S:ty;)*) => {$(
+        impl RationalReconstruction for $T {
+            type Signed = $S;
+
+            fn ratrecon(&self, m: &$T) -> Option<($S, $T)> {
+                // integer square root by the bitwise method
+                let target = (*m - 1) / 2;
+                let mut bound: $T = 0;
+                let mut bit: $T = 1 << ((<$T>::BITS - 1) & !1);
+                let mut rem = target;
+                while bit > target {
+                    bit >>= 2;
+                }
+                while bit != 0 {
+                    if rem >= bound + bit {
+                        rem -= bound + bit;
+                        bound = (bound >> 1) + bit;
+                    } else {
+                        bound >>= 1;
+                    }
+                    bit >>= 2;
+                }
+                self.ratrecon_bounded(m, &bound, &bound)
+            }
+
+            fn ratrecon_bounded(
+                &self,
+                m: &$T,
+                num_bound: &$T,
+                den_bound: &$T,
+            ) -> Option<($S, $T)> {
+                // (r0, r1) are the remainders, and (t0, t1) are the magnitudes of the
+                // cofactors of r, whose signs alternate starting from t1 = +1
+                let (mut r0, mut r1) = (*m, self % m);
+                let (mut t0, mut t1): ($T, $T) = (0, 1);
+                let mut negative = false;
+                while r1 > *num_bound {
+                    let q = r0 / r1;
+                    let r2 = r0 - q * r1;
+                    r0 = r1;
+                    r1 = r2;
+                    let t2 = t0 + q * t1;
+                    t0 = t1;
+                    t1 = t2;
+                    negative = !negative;
+                }
+                if t1 > *den_bound {
+                    return None;
+                }
+
+                if gcd(r1, t1) != 1 {
+                    return None;
+                }
+
+                let n = if r1 > <$S>::MAX as $T {
+                    return None;
+                } else {
+                    r1 as $S
+                };
+                Some((if negative { -n } else { n }, t1))
+            }
+        }
+    )*};
+}
+impl_ratrecon_for! {
+    u8 => i8; u16 => i16; u32 => i32; u64 => i64; u128 => i128; usize => isize;
+}
+
+#[cfg(feature = "num-bigint")]
+impl RationalReconstruction for num_bigint::BigUint {
+    type Signed = num_bigint::BigInt;
+
+    fn ratrecon(&self, m: &Self) -> Option<(Self::Signed, Self)> {
+        let bound = ((m - 1u8) >> 1u8).sqrt();
+        self.ratrecon_bounded(m, &bound, &bound)
+    }
+
+    fn ratrecon_bounded(
+        &self,
+        m: &Self,
+        num_bound: &Self,
+        den_bound: &Self,
+    ) -> Option<(Self::Signed, Self)> {
+        use num_bigint::BigInt;
+        use num_integer::Integer;
+        use num_traits::{One, Zero};
+
+        let (mut r0, mut r1) = (m.clone(), self % m);
+        let (mut t0, mut t1) = (Self::zero(), Self::one());
+        let mut negative = false;
+        while &r1 > num_bound {
+            let (q, r2) = r0.div_rem(&r1);
+            r0 = core::mem::replace(&mut r1, r2);
+            let t2 = &t0 + q * &t1;
+            t0 = core::mem::replace(&mut t1, t2);
+            negative = !negative;
+        }
+        if &t1 > den_bound || !r1.gcd(&t1).is_one() {
+            return None;
+        }
+
+        let n = BigInt::from(r1);
+        Some((if negative { -n } else { n }, t1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModularCoreOps, ModularUnaryOps};
+    use rand::random;
+
+    // n/d mod m
+    fn fraction(n: i64, d: u64, m: u64) -> u64 {
+        let r = n.unsigned_abs().mulm(d.invm(&m).unwrap(), &m);
+        if n < 0 {
+            r.negm(&m)
+        } else {
+            r
+        }
+    }
+
+    #[test]
+    fn ratrecon_test() {
+        let m = 1_000_000_007u64;
+        for &(n, d) in [
+            (0i64, 1u64),
+            (1, 1),
+            (-1, 1),
+            (3, 7),
+            (-3, 7),
+            (22360, 22359),
+        ]
+        .iter()
+        {
+            assert_eq!(ratrecon(&fraction(n, d, m), &m), Some((n, d)));
+        }
+
+        // a fraction outside of the default bounds
+        let r = fraction(-7, 123456, m);
+        assert_eq!(ratrecon(&r, &m), Some((-16201, 15806)));
+
+        for _ in 0..100 {
+            let m = random::<u32>() | 1;
+            let bound = (((m - 1) / 2) as f64).sqrt() as u32;
+            let d = random::<u32>() % bound + 1;
+            let n = (random::<u32>() % bound) as i32 * if random() { 1 } else { -1 };
+            if d.invm(&m).is_none() || gcd(n.unsigned_abs(), d) != 1 {
+                continue;
+            }
+            let r = fraction(n as i64, d as u64, m as u64) as u32;
+            assert_eq!(ratrecon(&r, &m), Some((n, d)));
+        }
+    }
+
+    #[test]
+    fn ratrecon_bounded_test() {
+        let m = 1_000_000_007u64;
+
+        // asymmetric bounds, small numerator and large denominator
+        let r = fraction(-7, 123456, m);
+        assert_eq!(ratrecon_bounded(&r, &m, &10, &200000), Some((-7, 123456)));
+        assert_eq!(ratrecon_bounded(&r, &m, &7, &123456), Some((-7, 123456)));
+        assert_eq!(ratrecon_bounded(&r, &m, &10, &100000), None);
+        assert_eq!(ratrecon_bounded(&r, &m, &6, &200000), None);
+
+        // large numerator and small denominator
+        let r = fraction(31415, 27, m);
+        assert_eq!(ratrecon_bounded(&r, &m, &40000, &30), Some((31415, 27)));
+        assert_eq!(ratrecon_bounded(&r, &m, &40000, &26), None);
+        assert_eq!(ratrecon_bounded(&r, &m, &31414, &1000), None);
+
+        // integers are recovered with the denominator bound 1
+        assert_eq!(ratrecon_bounded(&12345u64, &m, &m, &1), Some((12345, 1)));
+        assert_eq!(ratrecon_bounded(&(m - 5), &m, &5, &1), Some((-5, 1)));
+
+        // the numerator doesn't fit in the signed type
+        let m = 251u8;
+        assert_eq!(ratrecon_bounded(&200u8, &m, &250, &1), None);
+        assert_eq!(ratrecon_bounded(&100u8, &m, &250, &1), Some((100, 1)));
+
+        for _ in 0..100 {
+            let m = random::<u64>() >> 1 | 1 << 62 | 1;
+            let d = random::<u64>() % (1 << 40) + 1;
+            let n = (random::<u64>() % (1 << 20)) as i64 - (1 << 19);
+            if d.invm(&m).is_none() {
+                continue;
+            }
+            let r = fraction(n, d, m);
+            let (rn, rd) = ratrecon_bounded(&r, &m, &(1 << 20), &(1 << 40)).unwrap();
+            assert_eq!(rn as i128 * d as i128, n as i128 * rd as i128);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "num-bigint")]
+    fn ratrecon_biguint_test() {
+        use num_bigint::{BigInt, BigUint};
+
+        let m = (BigUint::from(1u8) << 127u8) - 1u8;
+        let d = BigUint::from(123456789u64);
+        let r = (&m - BigUint::from(7u8) * (&d).invm(&m).unwrap() % &m) % &m; // -7/d
+        assert_eq!(
+            ratrecon_bounded(&r, &m, &BigUint::from(10u8), &(BigUint::from(1u8) << 100u8)),
+            Some((BigInt::from(-7), d.clone()))
+        );
+        assert_eq!(ratrecon(&r, &m), Some((BigInt::from(-7), d.clone())));
+        assert_eq!(
+            ratrecon_bounded(&r, &m, &BigUint::from(10u8), &BigUint::from(1000u16)),
+            None
+        );
+
+        // agree with the primitive implementation
+        let m = 1_000_000_007u64;
+        for _ in 0..100 {
+            let r = random::<u64>() % m;
+            let (nb, db) = (random::<u64>() % 100000, random::<u64>() % 100000);
+            let expected = ratrecon_bounded(&r, &m, &nb, &db)
+                .map(|(n, d)| (BigInt::from(n), BigUint::from(d)));
+            let actual = ratrecon_bounded(
+                &BigUint::from(r),
+                &BigUint::from(m),
+                &BigUint::from(nb),
+                &BigUint::from(db),
+            );
+            assert_eq!(actual, expected);
+        }
+    }
+}
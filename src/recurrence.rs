@@ -0,0 +1,206 @@
+//! Linear recurrences over ℤ/pℤ: Berlekamp–Massey and far terms by Kitamasa's method
+
+use crate::ntt::convert_u64;
+use crate::poly::inv;
+use crate::{ModularInteger, Poly, QuotientRing};
+use core::convert::TryFrom;
+
+/// Find the shortest linear recurrence `a_n = c_1 a_(n-1) + ... + c_L a_(n-L)` satisfied by
+/// the sequence with the Berlekamp–Massey algorithm in `O(N^2)` operations, and return its
+/// characteristic polynomial `t^L - c_1 t^(L-1) - ... - c_L`, whose degree is the length L.
+///
+/// The recurrence is unique if the sequence has at least `2L` terms. Use
+/// [linear_recurrence_nth()] with the result to compute far terms.
+///
+/// # Panics
+/// if the sequence is empty, or if a discrepancy is not invertible, which happens only if
+/// the modulus is not a prime
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{berlekamp_massey, ModularInteger, MontgomeryInt};
+///
+/// let x = MontgomeryInt::new(0u64, &1_000_000_007);
+/// let fib: Vec<_> = [0, 1, 1, 2, 3, 5, 8, 13].iter().map(|&v| x.convert(v)).collect();
+/// let p = berlekamp_massey(&fib);
+/// assert_eq!(p.residues(), [1_000_000_006, 1_000_000_006, 1]); // t^2 - t - 1
+/// ```
+pub fn berlekamp_massey<M: ModularInteger + Clone>(seq: &[M]) -> Poly<M>
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    let zero = convert_u64(&seq[0], 0);
+    let one = convert_u64(&zero, 1);
+
+    // c(t) = 1 + c[1] t + ... is the current connection polynomial, and b(t) is the one
+    // before the last length change, whose discrepancy is db
+    let mut c = alloc::vec![one.clone()];
+    let mut b = alloc::vec![one.clone()];
+    let mut db = one;
+    let (mut len, mut shift) = (0usize, 1usize);
+    for n in 0..seq.len() {
+        let d = (1..=len).fold(seq[n].clone(), |acc, i| {
+            acc + c[i].clone() * seq[n - i].clone()
+        });
+        if d.is_zero() {
+            shift += 1;
+            continue;
+        }
+
+        let coef = d.clone() * inv(&db).expect("the discrepancy should be invertible");
+        let prev = c.clone();
+        if c.len() < b.len() + shift {
+            c.resize(b.len() + shift, zero.clone());
+        }
+        for (ci, bi) in c[shift..].iter_mut().zip(b.iter()) {
+            *ci = ci.clone() - coef.clone() * bi.clone();
+        }
+        if 2 * len <= n {
+            len = n + 1 - len;
+            b = prev;
+            db = d;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+
+    c.resize(len + 1, zero.clone());
+    c.reverse();
+    Poly::new(c, &zero)
+}
+
+/// Calculate the n-th term (from zero) of the linear recurrence with the characteristic
+/// polynomial and the initial terms `a_0, ..., a_(L-1)`. By Kitamasa's method, the term is
+/// `sum r_i a_i` where `r = t^n mod charpoly` is found by [QuotientRing::pow()], so it takes
+/// `O(M(L) log n)` operations where `M(L)` is the cost of a product of degree L.
+///
+/// # Panics
+/// if the characteristic polynomial is zero or not monic, or there are less than L initial terms
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{berlekamp_massey, linear_recurrence_nth, ModularInteger, MontgomeryInt};
+///
+/// let x = MontgomeryInt::new(0u64, &1_000_000_007);
+/// let fib: Vec<_> = [0, 1, 1, 2, 3, 5].iter().map(|&v| x.convert(v)).collect();
+/// let p = berlekamp_massey(&fib);
+/// assert_eq!(linear_recurrence_nth(&p, &fib, 90).residue(), 2880067194370816120 % 1_000_000_007);
+/// ```
+pub fn linear_recurrence_nth<M: ModularInteger + Clone>(charpoly: &Poly<M>, init: &[M], n: u64) -> M
+where
+    M::Base: Into<u64> + TryFrom<u64>,
+{
+    let len = charpoly
+        .degree()
+        .expect("the characteristic polynomial should not be zero");
+    let one = convert_u64(&charpoly.coeff(0), 1);
+    assert!(
+        charpoly.leading() == Some(&one),
+        "the characteristic polynomial should be monic"
+    );
+    assert!(init.len() >= len, "not enough initial terms");
+    if len == 0 {
+        return convert_u64(&one, 0);
+    }
+    if n < len as u64 {
+        return init[n as usize].clone();
+    }
+
+    let ring = QuotientRing::new(charpoly.clone());
+    let r = ring.pow(&Poly::monomial(one.clone(), 1), n);
+    r.coeffs()
+        .iter()
+        .zip(init)
+        .fold(convert_u64(&one, 0), |acc, (ri, ai)| {
+            acc + ri.clone() * ai.clone()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fibm, MontgomeryInt};
+    use rand::random;
+    use std::vec::Vec;
+
+    type Mint = MontgomeryInt<u64>;
+
+    /// Extend the sequence by the recurrence a_n = sum c_i a_(n-i)
+    fn generate(c: &[Mint], init: &[Mint], n: usize) -> Vec<Mint> {
+        let mut seq = init.to_vec();
+        while seq.len() < n {
+            let k = seq.len();
+            let v = c
+                .iter()
+                .enumerate()
+                .fold(init[0].convert(0), |acc, (i, ci)| {
+                    acc + *ci * seq[k - 1 - i]
+                });
+            seq.push(v);
+        }
+        seq
+    }
+
+    #[test]
+    fn berlekamp_massey_test() {
+        for &p in [998244353u64, 1_000_000_007, 13].iter() {
+            let x = Mint::new(0, &p);
+            for &len in [1usize, 2, 5, 20, 50].iter() {
+                let mut c: Vec<Mint> = (0..len).map(|_| x.convert(random())).collect();
+                if c[len - 1].is_zero() {
+                    c[len - 1] = x.convert(1);
+                }
+                let init: Vec<Mint> = (0..len).map(|_| x.convert(random())).collect();
+                let seq = generate(&c, &init, 2 * len + 10);
+
+                let q = berlekamp_massey(&seq);
+                assert!(q.degree() <= Some(len));
+                assert_eq!(q.leading(), Some(&x.convert(1)));
+
+                // the found recurrence reproduces the sequence
+                let l = q.degree().unwrap();
+                if l == 0 {
+                    assert!(seq.iter().all(|v| v.is_zero()));
+                    continue;
+                }
+                let found: Vec<Mint> = (0..l).map(|i| -q.coeff(l - 1 - i)).collect();
+                assert_eq!(generate(&found, &seq[..l], seq.len()), seq);
+            }
+        }
+
+        // the zero sequence and the geometric sequence
+        let x = Mint::new(0, &998244353);
+        assert_eq!(berlekamp_massey(&[x; 5]).residues(), [1]);
+        let geo: Vec<Mint> = (0..6u32).map(|i| x.convert(3u64.pow(i))).collect();
+        assert_eq!(berlekamp_massey(&geo).residues(), [998244350, 1]);
+        let delayed = [x, x, x.convert(1), x, x];
+        assert_eq!(berlekamp_massey(&delayed).degree(), Some(3));
+    }
+
+    #[test]
+    fn linear_recurrence_nth_test() {
+        let x = Mint::new(0, &1_000_000_007);
+        let fib: Vec<Mint> = (0..10u64)
+            .map(|i| x.convert(fibm(&i, &1_000_000_007)))
+            .collect();
+        let p = berlekamp_massey(&fib);
+        for &n in [0u64, 1, 5, 100, 12345, u64::MAX].iter() {
+            assert_eq!(
+                linear_recurrence_nth(&p, &fib, n).residue(),
+                fibm(&n, &1_000_000_007)
+            );
+        }
+
+        let c: Vec<Mint> = (0..7).map(|_| x.convert(random())).collect();
+        let init: Vec<Mint> = (0..7).map(|_| x.convert(random())).collect();
+        let seq = generate(&c, &init, 200);
+        let p = berlekamp_massey(&seq[..30]);
+        for n in [0usize, 6, 7, 100, 199].iter() {
+            assert_eq!(linear_recurrence_nth(&p, &seq, *n as u64), seq[*n]);
+        }
+
+        let one = Poly::monomial(x.convert(1), 0);
+        assert!(linear_recurrence_nth(&one, &[], 10).is_zero());
+    }
+}
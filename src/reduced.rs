@@ -1,9 +1,46 @@
-use crate::{udouble, ModularInteger, ModularUnaryOps, Reducer};
+use crate::{
+    udouble, AutoReducer, ModInt, ModularError, ModularInteger, ModularUnaryOps, MontgomeryInt,
+    Reducer,
+};
+use core::convert::TryFrom;
 use core::ops::*;
 #[cfg(feature = "num-traits")]
 use num_traits::{Inv, Pow};
-
-/// An integer in a modulo ring
+#[cfg(feature = "subtle")]
+use {crate::ct::CtSelect, subtle::Choice};
+
+/// An integer in a modulo ring, whose arithmetic is delegated to the reducer R.
+///
+/// The reduction strategy is chosen by the [Reducer] implementation: the naive [Vanilla],
+/// [Montgomery][crate::Montgomery] for odd moduli, [Barrett][crate::Barrett] and
+/// [PreMulInv2by1][crate::PreMulInv2by1] for any moduli, or the special reducers like
+/// [FixedMersenne][crate::FixedMersenne] and [Solinas][crate::Solinas]. Type aliases such as
+/// [MontgomeryInt], [BarrettInt][crate::BarrettInt] and [VanillaInt] are provided for them.
+///
+/// Code can be generic over the reduction strategy by being generic over R.
+///
+/// The reducer is stored by value instead of behind a shared pointer, so the integers are
+/// [Send] and [Sync] (and [Copy] for the builtin reducers) as long as T is. They can be freely
+/// moved across threads, e.g. in parallel iterators.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{Barrett, ModularInteger, Montgomery, ReducedInt, Reducer, Vanilla};
+///
+/// fn sum_of_squares<R: Reducer<u64> + Copy>(n: u64, m: u64) -> u64 {
+///     let r = R::new(&m);
+///     let mut sum = ReducedInt::from_reducer(0, r);
+///     for i in 1..=n {
+///         sum = sum + ReducedInt::from_reducer(i, r).square();
+///     }
+///     sum.residue()
+/// }
+///
+/// let expected = (1..=100u64).map(|i| i * i).sum::<u64>() % 997;
+/// assert_eq!(sum_of_squares::<Vanilla<u64>>(100, 997), expected);
+/// assert_eq!(sum_of_squares::<Montgomery<u64>>(100, 997), expected);
+/// assert_eq!(sum_of_squares::<Barrett<u64>>(100, 997), expected);
+/// ```
 #[derive(Debug, Clone, Copy)]
 pub struct ReducedInt<T, R: Reducer<T>> {
     /// The reduced representation of the integer in a modulo ring.
@@ -22,6 +59,26 @@ impl<T, R: Reducer<T>> ReducedInt<T, R> {
         Self { a, r }
     }
 
+    /// Convert n into the modulo ring of an existing reducer, which saves the
+    /// precomputation compared to [new()][Self::new]
+    #[inline]
+    pub fn from_reducer(n: T, r: R) -> Self {
+        let a = r.transform(n);
+        Self { a, r }
+    }
+
+    /// Assemble the integer from a reduced representation and its reducer
+    #[inline(always)]
+    pub(crate) fn from_parts(a: T, r: R) -> Self {
+        Self { a, r }
+    }
+
+    /// Get the reducer of the integer
+    #[inline(always)]
+    pub fn reducer(&self) -> &R {
+        &self.r
+    }
+
     #[inline(always)]
     fn check_modulus_eq(&self, rhs: &Self)
     where
@@ -38,6 +95,8 @@ impl<T, R: Reducer<T>> ReducedInt<T, R> {
         &self.a
     }
 
+    /// Calculate the modular inverse, or return [None] if self is not a unit.
+    /// The `Inv` operator (with the `num-traits` feature) panics in that case instead.
     #[inline(always)]
     pub fn inv(self) -> Option<Self> {
         Some(Self {
@@ -46,6 +105,20 @@ impl<T, R: Reducer<T>> ReducedInt<T, R> {
         })
     }
 
+    /// Calculate `self / rhs`, or return [None] if rhs is not a unit.
+    /// The [Div] operator panics in that case instead.
+    #[inline]
+    pub fn checked_div(self, rhs: &Self) -> Option<Self>
+    where
+        T: PartialEq + Clone,
+    {
+        self.check_modulus_eq(rhs);
+        let Self { a, r } = self;
+        let inv = r.inv(rhs.a.clone())?;
+        let a = r.mul(&a, &inv);
+        Some(Self { a, r })
+    }
+
     #[inline(always)]
     pub fn pow(self, exp: &T) -> Self {
         Self {
@@ -286,6 +359,7 @@ macro_rules! impl_uprim_vanilla_core_const {
         // These methods are for internal use only, wait for the introduction of const Trait in Rust
         impl Vanilla<$T> {
             #[inline]
+            #[cfg(not(feature = "subtle"))]
             pub(crate) const fn add(m: &$T, lhs: $T, rhs: $T) -> $T {
                 let (sum, overflow) = lhs.overflowing_add(rhs);
                 if overflow || sum >= *m {
@@ -298,11 +372,29 @@ macro_rules! impl_uprim_vanilla_core_const {
             }
 
             #[inline]
+            #[cfg(feature = "subtle")]
+            pub(crate) fn add(m: &$T, lhs: $T, rhs: $T) -> $T {
+                let (sum, overflow) = lhs.overflowing_add(rhs);
+                let (sum2, borrow) = sum.overflowing_sub(*m);
+                // select sum2 if overflow || sum >= m, without a branch
+                let cond = Choice::from(overflow as u8) | !Choice::from(borrow as u8);
+                <$T>::ct_select(sum, sum2, cond)
+            }
+
+            #[inline]
+            #[cfg(not(feature = "subtle"))]
             pub(crate) const fn dbl(m: &$T, target: $T) -> $T {
                 Self::add(m, target, target)
             }
 
             #[inline]
+            #[cfg(feature = "subtle")]
+            pub(crate) fn dbl(m: &$T, target: $T) -> $T {
+                Self::add(m, target, target)
+            }
+
+            #[inline]
+            #[cfg(not(feature = "subtle"))]
             pub(crate) const fn sub(m: &$T, lhs: $T, rhs: $T) -> $T {
                 // this implementation should be equivalent to using overflowing_add and _sub after optimization.
                 if lhs >= rhs {
@@ -313,12 +405,27 @@ macro_rules! impl_uprim_vanilla_core_const {
             }
 
             #[inline]
+            #[cfg(feature = "subtle")]
+            pub(crate) fn sub(m: &$T, lhs: $T, rhs: $T) -> $T {
+                // add back m if borrowed, without a branch
+                let (diff, borrow) = lhs.overflowing_sub(rhs);
+                diff.wrapping_add(<$T>::ct_select(0, *m, Choice::from(borrow as u8)))
+            }
+
+            #[inline]
+            #[cfg(not(feature = "subtle"))]
             pub(crate) const fn neg(m: &$T, target: $T) -> $T {
                 match target {
                     0 => 0,
                     x => *m - x
                 }
             }
+
+            #[inline]
+            #[cfg(feature = "subtle")]
+            pub(crate) fn neg(m: &$T, target: $T) -> $T {
+                Self::sub(m, 0, target)
+            }
         }
     )*};
 }
@@ -350,6 +457,57 @@ macro_rules! impl_reduced_binary_pow {
 
 pub(crate) use impl_reduced_binary_pow;
 
+/// Left-to-right sliding window exponentiation with a table of the odd powers
+/// `base, base^3, ..., base^(2^k - 1)`, where the width k is selected by the exponent length.
+/// Compared with the binary exponentiation, it replaces about a half of the multiplications
+/// with a multiplication for every `k + 1` bits, at the cost of `2^(k-1)` multiplications
+/// for the table. With `k = 1` for short exponents, it's the binary exponentiation.
+macro_rules! impl_reduced_window_pow {
+    ($T:ty) => {
+        fn pow(&self, base: $T, exp: &$T) -> $T {
+            let exp = *exp;
+            let bits = <$T>::BITS - exp.leading_zeros();
+            let k = match bits {
+                0..=16 => 1,
+                17..=64 => 3,
+                _ => 4,
+            };
+
+            let mut table = [base; 8];
+            let sq = if k > 1 { self.sqr(base) } else { base };
+            for i in 1..(1 << (k - 1)) {
+                table[i] = self.mul(&table[i - 1], &sq);
+            }
+
+            // the leading bit is always the start of a window
+            let mut result = self.transform(1);
+            let mut i = bits;
+            while i > 0 {
+                if (exp >> (i - 1)) & 1 == 0 {
+                    result = self.sqr(result);
+                    i -= 1;
+                    continue;
+                }
+
+                // the longest window [i - l, i) ending with a set bit
+                let mut l = k.min(i);
+                while (exp >> (i - l)) & 1 == 0 {
+                    l -= 1;
+                }
+                let w = (exp >> (i - l)) & ((1 << l) - 1);
+                for _ in 0..l {
+                    result = self.sqr(result);
+                }
+                result = self.mul(&result, &table[(w >> 1) as usize]);
+                i -= l;
+            }
+            result
+        }
+    };
+}
+
+pub(crate) use impl_reduced_window_pow;
+
 macro_rules! impl_uprim_vanilla_core {
     ($single:ty) => {
         #[inline(always)]
@@ -450,9 +608,174 @@ impl Reducer<u128> for Vanilla<u128> {
     }
 }
 
+#[cfg(feature = "crypto-bigint")]
+mod _crypto_bigint {
+    use super::*;
+    use crate::cryptobig::reduce;
+    use crate::{ModularCoreOps, ModularPow, ModularUnaryOps};
+    use crypto_bigint::UInt;
+
+    impl<const L: usize> Reducer<UInt<L>> for Vanilla<UInt<L>> {
+        #[inline]
+        fn new(m: &UInt<L>) -> Self {
+            assert!(m != &UInt::ZERO);
+            Self(*m)
+        }
+        #[inline]
+        fn transform(&self, target: UInt<L>) -> UInt<L> {
+            reduce(&target, &self.0)
+        }
+        #[inline]
+        fn check(&self, target: &UInt<L>) -> bool {
+            target < &self.0
+        }
+        #[inline]
+        fn residue(&self, target: UInt<L>) -> UInt<L> {
+            target
+        }
+        #[inline]
+        fn modulus(&self) -> UInt<L> {
+            self.0
+        }
+        #[inline]
+        fn is_zero(&self, target: &UInt<L>) -> bool {
+            target == &UInt::ZERO
+        }
+
+        #[inline]
+        fn add(&self, lhs: &UInt<L>, rhs: &UInt<L>) -> UInt<L> {
+            lhs.add_mod(rhs, &self.0)
+        }
+        #[inline]
+        fn dbl(&self, target: UInt<L>) -> UInt<L> {
+            target.add_mod(&target, &self.0)
+        }
+        #[inline]
+        fn sub(&self, lhs: &UInt<L>, rhs: &UInt<L>) -> UInt<L> {
+            lhs.sub_mod(rhs, &self.0)
+        }
+        #[inline]
+        fn neg(&self, target: UInt<L>) -> UInt<L> {
+            target.neg_mod(&self.0)
+        }
+        #[inline]
+        fn mul(&self, lhs: &UInt<L>, rhs: &UInt<L>) -> UInt<L> {
+            lhs.mulm(rhs, &self.0)
+        }
+        #[inline]
+        fn inv(&self, target: UInt<L>) -> Option<UInt<L>> {
+            target.invm(&self.0)
+        }
+        #[inline]
+        fn sqr(&self, target: UInt<L>) -> UInt<L> {
+            target.sqm(&self.0)
+        }
+        #[inline]
+        fn pow(&self, base: UInt<L>, exp: &UInt<L>) -> UInt<L> {
+            base.powm(exp, &self.0)
+        }
+    }
+}
+
 /// An integer in modulo ring based on conventional [Rem] operations
 pub type VanillaInt<T> = ReducedInt<T, Vanilla<T>>;
 
+/// Convert a modular integer into another representation with the same modulus as `ctx`,
+/// preserving the residue. Only the [ModularInteger] methods are used, so any pair of
+/// representations is supported, e.g. building the values with [VanillaInt] and computing
+/// with [MontgomeryInt].
+///
+/// The modulus of `ctx` should be the same as the one of `a`, which is only checked in debug builds.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{convert_repr, ModularInteger, MontgomeryInt, VanillaInt};
+///
+/// let ctx = MontgomeryInt::new(0u64, &1_000_000_007);
+/// let a = VanillaInt::new(12345u64, &1_000_000_007);
+/// let b = convert_repr(&a, &ctx);
+/// assert_eq!(b.residue(), 12345);
+/// ```
+#[inline]
+pub fn convert_repr<A, B>(a: &A, ctx: &B) -> B
+where
+    A: ModularInteger,
+    B: ModularInteger<Base = A::Base>,
+    A::Base: PartialEq,
+{
+    debug_assert!(
+        a.modulus() == ctx.modulus(),
+        "The modulus of two operators should be the same!"
+    );
+    ctx.convert(a.residue())
+}
+
+macro_rules! impl_montgomery_vanilla_conversion {
+    ($($T:ty)*) => {$(
+        impl From<&MontgomeryInt<$T>> for VanillaInt<$T> {
+            #[inline]
+            fn from(v: &MontgomeryInt<$T>) -> Self {
+                ReducedInt::new(v.residue(), &v.modulus())
+            }
+        }
+
+        impl TryFrom<&VanillaInt<$T>> for MontgomeryInt<$T> {
+            type Error = ModularError;
+
+            /// Fails with [ModularError::EvenModulus] if the modulus is even
+            #[inline]
+            fn try_from(v: &VanillaInt<$T>) -> Result<Self, ModularError> {
+                let m = v.modulus();
+                if m & 1 == 0 {
+                    return Err(ModularError::EvenModulus);
+                }
+                Ok(ReducedInt::new(v.residue(), &m))
+            }
+        }
+    )*};
+}
+impl_montgomery_vanilla_conversion!(u8 u16 u32 u64 u128 usize);
+
+macro_rules! impl_modint_conversion {
+    ($($T:ty)*) => {$(
+        impl From<&MontgomeryInt<$T>> for ModInt<$T> {
+            /// The Montgomery reducer is reused without any precomputation
+            #[inline]
+            fn from(v: &MontgomeryInt<$T>) -> Self {
+                ReducedInt::from_parts(v.a, AutoReducer::Montgomery(v.r))
+            }
+        }
+
+        impl From<&VanillaInt<$T>> for ModInt<$T> {
+            #[inline]
+            fn from(v: &VanillaInt<$T>) -> Self {
+                ReducedInt::new(v.residue(), &v.modulus())
+            }
+        }
+
+        impl From<&ModInt<$T>> for VanillaInt<$T> {
+            #[inline]
+            fn from(v: &ModInt<$T>) -> Self {
+                ReducedInt::new(v.residue(), &v.modulus())
+            }
+        }
+
+        impl TryFrom<&ModInt<$T>> for MontgomeryInt<$T> {
+            type Error = ModularError;
+
+            /// Fails with [ModularError::EvenModulus] if the modulus is even
+            #[inline]
+            fn try_from(v: &ModInt<$T>) -> Result<Self, ModularError> {
+                match v.r {
+                    AutoReducer::Montgomery(r) => Ok(ReducedInt::from_parts(v.a, r)),
+                    AutoReducer::Barrett(_) => Err(ModularError::EvenModulus),
+                }
+            }
+        }
+    )*};
+}
+impl_modint_conversion!(u8 u16 u32 u64 usize);
+
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
@@ -498,6 +821,36 @@ pub(crate) mod tests {
     }
     impl_reduced_test_for!(u8 u16 u32 u64 u128 usize);
 
+    macro_rules! impl_reduced_boundary_test_for {
+        ($($T:ty)*) => {$(
+            impl ReducedTester<$T> {
+                /// Test the additive operations with operands at the boundary of the modulus,
+                /// the modulus m must be supported by the reducer R.
+                pub fn test_boundary<R: Reducer<$T> + Copy>(m: $T) {
+                    let zero = ReducedInt::<$T, R>::new(0, &m);
+                    let one = zero.convert(1);
+                    let mm1 = zero.convert(m - 1);
+                    let mm2 = zero.convert(m - 2);
+
+                    assert!((one + mm1).is_zero(), "sum equals m");
+                    assert!((mm1 + one).is_zero(), "sum equals m");
+                    assert_eq!((mm1 + mm1).residue(), m - 2, "sum equals 2m - 2");
+                    assert_eq!((mm1 + mm2).residue(), m - 3, "sum equals 2m - 3");
+                    assert_eq!(mm1.double().residue(), m - 2);
+                    assert_eq!((zero - one).residue(), m - 1);
+                    assert_eq!((one - mm1).residue(), 2 % m);
+                    assert_eq!((-zero).residue(), 0);
+                    assert_eq!((-one).residue(), m - 1);
+                    assert_eq!((-mm1).residue(), 1);
+                    assert_eq!(zero.convert(m).residue(), 0);
+                    assert_eq!(zero.convert(<$T>::MAX).residue(), <$T>::MAX % m);
+                    assert!(zero.convert(m).is_zero());
+                }
+            }
+        )*};
+    }
+    impl_reduced_boundary_test_for!(u8 u16 u32 u64 u128 usize);
+
     #[test]
     fn test_against_modops() {
         for _ in 0..10 {
@@ -509,4 +862,117 @@ pub(crate) mod tests {
             ReducedTester::<usize>::test_against_modops::<Vanilla<usize>>(0);
         }
     }
+
+    #[test]
+    fn test_boundary() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for &m in [<$T>::MAX, <$T>::MAX - 1, <$T>::MAX / 2 + 1, 3].iter() {
+                    ReducedTester::<$T>::test_boundary::<Vanilla<$T>>(m);
+                    if m % 2 == 1 {
+                        ReducedTester::<$T>::test_boundary::<crate::Montgomery<$T>>(m);
+                    }
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+
+        macro_rules! tests_for_premulinv {
+            ($($T:ty)*) => ($(
+                for &m in [<$T>::MAX, <$T>::MAX - 1, <$T>::MAX / 2 + 1, 3].iter() {
+                    ReducedTester::<$T>::test_boundary::<crate::PreMulInv2by1<$T>>(m);
+                    ReducedTester::<$T>::test_boundary::<crate::Barrett<$T>>(m);
+                }
+            )*);
+        }
+        tests_for_premulinv!(u8 u16 u32 u64 usize);
+    }
+
+    #[test]
+    fn vanilla_core_test() {
+        // the helpers stay usable in constants without the subtle feature
+        #[cfg(not(feature = "subtle"))]
+        {
+            const D: u8 = Vanilla::<u8>::dbl(&251, 200);
+            assert_eq!(D, 149);
+        }
+
+        for &m in [1u64, 2, 1_000_000_007, u64::MAX].iter() {
+            for _ in 0..10 {
+                let (a, b) = (random::<u64>() % m, random::<u64>() % m);
+                assert_eq!(Vanilla::<u64>::add(&m, a, b), a.addm(b, &m));
+                assert_eq!(Vanilla::<u64>::dbl(&m, a), a.dblm(&m));
+                assert_eq!(Vanilla::<u64>::sub(&m, a, b), a.subm(b, &m));
+                assert_eq!(Vanilla::<u64>::neg(&m, a), a.negm(&m));
+            }
+            assert_eq!(Vanilla::<u64>::dbl(&m, m - 1), (m - 1).dblm(&m));
+        }
+    }
+
+    #[test]
+    fn send_sync_test() {
+        fn assert_send_sync<T: Send + Sync + Copy>() {}
+        assert_send_sync::<MontgomeryInt<u64>>();
+        assert_send_sync::<ModInt<u32>>();
+        assert_send_sync::<VanillaInt<u128>>();
+        assert_send_sync::<crate::BarrettInt<usize>>();
+        assert_send_sync::<crate::FixedMersenneInt<61, 1>>();
+        assert_send_sync::<crate::GoldilocksInt>();
+
+        // the residues are shared by multiple threads
+        let m = 1_000_000_007u64;
+        let x = MontgomeryInt::new(12345, &m);
+        let handles: std::vec::Vec<_> = (1..=4u64)
+            .map(|e| std::thread::spawn(move || x.pow(&e).residue()))
+            .collect();
+        for (e, h) in (1..=4u32).zip(handles) {
+            assert_eq!(h.join().unwrap(), 12345u64.powm(e as u64, &m));
+        }
+    }
+
+    #[test]
+    fn conversion_test() {
+        use core::convert::TryInto;
+
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..10 {
+                    for &m in [random::<$T>() | 1, (random::<$T>() & !1).max(2), 1, <$T>::MAX].iter() {
+                        let a = random::<$T>();
+                        let v = VanillaInt::new(a, &m);
+                        let x = ModInt::new(a, &m);
+
+                        let v2 = VanillaInt::from(&x);
+                        let x2 = ModInt::from(&v);
+                        assert_eq!((v2.residue(), v2.modulus()), (a % m, m));
+                        assert_eq!((x2.residue(), x2.modulus()), (a % m, m));
+                        assert_eq!(convert_repr(&x, &v), v);
+
+                        let mv: Result<MontgomeryInt<$T>, _> = (&v).try_into();
+                        let mx: Result<MontgomeryInt<$T>, _> = (&x).try_into();
+                        if m & 1 == 1 {
+                            let (mv, mx) = (mv.unwrap(), mx.unwrap());
+                            assert_eq!(mv, mx);
+                            assert_eq!((mv.residue(), mv.modulus()), (a % m, m));
+                            assert_eq!(VanillaInt::from(&mv), v);
+                            assert_eq!(ModInt::from(&mv), x);
+                            assert_eq!(convert_repr(&v, &mv), mv);
+                            assert_eq!(convert_repr(&mv, &x), x);
+                        } else {
+                            assert_eq!(mv, Err(ModularError::EvenModulus));
+                            assert_eq!(mx, Err(ModularError::EvenModulus));
+                        }
+                    }
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 usize);
+
+        let m = random::<u128>() | 1;
+        let v = VanillaInt::new(random::<u128>(), &m);
+        let mv = MontgomeryInt::try_from(&v).unwrap();
+        assert_eq!(VanillaInt::from(&mv), v);
+        let v = VanillaInt::new(random::<u128>(), &(m - 1));
+        assert!(MontgomeryInt::try_from(&v).is_err());
+    }
 }
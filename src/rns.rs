@@ -0,0 +1,587 @@
+//! Residue number system over a fixed set of word-size moduli
+
+use crate::{ModularCoreOps, ModularUnaryOps, Montgomery, Reducer};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Add, Mul, Neg, Sub};
+
+/// Errors when creating a [RnsContext]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RnsError {
+    /// No modulus is provided
+    Empty,
+    /// The modulus at the index is not an odd number larger than one
+    InvalidModulus(usize),
+    /// The moduli at the two indices are not coprime
+    NotCoprime(usize, usize),
+}
+
+impl fmt::Display for RnsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RnsError::Empty => f.write_str("no modulus is provided"),
+            RnsError::InvalidModulus(i) => {
+                write!(f, "the modulus #{} is not an odd number larger than one", i)
+            }
+            RnsError::NotCoprime(i, j) => write!(f, "the moduli #{} and #{} are not coprime", i, j),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RnsError {}
+
+/// Precomputed constants for a residue number system (RNS) with pairwise coprime odd moduli
+/// `m_0, m_1, ..., m_(n-1)`.
+///
+/// An integer x in range `[0, M)` where `M = m_0 * m_1 * ... * m_(n-1)` is represented
+/// by its residues modulo each `m_i` (called channels). Additions, subtractions and
+/// multiplications are performed independently in each channel with the [Montgomery]
+/// reducer, and the results are correct modulo M. Therefore the arithmetic wraps around
+/// silently when the true result is out of the dynamic range `[0, M)`, and it's the
+/// responsibility of the user to choose enough moduli.
+///
+/// The conversion back to a normal integer is done with Garner's algorithm.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::RnsContext;
+///
+/// let ctx = RnsContext::new(&[1_000_000_007, 998_244_353, 4_294_967_291]).unwrap();
+/// let x = ctx.from_uint(123_456_789_012_345);
+/// let y = ctx.from_uint(987_654_321);
+/// assert_eq!((&x * &y).to_uint(), Some(123_456_789_012_345 * 987_654_321));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RnsContext {
+    moduli: Vec<u64>,
+    reducers: Vec<Montgomery<u64>>,
+    // garner[i][j] = m_j^-1 mod m_i in the Montgomery form of channel i, for j < i
+    garner: Vec<Vec<u64>>,
+}
+
+impl RnsContext {
+    /// Create the context from a list of pairwise coprime odd moduli
+    pub fn new(moduli: &[u64]) -> Result<Self, RnsError> {
+        if moduli.is_empty() {
+            return Err(RnsError::Empty);
+        }
+        if let Some(i) = moduli.iter().position(|&m| m & 1 == 0 || m == 1) {
+            return Err(RnsError::InvalidModulus(i));
+        }
+
+        let reducers: Vec<Montgomery<u64>> =
+            moduli.iter().map(|&m| Montgomery::<u64>::new(m)).collect();
+        let mut garner = Vec::with_capacity(moduli.len());
+        for (i, (&mi, r)) in moduli.iter().zip(reducers.iter()).enumerate() {
+            let mut row = Vec::with_capacity(i);
+            for (j, &mj) in moduli[..i].iter().enumerate() {
+                let inv = mj.invm(&mi).ok_or(RnsError::NotCoprime(j, i))?;
+                row.push(r.transform(inv));
+            }
+            garner.push(row);
+        }
+
+        Ok(Self {
+            moduli: moduli.to_vec(),
+            reducers,
+            garner,
+        })
+    }
+
+    /// The moduli of the channels
+    #[inline]
+    pub fn moduli(&self) -> &[u64] {
+        &self.moduli
+    }
+
+    /// Number of bits of the dynamic range `M`
+    pub fn range_bits(&self) -> u32 {
+        // calculate the product in little-endian limbs
+        let mut limbs: Vec<u64> = Vec::with_capacity(self.moduli.len());
+        limbs.push(1);
+        for &m in self.moduli.iter() {
+            let mut carry = 0u64;
+            for limb in limbs.iter_mut() {
+                let p = *limb as u128 * m as u128 + carry as u128;
+                *limb = p as u64;
+                carry = (p >> 64) as u64;
+            }
+            if carry > 0 {
+                limbs.push(carry);
+            }
+        }
+        let top = limbs.last().unwrap();
+        (limbs.len() as u32 - 1) * 64 + (64 - top.leading_zeros())
+    }
+
+    /// Convert an integer into the residue number system. The integer is reduced modulo `M`.
+    pub fn from_uint(&self, n: u128) -> RnsInt<'_> {
+        let residues = self
+            .moduli
+            .iter()
+            .zip(self.reducers.iter())
+            .map(|(&m, r)| r.transform((n % m as u128) as u64))
+            .collect();
+        RnsInt {
+            ctx: self,
+            residues,
+        }
+    }
+
+    /// Convert an integer into the residue number system from its residue in each channel.
+    ///
+    /// # Panics
+    /// if the number of residues is not equal to the number of moduli
+    pub fn from_residues(&self, residues: &[u64]) -> RnsInt<'_> {
+        assert_eq!(
+            residues.len(),
+            self.moduli.len(),
+            "the number of residues should be equal to the number of moduli"
+        );
+        let residues = residues
+            .iter()
+            .zip(self.reducers.iter())
+            .map(|(&v, r)| r.transform(v))
+            .collect();
+        RnsInt {
+            ctx: self,
+            residues,
+        }
+    }
+
+    /// Convert an integer into the residue number system. The integer is reduced modulo `M`.
+    #[cfg(feature = "num-bigint")]
+    pub fn from_biguint(&self, n: &num_bigint::BigUint) -> RnsInt<'_> {
+        use num_traits::ToPrimitive;
+        let residues = self
+            .moduli
+            .iter()
+            .zip(self.reducers.iter())
+            .map(|(&m, r)| r.transform((n % m).to_u64().unwrap()))
+            .collect();
+        RnsInt {
+            ctx: self,
+            residues,
+        }
+    }
+}
+
+/// An integer represented in a residue number system, see [RnsContext] for details
+#[derive(Debug, Clone)]
+pub struct RnsInt<'a> {
+    ctx: &'a RnsContext,
+    residues: Vec<u64>, // in Montgomery form
+}
+
+impl<'a> RnsInt<'a> {
+    #[inline(always)]
+    fn check_context_eq(&self, rhs: &Self) {
+        if cfg!(debug_assertions) && !core::ptr::eq(self.ctx, rhs.ctx) {
+            panic!("The contexts of two operators should be the same!");
+        }
+    }
+
+    /// The context of the residue number system
+    #[inline]
+    pub fn context(&self) -> &'a RnsContext {
+        self.ctx
+    }
+
+    /// Get the normalized residues in each channel
+    pub fn residues(&self) -> Vec<u64> {
+        self.residues
+            .iter()
+            .zip(self.ctx.reducers.iter())
+            .map(|(&v, r)| r.residue(v))
+            .collect()
+    }
+
+    /// Calculate the digits `v_i` of the mixed radix representation with Garner's algorithm,
+    /// such that the integer is `v_0 + v_1 * m_0 + v_2 * m_0 * m_1 + ...` and `v_i < m_i`.
+    pub fn to_mixed_radix(&self) -> Vec<u64> {
+        let ctx = self.ctx;
+        let mut digits: Vec<u64> = Vec::with_capacity(ctx.moduli.len());
+        for (i, r) in ctx.reducers.iter().enumerate() {
+            let mut t = self.residues[i];
+            for (&v, inv) in digits.iter().zip(ctx.garner[i].iter()) {
+                t = r.mul(&r.sub(&t, &r.transform(v)), inv);
+            }
+            digits.push(r.residue(t));
+        }
+        digits
+    }
+
+    /// Convert back to a normal integer in range `[0, M)`. [None] is returned if the
+    /// integer cannot be represented by [u128].
+    pub fn to_uint(&self) -> Option<u128> {
+        let digits = self.to_mixed_radix();
+        let mut acc = 0u128;
+        for (&v, &m) in digits.iter().zip(self.ctx.moduli.iter()).rev() {
+            acc = acc.checked_mul(m as u128)?.checked_add(v as u128)?;
+        }
+        Some(acc)
+    }
+
+    /// Convert back to a normal integer in range `[0, M)`
+    #[cfg(feature = "num-bigint")]
+    pub fn to_biguint(&self) -> num_bigint::BigUint {
+        let digits = self.to_mixed_radix();
+        let mut acc = num_bigint::BigUint::from(0u8);
+        for (&v, &m) in digits.iter().zip(self.ctx.moduli.iter()).rev() {
+            acc = acc * m + v;
+        }
+        acc
+    }
+}
+
+impl PartialEq for RnsInt<'_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.check_context_eq(other);
+        self.residues == other.residues
+    }
+}
+impl Eq for RnsInt<'_> {}
+
+macro_rules! impl_binops {
+    ($method:ident, impl $op:ident) => {
+        impl<'a> $op<&RnsInt<'a>> for &RnsInt<'a> {
+            type Output = RnsInt<'a>;
+            fn $method(self, rhs: &RnsInt<'a>) -> Self::Output {
+                self.check_context_eq(rhs);
+                let residues = self
+                    .residues
+                    .iter()
+                    .zip(rhs.residues.iter())
+                    .zip(self.ctx.reducers.iter())
+                    .map(|((a, b), r)| r.$method(a, b))
+                    .collect();
+                RnsInt {
+                    ctx: self.ctx,
+                    residues,
+                }
+            }
+        }
+
+        impl<'a> $op<&RnsInt<'a>> for RnsInt<'a> {
+            type Output = RnsInt<'a>;
+            #[inline]
+            fn $method(mut self, rhs: &RnsInt<'a>) -> Self::Output {
+                self.check_context_eq(rhs);
+                for ((a, b), r) in self
+                    .residues
+                    .iter_mut()
+                    .zip(rhs.residues.iter())
+                    .zip(self.ctx.reducers.iter())
+                {
+                    *a = r.$method(a, b);
+                }
+                self
+            }
+        }
+
+        impl<'a> $op<RnsInt<'a>> for RnsInt<'a> {
+            type Output = RnsInt<'a>;
+            #[inline]
+            fn $method(self, rhs: RnsInt<'a>) -> Self::Output {
+                self.$method(&rhs)
+            }
+        }
+    };
+}
+impl_binops!(add, impl Add);
+impl_binops!(sub, impl Sub);
+impl_binops!(mul, impl Mul);
+
+impl<'a> Neg for RnsInt<'a> {
+    type Output = RnsInt<'a>;
+    #[inline]
+    fn neg(mut self) -> Self::Output {
+        for (a, r) in self.residues.iter_mut().zip(self.ctx.reducers.iter()) {
+            *a = r.neg(*a);
+        }
+        self
+    }
+}
+
+impl PartialOrd for RnsInt<'_> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The integers are ordered by their values in range `[0, M)`, which is determined by
+/// comparing the mixed radix digits from the most significant one.
+impl Ord for RnsInt<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.check_context_eq(other);
+        let (a, b) = (self.to_mixed_radix(), other.to_mixed_radix());
+        a.iter().rev().cmp(b.iter().rev())
+    }
+}
+
+/// Integer types supported by [to_mixed_radix()], [rns_cmp()] and [rns_in_range()]
+pub trait MixedRadix: Sized {
+    /// Calculate the mixed radix digits, see [to_mixed_radix()] for details
+    fn to_mixed_radix(residues: &[Self], moduli: &[Self]) -> Vec<Self>;
+}
+
+/// Convert the residues of an integer modulo the pairwise coprime moduli `m_0, m_1, ...`
+/// into the mixed radix digits `v_i` with Garner's algorithm, such that the integer is
+/// `v_0 + v_1 * m_0 + v_2 * m_0 * m_1 + ...` and `v_i < m_i`.
+///
+/// Unlike [RnsContext], the moduli can be even and the inverses are computed on the fly,
+/// so the context should be preferred for repeated conversions with the same moduli.
+///
+/// # Panics
+/// if the lengths of the slices are different, or the moduli are not pairwise coprime
+///
+/// Example code:
+/// ```rust
+/// use num_modular::to_mixed_radix;
+///
+/// // 100 = 0 + 0 * 4 + 2 * (4 * 5) + 1 * (4 * 5 * 3)
+/// assert_eq!(to_mixed_radix(&[0u32, 0, 1, 2], &[4, 5, 3, 7]), [0, 0, 2, 1]);
+/// ```
+#[inline]
+pub fn to_mixed_radix<T: MixedRadix>(residues: &[T], moduli: &[T]) -> Vec<T> {
+    T::to_mixed_radix(residues, moduli)
+}
+
+/// Compare two integers given by their residues modulo the same pairwise coprime moduli,
+/// by their values in range `[0, M)` where M is the product of the moduli. The integers
+/// are not reconstructed, only the mixed radix digits (see [to_mixed_radix()]) are compared.
+///
+/// # Panics
+/// if the lengths of the slices are different, or the moduli are not pairwise coprime
+#[inline]
+pub fn rns_cmp<T: MixedRadix + Ord>(a: &[T], b: &[T], moduli: &[T]) -> Ordering {
+    let (a, b) = (to_mixed_radix(a, moduli), to_mixed_radix(b, moduli));
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+/// Check whether the integer given by its residues is in range `[lower, upper)`, where the
+/// bounds are also given by their residues modulo the same pairwise coprime moduli.
+///
+/// # Panics
+/// if the lengths of the slices are different, or the moduli are not pairwise coprime
+///
+/// Example code:
+/// ```rust
+/// use num_modular::rns_in_range;
+///
+/// let moduli = [3u8, 5, 7];
+/// let rns = |n: u8| [n % 3, n % 5, n % 7];
+/// assert!(rns_in_range(&rns(42), &rns(10), &rns(50), &moduli));
+/// assert!(!rns_in_range(&rns(50), &rns(10), &rns(50), &moduli));
+/// ```
+#[inline]
+pub fn rns_in_range<T: MixedRadix + Ord>(x: &[T], lower: &[T], upper: &[T], moduli: &[T]) -> bool {
+    rns_cmp(x, lower, moduli) != Ordering::Less && rns_cmp(x, upper, moduli) == Ordering::Less
+}
+
+macro_rules! impl_mixed_radix_for {
+    ($($T:ty)*) => {$(
+        impl MixedRadix for $T {
+            fn to_mixed_radix(residues: &[$T], moduli: &[$T]) -> Vec<$T> {
+                assert_eq!(
+                    residues.len(),
+                    moduli.len(),
+                    "the number of residues should be equal to the number of moduli"
+                );
+                let mut digits: Vec<$T> = Vec::with_capacity(moduli.len());
+                for (&r, &mi) in residues.iter().zip(moduli) {
+                    let mut t = r % mi;
+                    for (&v, &mj) in digits.iter().zip(moduli) {
+                        let inv = mj.invm(&mi).expect("the moduli should be pairwise coprime");
+                        t = t.subm(v, &mi).mulm(inv, &mi);
+                    }
+                    digits.push(t);
+                }
+                digits
+            }
+        }
+    )*};
+}
+impl_mixed_radix_for!(u8 u16 u32 u64 u128 usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::random;
+
+    const NRANDOM: u32 = 10;
+    const MODULI: [u64; 4] = [
+        0xffffffffffffffc5, // 2^64 - 59
+        0xffffffffffffff9d, // 2^64 - 99
+        4294967291,
+        1000000007,
+    ];
+
+    #[test]
+    fn context_test() {
+        assert_eq!(RnsContext::new(&[]).unwrap_err(), RnsError::Empty);
+        assert_eq!(
+            RnsContext::new(&[3, 4]).unwrap_err(),
+            RnsError::InvalidModulus(1)
+        );
+        assert_eq!(
+            RnsContext::new(&[1, 3]).unwrap_err(),
+            RnsError::InvalidModulus(0)
+        );
+        assert_eq!(
+            RnsContext::new(&[3, 5, 7, 21]).unwrap_err(),
+            RnsError::NotCoprime(0, 3)
+        );
+
+        let ctx = RnsContext::new(&MODULI[..2]).unwrap();
+        assert_eq!(ctx.moduli(), &MODULI[..2]);
+        assert_eq!(ctx.range_bits(), 128);
+        assert_eq!(RnsContext::new(&[3, 5, 7]).unwrap().range_bits(), 7); // 105
+        assert_eq!(RnsContext::new(&[3, 5, 7, 11]).unwrap().range_bits(), 11); // 1155
+        assert_eq!(RnsContext::new(&MODULI).unwrap().range_bits(), 190);
+    }
+
+    #[test]
+    fn conversion_test() {
+        let ctx = RnsContext::new(&MODULI[..2]).unwrap();
+        let range = MODULI[0] as u128 * MODULI[1] as u128;
+        for _ in 0..NRANDOM {
+            let n = random::<u128>() % range;
+            let x = ctx.from_uint(n);
+            assert_eq!(x.to_uint(), Some(n));
+            assert_eq!(
+                x.residues(),
+                [
+                    (n % MODULI[0] as u128) as u64,
+                    (n % MODULI[1] as u128) as u64
+                ]
+            );
+            assert_eq!(ctx.from_residues(&x.residues()), x);
+
+            // values out of range are wrapped
+            if let Some(wrapped) = n.checked_add(range) {
+                assert_eq!(ctx.from_uint(wrapped).to_uint(), Some(n));
+            }
+        }
+
+        let ctx = RnsContext::new(&[3, 5, 7]).unwrap();
+        for n in 0..105 {
+            let digits = ctx.from_uint(n).to_mixed_radix();
+            assert_eq!(digits[0] + digits[1] * 3 + digits[2] * 15, n as u64);
+            assert_eq!(ctx.from_uint(n + 105).to_uint(), Some(n));
+        }
+
+        // the value doesn't fit in u128
+        let ctx = RnsContext::new(&MODULI[..3]).unwrap();
+        let x = ctx.from_uint(u128::MAX);
+        assert_eq!((x.clone() + x.clone()).to_uint(), None);
+        assert_eq!(x.to_uint(), Some(u128::MAX));
+    }
+
+    #[test]
+    fn mixed_radix_test() {
+        // agree with the context
+        let ctx = RnsContext::new(&MODULI).unwrap();
+        for _ in 0..NRANDOM {
+            let x = ctx.from_uint(random());
+            assert_eq!(to_mixed_radix(&x.residues(), &MODULI), x.to_mixed_radix());
+        }
+
+        // even moduli and exhaustive comparison over the small range
+        let moduli = [4u32, 9, 5, 7];
+        let rns = |n: u32| [n % 4, n % 9, n % 5, n % 7];
+        for a in 0..1260 {
+            let digits = to_mixed_radix(&rns(a), &moduli);
+            assert_eq!(
+                digits[0] + digits[1] * 4 + digits[2] * 36 + digits[3] * 180,
+                a
+            );
+            let b = random::<u32>() % 1260;
+            assert_eq!(rns_cmp(&rns(a), &rns(b), &moduli), a.cmp(&b));
+            assert_eq!(rns_cmp(&rns(a), &rns(a), &moduli), Ordering::Equal);
+            let (lo, hi) = (random::<u32>() % 1260, random::<u32>() % 1260);
+            assert_eq!(
+                rns_in_range(&rns(a), &rns(lo), &rns(hi), &moduli),
+                lo <= a && a < hi
+            );
+        }
+        assert_eq!(to_mixed_radix::<u8>(&[], &[]), []);
+    }
+
+    #[test]
+    #[should_panic]
+    fn mixed_radix_not_coprime_test() {
+        to_mixed_radix(&[1u32, 2], &[6, 9]);
+    }
+
+    #[test]
+    fn ordering_test() {
+        let ctx = RnsContext::new(&[MODULI[2], MODULI[3], MODULI[0]]).unwrap();
+        let range = MODULI[2] as u128 * MODULI[3] as u128;
+        for _ in 0..NRANDOM {
+            let (a, b) = (random::<u128>() >> 3, random::<u128>() >> 3); // < M
+            assert_eq!(ctx.from_uint(a).cmp(&ctx.from_uint(b)), a.cmp(&b));
+
+            // differ only in the most significant mixed radix digit
+            let low = random::<u128>() % range;
+            let (x, y) = (ctx.from_uint(low), ctx.from_uint(low + range));
+            assert!(x < y);
+            let dx = x.to_mixed_radix();
+            let dy = y.to_mixed_radix();
+            assert_eq!(dx[..2], dy[..2]);
+            assert_eq!(dx[2] + 1, dy[2]);
+        }
+        let x = ctx.from_uint(12345);
+        assert_eq!(x.partial_cmp(&x.clone()), Some(Ordering::Equal));
+    }
+
+    #[test]
+    fn arithmetic_test() {
+        let ctx = RnsContext::new(&MODULI[..2]).unwrap();
+        let range = MODULI[0] as u128 * MODULI[1] as u128;
+        for _ in 0..NRANDOM {
+            let (a, b) = (random::<u64>() as u128, random::<u64>() as u128);
+            let (x, y) = (ctx.from_uint(a), ctx.from_uint(b));
+            assert_eq!((&x + &y).to_uint(), Some(a + b));
+            assert_eq!((&x * &y).to_uint(), Some(a * b));
+            assert_eq!((x.clone() - &y).to_uint(), Some((a + range - b) % range));
+            assert_eq!((-x.clone()).to_uint(), Some((range - a) % range));
+            assert_eq!(x.clone() + y.clone() - y, x);
+        }
+
+        // wrap around at the boundary of the dynamic range
+        let max = ctx.from_uint(range - 1);
+        assert_eq!((max.clone() + ctx.from_uint(1)).to_uint(), Some(0));
+        assert_eq!((max.clone() * max).to_uint(), Some(1));
+    }
+
+    #[test]
+    #[cfg(feature = "num-bigint")]
+    fn against_biguint_test() {
+        use num_bigint::BigUint;
+
+        let ctx = RnsContext::new(&MODULI).unwrap();
+        let range = MODULI.iter().fold(BigUint::from(1u8), |acc, &m| acc * m);
+        for _ in 0..NRANDOM {
+            let a = BigUint::from_bytes_le(&random::<[u8; 32]>()) % &range;
+            let b = BigUint::from_bytes_le(&random::<[u8; 32]>()) % &range;
+            let (x, y) = (ctx.from_biguint(&a), ctx.from_biguint(&b));
+            assert_eq!(x.to_biguint(), a);
+            assert_eq!((&x + &y).to_biguint(), (&a + &b) % &range);
+            assert_eq!((&x - &y).to_biguint(), (&a + &range - &b) % &range);
+            assert_eq!((&x * &y).to_biguint(), (&a * &b) % &range);
+
+            assert_eq!(x.cmp(&y), a.cmp(&b));
+            assert_eq!(rns_cmp(&x.residues(), &y.residues(), &MODULI), a.cmp(&b));
+
+            // bounded results are exact
+            let (a, b) = (a >> 96u8, b >> 96u8);
+            let (x, y) = (ctx.from_biguint(&a), ctx.from_biguint(&b));
+            assert_eq!((x * y).to_biguint(), a * b);
+        }
+    }
+}
@@ -0,0 +1,536 @@
+//! Modular k-th roots by the Adleman-Manders-Miller algorithm
+
+use crate::prim::gcd;
+use crate::{ModularInteger, ModularUnaryOps, MontgomeryInt, Witness};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Modular k-th root
+pub trait ModularRoot<Modulus = Self> {
+    type Output;
+
+    /// Calculate a k-th root of self modulo a prime p, see [nth_rootm()] for details
+    fn nth_rootm(self, k: u32, p: Modulus) -> Option<Self::Output>;
+
+    /// Calculate all the k-th roots of self modulo a prime p, see [nth_rootm_all()] for details
+    #[cfg(feature = "alloc")]
+    fn nth_rootm_all(self, k: u32, p: Modulus) -> Vec<Self::Output>;
+}
+
+/// Calculate a k-th root x of a modulo a prime p (i.e. `x^k ≡ a (mod p)`), or return [None]
+/// if a is not a k-th power residue.
+///
+/// With `d = gcd(k, p-1)`, a is a k-th power residue iff `a^((p-1)/d) = 1`, and in this case
+/// there are exactly d roots. The exponent `k/d` is inverted modulo `(p-1)/d` first, so if
+/// `d = 1` the root is just a power of a. The remaining d-th root is calculated by the
+/// Adleman-Manders-Miller algorithm (the generalization of Tonelli-Shanks, see [sqrtm()][crate::sqrtm()])
+/// for each prime power factor `r^e` of d, which takes `O(r)` multiplications for each
+/// base r digit of a discrete logarithm in the Sylow r-subgroup. So the calculation can be
+/// slow if k shares a large prime factor r with p - 1 and `r^(e+1)` still divides p - 1.
+///
+/// The root is not necessarily the smallest one, use [nth_rootm_all()] to find all of them.
+/// If `a = 0 (mod p)`, the only root is zero, and for p = 2 the root is `a mod 2`.
+///
+/// Primality of p is not checked, because it's costly. For a composite p, the result is
+/// either [None] or a verified k-th root.
+///
+/// # Panics
+/// if p or k is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{nth_rootm, ModularPow};
+///
+/// let p = 1_000_000_007u64; // p - 1 = 2 * 500000003
+/// let x = nth_rootm(5u64, 3, &p).unwrap(); // gcd(3, p-1) = 1
+/// assert_eq!(x.powm(3, &p), 5);
+///
+/// let p = 998244353u64; // p - 1 = 2^23 * 7 * 17
+/// let x = nth_rootm(3u64.powm(28, &p), 28, &p).unwrap();
+/// assert_eq!(x.powm(28, &p), 3u64.powm(28, &p));
+/// assert_eq!(nth_rootm(3u64, 7, &p), None); // 3 is a primitive root
+/// ```
+#[inline]
+pub fn nth_rootm<T: ModularRoot<M>, M>(a: T, k: u32, p: M) -> Option<T::Output> {
+    a.nth_rootm(k, p)
+}
+
+/// Calculate all the k-th roots of a modulo a prime p in ascending order. The result is
+/// empty if a is not a k-th power residue.
+///
+/// The roots are the products of one root (from [nth_rootm()]) and the `gcd(k, p-1)`-th
+/// roots of unity, so the result can be huge if `gcd(k, p-1)` is large. Primality of p is
+/// not checked, for a composite p the result contains only verified roots (not necessarily
+/// all of them).
+///
+/// # Panics
+/// if p or k is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::nth_rootm_all;
+///
+/// assert_eq!(nth_rootm_all(1u32, 3, &13), [1, 3, 9]);
+/// assert_eq!(nth_rootm_all(8u32, 3, &13), [2, 5, 6]);
+/// assert!(nth_rootm_all(2u32, 3, &13).is_empty());
+/// ```
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn nth_rootm_all<T: ModularRoot<M>, M>(a: T, k: u32, p: M) -> Vec<T::Output> {
+    a.nth_rootm_all(k, p)
+}
+
+/// The prime factors below this bound are found by trial division in [prime_powers()]
+const TRIAL_BOUND: u128 = 128;
+
+/// Find a nontrivial factor of an odd composite n by Pollard's rho algorithm. The
+/// differences are multiplied together in batches of 64 to save the gcd computations.
+fn pollard_rho(n: u64) -> u64 {
+    let one = MontgomeryInt::<u64>::new(1, &n);
+    let mut c = one;
+    loop {
+        let f = |x: MontgomeryInt<u64>| x.square() + c;
+        let (mut x, mut y) = (one.double(), one.double());
+        let mut g = 1;
+        while g == 1 {
+            let (x0, y0) = (x, y);
+            let mut q = one;
+            for _ in 0..64 {
+                x = f(x);
+                y = f(f(y));
+                q = q * (x - y);
+            }
+            g = gcd(q.residue(), n);
+            if g == n {
+                // the batch went past a factor (or the cycle), redo it step by step
+                x = x0;
+                y = y0;
+                loop {
+                    x = f(x);
+                    y = f(f(y));
+                    g = gcd((x - y).residue(), n);
+                    if g != 1 {
+                        break;
+                    }
+                }
+            }
+        }
+        if g != n {
+            return g;
+        }
+        c = c + one;
+    }
+}
+
+/// Factorize n without prime factors below [TRIAL_BOUND] into `factors` in ascending order,
+/// and return the number of the distinct prime factors.
+fn factorize_u64(n: u64, factors: &mut [(u64, u32); 9]) -> usize {
+    // n has at most 9 prime factors counting multiplicity since they are all above 2^7
+    let mut len = 0;
+    let mut stack = [0u64; 9];
+    stack[0] = n;
+    let mut top = 1;
+    while top > 0 {
+        top -= 1;
+        let m = stack[top];
+        if m.is_prime() {
+            match factors[..len].iter_mut().find(|(p, _)| *p == m) {
+                Some((_, e)) => *e += 1,
+                None => {
+                    factors[len] = (m, 1);
+                    len += 1;
+                }
+            }
+        } else {
+            let d = pollard_rho(m);
+            stack[top] = d;
+            stack[top + 1] = m / d;
+            top += 2;
+        }
+    }
+    factors[..len].sort_unstable();
+    len
+}
+
+/// Iterate over the prime power factors `(r, e)` of n in ascending order of r.
+///
+/// The factors below [TRIAL_BOUND] are found by trial division. Once the remaining cofactor
+/// fits in u64, it's split by Pollard's rho algorithm and the deterministic primality test
+/// of [Witness], which is fast for any u64. A larger cofactor is still trial divided, which
+/// is only fast when it has at most one prime factor above 2^40 or so.
+pub(crate) fn prime_powers(mut n: u128) -> impl Iterator<Item = (u128, u32)> {
+    let mut r = 1u128;
+    let mut large = [(0u64, 0u32); 9];
+    let (mut large_len, mut large_pos) = (0, 0);
+    core::iter::from_fn(move || {
+        while n > 1 {
+            if r + 1 >= TRIAL_BOUND && n <= u64::MAX as u128 {
+                large_len = factorize_u64(n as u64, &mut large);
+                n = 1;
+                break;
+            }
+            r += 1;
+            if r > n / r {
+                // n is a prime now
+                let last = n;
+                n = 1;
+                return Some((last, 1));
+            }
+            if n % r == 0 {
+                let mut e = 0;
+                while n % r == 0 {
+                    n /= r;
+                    e += 1;
+                }
+                return Some((r, e));
+            }
+        }
+        if large_pos < large_len {
+            large_pos += 1;
+            let (p, e) = large[large_pos - 1];
+            return Some((p as u128, e));
+        }
+        None
+    })
+}
+
+macro_rules! impl_root_for {
+    ($T:ty, $m:ident) => {
+        mod $m {
+            use super::*;
+
+            /// Calculate x^k with an exponent that doesn't necessarily fit in the type
+            fn pow_u32(x: MontgomeryInt<$T>, k: u32) -> MontgomeryInt<$T> {
+                let mut result = x.convert(1);
+                for i in (0..32 - k.leading_zeros()).rev() {
+                    result = result.square();
+                    if k & (1 << i) != 0 {
+                        result = result * x;
+                    }
+                }
+                result
+            }
+
+            /// Find an r-th power non-residue z (i.e. `z^((p-1)/r) != 1`), where r is a prime
+            /// factor of p - 1
+            fn find_nonresidue(one: MontgomeryInt<$T>, r: $T) -> Option<MontgomeryInt<$T>> {
+                let p = one.modulus();
+                let e = (p - 1) / r;
+                (2..p).map(|z| one.convert(z)).find(|z| z.pow(&e) != one)
+            }
+
+            /// Solve `x^(r^e) = c` for a prime power `r^e` dividing p - 1, given an r-th power
+            /// non-residue rho. If c is a d-th power for some d with `r^e | d`, then the root
+            /// is a `(d / r^e)`-th power.
+            fn root_prime_power(
+                c: MontgomeryInt<$T>,
+                r: $T,
+                e: u32,
+                rho: MontgomeryInt<$T>,
+            ) -> Option<MontgomeryInt<$T>> {
+                let one = c.convert(1);
+
+                // p - 1 = r^s * t, where t is coprime to r
+                let (mut s, mut t) = (0, c.modulus() - 1);
+                while t % r == 0 {
+                    t /= r;
+                    s += 1;
+                }
+                let re = r.pow(e);
+
+                // x0 = c^(r^-e mod t) is a root up to the factor g in the Sylow r-subgroup
+                let w = if t == 1 { 0 } else { (re % t).invm(&t)? };
+                let x0 = c.pow(&w);
+                let g = c * x0.pow(&re).inv()?;
+
+                // find j such that g = zeta^j, digit by digit in base r
+                let zeta = rho.pow(&t);
+                let zeta_inv = zeta.pow(&(r.pow(s) - 1));
+                let gamma = zeta.pow(&r.pow(s - 1));
+                let (mut j, mut rpow) = (0, 1);
+                for i in 0..s {
+                    let h = (g * zeta_inv.pow(&j)).pow(&r.pow(s - 1 - i));
+                    let mut acc = one;
+                    let mut digit = 0;
+                    while acc != h {
+                        acc = acc * gamma;
+                        digit += 1;
+                        if digit == r {
+                            return None; // p is not a prime
+                        }
+                    }
+                    j += digit * rpow;
+                    rpow = rpow.wrapping_mul(r);
+                }
+
+                // g has order dividing r^(s-e) iff c is a r^e-th power
+                if j % re != 0 {
+                    return None;
+                }
+                Some(x0 * zeta.pow(&(j / re)))
+            }
+
+            /// Find a k-th root of a, and a primitive d-th root of unity with d = gcd(k, p-1),
+            /// where 0 < a < p and p is odd
+            fn nth_root_parts(
+                a: $T,
+                k: u32,
+                p: $T,
+            ) -> Option<(MontgomeryInt<$T>, MontgomeryInt<$T>, u32)> {
+                let am = MontgomeryInt::<$T>::new(a, &p);
+                let one = am.convert(1);
+                let n = p - 1;
+                let d = gcd(k, (n as u128 % k as u128) as u32);
+                let nd = n / d as $T;
+                if am.pow(&nd) != one {
+                    return None;
+                }
+
+                // reduce to a d-th root by inverting k / d modulo (p-1) / d
+                let kd = ((k / d) as u128 % nd as u128) as $T;
+                let u = if nd == 1 { 0 } else { kd.invm(&nd)? };
+                let mut x = am.pow(&u);
+                let mut omega = one;
+                for (r, e) in prime_powers(d as u128) {
+                    let rho = find_nonresidue(one, r as $T)?;
+                    x = root_prime_power(x, r as $T, e, rho)?;
+                    omega = omega * rho.pow(&(n / (r as $T).pow(e)));
+                }
+
+                if pow_u32(x, k) != am {
+                    return None; // p is not a prime
+                }
+                Some((x, omega, d))
+            }
+
+            impl ModularRoot<&$T> for $T {
+                type Output = $T;
+
+                fn nth_rootm(self, k: u32, p: &$T) -> Option<$T> {
+                    let p = *p;
+                    assert!(p > 0, "the modulus should not be zero");
+                    assert!(k > 0, "the exponent should not be zero");
+                    let a = self % p;
+                    if a == 0 || p <= 2 {
+                        return Some(a);
+                    }
+                    if p & 1 == 0 {
+                        return None;
+                    }
+                    nth_root_parts(a, k, p).map(|(x, _, _)| x.residue())
+                }
+
+                #[cfg(feature = "alloc")]
+                fn nth_rootm_all(self, k: u32, p: &$T) -> Vec<$T> {
+                    let p = *p;
+                    assert!(p > 0, "the modulus should not be zero");
+                    assert!(k > 0, "the exponent should not be zero");
+                    let a = self % p;
+                    if a == 0 || p <= 2 {
+                        return alloc::vec![a];
+                    }
+                    if p & 1 == 0 {
+                        return Vec::new();
+                    }
+
+                    let (mut x, omega, d) = match nth_root_parts(a, k, p) {
+                        Some(parts) => parts,
+                        None => return Vec::new(),
+                    };
+                    let mut roots = Vec::with_capacity(d as usize);
+                    for _ in 0..d {
+                        // omega is not necessarily a root of unity if p is not a prime
+                        if pow_u32(x, k).residue() == a {
+                            roots.push(x.residue());
+                        }
+                        x = x * omega;
+                    }
+                    roots.sort_unstable();
+                    roots.dedup();
+                    roots
+                }
+            }
+
+            impl ModularRoot<&$T> for &$T {
+                type Output = $T;
+
+                #[inline]
+                fn nth_rootm(self, k: u32, p: &$T) -> Option<$T> {
+                    (*self).nth_rootm(k, p)
+                }
+
+                #[cfg(feature = "alloc")]
+                #[inline]
+                fn nth_rootm_all(self, k: u32, p: &$T) -> Vec<$T> {
+                    (*self).nth_rootm_all(k, p)
+                }
+            }
+        }
+    };
+}
+impl_root_for!(u8, u8_impl);
+impl_root_for!(u16, u16_impl);
+impl_root_for!(u32, u32_impl);
+impl_root_for!(u64, u64_impl);
+impl_root_for!(u128, u128_impl);
+impl_root_for!(usize, usize_impl);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModularPow;
+    use rand::random;
+    use std::vec::Vec;
+
+    #[test]
+    fn prime_powers_test() {
+        let factors: Vec<_> = prime_powers(2 * 2 * 3 * 7 * 7 * 7 * 65537).collect();
+        assert_eq!(factors, [(2, 2), (3, 1), (7, 3), (65537, 1)]);
+        assert_eq!(prime_powers(1).count(), 0);
+        assert_eq!(
+            prime_powers(4294967291).collect::<Vec<_>>(),
+            [(4294967291, 1)]
+        );
+        assert_eq!(prime_powers(1 << 31).collect::<Vec<_>>(), [(2, 31)]);
+
+        // cofactors split by Pollard's rho
+        assert_eq!(
+            prime_powers(4294967291 * 4294967279).collect::<Vec<_>>(),
+            [(4294967279, 1), (4294967291, 1)]
+        );
+        assert_eq!(
+            prime_powers(2 * 3 * 65537 * 65537 * 65537 * 131).collect::<Vec<_>>(),
+            [(2, 1), (3, 1), (131, 1), (65537, 3)]
+        );
+        assert_eq!(
+            prime_powers(18446744073709551557).collect::<Vec<_>>(),
+            [(18446744073709551557, 1)]
+        );
+        for _ in 0..100 {
+            let n = random::<u64>() as u128;
+            let factors: Vec<_> = prime_powers(n).collect();
+            assert_eq!(
+                factors.iter().map(|&(p, e)| p.pow(e)).product::<u128>(),
+                n.max(1)
+            );
+            assert!(factors.iter().all(|&(p, _)| (p as u64).is_prime()));
+            assert!(factors.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+
+    #[test]
+    fn small_primes_test() {
+        for &p in [2u32, 3, 5, 7, 13, 17, 97, 181, 257, 433, 7681].iter() {
+            for k in 1..20u32 {
+                let mut roots = std::vec![Vec::new(); p as usize];
+                for x in 0..p {
+                    roots[x.powm(k, &p) as usize].push(x);
+                }
+                for a in 0..p {
+                    let expected = &roots[a as usize];
+                    #[cfg(feature = "alloc")]
+                    assert_eq!(
+                        nth_rootm_all(a, k, &p),
+                        *expected,
+                        "{}^(1/{}) mod {}",
+                        a,
+                        k,
+                        p
+                    );
+                    match nth_rootm(a, k, &p) {
+                        Some(x) => assert!(expected.contains(&x)),
+                        None => assert!(expected.is_empty()),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn random_test() {
+        macro_rules! tests_for {
+            ($($T:ty: $($p:expr),*;)*) => ($($(
+                let p: $T = $p;
+                for _ in 0..10 {
+                    let x = random::<$T>() % p;
+                    let k = random::<u32>() % 1000 + 1;
+                    let mut a = 1 % p;
+                    for _ in 0..k {
+                        a = a.mulm(x, &p);
+                    }
+                    let r = nth_rootm(a, k, &p).unwrap();
+                    let mut ar = 1 % p;
+                    for _ in 0..k {
+                        ar = ar.mulm(r, &p);
+                    }
+                    assert_eq!(ar, a, "{}^(1/{}) mod {}", a, k, p);
+                    assert_eq!((&a).nth_rootm(k, &p), Some(r));
+                }
+            )*)*);
+        }
+        use crate::ModularCoreOps;
+        tests_for!(
+            u8: 251, 241, 193;
+            u16: 65521, 40961, 12289;
+            u32: 4294967291, 998244353, 3221225473;
+            u64: 18446744073709551557, 0xffffffff00000001, 1000000007;
+            u128: u128::MAX - 158, (1 << 127) - 1;
+            usize: 65537, 257;
+        );
+    }
+
+    #[test]
+    fn structure_test() {
+        // p - 1 = 2^32 * 3 * 5 * 17 * 257 * 65537, with the prime factors of high multiplicity
+        let p = 0xffffffff00000001u64;
+        let g = 7u64; // a primitive root
+        for &k in [2u32, 4, 8, 1 << 20, 3 * 5 * 17, 65537, 1 << 31].iter() {
+            let a = g.powm(k as u64, &p);
+            let x = nth_rootm(a, k, &p).unwrap();
+            assert_eq!(x.powm(k as u64, &p), a);
+            assert_eq!(nth_rootm(g, k, &p), None);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn all_roots_test() {
+        let p = 0xffffffff00000001u64;
+        let roots = nth_rootm_all(1u64, 1 << 10, &p);
+        assert_eq!(roots.len(), 1 << 10);
+        assert!(roots.iter().all(|x| x.powm(1 << 10, &p) == 1));
+        assert!(roots.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(nth_rootm_all(1u64, 7, &p), [1]);
+
+        // consistent with the square roots
+        for _ in 0..10 {
+            let a = random::<u64>() % p;
+            let roots = nth_rootm_all(a, 2, &p);
+            match crate::sqrtm(a, &p) {
+                Some((x, y)) if x == y => assert_eq!(roots, [x]),
+                Some((x, y)) => assert_eq!(roots, [x, y]),
+                None => assert!(roots.is_empty()),
+            }
+        }
+    }
+
+    #[test]
+    fn composite_test() {
+        // any returned roots are verified
+        for &m in [9u32, 15, 21, 25, 45, 561, 1105].iter() {
+            for a in 0..m {
+                for k in 1..6 {
+                    if let Some(x) = nth_rootm(a, k, &m) {
+                        assert_eq!(x.powm(k, &m), a);
+                    }
+                    #[cfg(feature = "alloc")]
+                    for x in nth_rootm_all(a, k, &m) {
+                        assert_eq!(x.powm(k, &m), a);
+                    }
+                }
+            }
+        }
+        assert_eq!(nth_rootm(7u64, 5, &1), Some(0));
+        assert_eq!(nth_rootm(3u64, 3, &8), None);
+    }
+}
@@ -0,0 +1,690 @@
+//! Modular arithmetic kernels operating on slices of integers, and the lazily
+//! reduced accumulation used by them
+
+use crate::{ModularCoreOps, Montgomery, Reducer, Vanilla};
+#[cfg(feature = "alloc")]
+use {crate::prim::gcd, crate::ModularUnaryOps, alloc::vec::Vec};
+
+/// Integer types supported by [fmam_slice()]
+pub trait FmamSlice: Sized {
+    /// Calculate `dst[i] = (dst[i] + c * src[i]) % m` for all i, see [fmam_slice()] for details
+    fn fmam_slice(dst: &mut [Self], src: &[Self], c: Self, m: &Self);
+}
+
+/// Fused scalar-vector multiply-add modulo m, i.e. `dst[i] = (dst[i] + c * src[i]) % m` for all i.
+///
+/// This is the row operation `row_i += c * row_j` in modular linear algebra. For odd moduli,
+/// `c` is converted into the Montgomery form once, after which a Montgomery multiplication
+/// of `src[i]` by it directly yields `c * src[i] % m` in the normal form. So only one
+/// reduction is performed per element. Even moduli fall back to [ModularCoreOps].
+///
+/// The elements of `dst`, `src` and `c` are not required to be reduced, while the elements of
+/// `dst` will be in range `[0, m)` after the operation. The two slices cannot alias each other,
+/// which is guaranteed by the borrow rules.
+///
+/// # Panics
+/// if the lengths of the two slices are not equal, or if m is zero.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::fmam_slice;
+///
+/// let mut row = [1u32, 2, 3];
+/// fmam_slice(&mut row, &[4, 5, 6], 2, &7);
+/// assert_eq!(row, [2, 5, 1]);
+/// ```
+#[inline]
+pub fn fmam_slice<T: FmamSlice>(dst: &mut [T], src: &[T], c: T, m: &T) {
+    T::fmam_slice(dst, src, c, m)
+}
+
+macro_rules! impl_fmam_slice_for {
+    ($($T:ty)*) => {$(
+        impl FmamSlice for $T {
+            fn fmam_slice(dst: &mut [$T], src: &[$T], c: $T, m: &$T) {
+                assert_eq!(
+                    dst.len(),
+                    src.len(),
+                    "the lengths of the slices should be equal"
+                );
+                let m = *m;
+                assert!(m > 0, "the modulus should not be zero");
+
+                if m & 1 == 0 {
+                    for (d, &s) in dst.iter_mut().zip(src) {
+                        *d = (*d % m).addm(s.mulm(c, &m), &m);
+                    }
+                    return;
+                }
+
+                // REDC(s * cR) = s * c mod m, which is valid for any s < R since cR mod m < m
+                let r = Montgomery::<$T>::new(m);
+                let cm = r.transform(c % m);
+                for (d, s) in dst.iter_mut().zip(src) {
+                    let t = r.mul(s, &cm);
+                    let v = if *d >= m { *d % m } else { *d };
+                    *d = Vanilla::<$T>::add(&m, v, t);
+                }
+            }
+        }
+    )*};
+}
+impl_fmam_slice_for!(u8 u16 u32 u64 u128 usize);
+
+/// Integer types supported by [invm_batch_or_factor()]
+#[cfg(feature = "alloc")]
+pub trait InvmBatch: Sized {
+    /// Invert all values modulo n or find a factor of n, see [invm_batch_or_factor()] for details
+    fn invm_batch_or_factor(values: &[Self], n: &Self) -> Result<Vec<Self>, Self>;
+}
+
+/// Invert all the values modulo n with Montgomery's batch inversion, or return a factor
+/// of n shared by one of the values.
+///
+/// Only one modular inversion is performed, with `3(k-1)` modular multiplications for k
+/// values. If the product of the values is not invertible, the prefix products are searched
+/// by bisection to locate the first value `v` with `gcd(v, n) > 1`, and this gcd is returned
+/// as the error. This is the success condition in factoring algorithms like ECM or Pollard's
+/// p-1, where many values are inverted modulo the composite n.
+///
+/// If the first non-invertible value is a multiple of n, the remaining values are scanned
+/// for a nontrivial factor, and n itself is returned only if no value gives one.
+///
+/// # Panics
+/// if n is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::invm_batch_or_factor;
+///
+/// let n = 91u32; // 7 * 13
+/// assert_eq!(invm_batch_or_factor(&[2, 3, 5], &n), Ok(vec![46, 61, 73]));
+/// assert_eq!(invm_batch_or_factor(&[2, 26, 14], &n), Err(13));
+/// ```
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn invm_batch_or_factor<T: InvmBatch>(values: &[T], n: &T) -> Result<Vec<T>, T> {
+    T::invm_batch_or_factor(values, n)
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_invm_batch_for {
+    ($($T:ty)*) => {$(
+        impl InvmBatch for $T {
+            fn invm_batch_or_factor(values: &[$T], n: &$T) -> Result<Vec<$T>, $T> {
+                let n = *n;
+                assert!(n > 0, "the modulus should not be zero");
+                if n == 1 {
+                    return Ok(alloc::vec![0; values.len()]);
+                }
+                // prefix[i] = values[0] * ... * values[i] mod n
+                let mut prefix = Vec::with_capacity(values.len());
+                let mut acc = 1;
+                for &v in values {
+                    acc = acc.mulm(v, &n);
+                    prefix.push(acc);
+                }
+
+                let mut inv = match acc.invm(&n) {
+                    Some(inv) => inv,
+                    None => {
+                        // once a prefix product shares a factor with n, so do all the later ones
+                        let i = prefix.partition_point(|&p| gcd(p, n) == 1);
+                        let g = gcd(values[i] % n, n);
+                        if g != n {
+                            return Err(g);
+                        }
+                        let g = values[i + 1..]
+                            .iter()
+                            .map(|&v| gcd(v % n, n))
+                            .find(|&g| g != 1 && g != n)
+                            .unwrap_or(n);
+                        return Err(g);
+                    }
+                };
+
+                // inv = (values[0] * ... * values[i])^-1 at the start of each step
+                let mut result = alloc::vec![0; values.len()];
+                for i in (1..values.len()).rev() {
+                    result[i] = inv.mulm(prefix[i - 1], &n);
+                    inv = inv.mulm(values[i], &n);
+                }
+                if let Some(r) = result.first_mut() {
+                    *r = inv;
+                }
+                Ok(result)
+            }
+        }
+    )*};
+}
+#[cfg(feature = "alloc")]
+impl_invm_batch_for!(u8 u16 u32 u64 u128 usize);
+
+/// Accumulator of sums and sums of products, with the modular reduction delayed to the end.
+///
+/// The accumulator holds three words, so for a word type with B bits, at least `2^B`
+/// products (or words) can be accumulated before the capacity is exhausted, which is
+/// 2^32 for [u32] and 2^64 for [u64]. Exceeding the capacity will panic.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::Accumulator;
+///
+/// let mut acc = Accumulator::<u64>::new();
+/// acc.add_prod(u64::MAX, u64::MAX);
+/// acc.add_prod(u64::MAX, u64::MAX);
+/// acc.add(5);
+/// assert_eq!(acc.reduce(&1_000_000_007), 229888543);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Accumulator<T> {
+    lo: T,
+    mid: T,
+    hi: T,
+}
+
+/// Integer types supported by [dotm()]
+pub trait Dotm: Sized {
+    /// Calculate the dot product of a and b modulo m, see [dotm()] for details
+    fn dotm(a: &[Self], b: &[Self], m: &Self) -> Self;
+}
+
+/// Calculate the dot product `sum(a[i] * b[i]) % m` with the reduction delayed by [Accumulator].
+///
+/// # Panics
+/// if the lengths of the two slices are not equal, or if m is zero.
+#[inline]
+pub fn dotm<T: Dotm>(a: &[T], b: &[T], m: &T) -> T {
+    T::dotm(a, b, m)
+}
+
+macro_rules! impl_accumulator_for {
+    ($($T:ty => $D:ty;)*) => {$(
+        impl Accumulator<$T> {
+            /// Create an empty accumulator
+            #[inline]
+            pub const fn new() -> Self {
+                Self { lo: 0, mid: 0, hi: 0 }
+            }
+
+            #[inline(always)]
+            fn add_double(&mut self, x: $D) {
+                let low = (self.lo as $D) | ((self.mid as $D) << <$T>::BITS);
+                let (sum, carry) = low.overflowing_add(x);
+                self.lo = sum as $T;
+                self.mid = (sum >> <$T>::BITS) as $T;
+                if carry {
+                    self.hi = self.hi.checked_add(1).expect("the accumulator overflows");
+                }
+            }
+
+            /// Accumulate the product a * b
+            #[inline]
+            pub fn add_prod(&mut self, a: $T, b: $T) {
+                self.add_double(a as $D * b as $D)
+            }
+
+            /// Accumulate the integer x
+            #[inline]
+            pub fn add(&mut self, x: $T) {
+                self.add_double(x as $D)
+            }
+
+            /// Get the residue of the accumulated sum modulo m
+            ///
+            /// # Panics
+            /// if m is zero
+            pub fn reduce(&self, m: &$T) -> $T {
+                let m = *m as $D;
+                let mut acc: $D = 0;
+                for &w in [self.hi, self.mid, self.lo].iter() {
+                    acc = ((acc << <$T>::BITS) | w as $D) % m;
+                }
+                acc as $T
+            }
+        }
+
+        impl Dotm for $T {
+            fn dotm(a: &[$T], b: &[$T], m: &$T) -> $T {
+                assert_eq!(
+                    a.len(),
+                    b.len(),
+                    "the lengths of the slices should be equal"
+                );
+
+                // reserve one slot of the capacity to carry over the reduced value
+                let capacity = if <$T>::BITS >= usize::BITS {
+                    usize::MAX
+                } else {
+                    (1 << <$T>::BITS) - 1
+                };
+                let mut acc = Accumulator::<$T>::new();
+                for (ca, cb) in a.chunks(capacity).zip(b.chunks(capacity)) {
+                    let mut next = Accumulator::<$T>::new();
+                    next.add(acc.reduce(m));
+                    acc = next;
+                    for (&x, &y) in ca.iter().zip(cb) {
+                        acc.add_prod(x, y);
+                    }
+                }
+                acc.reduce(m)
+            }
+        }
+    )*};
+}
+impl_accumulator_for! { u8 => u16; u16 => u32; u32 => u64; u64 => u128; }
+
+/// Integer types supported by [summ_iter()] and [prodm_iter()]
+pub trait FoldIter: Sized {
+    /// Calculate the sum of the integers modulo m, see [summ_iter()] for details
+    fn summ_iter<I: IntoIterator<Item = Self>>(iter: I, m: &Self) -> Self;
+
+    /// Calculate the product of the integers modulo m, see [prodm_iter()] for details
+    fn prodm_iter<I: IntoIterator<Item = Self>>(iter: I, m: &Self) -> Self;
+}
+
+/// Calculate the sum of all integers yielded by an iterator modulo m, without collecting them.
+///
+/// For integers up to 64 bits, the integers are summed in an [Accumulator], so the
+/// reduction is only performed once per `2^B - 1` integers. The integers are not required
+/// to be reduced. The sum of an empty iterator is zero.
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::summ_iter;
+///
+/// let squares = (1u64..=100).map(|i| i * i);
+/// assert_eq!(summ_iter(squares, &1000), 338350 % 1000);
+/// ```
+#[inline]
+pub fn summ_iter<T: FoldIter, I: IntoIterator<Item = T>>(iter: I, m: &T) -> T {
+    T::summ_iter(iter, m)
+}
+
+/// Calculate the product of all integers yielded by an iterator modulo m, without collecting them.
+///
+/// For odd moduli, the product is kept in a scaled Montgomery form, so that each integer
+/// is multiplied with a single Montgomery reduction without being converted first, and the
+/// scaling is removed once per `2^B - 1` integers. Even moduli fall back to [ModularCoreOps].
+/// The integers are not required to be reduced. The product of an empty iterator is `1 % m`.
+///
+/// # Panics
+/// if m is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::prodm_iter;
+///
+/// // 20! mod 1000003
+/// assert_eq!(prodm_iter(1u64..=20, &1000003), 2432902008176640000 % 1000003);
+/// ```
+#[inline]
+pub fn prodm_iter<T: FoldIter, I: IntoIterator<Item = T>>(iter: I, m: &T) -> T {
+    T::prodm_iter(iter, m)
+}
+
+macro_rules! impl_prodm_iter {
+    ($T:ty) => {
+        fn prodm_iter<I: IntoIterator<Item = $T>>(iter: I, m: &$T) -> $T {
+            let m = *m;
+            assert!(m > 0, "the modulus should not be zero");
+            if m & 1 == 0 {
+                return iter.into_iter().fold(1 % m, |acc, x| acc.mulm(x, &m));
+            }
+
+            // after k integers in a chunk, v = product * R^-k, since REDC(v * x) = v * x / R
+            // for any v < m and x < R. The scaling is removed by REDC(v * (R^2)^k) with
+            // R^2 being the Montgomery form of R.
+            let r = Montgomery::<$T>::new(m);
+            let r2 = r.transform(r.transform(1));
+            let (mut v, mut k): ($T, $T) = (1 % m, 0);
+            for x in iter {
+                v = r.mul(&v, &x);
+                k += 1;
+                if k == <$T>::MAX {
+                    v = r.mul(&v, &r.pow(r2, &k));
+                    k = 0;
+                }
+            }
+            r.mul(&v, &r.pow(r2, &k))
+        }
+    };
+}
+
+macro_rules! impl_fold_iter_by_accumulator {
+    ($($T:ty)*) => {$(
+        impl FoldIter for $T {
+            fn summ_iter<I: IntoIterator<Item = $T>>(iter: I, m: &$T) -> $T {
+                assert!(*m > 0, "the modulus should not be zero");
+
+                // reserve one slot of the capacity to carry over the reduced value, as in dotm
+                let capacity = if <$T>::BITS >= usize::BITS {
+                    usize::MAX
+                } else {
+                    (1 << <$T>::BITS) - 1
+                };
+                let mut acc = Accumulator::<$T>::new();
+                let mut count = 0;
+                for x in iter {
+                    if count == capacity {
+                        let mut next = Accumulator::<$T>::new();
+                        next.add(acc.reduce(m));
+                        acc = next;
+                        count = 0;
+                    }
+                    acc.add(x);
+                    count += 1;
+                }
+                acc.reduce(m)
+            }
+
+            impl_prodm_iter!($T);
+        }
+    )*};
+}
+impl_fold_iter_by_accumulator!(u8 u16 u32 u64);
+
+macro_rules! impl_fold_iter_by_addm {
+    ($($T:ty)*) => {$(
+        impl FoldIter for $T {
+            fn summ_iter<I: IntoIterator<Item = $T>>(iter: I, m: &$T) -> $T {
+                let m = *m;
+                assert!(m > 0, "the modulus should not be zero");
+                iter.into_iter().fold(0, |acc, x| acc.addm(x, &m))
+            }
+
+            impl_prodm_iter!($T);
+        }
+    )*};
+}
+impl_fold_iter_by_addm!(u128 usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModularPow, ModularUnaryOps};
+    use rand::random;
+    use std::vec;
+    use std::vec::Vec;
+
+    const NRANDOM: u32 = 10;
+
+    #[test]
+    fn fmam_slice_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..NRANDOM {
+                    let len = random::<usize>() % 40;
+                    let src: Vec<$T> = (0..len).map(|_| random()).collect();
+                    let dst: Vec<$T> = (0..len).map(|_| random()).collect();
+                    let c = random::<$T>();
+                    let ms = [
+                        random::<$T>() | 1,
+                        (random::<$T>() & !1).max(2),
+                        <$T>::MAX,
+                        <$T>::MAX - 1,
+                        1,
+                        3,
+                    ];
+                    for &m in ms.iter() {
+                        let expected: Vec<$T> = dst
+                            .iter()
+                            .zip(src.iter())
+                            .map(|(&d, &s)| d.addm(s.mulm(c, &m), &m))
+                            .collect();
+                        let mut actual = dst.clone();
+                        fmam_slice(&mut actual, &src, c, &m);
+                        assert_eq!(actual, expected, "c = {}, m = {}", c, m);
+                    }
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+    }
+
+    #[test]
+    #[should_panic]
+    fn fmam_slice_length_mismatch_test() {
+        let mut dst = [1u64, 2, 3];
+        fmam_slice(&mut dst, &[1, 2], 3, &5);
+    }
+
+    #[test]
+    fn accumulator_test() {
+        let m = random::<u64>().max(1);
+        let mut acc = Accumulator::<u64>::new();
+        let mut expected = 0u64;
+        for _ in 0..100 {
+            let (a, b, c) = (random::<u64>(), random::<u64>(), random::<u64>());
+            acc.add_prod(a, b);
+            acc.add(c);
+            expected = expected.addm(a.mulm(b, &m), &m).addm(c, &m);
+        }
+        assert_eq!(acc.reduce(&m), expected);
+        assert_eq!(Accumulator::<u32>::default().reduce(&7), 0);
+    }
+
+    #[test]
+    fn accumulator_capacity_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                // 2^B products of the largest operands are safe
+                let mut acc = Accumulator::<$T>::new();
+                let m = random::<$T>().max(1);
+                let p = <$T>::MAX.mulm(<$T>::MAX, &m);
+                for _ in 0..(1u32 << <$T>::BITS) {
+                    acc.add_prod(<$T>::MAX, <$T>::MAX);
+                }
+                let count = ((1u64 << <$T>::BITS) % m as u64) as $T;
+                assert_eq!(acc.reduce(&m), p.mulm(count, &m));
+            )*);
+        }
+        tests_for!(u8 u16);
+    }
+
+    #[test]
+    #[should_panic]
+    fn accumulator_overflow_test() {
+        // floor((2^24 - 1) / 255^2) = 258 is the exact capacity for u8
+        let mut acc = Accumulator::<u8>::new();
+        for _ in 0..258 {
+            acc.add_prod(u8::MAX, u8::MAX);
+        }
+        acc.add(u8::MAX); // still fits
+        acc.add_prod(u8::MAX, u8::MAX);
+    }
+
+    #[test]
+    fn dotm_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for &len in [0usize, 1, 100, 255, 256, 257, 1000, 70000].iter() {
+                    let a: Vec<$T> = (0..len).map(|_| random()).collect();
+                    let b: Vec<$T> = (0..len).map(|_| random()).collect();
+                    for &m in [random::<$T>().max(1), <$T>::MAX, 1].iter() {
+                        let expected = a
+                            .iter()
+                            .zip(b.iter())
+                            .fold(0, |acc: $T, (&x, &y)| acc.addm(x.mulm(y, &m), &m));
+                        assert_eq!(dotm(&a, &b, &m), expected, "len = {}, m = {}", len, m);
+                    }
+                }
+
+                // the largest operands
+                let a = vec![<$T>::MAX; 1000];
+                let m = random::<$T>().max(1);
+                let expected = <$T>::MAX.mulm(<$T>::MAX, &m).mulm((1000 % m as u64) as $T, &m);
+                assert_eq!(dotm(&a, &a, &m), expected);
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn invm_batch_or_factor_test() {
+        assert_eq!(invm_batch_or_factor(&[], &7u32), Ok(vec![]));
+        assert_eq!(invm_batch_or_factor(&[3u32, 0, 5], &1), Ok(vec![0, 0, 0]));
+
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for _ in 0..NRANDOM {
+                    // a batch of units
+                    let n = random::<$T>() | 1;
+                    let values: Vec<$T> = (0..random::<usize>() % 40)
+                        .map(|_| random::<$T>())
+                        .filter(|v| v.invm(&n).is_some())
+                        .collect();
+                    let expected: Vec<$T> = values.iter().map(|v| v.invm(&n).unwrap()).collect();
+                    assert_eq!(invm_batch_or_factor(&values, &n), Ok(expected));
+                }
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+
+        // n = p * q with a value sharing a known factor inserted at random positions
+        let (p, q) = (1000000007u64, 998244353u64);
+        let n = p * q;
+        for _ in 0..NRANDOM {
+            let mut values: Vec<u64> = (0..100).map(|_| (random::<u64>() % n) | 1).collect();
+            let i = random::<usize>() % values.len();
+            values[i] = p * (random::<u64>() % (q - 1) + 1);
+            assert_eq!(invm_batch_or_factor(&values, &n), Err(p));
+
+            // with multiple non-invertible values, the first one is found
+            let j = random::<usize>() % values.len();
+            if j != i {
+                values[j] = q * (random::<u64>() % (p - 1) + 1);
+                let expected = if i < j { p } else { q };
+                assert_eq!(invm_batch_or_factor(&values, &n), Err(expected));
+            }
+
+            // multiples of n are skipped for a nontrivial factor
+            values.insert(0, 0);
+            values.insert(1, n);
+            let g = invm_batch_or_factor(&values, &n).unwrap_err();
+            assert!(g == p || g == q);
+
+            // only trivial factors
+            let mut values: Vec<u64> = (0..10).map(|_| (random::<u64>() % n) | 1).collect();
+            values[random::<usize>() % 10] = 0;
+            assert_eq!(invm_batch_or_factor(&values, &n), Err(n));
+        }
+    }
+
+    #[test]
+    fn fold_iter_test() {
+        macro_rules! tests_for {
+            ($($T:ty)*) => ($(
+                for &len in [0usize, 1, 2, 255, 256, 1000].iter() {
+                    let values: Vec<$T> = (0..len).map(|_| random()).collect();
+                    let ms = [random::<$T>() | 1, (random::<$T>() & !1).max(2), <$T>::MAX, 1, 2, 3];
+                    for &m in ms.iter() {
+                        let sum = values.iter().fold(0, |acc: $T, &x| acc.addm(x, &m));
+                        let prod = values.iter().fold(1 % m, |acc: $T, &x| acc.mulm(x, &m));
+                        assert_eq!(summ_iter(values.iter().copied(), &m), sum, "m = {}", m);
+                        assert_eq!(prodm_iter(values.iter().copied(), &m), prod, "m = {}", m);
+
+                        // filtered iterators
+                        let odd: Vec<$T> = values.iter().copied().filter(|x| x & 1 == 1).collect();
+                        let sum = odd.iter().fold(0, |acc: $T, &x| acc.addm(x, &m));
+                        let prod = odd.iter().fold(1 % m, |acc: $T, &x| acc.mulm(x, &m));
+                        assert_eq!(summ_iter(values.iter().copied().filter(|x| x & 1 == 1), &m), sum);
+                        assert_eq!(prodm_iter(values.iter().copied().filter(|x| x & 1 == 1), &m), prod);
+                    }
+                }
+
+                // ranges and the trivial modulus
+                let m = <$T>::MAX / 3 | 1;
+                let sum = (1..=100 as $T).fold(0, |acc: $T, x| acc.addm(x, &m));
+                let prod = (1..=100 as $T).fold(1, |acc: $T, x| acc.mulm(x, &m));
+                assert_eq!(summ_iter(1..=100 as $T, &m), sum);
+                assert_eq!(prodm_iter(1..=100 as $T, &m), prod);
+                assert_eq!(summ_iter(1..=100 as $T, &1), 0);
+                assert_eq!(prodm_iter(1..=100 as $T, &1), 0);
+                assert_eq!(summ_iter(core::iter::empty::<$T>(), &1), 0);
+                assert_eq!(prodm_iter(core::iter::empty::<$T>(), &1), 0);
+                assert_eq!(summ_iter(core::iter::empty::<$T>(), &m), 0);
+                assert_eq!(prodm_iter(core::iter::empty::<$T>(), &m), 1);
+            )*);
+        }
+        tests_for!(u8 u16 u32 u64 u128 usize);
+
+        // long iterators of integers larger than m, exceeding the chunk sizes
+        let m = 251u8;
+        let sum = (0..100000u32).fold(0u32, |acc, _| (acc + 255) % 251) as u8;
+        assert_eq!(summ_iter((0..100000).map(|_| u8::MAX), &m), sum);
+        let prod = (0..100000).fold(1u8, |acc, _| acc.mulm(u8::MAX, &m));
+        assert_eq!(prodm_iter((0..100000).map(|_| u8::MAX), &m), prod);
+        assert_eq!(prod, 4u8.powm((100000u32 % 250) as u8, &m));
+    }
+
+    // invert a square matrix modulo a prime by Gauss-Jordan elimination
+    fn gauss_inverse(mat: &[Vec<u64>], p: u64) -> Option<Vec<Vec<u64>>> {
+        let n = mat.len();
+        let mut aug: Vec<Vec<u64>> = mat
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let mut r = row.clone();
+                r.extend((0..n).map(|j| (i == j) as u64));
+                r
+            })
+            .collect();
+
+        for col in 0..n {
+            let pivot = (col..n).find(|&r| aug[r][col] % p != 0)?;
+            aug.swap(col, pivot);
+            let inv = aug[col][col].invm(&p)?;
+            let scaled: Vec<u64> = aug[col].iter().map(|&v| v.mulm(inv, &p)).collect();
+            aug[col] = scaled;
+            for r in 0..n {
+                if r != col && aug[r][col] % p != 0 {
+                    let c = aug[r][col].negm(&p);
+                    let pivot_row = aug[col].clone();
+                    fmam_slice(&mut aug[r], &pivot_row, c, &p);
+                }
+            }
+        }
+        Some(aug.into_iter().map(|r| r[n..].to_vec()).collect())
+    }
+
+    #[test]
+    fn gauss_inverse_test() {
+        // [[2, 1], [1, 1]]^-1 = [[1, -1], [-1, 2]]
+        let p = 1_000_000_007u64;
+        let inv = gauss_inverse(&[vec![2, 1], vec![1, 1]], p).unwrap();
+        assert_eq!(inv, [vec![1, p - 1], vec![p - 1, 2]]);
+
+        // [[1, 2, 3], [0, 1, 4], [5, 6, 0]]^-1 = [[-24, 18, 5], [20, -15, -4], [-5, 4, 1]]
+        let p = 17u64;
+        let mat = [vec![1, 2, 3], vec![0, 1, 4], vec![5, 6, 0]];
+        let expected = [[-24i64, 18, 5], [20, -15, -4], [-5, 4, 1]]
+            .iter()
+            .map(|row| row.iter().map(|&v| v.rem_euclid(17) as u64).collect())
+            .collect::<Vec<Vec<u64>>>();
+        assert_eq!(gauss_inverse(&mat, p).unwrap(), expected);
+
+        // singular matrix
+        assert!(gauss_inverse(&[vec![1, 2], vec![2, 4]], 101).is_none());
+
+        // random matrix times its inverse is identity
+        let p = 0xffffffffffffffc5u64; // 2^64 - 59
+        let n = 6;
+        let mat: Vec<Vec<u64>> = (0..n).map(|_| (0..n).map(|_| random()).collect()).collect();
+        if let Some(inv) = gauss_inverse(&mat, p) {
+            for (i, row) in mat.iter().enumerate() {
+                for j in 0..n {
+                    let v = row
+                        .iter()
+                        .zip(inv.iter())
+                        .fold(0u64, |acc, (&a, b)| acc.addm(a.mulm(b[j], &p), &p));
+                    assert_eq!(v, (i == j) as u64);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,372 @@
+//! Modular reduction specialized for primes of special forms
+
+use crate::reduced::impl_reduced_binary_pow;
+use crate::{ModularUnaryOps, Reducer, Vanilla};
+use core::marker::PhantomData;
+
+/// A prime modulus with a fast reduction of double-width integers, which is usually
+/// available for generalized Mersenne (Solinas) primes such as `2^64 - 2^32 + 1`,
+/// where the powers of two beyond the word size fold back into a few shifts and additions.
+///
+/// Implement this trait on a marker type to use a custom prime with [Solinas] and
+/// [SolinasInt][crate::SolinasInt]. Currently the words [u64] and [u128] are supported.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{ModularInteger, SolinasInt, SpecialReduce};
+///
+/// // the prime 2^31 - 1, reduced within u64 words
+/// struct M31;
+/// impl SpecialReduce for M31 {
+///     type Word = u64;
+///     const MODULUS: u64 = (1 << 31) - 1;
+///     fn reduce_double(hi: u64, lo: u64) -> u64 {
+///         let v = (hi as u128) << 64 | lo as u128;
+///         (v % Self::MODULUS as u128) as u64 // a slow reference implementation
+///     }
+/// }
+///
+/// let x = SolinasInt::<M31>::new(1 << 40, &M31::MODULUS);
+/// assert_eq!((x * x).residue(), ((1u128 << 80) % ((1 << 31) - 1)) as u64);
+/// ```
+pub trait SpecialReduce {
+    /// The word type of the modulus
+    type Word;
+
+    /// The prime modulus
+    const MODULUS: Self::Word;
+
+    /// Calculate `(hi * 2^B + lo) mod MODULUS` where B is the bit size of the word. The result
+    /// must be fully reduced for any `hi < MODULUS`.
+    fn reduce_double(hi: Self::Word, lo: Self::Word) -> Self::Word;
+}
+
+/// The Mersenne prime `2^61 - 1`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mersenne61;
+
+impl SpecialReduce for Mersenne61 {
+    type Word = u64;
+    const MODULUS: u64 = (1 << 61) - 1;
+
+    #[inline]
+    fn reduce_double(hi: u64, lo: u64) -> u64 {
+        // 2^61 = 1 mod p, and v < 2^125 since hi < p
+        let v = (hi as u128) << 64 | lo as u128;
+        let t = (v & Self::MODULUS as u128) + (v >> 61); // < 2^65
+        let t = (t as u64 & Self::MODULUS) + (t >> 61) as u64;
+        if t >= Self::MODULUS {
+            t - Self::MODULUS
+        } else {
+            t
+        }
+    }
+}
+
+/// The Mersenne prime `2^89 - 1`, reduced with [u128] words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mersenne89;
+
+impl SpecialReduce for Mersenne89 {
+    type Word = u128;
+    const MODULUS: u128 = (1 << 89) - 1;
+
+    #[inline]
+    fn reduce_double(hi: u128, lo: u128) -> u128 {
+        // 2^89 = 1 and 2^128 = 2^39 mod p, and hi * 2^39 < 2^128 since hi < p
+        let m = Self::MODULUS;
+        let hi = hi << 39;
+        let t = (lo & m) + (lo >> 89) + (hi & m) + (hi >> 89); // < 2^91
+        let t = (t & m) + (t >> 89);
+        if t >= m {
+            t - m
+        } else {
+            t
+        }
+    }
+}
+
+/// The prime `2^64 - 2^32 + 1` (also known as the Goldilocks prime)
+///
+/// The products are reduced with a few additions and subtractions using
+/// `2^64 = 2^32 - 1 (mod p)`. The multiplicative group has order `2^32 * (2^32 - 1)`,
+/// so the field contains the `2^32`-th roots of unity as powers of [Self::GENERATOR].
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{Goldilocks, GoldilocksInt, ModularInteger, SpecialReduce};
+///
+/// let g = GoldilocksInt::new(Goldilocks::GENERATOR, &Goldilocks::MODULUS);
+/// let w = g.pow(&((Goldilocks::MODULUS - 1) >> Goldilocks::TWO_ADICITY));
+/// assert_eq!(w.pow(&(1 << 31)).residue(), Goldilocks::MODULUS - 1); // a primitive 2^32-th root
+/// assert_eq!((w * w.inv().unwrap()).residue(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Goldilocks;
+
+impl Goldilocks {
+    /// A generator of the multiplicative group
+    pub const GENERATOR: u64 = 7;
+
+    /// The 2-adic valuation of `p - 1`
+    pub const TWO_ADICITY: u32 = 32;
+}
+
+impl SpecialReduce for Goldilocks {
+    type Word = u64;
+    const MODULUS: u64 = 0xFFFF_FFFF_0000_0001;
+
+    #[inline]
+    fn reduce_double(hi: u64, lo: u64) -> u64 {
+        // 2^64 = 2^32 - 1 and 2^96 = -1 mod p, the result holds for any hi
+        const EPSILON: u64 = (1 << 32) - 1;
+        let (hi_hi, hi_lo) = (hi >> 32, hi & EPSILON);
+
+        let (mut t0, borrow) = lo.overflowing_sub(hi_hi);
+        if borrow {
+            t0 = t0.wrapping_sub(EPSILON); // no underflow since t0 >= 2^64 - 2^32
+        }
+        let t1 = hi_lo * EPSILON;
+        let (mut t, carry) = t0.overflowing_add(t1);
+        if carry {
+            t = t.wrapping_add(EPSILON); // no overflow since t < t1 <= 2^64 - 2^33 + 1
+        }
+        if t >= Self::MODULUS {
+            t - Self::MODULUS
+        } else {
+            t
+        }
+    }
+}
+
+/// A modular reducer for a prime modulus implementing [SpecialReduce], the multiplications
+/// are reduced by [SpecialReduce::reduce_double()] without the REDC or division steps.
+/// The integers are stored in the normal form.
+pub struct Solinas<P>(PhantomData<P>);
+
+// implemented manually to avoid the bounds on the marker type
+impl<P> Clone for Solinas<P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<P> Copy for Solinas<P> {}
+impl<P> core::fmt::Debug for Solinas<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Solinas")
+    }
+}
+
+macro_rules! impl_solinas_reducer_for {
+    ($t:ident, $ns:ident) => {
+        mod $ns {
+            use super::*;
+            use crate::word::$t::*;
+
+            impl<P: SpecialReduce<Word = $t>> Reducer<$t> for Solinas<P> {
+                #[inline]
+                fn new(m: &$t) -> Self {
+                    assert!(
+                        *m == P::MODULUS,
+                        "the given modulus doesn't match with the generic params"
+                    );
+                    Self(PhantomData)
+                }
+                #[inline]
+                fn transform(&self, target: $t) -> $t {
+                    P::reduce_double(0, target)
+                }
+                #[inline]
+                fn check(&self, target: &$t) -> bool {
+                    *target < P::MODULUS
+                }
+                #[inline]
+                fn residue(&self, target: $t) -> $t {
+                    target
+                }
+                #[inline]
+                fn modulus(&self) -> $t {
+                    P::MODULUS
+                }
+                #[inline]
+                fn is_zero(&self, target: &$t) -> bool {
+                    *target == 0
+                }
+
+                #[inline]
+                fn add(&self, lhs: &$t, rhs: &$t) -> $t {
+                    Vanilla::<$t>::add(&P::MODULUS, *lhs, *rhs)
+                }
+                #[inline]
+                fn dbl(&self, target: $t) -> $t {
+                    Vanilla::<$t>::dbl(&P::MODULUS, target)
+                }
+                #[inline]
+                fn sub(&self, lhs: &$t, rhs: &$t) -> $t {
+                    Vanilla::<$t>::sub(&P::MODULUS, *lhs, *rhs)
+                }
+                #[inline]
+                fn neg(&self, target: $t) -> $t {
+                    Vanilla::<$t>::neg(&P::MODULUS, target)
+                }
+
+                #[inline]
+                fn mul(&self, lhs: &$t, rhs: &$t) -> $t {
+                    let (lo, hi) = split(wmul(*lhs, *rhs));
+                    P::reduce_double(hi, lo)
+                }
+                #[inline]
+                fn sqr(&self, target: $t) -> $t {
+                    let (lo, hi) = split(wsqr(target));
+                    P::reduce_double(hi, lo)
+                }
+                #[inline]
+                fn inv(&self, target: $t) -> Option<$t> {
+                    target.invm(&P::MODULUS)
+                }
+
+                impl_reduced_binary_pow!($t);
+            }
+        }
+    };
+}
+impl_solinas_reducer_for!(u64, u64_impl);
+impl_solinas_reducer_for!(u128, u128_impl);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reduced::tests::ReducedTester;
+    use crate::{udouble, ModularCoreOps, ModularPow, SolinasInt};
+    use rand::random;
+
+    const NRANDOM: u32 = 10;
+
+    fn reduce_u64<P: SpecialReduce<Word = u64>>(hi: u64, lo: u64) -> u64 {
+        let v = (hi as u128) << 64 | lo as u128;
+        (v % P::MODULUS as u128) as u64
+    }
+
+    fn reduce_u128<P: SpecialReduce<Word = u128>>(hi: u128, lo: u128) -> u128 {
+        udouble { hi, lo } % P::MODULUS
+    }
+
+    #[test]
+    fn reduce_double_test() {
+        let m61 = Mersenne61::MODULUS;
+        let gl = Goldilocks::MODULUS;
+        let m89 = Mersenne89::MODULUS;
+        let edges64 = |m: u64| [0, 1, m - 1, m, m + 1, u64::MAX];
+
+        for _ in 0..1000 {
+            let (hi, lo) = (random::<u64>(), random::<u64>());
+            let hi61 = hi % m61;
+            assert_eq!(
+                Mersenne61::reduce_double(hi61, lo),
+                reduce_u64::<Mersenne61>(hi61, lo)
+            );
+            let higl = hi % gl;
+            assert_eq!(
+                Goldilocks::reduce_double(higl, lo),
+                reduce_u64::<Goldilocks>(higl, lo)
+            );
+            assert_eq!(
+                Goldilocks::reduce_double(hi, lo),
+                reduce_u64::<Goldilocks>(hi, lo)
+            );
+
+            let (hi, lo) = (random::<u128>() % m89, random::<u128>());
+            assert_eq!(
+                Mersenne89::reduce_double(hi, lo),
+                reduce_u128::<Mersenne89>(hi, lo)
+            );
+        }
+
+        for &hi in edges64(m61).iter().filter(|&&h| h < m61) {
+            for &lo in edges64(m61).iter() {
+                assert_eq!(
+                    Mersenne61::reduce_double(hi, lo),
+                    reduce_u64::<Mersenne61>(hi, lo)
+                );
+            }
+        }
+        for &hi in edges64(gl).iter() {
+            for &lo in edges64(gl).iter() {
+                assert_eq!(
+                    Goldilocks::reduce_double(hi, lo),
+                    reduce_u64::<Goldilocks>(hi, lo)
+                );
+            }
+        }
+        let edges128 = [0, 1, m89 - 1, m89, m89 + 1, u128::MAX];
+        for &hi in edges128.iter().filter(|&&h| h < m89) {
+            for &lo in edges128.iter() {
+                assert_eq!(
+                    Mersenne89::reduce_double(hi, lo),
+                    reduce_u128::<Mersenne89>(hi, lo)
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "num-bigint")]
+    fn reduce_double_biguint_test() {
+        use num_bigint::BigUint;
+        for _ in 0..1000 {
+            let (hi, lo) = (random::<u128>() % Mersenne89::MODULUS, random::<u128>());
+            let v = (BigUint::from(hi) << 128u8) + lo;
+            assert_eq!(
+                BigUint::from(Mersenne89::reduce_double(hi, lo)),
+                v % Mersenne89::MODULUS
+            );
+        }
+    }
+
+    #[test]
+    fn goldilocks_group_test() {
+        // 7 is a generator: p - 1 = 2^32 * 3 * 5 * 17 * 257 * 65537
+        use crate::{GoldilocksInt, ModularInteger};
+        let p = Goldilocks::MODULUS;
+        let g = GoldilocksInt::new(Goldilocks::GENERATOR, &p);
+        let one = g.convert(1);
+        assert_eq!((p - 1) >> Goldilocks::TWO_ADICITY, 0xffffffff);
+        for &q in [2u64, 3, 5, 17, 257, 65537].iter() {
+            assert_ne!(g.pow(&((p - 1) / q)), one);
+        }
+        assert_eq!(g.pow(&(p - 1)), one);
+    }
+
+    #[test]
+    fn test_boundary() {
+        ReducedTester::<u64>::test_boundary::<Solinas<Mersenne61>>(Mersenne61::MODULUS);
+        ReducedTester::<u64>::test_boundary::<Solinas<Goldilocks>>(Goldilocks::MODULUS);
+        ReducedTester::<u128>::test_boundary::<Solinas<Mersenne89>>(Mersenne89::MODULUS);
+    }
+
+    #[test]
+    fn test_against_modops() {
+        macro_rules! tests_for {
+            ($($T:ty: $($P:ty)*;)*) => ($($(
+                for _ in 0..NRANDOM {
+                    const M: $T = <$P>::MODULUS;
+                    let (a, b) = (random::<$T>(), random::<$T>());
+                    let e = random::<$T>();
+                    let am = SolinasInt::<$P>::new(a, &M);
+                    let bm = SolinasInt::<$P>::new(b, &M);
+                    assert_eq!((am + bm).residue(), a.addm(b, &M));
+                    assert_eq!((am - bm).residue(), a.subm(b, &M));
+                    assert_eq!((am * bm).residue(), a.mulm(b, &M));
+                    assert_eq!((-am).residue(), a.negm(&M));
+                    assert_eq!(am.double().residue(), a.dblm(&M));
+                    assert_eq!(am.square().residue(), a.sqm(&M));
+                    assert_eq!(am.pow(&e).residue(), a.powm(e, &M));
+                    assert_eq!(am.inv().map(|v| v.residue()), a.invm(&M));
+                }
+            )*)*);
+        }
+        use crate::{ModularInteger, ModularUnaryOps};
+        tests_for!(u64: Mersenne61 Goldilocks; u128: Mersenne89;);
+    }
+}
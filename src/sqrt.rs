@@ -0,0 +1,782 @@
+//! Modular square roots by the Tonelli-Shanks and Cipolla algorithms
+
+use crate::{
+    FindQnr, ModularCoreOps, ModularInteger, ModularSymbols, ModularUnaryOps, MontgomeryInt,
+    MontgomeryQuadExt,
+};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Modular square root
+pub trait ModularSqrt<Modulus = Self> {
+    type Output;
+
+    /// Calculate the square roots of self modulo a prime p, see [sqrtm()] for details
+    fn sqrtm(self, p: Modulus) -> Option<(Self::Output, Self::Output)>;
+
+    /// Calculate the square roots of self modulo a prime p by the Tonelli-Shanks algorithm,
+    /// which takes `O(log p + s^2)` multiplications for `p - 1 = q * 2^s`. The results are
+    /// the same as [sqrtm()].
+    fn sqrtm_tonelli_shanks(self, p: Modulus) -> Option<(Self::Output, Self::Output)>;
+
+    /// Calculate the square roots of self modulo a prime p by Cipolla's algorithm, which
+    /// finds t such that `t^2 - a` is a non-residue and computes `(t + ω)^((p+1)/2)` in
+    /// `F_p[ω]/(ω^2 - (t^2 - a))`. It takes `O(log p)` multiplications regardless of the
+    /// 2-adic valuation of `p - 1`. The results are the same as [sqrtm()].
+    fn sqrtm_cipolla(self, p: Modulus) -> Option<(Self::Output, Self::Output)>;
+}
+
+/// Decide whether Cipolla's algorithm is faster than Tonelli-Shanks for an odd prime p with
+/// `bits` bits and `p - 1 = q * 2^s`, which holds iff `s(s - 1) > 8 * bits + 20`
+#[inline]
+fn prefer_cipolla(s: u32, bits: u64) -> bool {
+    s as u64 * (s as u64).saturating_sub(1) > 8 * bits + 20
+}
+
+/// Calculate the two square roots `(x, p - x)` of a modulo a prime p, where `x <= p - x`,
+/// or return [None] if a is a quadratic non-residue.
+///
+/// The roots are calculated by the Tonelli-Shanks algorithm in the Montgomery form, with the
+/// shortcut `x = a^((p+1)/4)` for `p = 3 (mod 4)`. When `p - 1` is divisible by a large power
+/// of two `2^s` with `s(s - 1) > 8 log2(p) + 20`, Cipolla's algorithm is used instead (see
+/// [ModularSqrt::sqrtm_cipolla()]). If `a = 0 (mod p)`, both roots are zero, and for p = 2
+/// both roots are `a mod 2`.
+///
+/// Primality of p is not checked, because it's costly. For a composite p, the result is
+/// either [None] or a pair of verified square roots (not necessarily all of them).
+///
+/// # Panics
+/// if p is zero
+///
+/// Example code:
+/// ```rust
+/// use num_modular::sqrtm;
+///
+/// assert_eq!(sqrtm(10u32, &13), Some((6, 7)));
+/// assert_eq!(sqrtm(5u32, &13), None);
+/// ```
+#[inline]
+pub fn sqrtm<T: ModularSqrt<M>, M>(a: T, p: M) -> Option<(T::Output, T::Output)> {
+    a.sqrtm(p)
+}
+
+/// Modular square roots modulo a composite number with known factorization
+pub trait ModularSqrtFactored: Sized {
+    /// Calculate a square root of self modulo the product of the prime powers, see
+    /// [sqrtm_factored()] for details
+    fn sqrtm_factored(self, factors: &[(Self, u32)]) -> Option<Self>;
+
+    /// Calculate all the square roots of self modulo the product of the prime powers, see
+    /// [sqrtm_factored_all()] for details
+    #[cfg(feature = "alloc")]
+    fn sqrtm_factored_all(self, factors: &[(Self, u32)]) -> Vec<Self>;
+}
+
+/// Calculate a square root x of a modulo `m = p1^k1 * p2^k2 * ...` (i.e. `x^2 ≡ a (mod m)`),
+/// given the factorization as `factors = [(p1, k1), (p2, k2), ...]` with distinct primes,
+/// or return [None] if a is not a quadratic residue modulo m.
+///
+/// For each prime power `p^k`, the residue is written as `a = p^v * b` with b coprime to p.
+/// If `a ≢ 0 (mod p^k)`, a root exists iff v is even and b is a square modulo `p^(k-v)`,
+/// and then the roots are `p^(v/2)` times the roots of b. For an odd p, the root of b
+/// modulo p from [sqrtm()] is lifted with [hensel_lift()][crate::hensel_lift()], and for
+/// p = 2 (where b should be 1 modulo 8, or modulo 4 for `k - v = 2`) the root is lifted bit
+/// by bit. The roots modulo the prime powers are combined with [crt()][crate::crt()].
+///
+/// The result is one of the roots, use [sqrtm_factored_all()] to find all of them. If the
+/// factorization is empty, the modulus is 1 and zero is returned. Primality of the factors
+/// is not checked, for composite factors the result is either [None] or a verified root.
+///
+/// # Panics
+/// if a factor is less than 2, or m cannot be represented by the integer type
+///
+/// Example code:
+/// ```rust
+/// use num_modular::sqrtm_factored;
+///
+/// let factors = [(2u32, 3), (3, 1), (5, 2)]; // m = 600
+/// let x = sqrtm_factored(49u32, &factors).unwrap();
+/// assert_eq!(x * x % 600, 49);
+/// assert_eq!(sqrtm_factored(2u32, &factors), None); // 2 is not a square modulo 3
+/// assert_eq!(sqrtm_factored(12u32, &factors), None); // 12 = 2^2 * 3 with 3 ≢ 1 (mod 2)
+/// ```
+#[inline]
+pub fn sqrtm_factored<T: ModularSqrtFactored>(a: T, factors: &[(T, u32)]) -> Option<T> {
+    a.sqrtm_factored(factors)
+}
+
+/// Calculate all the square roots of a modulo `m = p1^k1 * p2^k2 * ...` in ascending order,
+/// given the factorization as `factors = [(p1, k1), (p2, k2), ...]` with distinct primes.
+/// The result is empty if a is not a quadratic residue modulo m.
+///
+/// The roots modulo each prime power are found as in [sqrtm_factored()], and each
+/// combination of them is a root modulo m by the Chinese Remainder Theorem. For a coprime
+/// to m, there are `2^n` roots for n distinct odd primes (times 1, 2 or 4 for `2^k` with
+/// k = 1, k = 2 or k >= 3). If a shares factors with m, there can be many more, e.g. all
+/// the multiples of `2^(k/2)` are roots of zero modulo `2^k`, so the result can be huge.
+///
+/// # Panics
+/// if a factor is less than 2, or m cannot be represented by the integer type
+///
+/// Example code:
+/// ```rust
+/// use num_modular::sqrtm_factored_all;
+///
+/// assert_eq!(sqrtm_factored_all(4u32, &[(3, 1), (5, 1)]), [2, 7, 8, 13]);
+/// assert_eq!(sqrtm_factored_all(1u32, &[(2, 3)]), [1, 3, 5, 7]);
+/// assert_eq!(sqrtm_factored_all(0u32, &[(2, 4)]), [0, 4, 8, 12]);
+/// assert!(sqrtm_factored_all(2u32, &[(3, 1), (5, 1)]).is_empty());
+/// ```
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn sqrtm_factored_all<T: ModularSqrtFactored>(a: T, factors: &[(T, u32)]) -> Vec<T> {
+    a.sqrtm_factored_all(factors)
+}
+
+macro_rules! impl_sqrtm_for {
+    ($($T:ty)*) => {$(
+        impl ModularSqrt<&$T> for $T {
+            type Output = $T;
+
+            fn sqrtm(self, p: &$T) -> Option<($T, $T)> {
+                if *p > 2 && *p & 1 == 1 {
+                    let s = (*p - 1).trailing_zeros();
+                    if prefer_cipolla(s, (<$T>::BITS - p.leading_zeros()) as u64) {
+                        return self.sqrtm_cipolla(p);
+                    }
+                }
+                self.sqrtm_tonelli_shanks(p)
+            }
+
+            fn sqrtm_tonelli_shanks(self, p: &$T) -> Option<($T, $T)> {
+                let p = *p;
+                assert!(p > 0, "the modulus should not be zero");
+                let a = self % p;
+                if a == 0 || p <= 2 {
+                    return Some((a, a));
+                }
+                if p & 1 == 0 || a.checked_legendre(&p) != Some(1) {
+                    return None;
+                }
+
+                let am = MontgomeryInt::<$T>::new(a, &p);
+                let one = am.convert(1);
+                let x = if p & 3 == 3 {
+                    am.pow(&((p >> 2) + 1))
+                } else {
+                    // p - 1 = q * 2^s
+                    let s = (p - 1).trailing_zeros();
+                    let q = (p - 1) >> s;
+                    let z = <$T>::find_qnr(&p);
+                    if z == 0 {
+                        return None;
+                    }
+
+                    let mut c = am.convert(z).pow(&q);
+                    let mut x = am.pow(&((q >> 1) + 1));
+                    let mut t = am.pow(&q);
+                    let mut m = s;
+                    while t != one {
+                        // find the least i with t^(2^i) = 1
+                        let mut i = 0;
+                        let mut t2 = t;
+                        while t2 != one {
+                            t2 = t2.square();
+                            i += 1;
+                            if i == m {
+                                return None; // p is not a prime
+                            }
+                        }
+
+                        let mut b = c;
+                        for _ in 0..m - i - 1 {
+                            b = b.square();
+                        }
+                        x = x * b;
+                        c = b.square();
+                        t = t * c;
+                        m = i;
+                    }
+                    x
+                };
+
+                if x.square() != am {
+                    return None; // p is not a prime
+                }
+                let (x, y) = (x.residue(), (-x).residue());
+                Some(if x <= y { (x, y) } else { (y, x) })
+            }
+
+            fn sqrtm_cipolla(self, p: &$T) -> Option<($T, $T)> {
+                let p = *p;
+                assert!(p > 0, "the modulus should not be zero");
+                let a = self % p;
+                if a == 0 || p <= 2 {
+                    return Some((a, a));
+                }
+                if p & 1 == 0 || a.checked_legendre(&p) != Some(1) {
+                    return None;
+                }
+
+                // about half of the candidates t give a non-residue t^2 - a
+                let mut t: $T = 0;
+                let d = loop {
+                    let d = t.mulm(t, &p).subm(a, &p);
+                    match d.checked_legendre(&p) {
+                        Some(-1) => break d,
+                        None => return None, // p is not a prime
+                        _ => t += 1,
+                    }
+                    if t == p {
+                        return None;
+                    }
+                };
+
+                let w = MontgomeryQuadExt::<$T>::new(t, 1, d, &p);
+                let (x, zero) = w.pow(&((p >> 1) + 1)).residue();
+                if zero != 0 || x.mulm(x, &p) != a {
+                    return None; // p is not a prime
+                }
+                let y = x.negm(&p);
+                Some(if x <= y { (x, y) } else { (y, x) })
+            }
+        }
+
+        impl ModularSqrt<&$T> for &$T {
+            type Output = $T;
+
+            #[inline]
+            fn sqrtm(self, p: &$T) -> Option<($T, $T)> {
+                (*self).sqrtm(p)
+            }
+
+            #[inline]
+            fn sqrtm_tonelli_shanks(self, p: &$T) -> Option<($T, $T)> {
+                (*self).sqrtm_tonelli_shanks(p)
+            }
+
+            #[inline]
+            fn sqrtm_cipolla(self, p: &$T) -> Option<($T, $T)> {
+                (*self).sqrtm_cipolla(p)
+            }
+        }
+    )*};
+}
+impl_sqrtm_for!(u8 u16 u32 u64 u128 usize);
+
+macro_rules! impl_sqrtm_factored_for {
+    ($T:ty, $m:ident) => {
+        mod $m {
+            use super::*;
+            use crate::{crt_pair, hensel_lift};
+
+            const OVERFLOW: &str = "the modulus should fit in the integer type";
+
+            /// Find a square root of b modulo `p^j`, where b is coprime to p and j >= 1
+            fn unit_root(b: $T, p: $T, j: u32) -> Option<$T> {
+                if p == 2 {
+                    // b is odd, and the odd squares are 1 modulo 8
+                    let mask = (1 << j.min(3)) - 1;
+                    if b & mask != 1 & mask {
+                        return None;
+                    }
+
+                    // if r^2 = b (mod 2^i) for i >= 3, then r or r + 2^(i-1) is a root modulo 2^(i+1)
+                    let mut r: $T = 1;
+                    for i in 3..j {
+                        let q = (1 as $T) << (i + 1);
+                        if r.mulm(r, &q) != b & (q - 1) {
+                            r += 1 << (i - 1);
+                        }
+                    }
+                    Some(r)
+                } else {
+                    let (r, _) = (b % p).sqrtm(&p)?;
+                    let f = |x: $T, m: $T| x.mulm(x, &m).subm(b % m, &m);
+                    let df = |x: $T, m: $T| x.addm(x, &m);
+                    hensel_lift(f, df, r, p, j)
+                }
+            }
+
+            /// Write a (nonzero) as `p^v * b` with b coprime to p
+            fn split(mut a: $T, p: $T) -> ($T, u32) {
+                let mut v = 0;
+                while a % p == 0 {
+                    a /= p;
+                    v += 1;
+                }
+                (a, v)
+            }
+
+            /// Find a square root of a modulo `p^k`
+            fn prime_power_root(a: $T, p: $T, k: u32) -> Option<$T> {
+                let a = a % p.pow(k);
+                if a == 0 {
+                    return Some(0);
+                }
+                let (b, v) = split(a, p);
+                if v % 2 == 1 {
+                    return None;
+                }
+                Some(unit_root(b, p, k - v)? * p.pow(v / 2))
+            }
+
+            /// Find all the square roots of a modulo `p^k` in ascending order
+            #[cfg(feature = "alloc")]
+            fn prime_power_roots(a: $T, p: $T, k: u32) -> Vec<$T> {
+                let a = a % p.pow(k);
+                if a == 0 {
+                    // the multiples of p^ceil(k/2)
+                    let step = p.pow((k + 1) / 2);
+                    return (0..p.pow(k / 2)).map(|i| i * step).collect();
+                }
+                let (b, v) = split(a, p);
+                if v % 2 == 1 {
+                    return Vec::new();
+                }
+                let (j, w) = (k - v, v / 2);
+                let r = match unit_root(b, p, j) {
+                    Some(r) => r,
+                    None => return Vec::new(),
+                };
+
+                // the roots of b modulo p^j are ±r, and also ±r + 2^(j-1) for p = 2 and j >= 3
+                let q = p.pow(j);
+                let mut units = alloc::vec![r, q - r];
+                if p == 2 && j >= 3 {
+                    let half = q >> 1;
+                    units.push(r.addm(half, &q));
+                    units.push((q - r).addm(half, &q));
+                }
+                units.sort_unstable();
+                units.dedup();
+
+                // x = p^w * y where y is a root of b modulo p^j, and x is taken modulo p^k
+                let pw = p.pow(w);
+                let mut roots: Vec<$T> = units
+                    .into_iter()
+                    .flat_map(|u| (0..pw).map(move |t| (u + t * q) * pw))
+                    .collect();
+                roots.sort_unstable();
+                roots
+            }
+
+            /// Check the factors and calculate their product
+            fn modulus(factors: &[($T, u32)]) -> $T {
+                factors.iter().fold(1, |acc: $T, &(p, k)| {
+                    assert!(p >= 2, "the factors should be primes");
+                    p.checked_pow(k)
+                        .and_then(|pk| acc.checked_mul(pk))
+                        .expect(OVERFLOW)
+                })
+            }
+
+            impl ModularSqrtFactored for $T {
+                fn sqrtm_factored(self, factors: &[($T, u32)]) -> Option<$T> {
+                    modulus(factors);
+                    let (mut x, mut m) = (0, 1);
+                    for &(p, k) in factors {
+                        let r = prime_power_root(self, p, k)?;
+                        let combined = crt_pair(&x, &m, &r, &p.pow(k))
+                            .expect("the factors should be powers of distinct primes");
+                        x = combined.0;
+                        m = combined.1;
+                    }
+
+                    // verify the result in case of composite factors
+                    if x.mulm(x, &m) == self % m {
+                        Some(x)
+                    } else {
+                        None
+                    }
+                }
+
+                #[cfg(feature = "alloc")]
+                fn sqrtm_factored_all(self, factors: &[($T, u32)]) -> Vec<$T> {
+                    let n = modulus(factors);
+                    let mut roots = alloc::vec![0];
+                    let mut m = 1;
+                    for &(p, k) in factors {
+                        let (rs, q) = (prime_power_roots(self, p, k), p.pow(k));
+                        roots = roots
+                            .iter()
+                            .flat_map(|x| {
+                                rs.iter().map(move |r| {
+                                    crt_pair(x, &m, r, &q)
+                                        .expect("the factors should be powers of distinct primes")
+                                        .0
+                                })
+                            })
+                            .collect();
+                        m *= q;
+                    }
+                    roots.retain(|x| x.mulm(*x, &n) == self % n);
+                    roots.sort_unstable();
+                    roots
+                }
+            }
+        }
+    };
+}
+impl_sqrtm_factored_for!(u8, u8_impl);
+impl_sqrtm_factored_for!(u16, u16_impl);
+impl_sqrtm_factored_for!(u32, u32_impl);
+impl_sqrtm_factored_for!(u64, u64_impl);
+impl_sqrtm_factored_for!(u128, u128_impl);
+impl_sqrtm_factored_for!(usize, usize_impl);
+
+#[cfg(feature = "num-bigint")]
+mod _num_bigint {
+    use super::*;
+    use crate::ModularPow;
+    use num_bigint::BigUint;
+    use num_integer::Integer;
+    use num_traits::{One, Zero};
+
+    impl ModularSqrt<&BigUint> for &BigUint {
+        type Output = BigUint;
+
+        fn sqrtm(self, p: &BigUint) -> Option<(BigUint, BigUint)> {
+            if p > &BigUint::from(2u8) && p.is_odd() {
+                let s = (p - 1u8).trailing_zeros().unwrap() as u32;
+                if prefer_cipolla(s, p.bits()) {
+                    return self.sqrtm_cipolla(p);
+                }
+            }
+            self.sqrtm_tonelli_shanks(p)
+        }
+
+        fn sqrtm_tonelli_shanks(self, p: &BigUint) -> Option<(BigUint, BigUint)> {
+            assert!(!p.is_zero(), "the modulus should not be zero");
+            let a = self % p;
+            if a.is_zero() || p <= &BigUint::from(2u8) {
+                return Some((a.clone(), a));
+            }
+            if p.is_even() || a.checked_legendre(p) != Some(1) {
+                return None;
+            }
+
+            let one = BigUint::one();
+            let pm1 = p - 1u8;
+            let x = if (p % 4u8) == BigUint::from(3u8) {
+                (&a).powm((p >> 2u8) + 1u8, p)
+            } else {
+                let s = pm1.trailing_zeros().unwrap();
+                let q = &pm1 >> s;
+                let z = BigUint::find_qnr(p);
+                if z.is_zero() {
+                    return None;
+                }
+
+                let mut c = z.powm(&q, p);
+                let mut x = (&a).powm((&q >> 1u8) + 1u8, p);
+                let mut t = (&a).powm(&q, p);
+                let mut m = s;
+                while t != one {
+                    let mut i = 0;
+                    let mut t2 = t.clone();
+                    while t2 != one {
+                        t2 = (&t2).sqm(p);
+                        i += 1;
+                        if i == m {
+                            return None;
+                        }
+                    }
+
+                    let mut b = c;
+                    for _ in 0..m - i - 1 {
+                        b = (&b).sqm(p);
+                    }
+                    x = x.mulm(&b, p);
+                    c = (&b).sqm(p);
+                    t = t.mulm(&c, p);
+                    m = i;
+                }
+                x
+            };
+
+            if (&x).sqm(p) != a {
+                return None;
+            }
+            let y = (&x).negm(p);
+            Some(if x <= y { (x, y) } else { (y, x) })
+        }
+
+        fn sqrtm_cipolla(self, p: &BigUint) -> Option<(BigUint, BigUint)> {
+            assert!(!p.is_zero(), "the modulus should not be zero");
+            let a = self % p;
+            if a.is_zero() || p <= &BigUint::from(2u8) {
+                return Some((a.clone(), a));
+            }
+            if p.is_even() || a.checked_legendre(p) != Some(1) {
+                return None;
+            }
+
+            let mut t = BigUint::zero();
+            let d = loop {
+                let d = (&t).sqm(p).subm(&a, p);
+                match d.checked_legendre(p) {
+                    Some(-1) => break d,
+                    None => return None,
+                    _ => t += 1u8,
+                }
+                if &t == p {
+                    return None;
+                }
+            };
+
+            // (u0 + u1 ω)(v0 + v1 ω) with ω^2 = d
+            let mul = |u: &(BigUint, BigUint), v: &(BigUint, BigUint)| {
+                let u1v1d = (&u.1).mulm(&v.1, p).mulm(&d, p);
+                (
+                    (&u.0).mulm(&v.0, p).addm(u1v1d, p),
+                    (&u.0).mulm(&v.1, p).addm((&u.1).mulm(&v.0, p), p),
+                )
+            };
+            let exp: BigUint = (p >> 1u8) + 1u8;
+            let base = (t, BigUint::one());
+            let mut result = (BigUint::one(), BigUint::zero());
+            for i in (0..exp.bits()).rev() {
+                result = mul(&result, &result);
+                if exp.bit(i) {
+                    result = mul(&result, &base);
+                }
+            }
+
+            let (x, zero) = result;
+            if !zero.is_zero() || (&x).sqm(p) != a {
+                return None;
+            }
+            let y = (&x).negm(p);
+            Some(if x <= y { (x, y) } else { (y, x) })
+        }
+    }
+
+    impl ModularSqrt<&BigUint> for BigUint {
+        type Output = BigUint;
+
+        #[inline]
+        fn sqrtm(self, p: &BigUint) -> Option<(BigUint, BigUint)> {
+            (&self).sqrtm(p)
+        }
+
+        #[inline]
+        fn sqrtm_tonelli_shanks(self, p: &BigUint) -> Option<(BigUint, BigUint)> {
+            (&self).sqrtm_tonelli_shanks(p)
+        }
+
+        #[inline]
+        fn sqrtm_cipolla(self, p: &BigUint) -> Option<(BigUint, BigUint)> {
+            (&self).sqrtm_cipolla(p)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use rand::random;
+
+        #[test]
+        fn biguint_sqrtm_test() {
+            // 2^127 - 1 (3 mod 4), 2^255 - 19 (5 mod 8) and a prime with 2-adic valuation 32
+            let primes = [
+                (BigUint::one() << 127u8) - 1u8,
+                (BigUint::one() << 255u8) - 19u8,
+                BigUint::from(0xffffffff00000001u64),
+            ];
+            for p in primes.iter() {
+                for _ in 0..10 {
+                    let x = BigUint::from(random::<u128>()) % p;
+                    let a = (&x).sqm(p);
+                    let (r1, r2) = (&a).sqrtm(p).unwrap();
+                    assert!(r1 == x || r2 == x);
+                    assert_eq!(
+                        &r1 + &r2,
+                        if r1.is_zero() {
+                            BigUint::zero()
+                        } else {
+                            p.clone()
+                        }
+                    );
+                    let qnr = BigUint::find_qnr(p);
+                    assert!((&a * &qnr).sqrtm(p).is_none() || a.is_zero());
+
+                    // the two backends agree
+                    assert_eq!((&a).sqrtm_cipolla(p), (&a).sqrtm_tonelli_shanks(p));
+                    let b = BigUint::from(random::<u128>()) % p;
+                    assert_eq!((&b).sqrtm_cipolla(p), (&b).sqrtm_tonelli_shanks(p));
+
+                    // against the primitive implementation
+                    let a64 = random::<u64>();
+                    assert_eq!(
+                        BigUint::from(a64).sqrtm(&BigUint::from(0xffffffff00000001u64)),
+                        a64.sqrtm(&0xffffffff00000001u64)
+                            .map(|(u, v)| (u.into(), v.into()))
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModularCoreOps;
+    use rand::random;
+
+    #[test]
+    fn small_primes_test() {
+        for &p in [2u32, 3, 5, 7, 13, 17, 97, 257, 65537, 7681].iter() {
+            let mut roots = std::vec![std::vec::Vec::new(); p as usize];
+            for x in 0..p {
+                roots[(x as u64 * x as u64 % p as u64) as usize].push(x);
+            }
+            for a in 0..p {
+                let expected = match roots[a as usize].as_slice() {
+                    [] => None,
+                    [x] => Some((*x, *x)),
+                    [x, y] => Some((*x, *y)),
+                    _ => unreachable!(),
+                };
+                assert_eq!(sqrtm(a, &p), expected, "sqrt({}) mod {}", a, p);
+                assert_eq!(sqrtm(a + p, &p), expected);
+                assert_eq!(a.sqrtm_tonelli_shanks(&p), expected);
+                assert_eq!(a.sqrtm_cipolla(&p), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn random_test() {
+        macro_rules! tests_for {
+            ($($T:ty: $($p:expr),*;)*) => ($($(
+                let p: $T = $p;
+                for _ in 0..10 {
+                    let x = random::<$T>() % p;
+                    let a = x.mulm(x, &p);
+                    let (r1, r2) = sqrtm(a, &p).unwrap();
+                    assert!(r1 <= r2 && (r1 == x || r2 == x), "sqrt({}) mod {}", a, p);
+                    assert_eq!(r1.mulm(r1, &p), a);
+                    assert_eq!(r2.mulm(r2, &p), a);
+                    assert_eq!((&a).sqrtm(&p), Some((r1, r2)));
+                    assert_eq!(a.sqrtm_tonelli_shanks(&p), Some((r1, r2)));
+                    assert_eq!(a.sqrtm_cipolla(&p), Some((r1, r2)));
+
+                    // a times a non-residue is a non-residue
+                    if a != 0 {
+                        let z = <$T>::find_qnr(&p);
+                        assert_eq!(sqrtm(a.mulm(z, &p), &p), None);
+                        assert_eq!(a.mulm(z, &p).sqrtm_cipolla(&p), None);
+                    }
+                }
+            )*)*);
+        }
+        tests_for!(
+            u8: 251, 241, 193;
+            u16: 65521, 40961, 12289;
+            u32: 4294967291, 998244353, 3221225473;
+            u64: 18446744073709551557, 0xffffffff00000001, 1000000007;
+            u128: u128::MAX - 158, (1 << 127) - 1;
+            usize: 65537, 257;
+        );
+    }
+
+    #[test]
+    fn composite_test() {
+        // any returned roots are verified
+        for &m in [9u32, 15, 21, 25, 45, 561, 1105].iter() {
+            for a in 0..m {
+                if let Some((r1, r2)) = sqrtm(a, &m) {
+                    assert_eq!(r1 * r1 % m, a);
+                    assert_eq!(r2 * r2 % m, a);
+                }
+                if let Some((r1, r2)) = a.sqrtm_cipolla(&m) {
+                    assert_eq!(r1 * r1 % m, a);
+                    assert_eq!(r2 * r2 % m, a);
+                }
+            }
+        }
+        assert_eq!(sqrtm(3u32, &8), None);
+        assert_eq!(sqrtm(7u64, &1), Some((0, 0)));
+        assert_eq!(3u32.sqrtm_cipolla(&8), None);
+        assert_eq!(7u64.sqrtm_cipolla(&1), Some((0, 0)));
+    }
+
+    #[test]
+    fn factored_test() {
+        for m in 1u32..=600 {
+            let factors: std::vec::Vec<(u32, u32)> = crate::root::prime_powers(m as u128)
+                .map(|(p, k)| (p as u32, k))
+                .collect();
+            for a in 0..m {
+                let expected: std::vec::Vec<u32> = (0..m).filter(|x| x * x % m == a).collect();
+                match sqrtm_factored(a + m, &factors) {
+                    Some(x) => assert!(expected.contains(&x), "sqrt({}) mod {}", a, m),
+                    None => assert!(expected.is_empty(), "sqrt({}) mod {}", a, m),
+                }
+                #[cfg(feature = "alloc")]
+                assert_eq!(
+                    sqrtm_factored_all(a, &factors),
+                    expected,
+                    "sqrt({}) mod {}",
+                    a,
+                    m
+                );
+            }
+        }
+
+        // large prime powers, including 2^k near the bit width
+        let factors = [(2u64, 20), (3, 10), (1_000_003, 1)];
+        let m = (1u64 << 20) * 3u64.pow(10) * 1_000_003;
+        for _ in 0..10 {
+            let x = random::<u64>() % m;
+            let a = x.mulm(x, &m);
+            let r = sqrtm_factored(a, &factors).unwrap();
+            assert_eq!(r.mulm(r, &m), a);
+        }
+        let (x, m) = (random::<u64>() | 1, 1u64 << 63);
+        let a = x.mulm(x, &m);
+        let r = sqrtm_factored(a, &[(2, 63)]).unwrap();
+        assert_eq!(r.mulm(r, &m), a);
+        #[cfg(feature = "alloc")]
+        assert_eq!(sqrtm_factored_all(a, &[(2, 63)]).len(), 4);
+
+        let p = (1u128 << 127) - 1;
+        let x = random::<u128>() % p;
+        let a = x.mulm(x, &p);
+        let r = sqrtm_factored(a, &[(p, 1)]).unwrap();
+        assert!(r == x || r == p - x);
+
+        assert_eq!(sqrtm_factored(5u8, &[]), Some(0));
+        assert_eq!(
+            sqrtm_factored(4u8, &[(3, 0), (5, 1)]),
+            sqrtm_factored(4u8, &[(5, 1)])
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn factored_overflow_test() {
+        sqrtm_factored(1u8, &[(2, 4), (17, 1)]);
+    }
+
+    #[test]
+    fn prefer_cipolla_test() {
+        // p = 3 (mod 4) and small valuations always use Tonelli-Shanks
+        assert!(!prefer_cipolla(1, 8));
+        assert!(!prefer_cipolla(7, 8));
+        assert!(!prefer_cipolla(12, 14)); // 12289 = 3 * 2^12 + 1
+        assert!(prefer_cipolla(23, 30)); // 998244353 = 119 * 2^23 + 1
+        assert!(prefer_cipolla(32, 64)); // 0xffffffff00000001
+        assert!(!prefer_cipolla(16, 64));
+
+        // the primes with large valuations take the Cipolla path
+        let p = 0xffffffff00000001u64;
+        for _ in 0..10 {
+            let x = random::<u64>() % p;
+            let a = x.mulm(x, &p);
+            assert_eq!(sqrtm(a, &p), a.sqrtm_tonelli_shanks(&p));
+        }
+    }
+}
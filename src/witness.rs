@@ -0,0 +1,259 @@
+//! Search of the Miller-Rabin witnesses for composite numbers
+
+#[cfg(feature = "prime-check")]
+use crate::ModularSymbols;
+use crate::{ModularInteger, MontgomeryInt};
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+/// Integer types supported by [smallest_witness()] and [find_witness()]
+pub trait Witness: Sized {
+    /// Check whether self is a strong probable prime to base a, i.e. the Miller-Rabin
+    /// test with base a passes.
+    ///
+    /// # Panics
+    /// if self is even or smaller than 3
+    fn is_sprp(&self, a: Self) -> bool;
+
+    /// Find the smallest Miller-Rabin witness, see [smallest_witness()] for details
+    fn smallest_witness(&self) -> Option<Self>;
+
+    /// Check whether self is a prime. The test is deterministic since there's always a
+    /// witness among the primes up to 37 for the composite numbers below 3.18 * 10^23, which
+    /// cover all the supported types (see [smallest_witness()]).
+    fn is_prime(&self) -> bool;
+
+    /// Find a Miller-Rabin witness randomly, see [find_witness()] for details
+    #[cfg(feature = "rand")]
+    fn find_witness<R: Rng + ?Sized>(&self, max_tries: usize, rng: &mut R) -> Option<Self>;
+}
+
+/// Find the smallest base `a >= 2` proving that n is composite by the strong probable prime
+/// (Miller-Rabin) test, or return [None] if n is a prime (or n < 4).
+///
+/// The bases are scanned in ascending order up to 37, and it's proven that every composite
+/// number below 318665857834031151167461 (about 3.18 * 10^23, so all 64-bit integers) has a
+/// witness among the primes up to 37, hence the search is deterministic. Carmichael numbers
+/// always have witnesses under the strong test. For even n >= 4, the base 2 is always returned.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::smallest_witness;
+///
+/// assert_eq!(smallest_witness(&2047u32), Some(3)); // 2047 = 23 * 89 is a strong pseudoprime to base 2
+/// assert_eq!(smallest_witness(&561u32), Some(2)); // Carmichael number
+/// assert_eq!(smallest_witness(&1_000_000_007u64), None);
+/// ```
+#[inline]
+pub fn smallest_witness<T: Witness>(n: &T) -> Option<T> {
+    n.smallest_witness()
+}
+
+/// Find a Miller-Rabin witness of n by sampling the bases in `[2, n-2]` uniformly at random,
+/// at most `max_tries` times.
+///
+/// At least 3/4 of the bases are witnesses for any odd composite n, so the probability of
+/// returning [None] for a composite n is at most `4^-max_tries`. [None] is always returned
+/// for primes and n < 4. For even n >= 4, the base 2 is always returned.
+///
+/// Example code:
+/// ```rust
+/// use num_modular::{find_witness, Witness};
+///
+/// let n = 3215031751u64; // a strong pseudoprime to bases 2, 3, 5 and 7
+/// let a = find_witness(&n, 100, &mut rand::thread_rng()).unwrap();
+/// assert!(!n.is_sprp(a));
+/// ```
+#[cfg(feature = "rand")]
+#[inline]
+pub fn find_witness<T: Witness, R: Rng + ?Sized>(
+    n: &T,
+    max_tries: usize,
+    rng: &mut R,
+) -> Option<T> {
+    n.find_witness(max_tries, rng)
+}
+
+/// Calculate the Legendre symbol (a|p) for an odd prime p, with the primality of p checked
+/// by [Witness::is_prime()] in debug builds. In release builds, it's equivalent to
+/// [ModularSymbols::legendre()], which only rejects the composite p when the result
+/// doesn't look like a Legendre symbol.
+///
+/// # Panics
+/// if p is not a prime in debug builds, or if the result is not valid
+///
+/// Example code:
+/// ```rust
+/// use num_modular::legendre;
+///
+/// assert_eq!(legendre(&2u64, &7), 1);
+/// assert_eq!(legendre(&3u64, &7), -1);
+/// assert_eq!(legendre(&14u64, &7), 0);
+/// ```
+#[cfg(feature = "prime-check")]
+#[inline]
+pub fn legendre<T: Witness + for<'r> ModularSymbols<&'r T>>(a: &T, p: &T) -> i8 {
+    debug_assert!(p.is_prime(), "the modulus should be a prime");
+    a.legendre(p)
+}
+
+macro_rules! impl_witness_for {
+    ($($T:ty)*) => {$(
+        impl Witness for $T {
+            fn is_sprp(&self, a: $T) -> bool {
+                let n = *self;
+                assert!(n >= 3 && n & 1 == 1, "the number should be odd and larger than 2");
+
+                // n - 1 = d * 2^s
+                let s = (n - 1).trailing_zeros();
+                let d = (n - 1) >> s;
+                let mut x = MontgomeryInt::<$T>::new(a, &n).pow(&d);
+                let (one, minus_one) = (x.convert(1), x.convert(n - 1));
+                if x == one || x == minus_one {
+                    return true;
+                }
+                for _ in 1..s {
+                    x = x.square();
+                    if x == minus_one {
+                        return true;
+                    }
+                }
+                false
+            }
+
+            fn smallest_witness(&self) -> Option<$T> {
+                let n = *self;
+                if n < 4 {
+                    return None;
+                }
+                if n & 1 == 0 {
+                    return Some(2);
+                }
+                (2..=(n - 2).min(37)).find(|&a| !n.is_sprp(a))
+            }
+
+            #[inline]
+            fn is_prime(&self) -> bool {
+                *self >= 2 && self.smallest_witness().is_none()
+            }
+
+            #[cfg(feature = "rand")]
+            fn find_witness<R: Rng + ?Sized>(&self, max_tries: usize, rng: &mut R) -> Option<$T> {
+                let n = *self;
+                if n < 4 {
+                    return None;
+                }
+                if n & 1 == 0 {
+                    return Some(2);
+                }
+                (0..max_tries)
+                    .map(|_| rng.gen_range(2..=n - 2))
+                    .find(|&a| !n.is_sprp(a))
+            }
+        }
+    )*};
+}
+impl_witness_for!(u8 u16 u32 u64 usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smallest_witness_test() {
+        // the smallest strong pseudoprimes to all the prime bases below the witness
+        const CASES: [(u64, u64); 8] = [
+            (2047, 3),
+            (1373653, 5),
+            (25326001, 7),
+            (3215031751, 11),
+            (2152302898747, 13),
+            (3474749660383, 17),
+            (341550071728321, 23),
+            (3825123056546413051, 37),
+        ];
+        for &(n, a) in CASES.iter() {
+            assert_eq!(smallest_witness(&n), Some(a), "{}", n);
+        }
+        assert_eq!(smallest_witness(&2047u32), Some(3));
+        assert_eq!(smallest_witness(&2047u16), Some(3));
+
+        // Carmichael numbers, perfect powers, even numbers and small numbers
+        for &n in [561u64, 1105, 1729, 2465, 2821, 6601, 8911].iter() {
+            assert_eq!(smallest_witness(&n), Some(2), "{}", n);
+        }
+        assert_eq!(smallest_witness(&9u32), Some(2));
+        assert_eq!(smallest_witness(&177147u32), Some(2)); // 3^11
+        assert_eq!(smallest_witness(&4294967297u64), Some(3)); // F5 = 641 * 6700417
+        assert_eq!(smallest_witness(&(1u64 << 40)), Some(2));
+        assert_eq!(smallest_witness(&4u8), Some(2));
+        for n in 0..4u32 {
+            assert_eq!(smallest_witness(&n), None);
+        }
+        assert_eq!(smallest_witness(&u64::MAX), Some(2)); // 3 * 5 * 17 * ...
+        assert_eq!(smallest_witness(&18446744073709551557u64), None);
+    }
+
+    #[test]
+    fn sieve_test() {
+        const N: usize = 1_000_000;
+        let mut is_prime = std::vec![true; N];
+        is_prime[0] = false;
+        is_prime[1] = false;
+        for i in 2..N {
+            if is_prime[i] && i * i < N {
+                for j in (i * i..N).step_by(i) {
+                    is_prime[j] = false;
+                }
+            }
+        }
+
+        for (n, &prime) in is_prime.iter().enumerate() {
+            assert_eq!((n as u32).is_prime(), prime, "{}", n);
+        }
+        for (n, &prime) in is_prime.iter().enumerate().skip(4) {
+            let w = smallest_witness(&(n as u32));
+            assert_eq!(w.is_none(), prime, "{}", n);
+            if let Some(a) = w {
+                // the bases below the witness are not witnesses
+                assert!(n % 2 == 0 || !(n as u32).is_sprp(a));
+                assert!((2..a).all(|b| (n as u32).is_sprp(b)));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn find_witness_test() {
+        let mut rng = rand::thread_rng();
+        for &n in [2047u64, 1373653, 3215031751, 341550071728321, 561, 1729].iter() {
+            let a = find_witness(&n, 64, &mut rng).unwrap();
+            assert!((2..n - 1).contains(&a));
+            assert!(!n.is_sprp(a));
+        }
+        for &n in [0u64, 1, 2, 3, 5, 7, 1000000007, 18446744073709551557].iter() {
+            assert_eq!(find_witness(&n, 64, &mut rng), None);
+        }
+        assert_eq!(find_witness(&100u32, 1, &mut rng), Some(2));
+        assert_eq!(find_witness(&2047u32, 0, &mut rng), None);
+    }
+
+    #[test]
+    #[cfg(feature = "prime-check")]
+    fn legendre_test() {
+        for &p in [3u64, 5, 7, 1000000007, 18446744073709551557].iter() {
+            for a in 0..100u64 {
+                assert_eq!(legendre(&a, &p), a.legendre(&p));
+            }
+        }
+        assert_eq!(legendre(&2u8, &251), -1);
+    }
+
+    #[test]
+    #[cfg(all(feature = "prime-check", debug_assertions))]
+    #[should_panic]
+    fn legendre_composite_test() {
+        // 2 is a square modulo 7 * 17, though 7 * 17 is not a prime
+        legendre(&2u32, &(7 * 17));
+    }
+}
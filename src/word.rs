@@ -58,29 +58,79 @@ macro_rules! simple_word_impl {
 }
 use simple_word_impl;
 
+/// Widening multiplication returning the (low, high) words with the double word arithmetic
+macro_rules! wide_split_impl {
+    () => {
+        /// Widening multiplication returning the (low, high) words
+        #[inline(always)]
+        pub const fn wmul_split(a: Word, b: Word) -> (Word, Word) {
+            split(wmul(a, b))
+        }
+    };
+}
+use wide_split_impl;
+
+/// Widening multiplication returning the (low, high) words, which is computed from the
+/// half-word partial products when the `narrow-mul` feature is enabled
+macro_rules! narrow_split_impl {
+    () => {
+        /// Widening multiplication computed from four half-word partial products with
+        /// explicit carries, so that the (possibly emulated) double word type is not involved
+        #[cfg_attr(not(feature = "narrow-mul"), allow(dead_code))]
+        #[inline(always)]
+        pub const fn wmul_halves(a: Word, b: Word) -> (Word, Word) {
+            const HALF: u32 = Word::BITS / 2;
+            const MASK: Word = Word::MAX >> HALF;
+            let (a0, a1) = (a & MASK, a >> HALF);
+            let (b0, b1) = (b & MASK, b >> HALF);
+            let (p00, p01, p10, p11) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+
+            // the middle column is less than 3 * 2^HALF, so it doesn't overflow
+            let mid = (p00 >> HALF) + (p01 & MASK) + (p10 & MASK);
+            let lo = (p00 & MASK) | (mid << HALF);
+            let hi = p11 + (p01 >> HALF) + (p10 >> HALF) + (mid >> HALF);
+            (lo, hi)
+        }
+
+        #[cfg(feature = "narrow-mul")]
+        pub use self::wmul_halves as wmul_split;
+        #[cfg(not(feature = "narrow-mul"))]
+        super::wide_split_impl!();
+    };
+}
+use narrow_split_impl;
+
 pub mod u8 {
     super::simple_word_impl!(u8, u16);
+    super::wide_split_impl!();
 }
 
 pub mod u16 {
     super::simple_word_impl!(u16, u32);
+    super::wide_split_impl!();
 }
 
 pub mod u32 {
     super::simple_word_impl!(u32, u64);
+    super::narrow_split_impl!();
 }
 
 pub mod u64 {
     super::simple_word_impl!(u64, u128);
+    super::narrow_split_impl!();
 }
 
 pub mod usize {
     #[cfg(target_pointer_width = "16")]
     super::simple_word_impl!(usize, u32);
+    #[cfg(target_pointer_width = "16")]
+    super::wide_split_impl!();
     #[cfg(target_pointer_width = "32")]
     super::simple_word_impl!(usize, u64);
     #[cfg(target_pointer_width = "64")]
     super::simple_word_impl!(usize, u128);
+    #[cfg(not(target_pointer_width = "16"))]
+    super::narrow_split_impl!();
 }
 
 pub mod u128 {
@@ -127,4 +177,6 @@ pub mod u128 {
     pub fn nrem(n: DoubleWord, d: Word) -> Word {
         n % d
     }
+
+    super::wide_split_impl!();
 }